@@ -91,11 +91,40 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
                     }
                 };
             } else if attr.path.is_ident("persist") {
-                match attr.parse_meta() {
+                // This is either `#[persist = "key"]` or `#[persist("key", skip_presets)]`
+                let parsed = match attr.parse_meta() {
                     Ok(syn::Meta::NameValue(syn::MetaNameValue {
                         lit: syn::Lit::Str(s),
                         ..
-                    })) => {
+                    })) => Some((s, false)),
+                    Ok(syn::Meta::List(list)) => {
+                        let mut key: Option<syn::LitStr> = None;
+                        let mut skip_presets = false;
+                        let mut valid = true;
+                        for (idx, nested) in list.nested.into_iter().enumerate() {
+                            match nested {
+                                syn::NestedMeta::Lit(syn::Lit::Str(s)) if idx == 0 => {
+                                    key = Some(s);
+                                }
+                                syn::NestedMeta::Meta(syn::Meta::Path(p))
+                                    if p.is_ident("skip_presets") =>
+                                {
+                                    skip_presets = true;
+                                }
+                                _ => valid = false,
+                            }
+                        }
+
+                        match key {
+                            Some(key) if valid => Some((key, skip_presets)),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                };
+
+                match parsed {
+                    Some((s, skip_presets)) => {
                         if processed_attribute {
                             return syn::Error::new(
                                 attr.span(),
@@ -117,15 +146,17 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
                         persistent_fields.push(PersistentField {
                             key: s,
                             field: field_name.clone(),
+                            skip_presets,
                         });
 
                         processed_attribute = true;
                     }
-                    _ => {
+                    None => {
                         return syn::Error::new(
                             attr.span(),
                             "The persist attribute should be a key-value pair with a string \
-                             argument: #[persist = \"foo_bar\"]",
+                             argument: #[persist = \"foo_bar\"] or #[persist(\"foo_bar\", \
+                             skip_presets)]",
                         )
                         .to_compile_error()
                         .into()
@@ -273,45 +304,60 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
         let (serialize_fields_self_tokens, deserialize_fields_match_self_tokens): (Vec<_>, Vec<_>) =
             persistent_fields
                 .into_iter()
-                .map(|PersistentField { field, key }| {
-                    (
-                        quote! {
-                            match ::nih_plug::params::persist::PersistentField::map(
-                                &self.#field,
-                                ::nih_plug::params::persist::serialize_field,
-                            ) {
-                                Ok(data) => {
-                                    serialized.insert(String::from(#key), data);
+                .map(|PersistentField { field, key, skip_presets }| {
+                    let serialize_tokens = quote! {
+                        match ::nih_plug::params::persist::PersistentField::map(
+                            &self.#field,
+                            ::nih_plug::params::persist::serialize_field,
+                        ) {
+                            Ok(data) => {
+                                serialized.insert(String::from(#key), data);
+                            }
+                            Err(err) => {
+                                ::nih_plug::nih_debug_assert_failure!(
+                                    "Could not serialize '{}': {}",
+                                    #key,
+                                    err
+                                )
+                            }
+                        };
+                    };
+                    let deserialize_tokens = quote! {
+                        #key => {
+                            match ::nih_plug::params::persist::deserialize_field(&data) {
+                                Ok(deserialized) => {
+                                    ::nih_plug::params::persist::PersistentField::set(
+                                        &self.#field,
+                                        deserialized,
+                                    );
                                 }
                                 Err(err) => {
                                     ::nih_plug::nih_debug_assert_failure!(
-                                        "Could not serialize '{}': {}",
+                                        "Could not deserialize '{}': {}",
                                         #key,
                                         err
                                     )
                                 }
                             };
-                        },
-                        quote! {
-                            #key => {
-                                match ::nih_plug::params::persist::deserialize_field(&data) {
-                                    Ok(deserialized) => {
-                                        ::nih_plug::params::persist::PersistentField::set(
-                                            &self.#field,
-                                            deserialized,
-                                        );
-                                    }
-                                    Err(err) => {
-                                        ::nih_plug::nih_debug_assert_failure!(
-                                            "Could not deserialize '{}': {}",
-                                            #key,
-                                            err
-                                        )
-                                    }
-                                };
-                            }
-                        },
-                    )
+                        }
+                    };
+
+                    // Fields marked with `skip_presets` are neither saved to nor loaded from presets
+                    if skip_presets {
+                        (
+                            quote! {
+                                if context != ::nih_plug::prelude::StateContext::Preset {
+                                    #serialize_tokens
+                                }
+                            },
+                            quote! {
+                                #key if context == ::nih_plug::prelude::StateContext::Preset => (),
+                                #deserialize_tokens
+                            },
+                        )
+                    } else {
+                        (serialize_tokens, deserialize_tokens)
+                    }
                 })
                 .unzip();
 
@@ -325,8 +371,8 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
                 })
                 .map(|nested| match nested {
                     NestedParams::Inline { field, .. } => (
-                        quote! { serialized.extend(self.#field.serialize_fields()); },
-                        quote! { self.#field.deserialize_fields(serialized); },
+                        quote! { serialized.extend(self.#field.serialize_fields_with_context(context)); },
+                        quote! { self.#field.deserialize_fields_with_context(serialized, context); },
                     ),
                     NestedParams::Prefixed {
                         field, id_prefix, ..
@@ -334,7 +380,7 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
                         quote! {
                             let prefixed = self
                                 .#field
-                                .serialize_fields()
+                                .serialize_fields_with_context(context)
                                 .into_iter()
                                 .map(|(key, value)| (format!("{}_{}", #id_prefix, key), value));
 
@@ -350,7 +396,7 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
                                 })
                                 .collect();
 
-                            self.#field.deserialize_fields(&matching_fields);
+                            self.#field.deserialize_fields_with_context(&matching_fields, context);
                         },
                     ),
                     NestedParams::Array { field, .. } => (
@@ -358,7 +404,7 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
                             for (field_idx, field) in self.#field.iter().enumerate() {
                                 let idx = field_idx + 1;
                                 let suffixed = field
-                                    .serialize_fields()
+                                    .serialize_fields_with_context(context)
                                     .into_iter()
                                     .map(|(key, value)| (format!("{}_{}", key, idx), value));
 
@@ -377,7 +423,7 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
                                     })
                                     .collect();
 
-                                field.deserialize_fields(&matching_fields);
+                                field.deserialize_fields_with_context(&matching_fields, context);
                             }
                         },
                     ),
@@ -419,10 +465,30 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
             }
 
             fn serialize_fields(&self) -> ::std::collections::BTreeMap<String, String> {
-                #serialize_fields_tokens
+                self.serialize_fields_with_context(::nih_plug::prelude::StateContext::Project)
             }
 
             fn deserialize_fields(&self, serialized: &::std::collections::BTreeMap<String, String>) {
+                self.deserialize_fields_with_context(
+                    serialized,
+                    ::nih_plug::prelude::StateContext::Project,
+                )
+            }
+
+            #[allow(unused_variables)]
+            fn serialize_fields_with_context(
+                &self,
+                context: ::nih_plug::prelude::StateContext,
+            ) -> ::std::collections::BTreeMap<String, String> {
+                #serialize_fields_tokens
+            }
+
+            #[allow(unused_variables)]
+            fn deserialize_fields_with_context(
+                &self,
+                serialized: &::std::collections::BTreeMap<String, String>,
+                context: ::nih_plug::prelude::StateContext,
+            ) {
                 #deserialize_fields_tokens
             }
         }
@@ -466,6 +532,9 @@ struct PersistentField {
     field: syn::Ident,
     /// The field's unique key.
     key: syn::LitStr,
+    /// Whether the field should be omitted from presets, set with
+    /// `#[persist("key", skip_presets)]`.
+    skip_presets: bool,
 }
 
 /// A field containing another object whose parameters and persistent fields should be added to this
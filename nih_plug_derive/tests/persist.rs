@@ -15,6 +15,14 @@ struct ArrayWrapperParams {
     pub inners: [InnerParams; 3],
 }
 
+#[derive(Params, Default)]
+struct SkipPresetsParams {
+    #[persist = "kept"]
+    pub kept: Mutex<u32>,
+    #[persist("project_only", skip_presets)]
+    pub project_only: Mutex<u32>,
+}
+
 #[derive(Default)]
 struct InnerParams {
     /// The value `deserialize()` has been called with so we can check that the prefix has been
@@ -135,4 +143,36 @@ mod persist {
             }
         }
     }
+
+    mod skip_presets {
+        use super::super::*;
+
+        #[test]
+        fn serialize() {
+            let params = SkipPresetsParams::default();
+
+            let serialized = params.serialize_fields_with_context(StateContext::Project);
+            assert_eq!(serialized.len(), 2);
+            assert!(serialized.contains_key("project_only"));
+
+            let serialized = params.serialize_fields_with_context(StateContext::Preset);
+            assert_eq!(serialized.len(), 1);
+            assert!(serialized.contains_key("kept"));
+        }
+
+        #[test]
+        fn deserialize() {
+            let mut serialized = BTreeMap::new();
+            serialized.insert(String::from("kept"), String::from("1"));
+            serialized.insert(String::from("project_only"), String::from("2"));
+
+            let params = SkipPresetsParams::default();
+            params.deserialize_fields_with_context(&serialized, StateContext::Preset);
+            assert_eq!(*params.kept.lock().unwrap(), 1);
+            assert_eq!(*params.project_only.lock().unwrap(), 0);
+
+            params.deserialize_fields(&serialized);
+            assert_eq!(*params.project_only.lock().unwrap(), 2);
+        }
+    }
 }
@@ -9,7 +9,9 @@ use baseview::{Size, WindowHandle, WindowOpenOptions, WindowScalePolicy};
 use crossbeam::atomic::AtomicCell;
 use egui_baseview::egui::Context;
 use egui_baseview::EguiWindow;
-use nih_plug::prelude::{Editor, GuiContext, ParamSetter, ParentWindowHandle};
+use nih_plug::prelude::{
+    Editor, EditorSizeConstraints, GuiContext, ParamSetter, ParentWindowHandle,
+};
 use parking_lot::RwLock;
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 use std::sync::atomic::Ordering;
@@ -107,6 +109,16 @@ where
             move |egui_ctx, queue, state| {
                 let setter = ParamSetter::new(context.as_ref());
 
+                // If the host resized the window, then the window's contents need to be resized to
+                // match. The host already knows about the new size.
+                if let Some(new_size) = egui_state.host_requested_size.swap(None) {
+                    queue.resize(PhySize::new(new_size.0, new_size.1));
+                    egui_ctx.send_viewport_cmd(ViewportCommand::InnerSize(Vec2::new(
+                        new_size.0 as f32,
+                        new_size.1 as f32,
+                    )));
+                }
+
                 // If the window was requested to resize
                 if let Some(new_size) = egui_state.requested_size.swap(None) {
                     // Ask the plugin host to resize to self.size()
@@ -151,6 +163,30 @@ where
         }
     }
 
+    fn can_resize(&self) -> bool {
+        self.egui_state.size_constraints.is_some()
+    }
+
+    fn size_constraints(&self) -> EditorSizeConstraints {
+        self.egui_state.size_constraints.unwrap_or_default()
+    }
+
+    fn set_size(&self, width: u32, height: u32) -> bool {
+        if self.egui_state.size_constraints.is_none() {
+            return false;
+        }
+
+        // The window will be resized during the next frame if it is currently open
+        self.egui_state.size.store((width, height));
+        if self.egui_state.is_open() {
+            self.egui_state
+                .host_requested_size
+                .store(Some((width, height)));
+        }
+
+        true
+    }
+
     fn set_scale_factor(&self, factor: f32) -> bool {
         // If the editor is currently open then the host must not change the current HiDPI scale as
         // we don't have a way to handle that. Ableton Live does this.
@@ -8,7 +8,7 @@
 use crossbeam::atomic::AtomicCell;
 use egui::Context;
 use nih_plug::params::persist::PersistentField;
-use nih_plug::prelude::{Editor, ParamSetter};
+use nih_plug::prelude::{Editor, EditorSizeConstraints, ParamSetter};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -71,6 +71,14 @@ pub struct EguiState {
     /// The new size of the window, if it was requested to resize by the GUI.
     #[serde(skip)]
     requested_size: AtomicCell<Option<(u32, u32)>>,
+    /// The new size of the window, if the host resized it while the editor was open. The window's
+    /// contents are resized to match during the next frame.
+    #[serde(skip)]
+    host_requested_size: AtomicCell<Option<(u32, u32)>>,
+    /// The sizes the host can resize the window to. The host cannot resize the window if this is
+    /// not set.
+    #[serde(skip)]
+    size_constraints: Option<EditorSizeConstraints>,
 
     /// Whether the editor's window is currently open.
     #[serde(skip)]
@@ -97,6 +105,25 @@ impl EguiState {
         Arc::new(EguiState {
             size: AtomicCell::new((width, height)),
             requested_size: Default::default(),
+            host_requested_size: Default::default(),
+            size_constraints: None,
+            open: AtomicBool::new(false),
+        })
+    }
+
+    /// The same as [`from_size()`][Self::from_size()], but the host is also allowed to resize the
+    /// GUI within `size_constraints`, for instance when the user drags the border of the host's
+    /// window. All sizes are in logical pixels.
+    pub fn from_size_with_constraints(
+        width: u32,
+        height: u32,
+        size_constraints: EditorSizeConstraints,
+    ) -> Arc<EguiState> {
+        Arc::new(EguiState {
+            size: AtomicCell::new(size_constraints.constrain(width, height)),
+            requested_size: Default::default(),
+            host_requested_size: Default::default(),
+            size_constraints: Some(size_constraints),
             open: AtomicBool::new(false),
         })
     }
@@ -57,7 +57,17 @@ pub fn create(
                 }
 
                 ui.label(unsafe { param_ptr.name() });
-                unsafe { widget.add_widget_raw(ui, &param_ptr, setter) };
+                if flags.contains(ParamFlags::READ_ONLY) {
+                    // Read-only parameters can't be changed, so they're only displayed
+                    ui.label(unsafe {
+                        param_ptr.normalized_value_to_string(
+                            param_ptr.modulated_normalized_value(),
+                            true,
+                        )
+                    });
+                } else {
+                    unsafe { widget.add_widget_raw(ui, &param_ptr, setter) };
+                }
 
                 first_widget = false;
             }
@@ -2,8 +2,8 @@ use std::sync::{Arc, LazyLock};
 
 use egui_baseview::egui::emath::GuiRounding;
 use egui_baseview::egui::{
-    self, emath, vec2, Key, Response, Sense, Stroke, TextEdit, TextStyle, Ui, Vec2, Widget,
-    WidgetText,
+    self, emath, vec2, Color32, Key, Response, Sense, Stroke, TextEdit, TextStyle, Ui, Vec2,
+    Widget, WidgetText,
 };
 use nih_plug::prelude::{Param, ParamSetter};
 use parking_lot::Mutex;
@@ -21,7 +21,8 @@ static VALUE_ENTRY_MEMORY_ID: LazyLock<egui::Id> = LazyLock::new(|| egui::Id::ne
 
 /// A slider widget similar to [`egui::widgets::Slider`] that knows about NIH-plug parameters ranges
 /// and can get values for it. The slider supports double click and control click to reset,
-/// shift+drag for granular dragging, text value entry by clicking on the value text.
+/// shift+drag for granular dragging, text value entry by clicking on the value text, and right
+/// clicking to show the host's context menu for the parameter.
 ///
 /// TODO: Vertical orientation
 /// TODO: Check below for more input methods that should be added
@@ -35,6 +36,7 @@ pub struct ParamSlider<'a, P: Param> {
 
     draw_value: bool,
     slider_width: Option<f32>,
+    draw_indication: bool,
 
     /// Will be set in the `ui()` function so we can request keyboard input focus on Alt+click.
     keyboard_focus_id: Option<egui::Id>,
@@ -50,6 +52,7 @@ impl<'a, P: Param> ParamSlider<'a, P> {
 
             draw_value: true,
             slider_width: None,
+            draw_indication: false,
 
             keyboard_focus_id: None,
         }
@@ -67,6 +70,15 @@ impl<'a, P: Param> ParamSlider<'a, P> {
         self
     }
 
+    /// Draw the slider's border in the color the host uses to indicate the parameter, for instance
+    /// when the parameter has been mapped to a hardware controller or when its automation is
+    /// playing. This is only supported by CLAP hosts that implement the `param-indication`
+    /// extension.
+    pub fn with_indication(mut self) -> Self {
+        self.draw_indication = true;
+        self
+    }
+
     fn plain_value(&self) -> P::Plain {
         self.param.modulated_plain_value()
     }
@@ -152,6 +164,25 @@ impl<'a, P: Param> ParamSlider<'a, P> {
         self.setter.end_set_parameter(self.param);
     }
 
+    /// Ask the host to show its context menu for the parameter at the mouse cursor's position.
+    fn show_context_menu(&self, ui: &Ui, response: &Response) {
+        let Some(pos) = response
+            .interact_pointer_pos()
+            .or_else(|| ui.input(|i| i.pointer.latest_pos()))
+        else {
+            return;
+        };
+
+        // The host expects physical pixel coordinates
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        self.setter.show_context_menu(
+            self.param,
+            (pos.x * pixels_per_point).round() as i32,
+            (pos.y * pixels_per_point).round() as i32,
+            Vec::new(),
+        );
+    }
+
     fn get_drag_normalized_start_value_memory(ui: &Ui) -> f32 {
         ui.memory(|mem| {
             mem.data
@@ -184,7 +215,16 @@ impl<'a, P: Param> ParamSlider<'a, P> {
             self.begin_drag();
             Self::set_drag_amount_memory(ui, 0.0);
         }
-        if let Some(click_pos) = response.interact_pointer_pos() {
+        // Right clicks should not change the parameter's value
+        let is_secondary_click =
+            response.secondary_clicked() || ui.input(|i| i.pointer.secondary_down());
+        if response.secondary_clicked() {
+            self.show_context_menu(ui, response);
+        }
+        if let Some(click_pos) = response
+            .interact_pointer_pos()
+            .filter(|_| !is_secondary_click)
+        {
             if ui.input(|i| i.modifiers.command) {
                 // Like double clicking, Ctrl+Click should reset the parameter
                 self.reset_param();
@@ -233,12 +273,28 @@ impl<'a, P: Param> ParamSlider<'a, P> {
                 ui.painter().rect_filled(filled_rect, 0.0, filled_bg);
             }
 
-            ui.painter().rect_stroke(
-                response.rect,
-                0.0,
-                Stroke::new(1.0, ui.visuals().widgets.active.bg_fill),
-                egui::StrokeKind::Middle,
-            );
+            let indication_color = if self.draw_indication {
+                self.setter
+                    .raw_context
+                    .param_indication(self.param.as_ptr())
+                    .color()
+            } else {
+                None
+            };
+            let border_stroke = match indication_color {
+                Some(color) => Stroke::new(
+                    2.0,
+                    Color32::from_rgba_unmultiplied(
+                        color.red,
+                        color.green,
+                        color.blue,
+                        color.alpha,
+                    ),
+                ),
+                None => Stroke::new(1.0, ui.visuals().widgets.active.bg_fill),
+            };
+            ui.painter()
+                .rect_stroke(response.rect, 0.0, border_stroke, egui::StrokeKind::Middle);
         }
     }
 
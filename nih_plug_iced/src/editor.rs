@@ -4,10 +4,10 @@ use baseview::{WindowOpenOptions, WindowScalePolicy};
 use crossbeam::atomic::AtomicCell;
 use crossbeam::channel;
 pub use iced_baseview::*;
-use nih_plug::prelude::{Editor, GuiContext, ParentWindowHandle};
+use nih_plug::prelude::{Editor, EditorSizeConstraints, GuiContext, ParentWindowHandle};
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 use std::sync::atomic::Ordering;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use crate::{wrapper, IcedEditor, IcedState, ParameterUpdate};
 
@@ -23,8 +23,23 @@ pub(crate) struct IcedEditorWrapper<E: IcedEditor> {
     /// A subscription for sending messages about parameter updates to the `IcedEditor`.
     pub(crate) parameter_updates_sender: channel::Sender<ParameterUpdate>,
     pub(crate) parameter_updates_receiver: Arc<channel::Receiver<ParameterUpdate>>,
+
+    /// The editor's window while it is open. This is shared with the [`IcedEditorHandle`] so the
+    /// window can be reopened when the host resizes it.
+    pub(crate) window: Arc<Mutex<Option<IcedEditorWindow<E>>>>,
+}
+
+/// An opened editor window, along with everything needed to open it again.
+pub(crate) struct IcedEditorWindow<E: IcedEditor> {
+    parent: ParentWindowHandle,
+    context: Arc<dyn GuiContext>,
+    window: iced_baseview::WindowHandle<wrapper::Message<E>>,
 }
 
+/// The parent window handle and the window handle contain raw pointers, but they're only ever used
+/// from the GUI thread.
+unsafe impl<E: IcedEditor> Send for IcedEditorWindow<E> {}
+
 /// This version of `baseview` uses a different version of `raw_window_handle than NIH-plug, so we
 /// need to adapt it ourselves.
 struct ParentWindowHandleAdapter(nih_plug::editor::ParentWindowHandle);
@@ -51,18 +66,19 @@ unsafe impl HasRawWindowHandle for ParentWindowHandleAdapter {
     }
 }
 
-impl<E: IcedEditor> Editor for IcedEditorWrapper<E> {
-    fn spawn(
+impl<E: IcedEditor> IcedEditorWrapper<E> {
+    /// Open the editor's window at the current size.
+    fn open_window(
         &self,
         parent: ParentWindowHandle,
         context: Arc<dyn GuiContext>,
-    ) -> Box<dyn std::any::Any + Send> {
+    ) -> iced_baseview::WindowHandle<wrapper::Message<E>> {
         let (unscaled_width, unscaled_height) = self.iced_state.size();
         let scaling_factor = self.scaling_factor.load();
 
         // TODO: iced_baseview does not have gracefuly error handling for context creation failures.
         //       This will panic if the context could not be created.
-        let window = IcedWindow::<wrapper::IcedEditorWrapperApplication<E>>::open_parented(
+        IcedWindow::<wrapper::IcedEditorWrapperApplication<E>>::open_parented(
             &ParentWindowHandleAdapter(parent),
             Settings {
                 window: WindowOpenOptions {
@@ -108,14 +124,31 @@ impl<E: IcedEditor> Editor for IcedEditorWrapper<E> {
                     context,
                     self.parameter_updates_receiver.clone(),
                     self.initialization_flags.clone(),
+                    // On macOS the host's coordinates are in logical pixels
+                    scaling_factor.unwrap_or(1.0),
                 ),
             },
-        );
+        )
+    }
+}
+
+impl<E: IcedEditor> Editor for IcedEditorWrapper<E> {
+    fn spawn(
+        &self,
+        parent: ParentWindowHandle,
+        context: Arc<dyn GuiContext>,
+    ) -> Box<dyn std::any::Any + Send> {
+        let window = self.open_window(parent, context.clone());
+        *self.window.lock().unwrap() = Some(IcedEditorWindow {
+            parent,
+            context,
+            window,
+        });
 
         self.iced_state.open.store(true, Ordering::Release);
         Box::new(IcedEditorHandle {
             iced_state: self.iced_state.clone(),
-            window,
+            window: self.window.clone(),
         })
     }
 
@@ -123,6 +156,35 @@ impl<E: IcedEditor> Editor for IcedEditorWrapper<E> {
         self.iced_state.size()
     }
 
+    fn can_resize(&self) -> bool {
+        self.iced_state.size_constraints.is_some()
+    }
+
+    fn size_constraints(&self) -> EditorSizeConstraints {
+        self.iced_state.size_constraints.unwrap_or_default()
+    }
+
+    fn set_size(&self, width: u32, height: u32) -> bool {
+        if self.iced_state.size_constraints.is_none() {
+            return false;
+        }
+
+        let old_size = self.iced_state.size.swap((width, height));
+        if old_size == (width, height) {
+            return true;
+        }
+
+        // This version of `iced_baseview` cannot resize a window that's already open, so the window
+        // is reopened at the new size instead. This recreates the `IcedEditor`.
+        if let Some(editor_window) = self.window.lock().unwrap().as_mut() {
+            editor_window.window.close_window();
+            editor_window.window =
+                self.open_window(editor_window.parent, editor_window.context.clone());
+        }
+
+        true
+    }
+
     fn set_scale_factor(&self, factor: f32) -> bool {
         // If the editor is currently open then the host must not change the current HiDPI scale as
         // we don't have a way to handle that. Ableton Live does this.
@@ -152,18 +214,16 @@ impl<E: IcedEditor> Editor for IcedEditorWrapper<E> {
 }
 
 /// The window handle used for [`IcedEditorWrapper`].
-struct IcedEditorHandle<Message: 'static + Send> {
+struct IcedEditorHandle<E: IcedEditor> {
     iced_state: Arc<IcedState>,
-    window: iced_baseview::WindowHandle<Message>,
+    window: Arc<Mutex<Option<IcedEditorWindow<E>>>>,
 }
 
-/// The window handle enum stored within 'WindowHandle' contains raw pointers. Is there a way around
-/// having this requirement?
-unsafe impl<Message: Send> Send for IcedEditorHandle<Message> {}
-
-impl<Message: Send> Drop for IcedEditorHandle<Message> {
+impl<E: IcedEditor> Drop for IcedEditorHandle<E> {
     fn drop(&mut self) {
         self.iced_state.open.store(false, Ordering::Release);
-        self.window.close_window();
+        if let Some(mut editor_window) = self.window.lock().unwrap().take() {
+            editor_window.window.close_window();
+        }
     }
 }
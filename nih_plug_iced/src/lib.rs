@@ -93,7 +93,7 @@ use baseview::WindowScalePolicy;
 use crossbeam::atomic::AtomicCell;
 use crossbeam::channel;
 use nih_plug::params::persist::PersistentField;
-use nih_plug::prelude::{Editor, GuiContext};
+use nih_plug::prelude::{Editor, EditorSizeConstraints, GuiContext};
 use serde::{Deserialize, Serialize};
 // This doesn't need to be re-export but otherwise the compiler complains about
 // `hidden_glob_reexports`
@@ -147,6 +147,8 @@ pub fn create_iced_editor<E: IcedEditor>(
 
         parameter_updates_sender,
         parameter_updates_receiver: Arc::new(parameter_updates_receiver),
+
+        window: Default::default(),
     }))
 }
 
@@ -230,6 +232,16 @@ pub trait IcedEditor: 'static + Send + Sync + Sized {
                 context.raw_set_parameter_normalized(p, v)
             },
             ParamMessage::EndSetParameter(p) => unsafe { context.raw_end_set_parameter(p) },
+            // The widgets' positions are in logical pixels, but the host expects physical pixels
+            ParamMessage::ShowContextMenu(p, position) => {
+                let scale_factor = wrapper::window_scale_factor();
+                context.show_param_context_menu(
+                    p,
+                    (position.x * scale_factor).round() as i32,
+                    (position.y * scale_factor).round() as i32,
+                    Vec::new(),
+                );
+            }
         }
     }
 }
@@ -240,6 +252,10 @@ pub struct IcedState {
     /// The window's size in logical pixels before applying `scale_factor`.
     #[serde(with = "nih_plug::params::persist::serialize_atomic_cell")]
     size: AtomicCell<(u32, u32)>,
+    /// The sizes the host can resize the window to. The host cannot resize the window if this is
+    /// not set.
+    #[serde(skip)]
+    size_constraints: Option<EditorSizeConstraints>,
     /// Whether the editor's window is currently open.
     #[serde(skip)]
     open: AtomicBool,
@@ -264,6 +280,24 @@ impl IcedState {
     pub fn from_size(width: u32, height: u32) -> Arc<IcedState> {
         Arc::new(IcedState {
             size: AtomicCell::new((width, height)),
+            size_constraints: None,
+            open: AtomicBool::new(false),
+        })
+    }
+
+    /// The same as [`from_size()`][Self::from_size()], but the host is also allowed to resize the
+    /// GUI within `size_constraints`, for instance when the user drags the border of the host's
+    /// window. All sizes are in logical pixels. Since iced's windows cannot be resized while they
+    /// are open, the editor's window is reopened at the new size. This means that the
+    /// [`IcedEditor`] is recreated and any state that's only stored in the editor is lost.
+    pub fn from_size_with_constraints(
+        width: u32,
+        height: u32,
+        size_constraints: EditorSizeConstraints,
+    ) -> Arc<IcedState> {
+        Arc::new(IcedState {
+            size: AtomicCell::new(size_constraints.constrain(width, height)),
+            size_constraints: Some(size_constraints),
             open: AtomicBool::new(false),
         })
     }
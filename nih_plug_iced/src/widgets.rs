@@ -7,6 +7,8 @@
 
 use nih_plug::prelude::ParamPtr;
 
+use crate::Point;
+
 pub mod generic_ui;
 pub mod param_slider;
 pub mod peak_meter;
@@ -29,4 +31,8 @@ pub enum ParamMessage {
     SetParameterNormalized(ParamPtr, f32),
     /// End an automation gesture for a parameter.
     EndSetParameter(ParamPtr),
+    /// Ask the host to show its context menu for a parameter at a position relative to the
+    /// window. See
+    /// [`GuiContext::show_param_context_menu()`][nih_plug::prelude::GuiContext::show_param_context_menu()].
+    ShowContextMenu(ParamPtr, Point),
 }
@@ -182,6 +182,20 @@ where
             let widget_state: &'a mut W::State =
                 unsafe { &mut *(widget_state.get_mut(&param_ptr).unwrap() as *mut _) };
 
+            // Read-only parameters can't be changed, so they're only displayed
+            let widget: Element<'a, ParamMessage> = if flags.contains(ParamFlags::READ_ONLY) {
+                Text::new(unsafe {
+                    param_ptr
+                        .normalized_value_to_string(param_ptr.modulated_normalized_value(), true)
+                })
+                .height(20.into())
+                .width(Length::Fill)
+                .vertical_alignment(alignment::Vertical::Center)
+                .into()
+            } else {
+                unsafe { W::into_widget_element_raw(&param_ptr, widget_state) }
+            };
+
             // Show the label next to the parameter for better use of the space
             let mut row = Row::new()
                 .width(Length::Fill)
@@ -194,7 +208,7 @@ where
                         .horizontal_alignment(alignment::Horizontal::Right)
                         .vertical_alignment(alignment::Vertical::Center),
                 )
-                .push(unsafe { W::into_widget_element_raw(&param_ptr, widget_state) });
+                .push(widget);
             if self.pad_scrollbar {
                 // There's already spacing applied, so this element doesn't actually need to hae any
                 // size of its own
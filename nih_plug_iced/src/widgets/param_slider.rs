@@ -346,6 +346,17 @@ impl<'a, P: Param> Widget<ParamMessage, Renderer> for ParamSlider<'a, P> {
                     return event::Status::Captured;
                 }
             }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                // Right clicks show the host's context menu for the parameter
+                if bounds.contains(cursor_position) {
+                    shell.publish(ParamMessage::ShowContextMenu(
+                        self.param.as_ptr(),
+                        cursor_position,
+                    ));
+
+                    return event::Status::Captured;
+                }
+            }
             Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))
             | Event::Touch(touch::Event::FingerLifted { .. } | touch::Event::FingerLost { .. }) => {
                 if self.state.drag_active {
@@ -3,6 +3,7 @@
 
 use crossbeam::channel;
 use nih_plug::prelude::GuiContext;
+use std::cell::Cell;
 use std::sync::Arc;
 
 use crate::futures::FutureExt;
@@ -20,6 +21,23 @@ pub(crate) struct IcedEditorWrapperApplication<E: IcedEditor> {
     /// update gets sent, we will trigger a [`Message::parameterUpdate`] which causes the UI to be
     /// redrawn.
     parameter_updates_receiver: Arc<channel::Receiver<ParameterUpdate>>,
+
+    /// The window's scale factor, used to convert iced's logical coordinates to physical pixels.
+    scale_factor: f32,
+}
+
+thread_local! {
+    /// The scale factor of the window whose editor is currently handling a message. iced doesn't
+    /// expose the scale factor to widgets or to [`IcedEditor::update()`], so this is set before
+    /// every update. See [`window_scale_factor()`].
+    static WINDOW_SCALE_FACTOR: Cell<f32> = Cell::new(1.0);
+}
+
+/// The scale factor of the window whose editor is currently handling a message. Used by
+/// [`IcedEditor::handle_param_message()`] to convert iced's logical coordinates to the physical
+/// pixels expected by the [`GuiContext`].
+pub(crate) fn window_scale_factor() -> f32 {
+    WINDOW_SCALE_FACTOR.with(Cell::get)
 }
 
 /// This wraps around `E::Message` to add a parameter update message which can be handled directly
@@ -55,10 +73,11 @@ impl<E: IcedEditor> Application for IcedEditorWrapperApplication<E> {
         Arc<dyn GuiContext>,
         Arc<channel::Receiver<ParameterUpdate>>,
         E::InitializationFlags,
+        f32,
     );
 
     fn new(
-        (context, parameter_updates_receiver, flags): Self::Flags,
+        (context, parameter_updates_receiver, flags, scale_factor): Self::Flags,
     ) -> (Self, Command<Self::Message>) {
         let (editor, command) = E::new(flags, context);
 
@@ -66,6 +85,7 @@ impl<E: IcedEditor> Application for IcedEditorWrapperApplication<E> {
             Self {
                 editor,
                 parameter_updates_receiver,
+                scale_factor,
             },
             command.map(Message::EditorMessage),
         )
@@ -78,10 +98,12 @@ impl<E: IcedEditor> Application for IcedEditorWrapperApplication<E> {
         message: Self::Message,
     ) -> Command<Self::Message> {
         match message {
-            Message::EditorMessage(message) => self
-                .editor
-                .update(window, message)
-                .map(Message::EditorMessage),
+            Message::EditorMessage(message) => {
+                WINDOW_SCALE_FACTOR.with(|scale_factor| scale_factor.set(self.scale_factor));
+                self.editor
+                    .update(window, message)
+                    .map(Message::EditorMessage)
+            }
             // This message only exists to force a redraw
             Message::ParameterUpdate => Command::none(),
         }
@@ -3,13 +3,15 @@
 use baseview::{WindowHandle, WindowScalePolicy};
 use crossbeam::atomic::AtomicCell;
 use nih_plug::debug::*;
-use nih_plug::prelude::{Editor, GuiContext, ParentWindowHandle};
+use nih_plug::prelude::{
+    Editor, EditorSizeConstraints, GuiContext, ParamIndication, ParentWindowHandle,
+};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use vizia::context::backend::TextConfig;
 use vizia::prelude::*;
 
-use crate::widgets::RawParamEvent;
+use crate::widgets::{HostResizeEvent, RawParamEvent};
 use crate::{assets, widgets, ViziaState, ViziaTheming};
 
 /// An [`Editor`] implementation that calls an vizia draw loop.
@@ -30,6 +32,11 @@ pub(crate) struct ViziaEditor {
     /// to compute a property in an event handler. Like when positioning an element based on the
     /// display value's width.
     pub(crate) emit_parameters_changed_event: Arc<AtomicBool>,
+    /// A user scale factor resulting from a host-driven resize that should be applied to the window
+    /// during the next idle callback. The window cannot be resized directly from
+    /// [`Editor::set_size()`] as that is called from the host's GUI thread outside of vizia's
+    /// event loop.
+    pub(crate) requested_user_scale_factor: Arc<AtomicCell<Option<f64>>>,
 }
 
 impl Editor for ViziaEditor {
@@ -94,7 +101,15 @@ impl Editor for ViziaEditor {
         })
         .on_idle({
             let emit_parameters_changed_event = self.emit_parameters_changed_event.clone();
+            let requested_user_scale_factor = self.requested_user_scale_factor.clone();
             move |cx| {
+                if let Some(scale_factor) = requested_user_scale_factor.take() {
+                    cx.emit_custom(
+                        Event::new(HostResizeEvent::SetUserScaleFactor(scale_factor))
+                            .propagate(Propagation::Subtree),
+                    );
+                }
+
                 if emit_parameters_changed_event
                     .compare_exchange(true, false, Ordering::AcqRel, Ordering::Relaxed)
                     .is_ok()
@@ -126,6 +141,53 @@ impl Editor for ViziaEditor {
         self.vizia_state.scaled_logical_size()
     }
 
+    fn can_resize(&self) -> bool {
+        self.vizia_state.scale_factor_range.is_some()
+    }
+
+    fn size_constraints(&self) -> EditorSizeConstraints {
+        let Some(scale_factor_range) = &self.vizia_state.scale_factor_range else {
+            return EditorSizeConstraints::default();
+        };
+
+        // Host-driven resizes only change the user scale factor, so the aspect ratio is fixed
+        let (inner_width, inner_height) = self.vizia_state.inner_logical_size();
+        let scale = |factor: f64| {
+            (
+                ((inner_width as f64 * factor).round() as u32).max(1),
+                ((inner_height as f64 * factor).round() as u32).max(1),
+            )
+        };
+
+        EditorSizeConstraints {
+            min_size: scale(*scale_factor_range.start()),
+            max_size: Some(scale(*scale_factor_range.end())),
+            aspect_ratio: Some((inner_width.max(1), inner_height.max(1))),
+        }
+    }
+
+    fn set_size(&self, width: u32, _height: u32) -> bool {
+        let Some(scale_factor_range) = &self.vizia_state.scale_factor_range else {
+            return false;
+        };
+
+        let (inner_width, _) = self.vizia_state.inner_logical_size();
+        if inner_width == 0 {
+            return false;
+        }
+
+        // The size has already been constrained to our aspect ratio, so the width alone determines
+        // the new scale factor. This is stored right away so `size()` reflects the new size.
+        let scale_factor = (width as f64 / inner_width as f64)
+            .clamp(*scale_factor_range.start(), *scale_factor_range.end());
+        self.vizia_state.scale_factor.store(scale_factor);
+        if self.vizia_state.is_open() {
+            self.requested_user_scale_factor.store(Some(scale_factor));
+        }
+
+        true
+    }
+
     fn set_scale_factor(&self, factor: f32) -> bool {
         // If the editor is currently open then the host must not change the current HiDPI scale as
         // we don't have a way to handle that. Ableton Live does this.
@@ -157,6 +219,12 @@ impl Editor for ViziaEditor {
         self.emit_parameters_changed_event
             .store(true, Ordering::Relaxed);
     }
+
+    fn param_indication_changed(&self, _id: &str, _indication: &ParamIndication) {
+        // Widgets can query the new indication when they receive the parameters changed event
+        self.emit_parameters_changed_event
+            .store(true, Ordering::Relaxed);
+    }
 }
 
 /// The window handle used for [`ViziaEditor`].
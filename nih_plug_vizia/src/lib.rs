@@ -8,6 +8,7 @@ use nih_plug::params::persist::PersistentField;
 use nih_plug::prelude::{Editor, GuiContext};
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
+use std::ops::RangeInclusive;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use vizia::prelude::*;
@@ -63,6 +64,7 @@ where
         scaling_factor: AtomicCell::new(Some(1.0)),
 
         emit_parameters_changed_event: Arc::new(AtomicBool::new(false)),
+        requested_user_scale_factor: Arc::new(AtomicCell::new(None)),
     }))
 }
 
@@ -95,6 +97,10 @@ pub struct ViziaState {
     /// This can be used to allow GUIs to be scaled uniformly.
     #[serde(with = "nih_plug::params::persist::serialize_atomic_cell")]
     scale_factor: AtomicCell<f64>,
+    /// The range the user scale factor may be in when the host resizes the window. If this is
+    /// `None`, then the host is not allowed to resize the window.
+    #[serde(skip)]
+    scale_factor_range: Option<RangeInclusive<f64>>,
     /// Whether the editor's window is currently open.
     #[serde(skip)]
     open: AtomicBool,
@@ -112,6 +118,7 @@ impl Debug for ViziaState {
         f.debug_struct("ViziaState")
             .field("size_fn", &format!("<fn> ({}, {})", width, height))
             .field("scale_factor", &self.scale_factor)
+            .field("scale_factor_range", &self.scale_factor_range)
             .field("open", &self.open)
             .finish()
     }
@@ -139,6 +146,7 @@ impl ViziaState {
         Arc::new(ViziaState {
             size_fn: Box::new(size_fn),
             scale_factor: AtomicCell::new(1.0),
+            scale_factor_range: None,
             open: AtomicBool::new(false),
         })
     }
@@ -153,6 +161,27 @@ impl ViziaState {
         Arc::new(ViziaState {
             size_fn: Box::new(size_fn),
             scale_factor: AtomicCell::new(default_scale_factor),
+            scale_factor_range: None,
+            open: AtomicBool::new(false),
+        })
+    }
+
+    /// The same as [`new_with_default_scale_factor()`][Self::new_with_default_scale_factor()], but
+    /// this also allows the host to resize the window. Since the GUI's layout is determined by
+    /// `size_fn`, resizing the window from the host changes the user scale factor instead, which
+    /// means that the window's aspect ratio always stays the same. The resulting user scale factor
+    /// is clamped to `scale_factor_range`.
+    pub fn new_resizable(
+        size_fn: impl Fn() -> (u32, u32) + Send + Sync + 'static,
+        default_scale_factor: f64,
+        scale_factor_range: RangeInclusive<f64>,
+    ) -> Arc<ViziaState> {
+        Arc::new(ViziaState {
+            size_fn: Box::new(size_fn),
+            scale_factor: AtomicCell::new(
+                default_scale_factor.clamp(*scale_factor_range.start(), *scale_factor_range.end()),
+            ),
+            scale_factor_range: Some(scale_factor_range),
             open: AtomicBool::new(false),
         })
     }
@@ -66,6 +66,12 @@ pub enum RawParamEvent {
     SetParameterNormalized(ParamPtr, f32),
     /// End an automation gesture for a parameter.
     EndSetParameter(ParamPtr),
+    /// Ask the host to show its context menu for a parameter at a position in physical pixels
+    /// relative to the window. See
+    /// [`GuiContext::show_param_context_menu()`][nih_plug::prelude::GuiContext::show_param_context_menu()].
+    /// If the host cannot show a context menu, then the parameter is reset to its default value
+    /// instead.
+    ShowContextMenu(ParamPtr, i32, i32),
     /// Sent by the wrapper to indicate that one or more parameter values have changed. Useful when
     /// using properties based on a parameter's value that are computed inside of an event handler.
     ParametersChanged,
@@ -108,6 +114,14 @@ pub enum GuiContextEvent {
     Resize,
 }
 
+/// Internal events used to apply host-driven resizes to the window. Emitted from the editor's idle
+/// callback.
+pub(crate) enum HostResizeEvent {
+    /// The host has resized the window and the new user scale factor has already been stored in the
+    /// [`ViziaState`]. This only needs to be applied to the window.
+    SetUserScaleFactor(f64),
+}
+
 /// Handles parameter updates for VIZIA GUIs. Registered in
 /// [`ViziaEditor::spawn()`][super::ViziaEditor::spawn()].
 pub(crate) struct ParamModel {
@@ -138,6 +152,18 @@ impl Model for ParamModel {
                 self.context.raw_set_parameter_normalized(p, v)
             },
             RawParamEvent::EndSetParameter(p) => unsafe { self.context.raw_end_set_parameter(p) },
+            RawParamEvent::ShowContextMenu(p, x, y) => {
+                if !self.context.show_param_context_menu(p, x, y, Vec::new()) {
+                    // Right clicking a parameter used to reset it, so this is a sensible fallback
+                    // for hosts without context menus
+                    unsafe {
+                        self.context.raw_begin_set_parameter(p);
+                        self.context
+                            .raw_set_parameter_normalized(p, p.default_normalized_value());
+                        self.context.raw_end_set_parameter(p);
+                    }
+                }
+            }
             // This can be used by widgets to be notified when parameter values have changed
             RawParamEvent::ParametersChanged => (),
         });
@@ -157,6 +183,17 @@ impl Model for WindowModel {
             }
         });
 
+        event.map(|host_resize_event, meta| match *host_resize_event {
+            HostResizeEvent::SetUserScaleFactor(scale_factor) => {
+                // Since the `ViziaState` already contains this scale factor, the
+                // `WindowEvent::GeometryChanged` handler below won't send a resize request back to
+                // the host
+                cx.set_user_scale_factor(scale_factor);
+
+                meta.consume();
+            }
+        });
+
         // This gets fired whenever the inner window gets resized
         event.map(|window_event, _| {
             if let WindowEvent::GeometryChanged { .. } = window_event {
@@ -1,8 +1,9 @@
 //! Generic UIs for NIH-plug using VIZIA.
 
-use nih_plug::prelude::{ParamFlags, ParamPtr, Params};
+use nih_plug::prelude::{Param, ParamFlags, ParamPtr, Params};
 use vizia::prelude::*;
 
+use super::param_base::ParamWidgetBase;
 use super::{ParamSlider, ParamSliderExt, ParamSliderStyle};
 
 /// Shows a generic UI for a [`Params`] object. For additional flexibility you can either use the
@@ -77,6 +78,22 @@ impl GenericUi {
         PsRef: AsRef<Ps> + 'static,
         Ps: Params + 'static,
     {
+        // Read-only parameters can't be changed, so they're only displayed
+        if unsafe { param_ptr.flags() }.contains(ParamFlags::READ_ONLY) {
+            unsafe {
+                match param_ptr {
+                    ParamPtr::FloatParam(p) => Self::value_label(cx, params, move |_| &*p),
+                    ParamPtr::IntParam(p) => Self::value_label(cx, params, move |_| &*p),
+                    ParamPtr::BoolParam(p) => Self::value_label(cx, params, move |_| &*p),
+                    ParamPtr::EnumParam(p) => Self::value_label(cx, params, move |_| &*p),
+                }
+            }
+            .class("widget")
+            .class("read-only");
+
+            return;
+        }
+
         unsafe {
             match param_ptr {
                 ParamPtr::FloatParam(p) => ParamSlider::new(cx, params, move |_| &*p),
@@ -99,6 +116,26 @@ impl GenericUi {
         })
         .class("widget");
     }
+
+    /// Draw a label containing the parameter's current value. Used for read-only parameters.
+    fn value_label<L, Params, P, FMap>(
+        cx: &mut Context,
+        params: L,
+        params_to_param: FMap,
+    ) -> Handle<'_, Label>
+    where
+        L: Lens<Target = Params> + Clone,
+        Params: 'static,
+        P: Param + 'static,
+        FMap: Fn(&Params) -> &P + Copy + 'static,
+    {
+        Label::new(
+            cx,
+            ParamWidgetBase::make_lens(params, params_to_param, |param| {
+                param.normalized_value_to_string(param.modulated_normalized_value(), true)
+            }),
+        )
+    }
 }
 
 impl View for GenericUi {
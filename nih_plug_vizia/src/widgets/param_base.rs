@@ -3,7 +3,7 @@
 use nih_plug::prelude::*;
 use vizia::prelude::*;
 
-use super::RawParamEvent;
+use super::{ParamModel, RawParamEvent};
 
 /// A helper for creating parameter widgets. The general idea is that a parameter widget struct can
 /// adds a `ParamWidgetBase` field on its struct, and then calls [`ParamWidgetBase::view()`] in its
@@ -227,6 +227,27 @@ impl ParamWidgetBase {
         cx.emit(RawParamEvent::EndSetParameter(self.param_ptr));
     }
 
+    /// Ask the host to show its context menu for the parameter at the mouse cursor's position.
+    /// Usually this is done on right click. If the host does not support this, then the parameter
+    /// is reset to its default value instead.
+    pub fn show_context_menu(&self, cx: &mut EventContext) {
+        cx.emit(RawParamEvent::ShowContextMenu(
+            self.param_ptr,
+            cx.mouse().cursorx.round() as i32,
+            cx.mouse().cursory.round() as i32,
+        ));
+    }
+
+    /// The host's current indication for the parameter, for instance because it has been mapped
+    /// to a hardware controller. See
+    /// [`GuiContext::param_indication()`][nih_plug::prelude::GuiContext::param_indication()].
+    /// Widgets receive a [`RawParamEvent::ParametersChanged`] event when this changes.
+    pub fn indication(&self, cx: &impl DataContext) -> ParamIndication {
+        cx.data::<ParamModel>()
+            .map(|model| model.context.param_indication(self.param_ptr))
+            .unwrap_or_default()
+    }
+
     param_ptr_forward!(pub fn name(&self) -> &str);
     param_ptr_forward!(pub fn unit(&self) -> &'static str);
     param_ptr_forward!(pub fn poly_modulation_id(&self) -> Option<u32>);
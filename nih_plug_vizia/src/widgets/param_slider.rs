@@ -1,10 +1,11 @@
 //! A slider that integrates with NIH-plug's [`Param`] types.
 
-use nih_plug::prelude::Param;
+use nih_plug::prelude::{Param, ParamIndication};
 use vizia::prelude::*;
 
 use super::param_base::ParamWidgetBase;
 use super::util::{self, ModifiersExt};
+use super::RawParamEvent;
 
 /// When shift+dragging a parameter, one pixel dragged corresponds to this much change in the
 /// normalized parameter.
@@ -26,6 +27,9 @@ pub struct ParamSlider {
     /// dragging for higher precision dragging. This is a `None` value when granular dragging is not
     /// active.
     granular_drag_status: Option<GranularDragStatus>,
+    /// The host's indication color for the parameter packed as `0xRRGGBBAA`, if it has one. See
+    /// [`ParamSliderExt::show_indication()`].
+    indication_color: Option<u32>,
 
     // These fields are set through modifiers:
     /// Whether or not to listen to scroll events for changing the parameter's value in steps.
@@ -37,6 +41,8 @@ pub struct ParamSlider {
     style: ParamSliderStyle,
     /// A specific label to use instead of displaying the parameter's value.
     label_override: Option<String>,
+    /// Whether to draw the host's indication color for the parameter.
+    show_indication: bool,
 }
 
 /// How the [`ParamSlider`] should display its values. Set this using
@@ -101,17 +107,22 @@ impl ParamSlider {
         // We'll visualize the difference between the current value and the default value if the
         // default value lies somewhere in the middle and the parameter is continuous. Otherwise
         // this approach looks a bit jarring.
+        let param_base = ParamWidgetBase::new(cx, params, params_to_param);
+        let indication_color = pack_indication_color(&param_base.indication(&*cx));
+
         Self {
-            param_base: ParamWidgetBase::new(cx, params, params_to_param),
+            param_base,
 
             text_input_active: false,
             drag_active: false,
             granular_drag_status: None,
+            indication_color,
 
             use_scroll_wheel: true,
             scrolled_lines: 0.0,
             style: ParamSliderStyle::Centered,
             label_override: None,
+            show_indication: false,
         }
         .build(
             cx,
@@ -170,6 +181,7 @@ impl ParamSlider {
                                         fill_start_delta_lens,
                                         modulation_start_delta_lens,
                                     );
+                                    Self::slider_indication_view(cx);
                                     Self::slider_label_view(
                                         cx,
                                         param_data.param(),
@@ -252,6 +264,28 @@ impl ParamSlider {
             .hoverable(false);
     }
 
+    /// Create a strip showing the host's indication color for the parameter, if it has one and
+    /// [`ParamSliderExt::show_indication()`] has been used.
+    fn slider_indication_view(cx: &mut Context) {
+        Binding::new(cx, ParamSlider::show_indication, |cx, show_indication| {
+            if !show_indication.get(cx) {
+                return;
+            }
+
+            Binding::new(cx, ParamSlider::indication_color, |cx, indication_color| {
+                if let Some(color) = indication_color.get(cx) {
+                    let [red, green, blue, alpha] = color.to_be_bytes();
+                    Element::new(cx)
+                        .class("indication")
+                        .height(Stretch(1.0))
+                        .width(Pixels(3.0))
+                        .background_color(Color::rgba(red, green, blue, alpha))
+                        .hoverable(false);
+                }
+            });
+        });
+    }
+
     /// Create the text part of the slider. Shown on top of the fill using a `ZStack`.
     fn slider_label_view<P: Param, L: Lens<Target = String>>(
         cx: &mut Context,
@@ -425,6 +459,12 @@ impl View for ParamSlider {
     }
 
     fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|param_event, _| {
+            if let RawParamEvent::ParametersChanged = param_event {
+                self.indication_color = pack_indication_color(&self.param_base.indication(&*cx));
+            }
+        });
+
         event.map(|param_slider_event, meta| match param_slider_event {
             ParamSliderEvent::CancelTextInput => {
                 self.text_input_active = false;
@@ -492,12 +532,9 @@ impl View for ParamSlider {
 
                 meta.consume();
             }
-            WindowEvent::MouseDoubleClick(MouseButton::Left)
-            | WindowEvent::MouseDown(MouseButton::Right)
-            | WindowEvent::MouseDoubleClick(MouseButton::Right)
-            | WindowEvent::MouseTripleClick(MouseButton::Right) => {
-                // Ctrl+Click, double click, and right clicks should reset the parameter instead of
-                // initiating a drag operation
+            WindowEvent::MouseDoubleClick(MouseButton::Left) => {
+                // Ctrl+Click and double click should reset the parameter instead of initiating a
+                // drag operation
                 self.param_base.begin_set_parameter(cx);
                 self.param_base
                     .set_normalized_value(cx, self.param_base.default_normalized_value());
@@ -505,6 +542,17 @@ impl View for ParamSlider {
 
                 meta.consume();
             }
+            WindowEvent::MouseDown(MouseButton::Right)
+            | WindowEvent::MouseDoubleClick(MouseButton::Right)
+            | WindowEvent::MouseTripleClick(MouseButton::Right) => {
+                // Right clicks show the host's context menu for the parameter. If the host doesn't
+                // support that, then this resets the parameter like a double click would.
+                if !self.text_input_active {
+                    self.param_base.show_context_menu(cx);
+                }
+
+                meta.consume();
+            }
             WindowEvent::MouseUp(MouseButton::Left) => {
                 if self.drag_active {
                     self.drag_active = false;
@@ -617,6 +665,11 @@ pub trait ParamSliderExt {
     /// Manually set a fixed label for the slider instead of displaying the current value. This is
     /// currently not reactive.
     fn with_label(self, value: impl Into<String>) -> Self;
+
+    /// Draw a strip in the color the host uses to indicate the parameter, for instance when the
+    /// parameter has been mapped to a hardware controller or when its automation is playing. This
+    /// is only supported by CLAP hosts that implement the `param-indication` extension.
+    fn show_indication(self) -> Self;
 }
 
 impl ParamSliderExt for Handle<'_, ParamSlider> {
@@ -633,4 +686,15 @@ impl ParamSliderExt for Handle<'_, ParamSlider> {
             param_slider.label_override = Some(value.into())
         })
     }
+
+    fn show_indication(self) -> Self {
+        self.modify(|param_slider: &mut ParamSlider| param_slider.show_indication = true)
+    }
+}
+
+/// Pack a parameter indication's color into a `0xRRGGBBAA` integer so it can be used in a lens.
+fn pack_indication_color(indication: &ParamIndication) -> Option<u32> {
+    indication
+        .color()
+        .map(|color| u32::from_be_bytes([color.red, color.green, color.blue, color.alpha]))
 }
@@ -183,6 +183,7 @@ impl Plugin for Crossover {
             aux_inputs: &[],
             aux_outputs: &["Band 1", "Band 2", "Band 3", "Band 4", "Band 5"],
         },
+        channel_layouts: PortChannelLayouts::const_default(),
     }];
 
     type SysExMessage = ();
@@ -142,14 +142,9 @@ impl Plugin for Diopser {
     fn filter_state(state: &mut PluginState) {
         // Safe-mode is enabled by default, so to avoid changing the behavior we'll keep it disabled
         // for older presets
-        if semver::Version::parse(&state.version)
-            .map(|version| version < semver::Version::parse("0.4.0").unwrap())
-            .unwrap_or(true)
-        {
-            state
-                .fields
-                .insert(String::from("safe-mode"), String::from("false"));
-        }
+        StateMigrations::new()
+            .add("0.4.0", [MigrationStep::set_field("safe-mode", "false")])
+            .apply(state);
     }
 
     fn initialize(
@@ -134,6 +134,8 @@ impl Plugin for Gain {
             // are generated as needed. This layout will be called 'Stereo', while the other one is
             // given the name 'Mono' based no the number of input and output channels.
             names: PortNames::const_default(),
+            // Surround and ambisonic ports can be given a speaker arrangement here
+            channel_layouts: PortChannelLayouts::const_default(),
         },
         AudioIOLayout {
             main_input_channels: NonZeroU32::new(1),
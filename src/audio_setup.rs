@@ -30,6 +30,9 @@ pub struct AudioIOLayout {
     /// Optional names for the audio ports. Defining these can be useful for plugins with multiple
     /// output and input ports.
     pub names: PortNames,
+    /// Optional speaker arrangements for the audio ports. Without these, ports with more than two
+    /// channels are treated as a number of discrete channels.
+    pub channel_layouts: PortChannelLayouts,
 }
 
 /// Construct a `NonZeroU32` value at compile time. Equivalent to `NonZeroU32::new(n).unwrap()`.
@@ -40,15 +43,34 @@ pub const fn new_nonzero_u32(n: u32) -> NonZeroU32 {
     }
 }
 
-/// Contains auxiliary (sidechain) input and output buffers for a process call.
-pub struct AuxiliaryBuffers<'a> {
+/// Contains auxiliary (sidechain) input and output buffers for a process call. These contain `f64`
+/// samples when the plugin processes audio in double precision using
+/// [`Plugin::process_f64()`][crate::prelude::Plugin::process_f64()].
+pub struct AuxiliaryBuffers<'a, T = f32> {
+    /// A copy of all of the main input's channels. The main input is already copied to the main
+    /// output buffer passed to [`Plugin::process()`][crate::prelude::Plugin::process()], but if
+    /// the main input has more channels than the main output (for instance in a stereo-to-mono
+    /// layout) then the excess channels are only available here. This buffer only has channels if
+    /// the layout's main input has more channels than its main output, regardless of whether the
+    /// host processes audio in place. In all other layouts the main output buffer already contains
+    /// the entire input. The data in this buffer can safely be overwritten.
+    pub main_input: &'a mut Buffer<'a, T>,
     /// Buffers for all auxiliary (sidechain) inputs defined for this plugin. The data in these
     /// buffers can safely be overwritten. Auxiliary inputs can be defined using the
     /// [`AudioIOLayout::aux_input_ports`] field.
-    pub inputs: &'a mut [Buffer<'a>],
+    pub inputs: &'a mut [Buffer<'a, T>],
     /// Buffers for all auxiliary outputs defined for this plugin. Auxiliary outputs can be defined using the
     /// [`AudioIOLayout::aux_output_ports`] field.
-    pub outputs: &'a mut [Buffer<'a>],
+    pub outputs: &'a mut [Buffer<'a, T>],
+    /// Whether the host has activated the auxiliary inputs in [`inputs`][Self::inputs]. Hosts may
+    /// deactivate inputs that aren't connected to anything, in which case the buffer only contains
+    /// silence and any analysis of that input can be skipped entirely. Ports are active unless the
+    /// host explicitly deactivates them, which is currently only supported by CLAP and VST3.
+    pub inputs_active: &'a [bool],
+    /// Whether the host has activated the auxiliary outputs in [`outputs`][Self::outputs]. Inactive
+    /// outputs are not used by the host, so the plugin doesn't need to write anything to them. The
+    /// host may not provide any buffers for these outputs, in which case they contain empty slices.
+    pub outputs_active: &'a [bool],
 }
 
 /// Contains names for the ports defined in an `AudioIOLayout`. Setting these is optional, but it
@@ -74,6 +96,43 @@ pub struct PortNames {
     pub aux_outputs: &'static [&'static str],
 }
 
+/// Contains speaker arrangements for the ports defined in an `AudioIOLayout`. Setting these is
+/// optional. A port's channel layout needs to have the same number of channels as the port itself,
+/// see [`ChannelLayout::num_channels()`]. Ports without a channel layout use the host's default
+/// arrangement for their channel count.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PortChannelLayouts {
+    /// The channel layout for the main input port.
+    pub main_input: Option<ChannelLayout>,
+    /// The channel layout for the main output port.
+    pub main_output: Option<ChannelLayout>,
+    /// Channel layouts for the auxiliary (sidechain) input ports. Ports past the end of this slice
+    /// don't have a channel layout.
+    pub aux_inputs: &'static [Option<ChannelLayout>],
+    /// Channel layouts for the auxiliary output ports. Ports past the end of this slice don't have
+    /// a channel layout.
+    pub aux_outputs: &'static [Option<ChannelLayout>],
+}
+
+/// A speaker arrangement for a surround or ambisonic audio port. The variants' docstrings list the
+/// order the channels appear in in the port's buffers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChannelLayout {
+    /// 5.1 surround. The channels are left, right, center, LFE, left surround, and right surround.
+    Surround5_1,
+    /// 7.1 surround. The channels are left, right, center, LFE, left rear surround, right rear
+    /// surround, left side surround, and right side surround.
+    Surround7_1,
+    /// 7.1.4 surround. This contains the same channels as [`Surround7_1`][Self::Surround7_1],
+    /// followed by the top front left, top front right, top rear left, and top rear right channels.
+    Surround7_1_4,
+    /// Full sphere ambisonics of the given order, with `(order + 1)^2` channels in ACN channel
+    /// order using SN3D normalization (also known as AmbiX). The order must be between 1 and
+    /// [`MAX_AMBISONIC_ORDER`][Self::MAX_AMBISONIC_ORDER], and ports with other orders don't get a
+    /// channel layout. VST3 only supports up to fourth order ambisonics.
+    Ambisonic { order: u32 },
+}
+
 /// Configuration for (the host's) audio buffers.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct BufferConfig {
@@ -115,6 +174,7 @@ impl AudioIOLayout {
             aux_input_ports: &[],
             aux_output_ports: &[],
             names: PortNames::const_default(),
+            channel_layouts: PortChannelLayouts::const_default(),
         }
     }
 
@@ -125,6 +185,39 @@ impl AudioIOLayout {
             return name.to_owned();
         }
 
+        // Surround and ambisonic layouts are named after their main ports' channel layouts
+        let main_input_channel_layout = self.main_input_channel_layout();
+        let main_output_channel_layout = self.main_output_channel_layout();
+        if main_input_channel_layout.is_some() || main_output_channel_layout.is_some() {
+            let port_description = |channel_layout: Option<ChannelLayout>, num_channels| match (
+                channel_layout,
+                num_channels,
+            ) {
+                (Some(channel_layout), _) => channel_layout.name(),
+                (None, 1) => String::from("Mono"),
+                (None, 2) => String::from("Stereo"),
+                (None, n) => format!("{n} channels"),
+            };
+            let input_description = self.main_input_channels.map(|num_channels| {
+                port_description(main_input_channel_layout, num_channels.get())
+            });
+            let output_description = self.main_output_channels.map(|num_channels| {
+                port_description(main_output_channel_layout, num_channels.get())
+            });
+
+            let name = match (input_description, output_description) {
+                (Some(input), Some(output)) if input != output => format!("{input} to {output}"),
+                (_, Some(description)) | (Some(description), None) => description,
+                (None, None) => unreachable!(),
+            };
+
+            return if self.aux_input_ports.is_empty() {
+                name
+            } else {
+                format!("{name} with sidechain")
+            };
+        }
+
         // If the name is not set then we'll try to come up with something descriptive
         match (
             self.main_input_channels
@@ -160,6 +253,35 @@ impl AudioIOLayout {
         self.names.main_input.unwrap_or("Output").to_owned()
     }
 
+    /// The main input port's channel layout, if it has one. See
+    /// [`PortChannelLayouts`].
+    pub fn main_input_channel_layout(&self) -> Option<ChannelLayout> {
+        checked_channel_layout(self.channel_layouts.main_input, self.main_input_channels)
+    }
+
+    /// The main output port's channel layout, if it has one. See [`PortChannelLayouts`].
+    pub fn main_output_channel_layout(&self) -> Option<ChannelLayout> {
+        checked_channel_layout(self.channel_layouts.main_output, self.main_output_channels)
+    }
+
+    /// The channel layout for the auxiliary input port with the given index, if it has one. See
+    /// [`PortChannelLayouts`].
+    pub fn aux_input_channel_layout(&self, idx: usize) -> Option<ChannelLayout> {
+        checked_channel_layout(
+            self.channel_layouts.aux_inputs.get(idx).copied().flatten(),
+            self.aux_input_ports.get(idx).copied(),
+        )
+    }
+
+    /// The channel layout for the auxiliary output port with the given index, if it has one. See
+    /// [`PortChannelLayouts`].
+    pub fn aux_output_channel_layout(&self, idx: usize) -> Option<ChannelLayout> {
+        checked_channel_layout(
+            self.channel_layouts.aux_outputs.get(idx).copied().flatten(),
+            self.aux_output_ports.get(idx).copied(),
+        )
+    }
+
     /// The name for the auxiliary input port with the given index. Either generated or taken from
     /// the `names` field.
     pub fn aux_input_name(&self, idx: usize) -> Option<String> {
@@ -189,6 +311,36 @@ impl AudioIOLayout {
     }
 }
 
+/// Only return a port's channel layout if the port exists and the number of channels match.
+fn checked_channel_layout(
+    channel_layout: Option<ChannelLayout>,
+    num_channels: Option<NonZeroU32>,
+) -> Option<ChannelLayout> {
+    let channel_layout = channel_layout?;
+    let num_channels = num_channels?.get();
+    if let ChannelLayout::Ambisonic { order } = channel_layout {
+        if !(1..=ChannelLayout::MAX_AMBISONIC_ORDER).contains(&order) {
+            nih_debug_assert_failure!(
+                "Ambisonic orders must be between 1 and {}, ignoring the {:?} channel layout",
+                ChannelLayout::MAX_AMBISONIC_ORDER,
+                channel_layout
+            );
+            return None;
+        }
+    }
+
+    if channel_layout.num_channels() == Some(num_channels) {
+        Some(channel_layout)
+    } else {
+        nih_debug_assert_failure!(
+            "The {:?} channel layout is used for a port with {} channels, ignoring it",
+            channel_layout,
+            num_channels
+        );
+        None
+    }
+}
+
 impl PortNames {
     /// [`PortNames::default()`], but as a const function. Used when initializing
     /// `Plugin::AUDIO_IO_LAYOUTS`. (<https://github.com/rust-lang/rust/issues/67792>)
@@ -202,3 +354,147 @@ impl PortNames {
         }
     }
 }
+
+impl PortChannelLayouts {
+    /// [`PortChannelLayouts::default()`], but as a const function. Used when initializing
+    /// `Plugin::AUDIO_IO_LAYOUTS`. (<https://github.com/rust-lang/rust/issues/67792>)
+    pub const fn const_default() -> Self {
+        Self {
+            main_input: None,
+            main_output: None,
+            aux_inputs: &[],
+            aux_outputs: &[],
+        }
+    }
+}
+
+impl ChannelLayout {
+    /// The highest supported order for [`Ambisonic`][Self::Ambisonic] layouts. Seventh order
+    /// ambisonics uses 64 channels.
+    pub const MAX_AMBISONIC_ORDER: u32 = 7;
+
+    /// The number of channels in this layout. Returns `None` if an ambisonic layout's order is so
+    /// high that the number of channels does not fit in a `u32`.
+    pub const fn num_channels(&self) -> Option<u32> {
+        match self {
+            ChannelLayout::Surround5_1 => Some(6),
+            ChannelLayout::Surround7_1 => Some(8),
+            ChannelLayout::Surround7_1_4 => Some(12),
+            ChannelLayout::Ambisonic { order } => match order.checked_add(1) {
+                Some(order_plus_one) => order_plus_one.checked_mul(order_plus_one),
+                None => None,
+            },
+        }
+    }
+
+    /// A human readable name for this layout, like `5.1 Surround` or `3rd order Ambisonics`.
+    pub fn name(&self) -> String {
+        match self {
+            ChannelLayout::Surround5_1 => String::from("5.1 Surround"),
+            ChannelLayout::Surround7_1 => String::from("7.1 Surround"),
+            ChannelLayout::Surround7_1_4 => String::from("7.1.4 Surround"),
+            ChannelLayout::Ambisonic { order } => {
+                let suffix = match (order % 10, order % 100) {
+                    (_, 11..=13) => "th",
+                    (1, _) => "st",
+                    (2, _) => "nd",
+                    (3, _) => "rd",
+                    _ => "th",
+                };
+
+                format!("{order}{suffix} order Ambisonics")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surround_layout_names() {
+        let layout = AudioIOLayout {
+            main_input_channels: Some(new_nonzero_u32(6)),
+            main_output_channels: Some(new_nonzero_u32(6)),
+            channel_layouts: PortChannelLayouts {
+                main_input: Some(ChannelLayout::Surround5_1),
+                main_output: Some(ChannelLayout::Surround5_1),
+                ..PortChannelLayouts::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        };
+        assert_eq!(layout.name(), "5.1 Surround");
+
+        let layout = AudioIOLayout {
+            main_input_channels: Some(new_nonzero_u32(16)),
+            main_output_channels: Some(new_nonzero_u32(2)),
+            aux_input_ports: &[new_nonzero_u32(2)],
+            channel_layouts: PortChannelLayouts {
+                main_input: Some(ChannelLayout::Ambisonic { order: 3 }),
+                ..PortChannelLayouts::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        };
+        assert_eq!(
+            layout.name(),
+            "3rd order Ambisonics to Stereo with sidechain"
+        );
+    }
+
+    #[test]
+    fn aux_channel_layouts() {
+        let layout = AudioIOLayout {
+            main_output_channels: Some(new_nonzero_u32(2)),
+            aux_output_ports: &[new_nonzero_u32(8), new_nonzero_u32(12)],
+            channel_layouts: PortChannelLayouts {
+                aux_outputs: &[None, Some(ChannelLayout::Surround7_1_4)],
+                ..PortChannelLayouts::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        };
+        assert_eq!(layout.aux_output_channel_layout(0), None);
+        assert_eq!(
+            layout.aux_output_channel_layout(1),
+            Some(ChannelLayout::Surround7_1_4)
+        );
+        assert_eq!(layout.name(), "Stereo");
+    }
+
+    #[test]
+    fn ambisonic_channel_counts() {
+        assert_eq!(
+            ChannelLayout::Ambisonic { order: 1 }.num_channels(),
+            Some(4)
+        );
+        assert_eq!(
+            ChannelLayout::Ambisonic {
+                order: ChannelLayout::MAX_AMBISONIC_ORDER
+            }
+            .num_channels(),
+            Some(64)
+        );
+        assert_eq!(
+            ChannelLayout::Ambisonic { order: 65535 }.num_channels(),
+            None
+        );
+        assert_eq!(
+            ChannelLayout::Ambisonic { order: u32::MAX }.num_channels(),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn zeroth_order_ambisonics() {
+        let layout = AudioIOLayout {
+            main_input_channels: Some(new_nonzero_u32(1)),
+            channel_layouts: PortChannelLayouts {
+                main_input: Some(ChannelLayout::Ambisonic { order: 0 }),
+                ..PortChannelLayouts::const_default()
+            },
+            ..AudioIOLayout::const_default()
+        };
+        layout.main_input_channel_layout();
+    }
+}
@@ -13,10 +13,14 @@ pub use samples::{ChannelSamples, ChannelSamplesIter, SamplesIter};
 /// and efficiently iterate over the samples, or you can do your own thing using the raw audio
 /// buffers.
 ///
+/// Buffers contain single precision `f32` samples by default. Plugins that opt into double
+/// precision processing through [`Plugin::PROCESS_F64`][crate::prelude::Plugin::PROCESS_F64]
+/// receive a `Buffer<f64>` instead.
+///
 /// TODO: This lifetime makes zero sense because you're going to need unsafe lifetime casts to use
 ///       this either way. Maybe just get rid of it in favor for raw pointers.
 #[derive(Default)]
-pub struct Buffer<'a> {
+pub struct Buffer<'a, T = f32> {
     /// The number of samples contained within `output_slices`. This needs to be stored separately
     /// to be able to handle 0 channel IO for MIDI-only plugins.
     num_samples: usize,
@@ -28,10 +32,10 @@ pub struct Buffer<'a> {
     /// because this `Buffers` either cannot have the same lifetime as the separately stored output
     /// buffers, and it also cannot be stored in a field next to it because that would mean
     /// containing mutable references to data stored in a mutex.
-    output_slices: Vec<&'a mut [f32]>,
+    output_slices: Vec<&'a mut [T]>,
 }
 
-impl<'a> Buffer<'a> {
+impl<'a, T> Buffer<'a, T> {
     /// Returns the number of samples per channel in this buffer.
     #[inline]
     pub fn samples(&self) -> usize {
@@ -52,20 +56,20 @@ impl<'a> Buffer<'a> {
 
     /// Obtain the raw audio buffers.
     #[inline]
-    pub fn as_slice(&mut self) -> &mut [&'a mut [f32]] {
+    pub fn as_slice(&mut self) -> &mut [&'a mut [T]] {
         &mut self.output_slices
     }
 
     /// The same as [`as_slice()`][Self::as_slice()], but for a non-mutable reference. This is
     /// usually not needed.
     #[inline]
-    pub fn as_slice_immutable(&self) -> &[&'a mut [f32]] {
+    pub fn as_slice_immutable(&self) -> &[&'a mut [T]] {
         &self.output_slices
     }
 
     /// Iterate over the samples, returning a channel iterator for each sample.
     #[inline]
-    pub fn iter_samples<'slice>(&'slice mut self) -> SamplesIter<'slice, 'a> {
+    pub fn iter_samples<'slice>(&'slice mut self) -> SamplesIter<'slice, 'a, T> {
         SamplesIter {
             buffers: self.output_slices.as_mut_slice(),
             current_sample: 0,
@@ -99,7 +103,10 @@ impl<'a> Buffer<'a> {
     /// }
     /// ````
     #[inline]
-    pub fn iter_blocks<'slice>(&'slice mut self, max_block_size: usize) -> BlocksIter<'slice, 'a> {
+    pub fn iter_blocks<'slice>(
+        &'slice mut self,
+        max_block_size: usize,
+    ) -> BlocksIter<'slice, 'a, T> {
         BlocksIter {
             buffers: self.output_slices.as_mut_slice(),
             max_block_size,
@@ -121,7 +128,7 @@ impl<'a> Buffer<'a> {
     pub unsafe fn set_slices(
         &mut self,
         num_samples: usize,
-        update: impl FnOnce(&mut Vec<&'a mut [f32]>),
+        update: impl FnOnce(&mut Vec<&'a mut [T]>),
     ) {
         self.num_samples = num_samples;
         update(&mut self.output_slices);
@@ -139,7 +146,7 @@ mod miri {
 
     #[test]
     fn repeated_access() {
-        let mut real_buffers = vec![vec![0.0; 512]; 2];
+        let mut real_buffers = vec![vec![0.0f32; 512]; 2];
         let mut buffer = Buffer::default();
         unsafe {
             buffer.set_slices(512, |output_slices| {
@@ -167,7 +174,7 @@ mod miri {
 
     #[test]
     fn repeated_slices() {
-        let mut real_buffers = vec![vec![0.0; 512]; 2];
+        let mut real_buffers = vec![vec![0.0f32; 512]; 2];
         let mut buffer = Buffer::default();
         unsafe {
             buffer.set_slices(512, |output_slices| {
@@ -8,6 +8,7 @@ pub mod process;
 
 // Contexts for more plugin-API specific features
 pub mod remote_controls;
+pub mod track_info;
 
 /// The currently active plugin API. This may be useful to display in an about screen in the
 /// plugin's GUI for debugging purposes.
@@ -27,3 +28,13 @@ impl Display for PluginApi {
         }
     }
 }
+
+/// An RGBA color provided by the host, for instance for the track the plugin has been inserted on
+/// or to highlight a parameter that has been mapped to a hardware controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HostColor {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
@@ -2,8 +2,11 @@
 
 use std::sync::Arc;
 
+use super::track_info::TrackInfo;
 use super::PluginApi;
-use crate::prelude::{Param, ParamPtr, Plugin, PluginState};
+use crate::prelude::{
+    MidiMapping, Param, ParamIndication, ParamInfoChanges, ParamPtr, Plugin, PluginState,
+};
 
 /// Callbacks the plugin can make when the user interacts with its GUI such as updating parameter
 /// values. This is passed to the plugin during [`Editor::spawn()`][crate::prelude::Editor::spawn()]. All of
@@ -26,6 +29,47 @@ pub trait GuiContext: Send + Sync + 'static {
     /// TODO: Host->Plugin resizing has not been implemented yet
     fn request_resize(&self) -> bool;
 
+    /// Inform the host that the plugin's [`note_names()`][crate::prelude::Plugin::note_names()]
+    /// have changed and that they should be queried again.
+    fn note_names_changed(&self);
+
+    /// Inform the host that the plugin's parameter names, value strings, or default values have
+    /// changed at runtime and that they should be queried again. `changes` describes what has
+    /// changed, and the host may skip rescanning anything that hasn't.
+    fn param_info_changed(&self, changes: ParamInfoChanges);
+
+    /// Get information about the track the plugin has been inserted on, like the track's name and
+    /// color. Returns `None` if the host does not provide this information. The editor is notified
+    /// when this changes through
+    /// [`Editor::track_info_changed()`][crate::prelude::Editor::track_info_changed()].
+    fn track_info(&self) -> Option<TrackInfo>;
+
+    /// Get the host's current indication for a parameter, like whether it has been mapped to a
+    /// hardware controller and the color that should be used to highlight it. This returns the
+    /// default indication if the host does not provide this information. The editor is notified
+    /// when this changes through
+    /// [`Editor::param_indication_changed()`][crate::prelude::Editor::param_indication_changed()].
+    fn param_indication(&self, param: ParamPtr) -> ParamIndication;
+
+    /// Ask the host to show its context menu for a parameter, for instance when the parameter's
+    /// widget is right clicked. This menu usually contains options for automating, modulating, or
+    /// MIDI learning the parameter. `items` are added to the host's menu, and their actions are
+    /// called on the GUI thread when the user selects them. The `x` and `y` coordinates are in
+    /// physical pixels relative to the top left corner of the editor's window. Use
+    /// [`ParamSetter::show_context_menu()`] for a more convenient API.
+    ///
+    /// Returns `false` if the host does not support showing context menus for parameters, in which
+    /// case the editor may want to fall back to other behavior. This is currently supported by
+    /// CLAP hosts that implement the `context-menu` extension and by VST3 hosts that implement
+    /// `IComponentHandler3`.
+    fn show_param_context_menu(
+        &self,
+        param: ParamPtr,
+        x: i32,
+        y: i32,
+        items: Vec<ContextMenuItem>,
+    ) -> bool;
+
     /// Inform the host a parameter will be automated. Create a [`ParamSetter`] and use
     /// [`ParamSetter::begin_set_parameter()`] instead for a safe, user friendly API.
     ///
@@ -54,15 +98,52 @@ pub trait GuiContext: Send + Sync + 'static {
     /// mostly marked as unsafe for API reasons.
     unsafe fn raw_end_set_parameter(&self, param: ParamPtr);
 
+    /// Change several parameters at once as a single grouped edit, using `(param, normalized_value)`
+    /// pairs. Every change is sent with its own automation gesture, and the plugin sees all of the
+    /// new values in the same block. Create a [`ParamBatch`] and use
+    /// [`ParamSetter::set_parameters()`] instead for a safe, user friendly API.
+    ///
+    /// # Safety
+    ///
+    /// The implementing function still needs to check if the parameters actually exist. This
+    /// function is mostly marked as unsafe for API reasons.
+    unsafe fn raw_set_parameters_normalized(&self, changes: &[(ParamPtr, f32)]);
+
     /// Serialize the plugin's current state to a serde-serializable object. Useful for implementing
-    /// preset handling within a plugin's GUI.
+    /// preset handling within a plugin's GUI. The state is serialized using
+    /// [`StateContext::Preset`][crate::prelude::StateContext::Preset].
     fn get_state(&self) -> PluginState;
 
     /// Restore the state from a previously serialized state object. This will block the GUI thread
     /// until the state has been restored and a parameter value rescan has been requested from the
     /// host. If the plugin is currently processing audio, then the parameter values will be
-    /// restored at the end of the current processing cycle.
+    /// restored at the end of the current processing cycle. Like with
+    /// [`get_state()`][Self::get_state()], the state is loaded using
+    /// [`StateContext::Preset`][crate::prelude::StateContext::Preset].
     fn set_state(&self, state: PluginState);
+
+    /// Start MIDI learn for a parameter. The next MIDI CC, 14-bit CC, or NRPN the plugin receives
+    /// will be mapped to this parameter, replacing the parameter's existing mappings. Returns
+    /// `false` if the parameter cannot be mapped because it is non-automatable or read-only, or
+    /// because the plugin does not receive MIDI CCs. See [`crate::midi::learn`] for more
+    /// information, and use [`ParamSetter::start_midi_learn()`] for a more convenient API.
+    fn start_midi_learn(&self, param: ParamPtr) -> bool;
+
+    /// Stop MIDI learn without mapping a controller, if it is active.
+    fn cancel_midi_learn(&self);
+
+    /// The parameter MIDI learn is currently active for, if any. This changes back to `None` once
+    /// a controller has been learned, so the editor can poll this to update its MIDI learn
+    /// indicator.
+    fn midi_learn_target(&self) -> Option<ParamPtr>;
+
+    /// Get the plugin's current MIDI learn mappings.
+    fn midi_mappings(&self) -> Vec<MidiMapping>;
+
+    /// Replace the plugin's MIDI learn mappings. This can be used to edit a mapping's range and
+    /// curve, or to remove mappings. Mappings for unknown, non-automatable, and read-only
+    /// parameters are ignored.
+    fn set_midi_mappings(&self, mappings: Vec<MidiMapping>);
 }
 
 /// An way to run background tasks from the plugin's GUI, equivalent to the
@@ -95,6 +176,33 @@ impl<P: Plugin> Clone for AsyncExecutor<P> {
     }
 }
 
+/// An item the plugin adds to a parameter's context menu. See
+/// [`GuiContext::show_param_context_menu()`].
+#[derive(Clone)]
+pub enum ContextMenuItem {
+    /// A regular menu entry. `action` is called when the entry is selected.
+    Entry {
+        label: String,
+        enabled: bool,
+        action: Arc<dyn Fn() + Send + Sync>,
+    },
+    /// A menu entry with a check mark. `action` is called when the entry is selected, it's up to
+    /// the plugin to toggle the state.
+    CheckEntry {
+        label: String,
+        enabled: bool,
+        checked: bool,
+        action: Arc<dyn Fn() + Send + Sync>,
+    },
+    /// A separator line.
+    Separator,
+    /// A submenu containing more items.
+    Submenu {
+        label: String,
+        items: Vec<ContextMenuItem>,
+    },
+}
+
 /// A convenience helper for setting parameter values. Any changes made here will be broadcasted to
 /// the host and reflected in the plugin's [`Params`][crate::params::Params] object. These
 /// functions should only be called from the main thread.
@@ -102,6 +210,15 @@ pub struct ParamSetter<'a> {
     pub raw_context: &'a dyn GuiContext,
 }
 
+/// A group of parameter changes that is applied at once using [`ParamSetter::set_parameters()`],
+/// for instance when loading an A/B snapshot or when morphing between two states. Hosts record
+/// these changes as a single edit, and the plugin sees all of the new values in the same block.
+#[derive(Debug, Default, Clone)]
+pub struct ParamBatch {
+    /// The `(param, normalized_value)` pairs in this batch. Every parameter occurs at most once.
+    changes: Vec<(ParamPtr, f32)>,
+}
+
 impl<P: Plugin> AsyncExecutor<P> {
     /// Execute a task on a background thread using `[Plugin::task_executor]`. This allows you to
     /// defer expensive tasks for later without blocking either the process function or the GUI
@@ -128,6 +245,44 @@ impl<P: Plugin> AsyncExecutor<P> {
     }
 }
 
+impl ContextMenuItem {
+    /// A shorthand for creating an enabled [`ContextMenuItem::Entry`].
+    pub fn entry(label: impl Into<String>, action: impl Fn() + Send + Sync + 'static) -> Self {
+        Self::Entry {
+            label: label.into(),
+            enabled: true,
+            action: Arc::new(action),
+        }
+    }
+}
+
+impl ParamBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a change to the specified parameter value to the batch. If the batch already contains a
+    /// change for this parameter, then that change is replaced.
+    pub fn set_parameter<P: Param>(&mut self, param: &P, value: P::Plain) {
+        self.set_parameter_normalized(param, param.preview_normalized(value));
+    }
+
+    /// Add a change to an already normalized parameter value to the batch. See
+    /// [`ParamSetter::set_parameter_normalized()`] for the caveats.
+    pub fn set_parameter_normalized<P: Param>(&mut self, param: &P, normalized: f32) {
+        let ptr = param.as_ptr();
+        match self.changes.iter_mut().find(|(param, _)| *param == ptr) {
+            Some((_, value)) => *value = normalized,
+            None => self.changes.push((ptr, normalized)),
+        }
+    }
+
+    /// Whether the batch does not contain any changes.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
 impl<'a> ParamSetter<'a> {
     pub fn new(context: &'a dyn GuiContext) -> Self {
         Self {
@@ -180,4 +335,43 @@ impl<'a> ParamSetter<'a> {
     pub fn end_set_parameter<P: Param>(&self, param: &P) {
         unsafe { self.raw_context.raw_end_set_parameter(param.as_ptr()) };
     }
+
+    /// Apply all changes from a [`ParamBatch`] at once. Unlike calling
+    /// [`set_parameter()`][Self::set_parameter()] for every parameter, the host records this as a
+    /// single edit and the plugin's audio thread sees all of the new values in the same block.
+    /// This should not be called while one of the batch's parameters is in the middle of an
+    /// automation gesture started with [`begin_set_parameter()`][Self::begin_set_parameter()].
+    pub fn set_parameters(&self, batch: &ParamBatch) {
+        if !batch.is_empty() {
+            unsafe {
+                self.raw_context
+                    .raw_set_parameters_normalized(&batch.changes)
+            };
+        }
+    }
+
+    /// Ask the host to show its context menu for a parameter at a position relative to the editor
+    /// window, optionally with some additional items. Returns `false` if the host does not support
+    /// this. See [`GuiContext::show_param_context_menu()`] for more information.
+    pub fn show_context_menu<P: Param>(
+        &self,
+        param: &P,
+        x: i32,
+        y: i32,
+        items: Vec<ContextMenuItem>,
+    ) -> bool {
+        self.raw_context
+            .show_param_context_menu(param.as_ptr(), x, y, items)
+    }
+
+    /// Start MIDI learn for a parameter. Returns `false` if the parameter cannot be mapped to a MIDI
+    /// controller. See [`GuiContext::start_midi_learn()`] for more information.
+    pub fn start_midi_learn<P: Param>(&self, param: &P) -> bool {
+        self.raw_context.start_midi_learn(param.as_ptr())
+    }
+
+    /// Whether MIDI learn is currently active for this parameter.
+    pub fn is_midi_learning<P: Param>(&self, param: &P) -> bool {
+        self.raw_context.midi_learn_target() == Some(param.as_ptr())
+    }
 }
@@ -1,7 +1,8 @@
 //! A context passed during plugin initialization.
 
+use super::track_info::TrackInfo;
 use super::PluginApi;
-use crate::prelude::Plugin;
+use crate::prelude::{ParamInfoChanges, Plugin};
 
 /// Callbacks the plugin can make while it is being initialized. This is passed to the plugin during
 /// [`Plugin::initialize()`][crate::plugin::Plugin::initialize()].
@@ -34,4 +35,25 @@ pub trait InitContext<P: Plugin> {
     /// runtime allows the host to better optimize polyphonic modulation, or to switch to strictly
     /// monophonic modulation when dropping the capacity down to 1.
     fn set_current_voice_capacity(&self, capacity: u32);
+
+    /// Get information about the track the plugin has been inserted on, like the track's name and
+    /// color. Returns `None` if the host does not provide this information.
+    fn track_info(&self) -> Option<TrackInfo>;
+
+    /// Whether the host has activated an auxiliary input port. `index` is an index into
+    /// [`AudioIOLayout::aux_input_ports`][crate::prelude::AudioIOLayout::aux_input_ports]. Hosts
+    /// may deactivate inputs that aren't connected to anything, like an unused sidechain input, so
+    /// the plugin can skip any work related to that input. Returns `false` if the port does not
+    /// exist. The same information is also available during processing through
+    /// [`AuxiliaryBuffers::inputs_active`][crate::prelude::AuxiliaryBuffers::inputs_active].
+    fn aux_input_active(&self, index: usize) -> bool;
+
+    /// Whether the host has activated an auxiliary output port. See
+    /// [`aux_input_active()`][Self::aux_input_active()].
+    fn aux_output_active(&self, index: usize) -> bool;
+
+    /// Inform the host that the plugin's parameter names, value strings, or default values have
+    /// changed. This can be used when they depend on the restored state. See
+    /// [`GuiContext::param_info_changed()`][crate::prelude::GuiContext::param_info_changed()].
+    fn param_info_changed(&self, changes: ParamInfoChanges);
 }
@@ -1,7 +1,7 @@
 //! A context passed during the process function.
 
 use super::PluginApi;
-use crate::prelude::{Plugin, PluginNoteEvent};
+use crate::prelude::{Param, ParamFlags, ParamPtr, Plugin, PluginNoteEvent};
 
 /// Contains both context data and callbacks the plugin can use during processing. Most notably this
 /// is how a plugin sends and receives note events, gets transport information, and accesses
@@ -92,10 +92,116 @@ pub trait ProcessContext<P: Plugin> {
     /// monophonic modulation when dropping the capacity down to 1.
     fn set_current_voice_capacity(&self, capacity: u32);
 
-    // TODO: Add this, this works similar to [GuiContext::set_parameter] but it adds the parameter
-    //       change to a queue (or directly to the VST3 plugin's parameter output queues) instead of
-    //       using main thread host automation (and all the locks involved there).
-    // fn set_parameter<P: Param>(&self, param: &P, value: P::Plain);
+    /// Inform the host that the plugin's [`note_names()`][crate::prelude::Plugin::note_names()]
+    /// have changed and that they should be queried again. The host is notified from the main
+    /// thread, so this is realtime-safe.
+    fn note_names_changed(&self);
+
+    /// Get the frequency in Hz for a note using the host's current tuning. `channel` and `note`
+    /// are the note event's MIDI channel and note number, and `timing` is the sample offset within
+    /// the current buffer, just like [`NoteEvent::timing()`][crate::prelude::NoteEvent::timing()].
+    /// This is realtime-safe. If the host does not provide a tuning, then this is the same as
+    /// [`util::midi_note_to_freq()`][crate::util::midi_note_to_freq()]. Implement
+    /// [`ClapPlugin::tuning_changed()`][crate::prelude::ClapPlugin::tuning_changed()] to be
+    /// notified when the host's tuning changes.
+    ///
+    /// Host tunings are currently only supported by CLAP.
+    fn note_frequency(&self, channel: u8, note: u8, timing: u32) -> f32;
+
+    /// Run `task` for every task index in `0..num_tasks`, possibly in parallel using the host's
+    /// thread pool. This returns once all tasks have finished. The tasks are run on the host's
+    /// audio threads, so they need to be realtime-safe just like the process function itself. This
+    /// can be used to, for instance, process independent channels in parallel.
+    ///
+    /// The host's thread pool is currently only used by CLAP, and only if the host supports it.
+    /// Otherwise the tasks are run sequentially on the current thread.
+    fn execute_parallel(&self, num_tasks: u32, task: &(dyn Fn(u32) + Sync));
+
+    /// Inform the host that a parameter will be changed from the audio thread. Use
+    /// [`begin_set_parameter()`][Self::begin_set_parameter()] instead for a safe, user friendly
+    /// API.
+    ///
+    /// # Safety
+    ///
+    /// The implementing function still needs to check if `param` actually exists. This function is
+    /// mostly marked as unsafe for API reasons.
+    unsafe fn raw_begin_set_parameter(&mut self, param: ParamPtr);
+
+    /// Change a parameter's value from the audio thread using an already normalized value. Use
+    /// [`set_parameter()`][Self::set_parameter()] instead for a safe, user friendly API.
+    ///
+    /// # Safety
+    ///
+    /// The implementing function still needs to check if `param` actually exists. This function is
+    /// mostly marked as unsafe for API reasons.
+    unsafe fn raw_set_parameter_normalized(&mut self, param: ParamPtr, normalized: f32);
+
+    /// Inform the host that the plugin is done changing a parameter from the audio thread. Use
+    /// [`end_set_parameter()`][Self::end_set_parameter()] instead for a safe, user friendly API.
+    ///
+    /// # Safety
+    ///
+    /// The implementing function still needs to check if `param` actually exists. This function is
+    /// mostly marked as unsafe for API reasons.
+    unsafe fn raw_end_set_parameter(&mut self, param: ParamPtr);
+
+    /// Inform the host that the plugin will start changing a parameter from the audio thread. This
+    /// works like [`ParamSetter::begin_set_parameter()`][crate::prelude::ParamSetter::begin_set_parameter()],
+    /// but it is realtime-safe. This needs to be called before calling
+    /// [`set_parameter()`][Self::set_parameter()] for the specified parameter. VST3 does not
+    /// support automation gestures from the audio thread, so there this does not do anything.
+    fn begin_set_parameter<T: Param>(&mut self, param: &T) {
+        unsafe { self.raw_begin_set_parameter(param.as_ptr()) };
+    }
+
+    /// Set a parameter to the specified parameter value from the audio thread. This can be used
+    /// for things like DSP-driven parameter changes or MIDI learn. The change is sent to the host
+    /// as an output parameter change at the start of the current block, and the parameter's value
+    /// changes once the current block has been processed so it won't change in the middle of a
+    /// [`Plugin::process()`][crate::prelude::Plugin::process()] call. The editor is notified just
+    /// like when the host changes a parameter.
+    ///
+    /// You will need to call [`begin_set_parameter()`][Self::begin_set_parameter()] before and
+    /// [`end_set_parameter()`][Self::end_set_parameter()] after calling this so the host can
+    /// properly record automation for the parameter.
+    fn set_parameter<T: Param>(&mut self, param: &T, value: T::Plain) {
+        let normalized = param.preview_normalized(value);
+        unsafe { self.raw_set_parameter_normalized(param.as_ptr(), normalized) };
+    }
+
+    /// Set a parameter to an already normalized value from the audio thread. Works exactly the same
+    /// as [`set_parameter()`][Self::set_parameter()] and needs to follow the same rules.
+    ///
+    /// This does not perform any snapping. Consider converting the normalized value to a plain
+    /// value and setting that with [`set_parameter()`][Self::set_parameter()] instead so the
+    /// normalized value known to the host matches `param.normalized_value()`.
+    fn set_parameter_normalized<T: Param>(&mut self, param: &T, normalized: f32) {
+        unsafe { self.raw_set_parameter_normalized(param.as_ptr(), normalized) };
+    }
+
+    /// Inform the host that the plugin is done changing a parameter from the audio thread. This
+    /// needs to be called after one or more [`set_parameter()`][Self::set_parameter()] calls for a
+    /// parameter so the host knows the automation gesture has finished.
+    fn end_set_parameter<T: Param>(&mut self, param: &T) {
+        unsafe { self.raw_end_set_parameter(param.as_ptr()) };
+    }
+
+    /// Publish a new value for a parameter marked as [`ParamFlags::READ_ONLY`]. This is used for
+    /// meters like gain reduction or detected pitch that are exposed to the host as parameters.
+    /// Unlike [`set_parameter()`][Self::set_parameter()], this does not need to be wrapped in an
+    /// automation gesture. The change is only sent to the host if the value actually changed, so
+    /// this can be called once per block.
+    fn set_output_parameter<T: Param>(&mut self, param: &T, value: T::Plain) {
+        nih_debug_assert!(
+            param.flags().contains(ParamFlags::READ_ONLY),
+            "Output parameter values can only be set for read-only parameters"
+        );
+
+        let normalized = param.preview_normalized(value);
+        if normalized != param.unmodulated_normalized_value() {
+            unsafe { self.raw_set_parameter_normalized(param.as_ptr(), normalized) };
+        }
+    }
 }
 
 /// Information about the plugin's transport. Depending on the plugin API and the host not all
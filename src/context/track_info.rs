@@ -0,0 +1,37 @@
+//! Information about the track the plugin has been inserted on.
+
+use super::HostColor;
+
+/// Information about the track the plugin has been inserted on, as reported by the host. This can
+/// be queried from the [`InitContext`][crate::prelude::InitContext] and the
+/// [`GuiContext`][crate::prelude::GuiContext]. Right now only CLAP hosts that support the
+/// `track-info` extension provide this information. Editors are notified of changes through
+/// [`Editor::track_info_changed()`][crate::prelude::Editor::track_info_changed()].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackInfo {
+    /// The track's name, if the host provided one.
+    pub name: Option<String>,
+    /// The track's color, if the host provided one.
+    pub color: Option<HostColor>,
+    /// The number of audio channels on the track, if the host provided it.
+    pub audio_channel_count: Option<u32>,
+    /// The track's audio port type, if the host provided it. For CLAP this is one of the port types
+    /// like `mono` or `stereo`.
+    pub audio_port_type: Option<String>,
+    /// The kind of track the plugin has been inserted on.
+    pub track_type: TrackType,
+}
+
+/// The kind of track the plugin has been inserted on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrackType {
+    /// A regular track, or the host did not specify the track's type.
+    #[default]
+    Regular,
+    /// A return or aux track.
+    Return,
+    /// A bus or group track.
+    Bus,
+    /// The master track.
+    Master,
+}
@@ -5,6 +5,9 @@
 //! should avoid using them during release builds in any of the functions that may be called from an
 //! audio thread.
 
+#[doc(hidden)]
+pub use crate::wrapper::util::host_log::log_to_host;
+
 // NOTE: Exporting macros in Rust is a bit weird. `#[macro_export]` causes them to be exported to
 //       the crate root, but that makes it difficult to include just the macros without using
 //       `#[macro_use] extern crate nih_plug;`. That's why the macros are also re-exported from this
@@ -20,10 +23,26 @@
 ///   `OutputDebugString()`.
 /// - A file path, in which case the output gets appended to the end of that file which will be
 ///   created if necessary.
+///
+/// Messages from this macro, [`nih_warn!()`], and [`nih_error!()`] are also sent to the host's log
+/// when the plugin is loaded by a CLAP host that supports the `log` extension.
 #[macro_export]
 macro_rules! nih_log {
-    ($($args:tt)*) => (
-        $crate::log::info!($($args)*)
+    (target: $target:expr, $($args:tt)+) => (
+        match format_args!($($args)+) {
+            args => {
+                $crate::log::info!(target: $target, "{}", args);
+                $crate::debug::log_to_host($crate::log::Level::Info, args);
+            }
+        }
+    );
+    ($($args:tt)+) => (
+        match format_args!($($args)+) {
+            args => {
+                $crate::log::info!("{}", args);
+                $crate::debug::log_to_host($crate::log::Level::Info, args);
+            }
+        }
     );
 }
 #[doc(inline)]
@@ -32,8 +51,21 @@ pub use nih_log;
 /// Similar to `nih_log!()`, but less subtle. Used for printing warnings.
 #[macro_export]
 macro_rules! nih_warn {
-    ($($args:tt)*) => (
-        $crate::log::warn!($($args)*)
+    (target: $target:expr, $($args:tt)+) => (
+        match format_args!($($args)+) {
+            args => {
+                $crate::log::warn!(target: $target, "{}", args);
+                $crate::debug::log_to_host($crate::log::Level::Warn, args);
+            }
+        }
+    );
+    ($($args:tt)+) => (
+        match format_args!($($args)+) {
+            args => {
+                $crate::log::warn!("{}", args);
+                $crate::debug::log_to_host($crate::log::Level::Warn, args);
+            }
+        }
     );
 }
 #[doc(inline)]
@@ -42,8 +74,21 @@ pub use nih_warn;
 /// Similar to `nih_log!()`, but more scream-y. Used for printing fatal errors.
 #[macro_export]
 macro_rules! nih_error {
-    ($($args:tt)*) => (
-        $crate::log::error!($($args)*)
+    (target: $target:expr, $($args:tt)+) => (
+        match format_args!($($args)+) {
+            args => {
+                $crate::log::error!(target: $target, "{}", args);
+                $crate::debug::log_to_host($crate::log::Level::Error, args);
+            }
+        }
+    );
+    ($($args:tt)+) => (
+        match format_args!($($args)+) {
+            args => {
+                $crate::log::error!("{}", args);
+                $crate::debug::log_to_host($crate::log::Level::Error, args);
+            }
+        }
     );
 }
 #[doc(inline)]
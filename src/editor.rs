@@ -5,7 +5,7 @@ use std::any::Any;
 use std::ffi::c_void;
 use std::sync::Arc;
 
-use crate::prelude::GuiContext;
+use crate::prelude::{GuiContext, HostColor, TrackInfo};
 
 /// An editor for a [`Plugin`][crate::prelude::Plugin].
 pub trait Editor: Send {
@@ -45,6 +45,30 @@ pub trait Editor: Send {
     /// scaling factor to get the actual physical screen pixels.
     fn size(&self) -> (u32, u32);
 
+    /// Whether the host is allowed to resize the editor, for instance when the user drags the
+    /// border of the host's window. If this returns `true`, then the host's size is limited to the
+    /// constraints returned by [`size_constraints()`][Self::size_constraints()], and
+    /// [`set_size()`][Self::set_size()] will be called when the host resizes the editor.
+    fn can_resize(&self) -> bool {
+        false
+    }
+
+    /// The sizes the host is allowed to resize the editor to. Only used when
+    /// [`can_resize()`][Self::can_resize()] returns `true`.
+    fn size_constraints(&self) -> EditorSizeConstraints {
+        EditorSizeConstraints::default()
+    }
+
+    /// Called when the host resizes the editor. The `width` and `height` are in _logical pixels_,
+    /// and they already satisfy the constraints returned by
+    /// [`size_constraints()`][Self::size_constraints()]. If the editor is open, then its window
+    /// should be resized to match. After this function returns `true`, [`size()`][Self::size()]
+    /// must also return the new size. Return `false` to reject the new size. This is only called
+    /// when [`can_resize()`][Self::can_resize()] returns `true`.
+    fn set_size(&self, _width: u32, _height: u32) -> bool {
+        false
+    }
+
     /// Set the DPI scaling factor, if supported. The plugin APIs don't make any guarantees on when
     /// this is called, but for now just assume it will be the first function that gets called
     /// before creating the editor. If this is set, then any windows created by this editor should
@@ -69,11 +93,129 @@ pub trait Editor: Send {
     /// loaded.
     fn param_values_changed(&self);
 
+    /// Called when the host reports that the information about the track the plugin has been
+    /// inserted on has changed while the editor is open. See
+    /// [`GuiContext::track_info()`][crate::prelude::GuiContext::track_info()].
+    fn track_info_changed(&self, _track_info: &TrackInfo) {}
+
+    /// Called when the host changes how a parameter should be indicated in the editor, for instance
+    /// because it has been mapped to a hardware controller or because its automation started
+    /// playing. This can be used to draw the parameter's indication color. The current indication
+    /// can also be queried using
+    /// [`GuiContext::param_indication()`][crate::prelude::GuiContext::param_indication()]. Only
+    /// CLAP hosts that support the `param-indication` extension send these.
+    fn param_indication_changed(&self, _id: &str, _indication: &ParamIndication) {}
+
     // TODO: Reconsider adding a tick function here for the Linux `IRunLoop`. To keep this platform
     //       and API agnostic, add a way to ask the GuiContext if the wrapper already provides a
     //       tick function. If it does not, then the Editor implementation must handle this by
     //       itself. This would also need an associated `PREFERRED_FRAME_RATE` constant.
-    // TODO: Host->Plugin resizing
+}
+
+/// Limits for the editor's size when the host resizes the editor. Returned from
+/// [`Editor::size_constraints()`]. All sizes are in _logical pixels_.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EditorSizeConstraints {
+    /// The smallest allowed size as a `(width, height)` pair.
+    pub min_size: (u32, u32),
+    /// The largest allowed size as a `(width, height)` pair, if the editor has a maximum size.
+    pub max_size: Option<(u32, u32)>,
+    /// The `(width, height)` ratio the editor's size should keep, if any.
+    pub aspect_ratio: Option<(u32, u32)>,
+}
+
+impl Default for EditorSizeConstraints {
+    fn default() -> Self {
+        Self {
+            min_size: (1, 1),
+            max_size: None,
+            aspect_ratio: None,
+        }
+    }
+}
+
+impl EditorSizeConstraints {
+    /// Adjust a `(width, height)` pair so it satisfies these constraints. When the editor has an
+    /// aspect ratio, then the height is computed from the width, unless that would put the height
+    /// outside of the allowed range.
+    pub fn constrain(&self, width: u32, height: u32) -> (u32, u32) {
+        let (min_width, min_height) = (self.min_size.0.max(1), self.min_size.1.max(1));
+        let (max_width, max_height) = self
+            .max_size
+            .map(|(width, height)| (width.max(min_width), height.max(min_height)))
+            .unwrap_or((u32::MAX, u32::MAX));
+
+        let width = width.clamp(min_width, max_width);
+        let height = height.clamp(min_height, max_height);
+        match self.aspect_ratio {
+            Some((ratio_width, ratio_height)) if ratio_width > 0 && ratio_height > 0 => {
+                let ratio = ratio_width as f64 / ratio_height as f64;
+                let height = (width as f64 / ratio).round() as u32;
+                if (min_height..=max_height).contains(&height) {
+                    (width, height)
+                } else {
+                    let height = height.clamp(min_height, max_height);
+                    let width = (height as f64 * ratio).round() as u32;
+
+                    (width.clamp(min_width, max_width), height)
+                }
+            }
+            _ => (width, height),
+        }
+    }
+}
+
+/// How the host wants a parameter to be indicated in the editor. See
+/// [`Editor::param_indication_changed()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParamIndication {
+    /// The parameter's mapping to a physical controller, if it is mapped.
+    pub mapping: Option<ParamMapping>,
+    /// The parameter's current automation state.
+    pub automation: AutomationState,
+    /// The color the host uses to indicate the automation state, if it provided one.
+    pub automation_color: Option<HostColor>,
+}
+
+/// A parameter's mapping to a physical controller, as reported by the host.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParamMapping {
+    /// The color used to indicate the mapping, if the host provided one.
+    pub color: Option<HostColor>,
+    /// A short label identifying the controller, if the host provided one.
+    pub label: Option<String>,
+    /// A longer description of the controller, if the host provided one.
+    pub description: Option<String>,
+}
+
+/// A parameter's automation state as reported by the host.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AutomationState {
+    /// The host does not have any automation for the parameter.
+    #[default]
+    None,
+    /// The parameter has automation, but it is not being played.
+    Present,
+    /// The parameter's automation is being played.
+    Playing,
+    /// The parameter's automation is being recorded.
+    Recording,
+    /// The parameter has automation, but the user is overriding it.
+    Overriding,
+}
+
+impl ParamIndication {
+    /// The color an editor should use to indicate this parameter, if any. The mapping's color takes
+    /// precedence over the automation color.
+    pub fn color(&self) -> Option<HostColor> {
+        let mapping_color = self.mapping.as_ref().and_then(|mapping| mapping.color);
+        let automation_color = match self.automation {
+            AutomationState::None => None,
+            _ => self.automation_color,
+        };
+
+        mapping_color.or(automation_color)
+    }
 }
 
 /// A raw window handle for platform and GUI framework agnostic editors. This implements
@@ -111,3 +253,34 @@ unsafe impl HasRawWindowHandle for ParentWindowHandle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constrain_min_max() {
+        let constraints = EditorSizeConstraints {
+            min_size: (200, 100),
+            max_size: Some((800, 400)),
+            aspect_ratio: None,
+        };
+
+        assert_eq!(constraints.constrain(100, 50), (200, 100));
+        assert_eq!(constraints.constrain(300, 300), (300, 300));
+        assert_eq!(constraints.constrain(1000, 1000), (800, 400));
+    }
+
+    #[test]
+    fn constrain_aspect_ratio() {
+        let constraints = EditorSizeConstraints {
+            min_size: (200, 100),
+            max_size: Some((800, 400)),
+            aspect_ratio: Some((2, 1)),
+        };
+
+        assert_eq!(constraints.constrain(300, 300), (300, 150));
+        assert_eq!(constraints.constrain(1000, 100), (800, 400));
+        assert_eq!(constraints.constrain(100, 400), (200, 100));
+    }
+}
@@ -5,6 +5,9 @@ use midi_consts::channel_event as midi;
 use self::sysex::SysExMessage;
 use crate::prelude::Plugin;
 
+pub mod decoder;
+pub mod learn;
+pub mod mpe;
 pub mod sysex;
 
 pub use midi_consts::channel_event::control_change;
@@ -36,6 +39,18 @@ pub enum MidiConfig {
     MidiCCs,
 }
 
+/// A name for a single note, used by drum-map style instruments to tell the host what each key
+/// does. See [`Plugin::note_names()`][crate::prelude::Plugin::note_names()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteName {
+    /// The zero-indexed MIDI channel this name applies to, or `None` if it applies to all channels.
+    pub channel: Option<u8>,
+    /// The MIDI note number this name applies to.
+    pub note: u8,
+    /// The note's name, for instance `Kick` or `Closed Hi-Hat`.
+    pub name: String,
+}
+
 // FIXME: Like the voice ID, channel and note number can also be omitted in CLAP. And instead of an
 //        Option, maybe this should use a dedicated type to more clearly indicate that missing
 //        values should be treated as wildcards.
@@ -173,6 +188,29 @@ pub enum NoteEvent<S> {
         /// docstring for more information.
         normalized_value: f32,
     },
+    /// A polyphonic modulation event that applies to all voices matching a key, a MIDI channel,
+    /// and/or a note port instead of to a single voice. This is only sent for parameters that have
+    /// been marked as modulatable per key, channel, or port using for instance
+    /// [`FloatParam::modulatable_per_key()`][crate::prelude::FloatParam::modulatable_per_key()].
+    /// Fields that are set to `None` match any value. Voices matching this event should handle it
+    /// the same way as a [`PolyModulation`][Self::PolyModulation] event, and a `PolyModulation`
+    /// event for a specific voice takes precedence over this event.
+    TargetedModulation {
+        timing: u32,
+        /// The note port the modulation applies to. NIH-plug plugins only have a single note input
+        /// port, so this is always `Some(0)` when set.
+        port: Option<u16>,
+        /// The MIDI channel the modulation applies to.
+        channel: Option<u8>,
+        /// The key the modulation applies to.
+        note: Option<u8>,
+        /// The ID that was set for the modulated parameter using the `.with_poly_modulation_id()`
+        /// method.
+        poly_modulation_id: u32,
+        /// The normalized offset value. See the `PolyModulation` event's docstring for more
+        /// information.
+        normalized_offset: f32,
+    },
 
     /// A polyphonic note pressure/aftertouch event, available on [`MidiConfig::Basic`] and up. Not
     /// all hosts may support polyphonic aftertouch.
@@ -301,7 +339,9 @@ pub enum NoteEvent<S> {
     ///
     /// The wrapper does not perform any special handling for two message 14-bit CCs (where the CC
     /// number is in `0..32`, and the next CC is that number plus 32) or for four message RPN
-    /// messages. For now you will need to handle these CCs yourself.
+    /// messages. A [`MidiDecoder`][decoder::MidiDecoder] can be used to convert these messages to
+    /// [`MidiCC14Bit`][Self::MidiCC14Bit], [`MidiRpn`][Self::MidiRpn], and
+    /// [`MidiNrpn`][Self::MidiNrpn] events.
     MidiCC {
         timing: u32,
         /// The affected channel, in `0..16`.
@@ -311,6 +351,47 @@ pub enum NoteEvent<S> {
         /// The CC's value, normalized to `[0, 1]`. Multiply by 127 to get the original raw value.
         value: f32,
     },
+    /// A high resolution MIDI control change event made up of an MSB message on a CC in `0..32` and
+    /// an LSB message on that CC number plus 32, available on [`MidiConfig::MidiCCs`] and up. These
+    /// events are only received when using a [`MidiDecoder`][decoder::MidiDecoder], but they can
+    /// always be sent.
+    MidiCC14Bit {
+        timing: u32,
+        /// The affected channel, in `0..16`.
+        channel: u8,
+        /// The control change number of the MSB message, in `0..32`.
+        cc: u8,
+        /// The CC's 14-bit value, normalized to `[0, 1]`. Multiply by 16383 to get the original
+        /// raw value.
+        value: f32,
+    },
+    /// A registered parameter number (RPN) data entry event, available on [`MidiConfig::MidiCCs`]
+    /// and up. These events are only received when using a [`MidiDecoder`][decoder::MidiDecoder],
+    /// but they can always be sent.
+    MidiRpn {
+        timing: u32,
+        /// The affected channel, in `0..16`.
+        channel: u8,
+        /// The 14-bit registered parameter number, in `0..16384`. For instance, 0 is the pitch bend
+        /// sensitivity.
+        parameter: u16,
+        /// The parameter's 14-bit value, normalized to `[0, 1]`. Multiply by 16383 to get the
+        /// original raw value.
+        value: f32,
+    },
+    /// A non-registered parameter number (NRPN) data entry event, available on
+    /// [`MidiConfig::MidiCCs`] and up. These events are only received when using a
+    /// [`MidiDecoder`][decoder::MidiDecoder], but they can always be sent.
+    MidiNrpn {
+        timing: u32,
+        /// The affected channel, in `0..16`.
+        channel: u8,
+        /// The 14-bit non-registered parameter number, in `0..16384`.
+        parameter: u16,
+        /// The parameter's 14-bit value, normalized to `[0, 1]`. Multiply by 16383 to get the
+        /// original raw value.
+        value: f32,
+    },
     /// A MIDI program change event, available on [`MidiConfig::MidiCCs`] and up. VST3 plugins
     /// cannot receive these events.
     MidiProgramChange {
@@ -337,6 +418,10 @@ pub enum MidiResult<S: SysExMessage> {
     /// end. The `usize` value indicates the message's actual length, including headers and end of
     /// SysEx byte.
     SysEx(S::Buffer, usize),
+    /// Multiple basic three byte MIDI events that need to be sent in order. This is used for
+    /// 14-bit CCs, RPN, and NRPN messages. The `usize` value indicates how many of the messages are
+    /// used.
+    Multiple([[u8; 3]; 4], usize),
 }
 
 impl<S> NoteEvent<S> {
@@ -349,6 +434,7 @@ impl<S> NoteEvent<S> {
             NoteEvent::VoiceTerminated { timing, .. } => *timing,
             NoteEvent::PolyModulation { timing, .. } => *timing,
             NoteEvent::MonoAutomation { timing, .. } => *timing,
+            NoteEvent::TargetedModulation { timing, .. } => *timing,
             NoteEvent::PolyPressure { timing, .. } => *timing,
             NoteEvent::PolyVolume { timing, .. } => *timing,
             NoteEvent::PolyPan { timing, .. } => *timing,
@@ -359,6 +445,9 @@ impl<S> NoteEvent<S> {
             NoteEvent::MidiChannelPressure { timing, .. } => *timing,
             NoteEvent::MidiPitchBend { timing, .. } => *timing,
             NoteEvent::MidiCC { timing, .. } => *timing,
+            NoteEvent::MidiCC14Bit { timing, .. } => *timing,
+            NoteEvent::MidiRpn { timing, .. } => *timing,
+            NoteEvent::MidiNrpn { timing, .. } => *timing,
             NoteEvent::MidiProgramChange { timing, .. } => *timing,
             NoteEvent::MidiSysEx { timing, .. } => *timing,
         }
@@ -373,6 +462,7 @@ impl<S> NoteEvent<S> {
             NoteEvent::VoiceTerminated { voice_id, .. } => *voice_id,
             NoteEvent::PolyModulation { voice_id, .. } => Some(*voice_id),
             NoteEvent::MonoAutomation { .. } => None,
+            NoteEvent::TargetedModulation { .. } => None,
             NoteEvent::PolyPressure { voice_id, .. } => *voice_id,
             NoteEvent::PolyVolume { voice_id, .. } => *voice_id,
             NoteEvent::PolyPan { voice_id, .. } => *voice_id,
@@ -383,6 +473,9 @@ impl<S> NoteEvent<S> {
             NoteEvent::MidiChannelPressure { .. } => None,
             NoteEvent::MidiPitchBend { .. } => None,
             NoteEvent::MidiCC { .. } => None,
+            NoteEvent::MidiCC14Bit { .. } => None,
+            NoteEvent::MidiRpn { .. } => None,
+            NoteEvent::MidiNrpn { .. } => None,
             NoteEvent::MidiProgramChange { .. } => None,
             NoteEvent::MidiSysEx { .. } => None,
         }
@@ -397,6 +490,7 @@ impl<S> NoteEvent<S> {
             NoteEvent::VoiceTerminated { channel, .. } => Some(*channel),
             NoteEvent::PolyModulation { .. } => None,
             NoteEvent::MonoAutomation { .. } => None,
+            NoteEvent::TargetedModulation { channel, .. } => *channel,
             NoteEvent::PolyPressure { channel, .. } => Some(*channel),
             NoteEvent::PolyVolume { channel, .. } => Some(*channel),
             NoteEvent::PolyPan { channel, .. } => Some(*channel),
@@ -407,6 +501,9 @@ impl<S> NoteEvent<S> {
             NoteEvent::MidiChannelPressure { channel, .. } => Some(*channel),
             NoteEvent::MidiPitchBend { channel, .. } => Some(*channel),
             NoteEvent::MidiCC { channel, .. } => Some(*channel),
+            NoteEvent::MidiCC14Bit { channel, .. } => Some(*channel),
+            NoteEvent::MidiRpn { channel, .. } => Some(*channel),
+            NoteEvent::MidiNrpn { channel, .. } => Some(*channel),
             NoteEvent::MidiProgramChange { channel, .. } => Some(*channel),
             NoteEvent::MidiSysEx { .. } => None,
         }
@@ -422,8 +519,7 @@ impl<S: SysExMessage> NoteEvent<S> {
         let channel = status_byte & midi::MIDI_CHANNEL_MASK;
 
         if midi_data.len() >= 3 {
-            // TODO: Maybe add special handling for 14-bit CCs and RPN messages at some
-            //       point, right now the plugin has to figure it out for itself
+            // 14-bit CCs and RPN messages can be decoded using a `MidiDecoder`
             match event_type {
                 // You thought this was a note on? Think again! This is a cleverly disguised note off
                 // event straight from the 80s when Baud rate was still a limiting factor!
@@ -526,7 +622,8 @@ impl<S: SysExMessage> NoteEvent<S> {
 
     /// Create a MIDI message from this note event. Returns `None` if this even does not have a
     /// direct MIDI equivalent. `PolyPressure` will be converted to polyphonic key pressure, but the
-    /// other polyphonic note expression types will not be converted to MIDI CC messages.
+    /// other polyphonic note expression types will not be converted to MIDI CC messages. 14-bit
+    /// CCs, RPN, and NRPN events are converted to multiple CC messages.
     pub fn as_midi(self) -> Option<MidiResult<S>> {
         match self {
             NoteEvent::NoteOn {
@@ -598,6 +695,72 @@ impl<S: SysExMessage> NoteEvent<S> {
                 cc,
                 (value * 127.0).round().clamp(0.0, 127.0) as u8,
             ])),
+            NoteEvent::MidiCC14Bit {
+                timing: _,
+                channel,
+                cc,
+                value,
+            } => {
+                let (msb, lsb) = split_14_bit_value(value);
+
+                Some(MidiResult::Multiple(
+                    [
+                        [midi::CONTROL_CHANGE | channel, cc, msb],
+                        [midi::CONTROL_CHANGE | channel, cc + 32, lsb],
+                        [0; 3],
+                        [0; 3],
+                    ],
+                    2,
+                ))
+            }
+            NoteEvent::MidiRpn {
+                timing: _,
+                channel,
+                parameter,
+                value,
+            } => {
+                let (msb, lsb) = split_14_bit_value(value);
+
+                Some(MidiResult::Multiple(
+                    [
+                        // RPN MSB and LSB
+                        [
+                            midi::CONTROL_CHANGE | channel,
+                            101,
+                            (parameter >> 7) as u8 & 0x7f,
+                        ],
+                        [midi::CONTROL_CHANGE | channel, 100, parameter as u8 & 0x7f],
+                        // Data entry MSB and LSB
+                        [midi::CONTROL_CHANGE | channel, 6, msb],
+                        [midi::CONTROL_CHANGE | channel, 38, lsb],
+                    ],
+                    4,
+                ))
+            }
+            NoteEvent::MidiNrpn {
+                timing: _,
+                channel,
+                parameter,
+                value,
+            } => {
+                let (msb, lsb) = split_14_bit_value(value);
+
+                Some(MidiResult::Multiple(
+                    [
+                        // NRPN MSB and LSB
+                        [
+                            midi::CONTROL_CHANGE | channel,
+                            99,
+                            (parameter >> 7) as u8 & 0x7f,
+                        ],
+                        [midi::CONTROL_CHANGE | channel, 98, parameter as u8 & 0x7f],
+                        // Data entry MSB and LSB
+                        [midi::CONTROL_CHANGE | channel, 6, msb],
+                        [midi::CONTROL_CHANGE | channel, 38, lsb],
+                    ],
+                    4,
+                ))
+            }
             NoteEvent::MidiProgramChange {
                 timing: _,
                 channel,
@@ -617,6 +780,7 @@ impl<S: SysExMessage> NoteEvent<S> {
             | NoteEvent::VoiceTerminated { .. }
             | NoteEvent::PolyModulation { .. }
             | NoteEvent::MonoAutomation { .. }
+            | NoteEvent::TargetedModulation { .. }
             | NoteEvent::PolyVolume { .. }
             | NoteEvent::PolyPan { .. }
             | NoteEvent::PolyTuning { .. }
@@ -628,7 +792,6 @@ impl<S: SysExMessage> NoteEvent<S> {
 
     /// Subtract a sample offset from this event's timing, needed to compensate for the block
     /// splitting in the VST3 wrapper implementation because all events have to be read upfront.
-    #[cfg_attr(not(feature = "vst3"), allow(dead_code))]
     pub(crate) fn subtract_timing(&mut self, samples: u32) {
         match self {
             NoteEvent::NoteOn { timing, .. } => *timing -= samples,
@@ -637,6 +800,7 @@ impl<S: SysExMessage> NoteEvent<S> {
             NoteEvent::VoiceTerminated { timing, .. } => *timing -= samples,
             NoteEvent::PolyModulation { timing, .. } => *timing -= samples,
             NoteEvent::MonoAutomation { timing, .. } => *timing -= samples,
+            NoteEvent::TargetedModulation { timing, .. } => *timing -= samples,
             NoteEvent::PolyPressure { timing, .. } => *timing -= samples,
             NoteEvent::PolyVolume { timing, .. } => *timing -= samples,
             NoteEvent::PolyPan { timing, .. } => *timing -= samples,
@@ -647,10 +811,51 @@ impl<S: SysExMessage> NoteEvent<S> {
             NoteEvent::MidiChannelPressure { timing, .. } => *timing -= samples,
             NoteEvent::MidiPitchBend { timing, .. } => *timing -= samples,
             NoteEvent::MidiCC { timing, .. } => *timing -= samples,
+            NoteEvent::MidiCC14Bit { timing, .. } => *timing -= samples,
+            NoteEvent::MidiRpn { timing, .. } => *timing -= samples,
+            NoteEvent::MidiNrpn { timing, .. } => *timing -= samples,
             NoteEvent::MidiProgramChange { timing, .. } => *timing -= samples,
             NoteEvent::MidiSysEx { timing, .. } => *timing -= samples,
         }
     }
+
+    /// Add a sample offset to this event's timing. This is the inverse of
+    /// [`subtract_timing()`][Self::subtract_timing()], used to turn block-relative output event
+    /// timings back into buffer-relative timings.
+    pub(crate) fn add_timing(&mut self, samples: u32) {
+        match self {
+            NoteEvent::NoteOn { timing, .. } => *timing += samples,
+            NoteEvent::NoteOff { timing, .. } => *timing += samples,
+            NoteEvent::Choke { timing, .. } => *timing += samples,
+            NoteEvent::VoiceTerminated { timing, .. } => *timing += samples,
+            NoteEvent::PolyModulation { timing, .. } => *timing += samples,
+            NoteEvent::MonoAutomation { timing, .. } => *timing += samples,
+            NoteEvent::TargetedModulation { timing, .. } => *timing += samples,
+            NoteEvent::PolyPressure { timing, .. } => *timing += samples,
+            NoteEvent::PolyVolume { timing, .. } => *timing += samples,
+            NoteEvent::PolyPan { timing, .. } => *timing += samples,
+            NoteEvent::PolyTuning { timing, .. } => *timing += samples,
+            NoteEvent::PolyVibrato { timing, .. } => *timing += samples,
+            NoteEvent::PolyExpression { timing, .. } => *timing += samples,
+            NoteEvent::PolyBrightness { timing, .. } => *timing += samples,
+            NoteEvent::MidiChannelPressure { timing, .. } => *timing += samples,
+            NoteEvent::MidiPitchBend { timing, .. } => *timing += samples,
+            NoteEvent::MidiCC { timing, .. } => *timing += samples,
+            NoteEvent::MidiCC14Bit { timing, .. } => *timing += samples,
+            NoteEvent::MidiRpn { timing, .. } => *timing += samples,
+            NoteEvent::MidiNrpn { timing, .. } => *timing += samples,
+            NoteEvent::MidiProgramChange { timing, .. } => *timing += samples,
+            NoteEvent::MidiSysEx { timing, .. } => *timing += samples,
+        }
+    }
+}
+
+/// Convert a normalized 14-bit value to a `(msb, lsb)` pair.
+fn split_14_bit_value(value: f32) -> (u8, u8) {
+    const MAX_VALUE: f32 = ((1 << 14) - 1) as f32;
+    let midi_value = (value * MAX_VALUE).round().clamp(0.0, MAX_VALUE) as u16;
+
+    ((midi_value >> 7) as u8, (midi_value & ((1 << 7) - 1)) as u8)
 }
 
 #[cfg(test)]
@@ -743,6 +948,61 @@ mod tests {
         assert_eq!(roundtrip_basic_event(event), event);
     }
 
+    /// Converts an event that is encoded as multiple MIDI messages to MIDI and back using a
+    /// [`MidiDecoder`][decoder::MidiDecoder]. Returns the last decoded event.
+    fn roundtrip_multiple_event(event: NoteEvent<()>) -> NoteEvent<()> {
+        let (messages, num_messages) = match event.as_midi().unwrap() {
+            MidiResult::Multiple(messages, num_messages) => (messages, num_messages),
+            result => panic!("Unexpected result: {result:?}"),
+        };
+
+        let mut decoder = decoder::MidiDecoder::new();
+        let mut decoded_events = Vec::new();
+        for midi_data in &messages[..num_messages] {
+            decoder.process(NoteEvent::from_midi(TIMING, midi_data).unwrap(), |event| {
+                decoded_events.push(event)
+            });
+        }
+
+        decoded_events.pop().unwrap()
+    }
+
+    #[test]
+    fn test_cc_14_bit_midi_conversion() {
+        let event = NoteEvent::<()>::MidiCC14Bit {
+            timing: TIMING,
+            channel: 1,
+            cc: 2,
+            value: 12345.0 / 16383.0,
+        };
+
+        assert_eq!(roundtrip_multiple_event(event), event);
+    }
+
+    #[test]
+    fn test_rpn_midi_conversion() {
+        let event = NoteEvent::<()>::MidiRpn {
+            timing: TIMING,
+            channel: 1,
+            parameter: 6,
+            value: 12345.0 / 16383.0,
+        };
+
+        assert_eq!(roundtrip_multiple_event(event), event);
+    }
+
+    #[test]
+    fn test_nrpn_midi_conversion() {
+        let event = NoteEvent::<()>::MidiNrpn {
+            timing: TIMING,
+            channel: 1,
+            parameter: 1234,
+            value: 12345.0 / 16383.0,
+        };
+
+        assert_eq!(roundtrip_multiple_event(event), event);
+    }
+
     #[test]
     fn test_program_change_midi_conversion() {
         let event = NoteEvent::<()>::MidiProgramChange {
@@ -0,0 +1,312 @@
+//! A stateful decoder for MIDI messages that are spread out over multiple control change messages.
+
+use super::NoteEvent;
+
+/// The CCs used for data entry.
+const DATA_ENTRY_MSB_CC: u8 = 6;
+const DATA_ENTRY_LSB_CC: u8 = 38;
+const DATA_INCREMENT_CC: u8 = 96;
+const DATA_DECREMENT_CC: u8 = 97;
+/// The CCs used to select a non-registered parameter number.
+const NRPN_LSB_CC: u8 = 98;
+const NRPN_MSB_CC: u8 = 99;
+/// The CCs used to select a registered parameter number.
+const RPN_LSB_CC: u8 = 100;
+const RPN_MSB_CC: u8 = 101;
+
+/// The registered parameter number used to deselect the current parameter.
+const RPN_NULL: u16 = 0x3fff;
+/// The maximum value for 14-bit values.
+const MAX_14_BIT_VALUE: u16 = (1 << 14) - 1;
+
+/// A stateful decoder that combines MIDI control change messages into higher level events. Plugins
+/// that use [`MidiConfig::MidiCCs`][super::MidiConfig::MidiCCs] can opt into this by passing their
+/// incoming events through [`process()`][Self::process()]. The decoder:
+///
+/// - Pairs CCs `0..32` with CCs `32..64` and emits [`NoteEvent::MidiCC14Bit`] events. An event is
+///   emitted for both the MSB and the LSB message, since not every device sends the LSB. LSB
+///   messages without a preceding MSB message are passed through as regular CCs.
+/// - Keeps track of RPN and NRPN selection and data entry for every channel, and it emits
+///   [`NoteEvent::MidiRpn`] and [`NoteEvent::MidiNrpn`] events for data entry, data increment, and
+///   data decrement messages. The parameter selection messages are consumed by the decoder.
+///
+/// All other events are passed through unchanged. These events can be converted back to MIDI using
+/// [`NoteEvent::as_midi()`], so they can also be used for MIDI output.
+#[derive(Debug, Clone)]
+pub struct MidiDecoder {
+    channels: [ChannelState; 16],
+}
+
+/// The decoder's state for a single MIDI channel.
+#[derive(Debug, Clone, Copy)]
+struct ChannelState {
+    /// The last MSB values received on CCs `0..32`, if any.
+    cc_msbs: [Option<u8>; 32],
+
+    /// The registered or non-registered parameter that is currently selected.
+    selected_parameter: SelectedParameter,
+    /// The last selected registered parameter number, assembled from CC 101 and CC 100.
+    rpn: u16,
+    /// The last selected non-registered parameter number, assembled from CC 99 and CC 98.
+    nrpn: u16,
+    /// The selected parameter's current 14-bit data value.
+    data: u16,
+}
+
+/// Which kind of parameter number data entry messages currently apply to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SelectedParameter {
+    None,
+    Registered,
+    NonRegistered,
+}
+
+impl Default for MidiDecoder {
+    fn default() -> Self {
+        Self {
+            channels: [ChannelState::default(); 16],
+        }
+    }
+}
+
+impl Default for ChannelState {
+    fn default() -> Self {
+        Self {
+            cc_msbs: [None; 32],
+
+            selected_parameter: SelectedParameter::None,
+            rpn: RPN_NULL,
+            nrpn: RPN_NULL,
+            data: 0,
+        }
+    }
+}
+
+impl MidiDecoder {
+    /// Create a new decoder with no selected parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reset the decoder's state for all channels.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Process an incoming event, calling `emit` for every resulting event. See the struct's
+    /// docstring for more information.
+    pub fn process<S>(&mut self, event: NoteEvent<S>, mut emit: impl FnMut(NoteEvent<S>)) {
+        let NoteEvent::MidiCC {
+            timing,
+            channel,
+            cc,
+            value,
+        } = event
+        else {
+            emit(event);
+            return;
+        };
+        let Some(state) = self.channels.get_mut(channel as usize) else {
+            emit(event);
+            return;
+        };
+
+        let value = (value * 127.0).round().clamp(0.0, 127.0) as u8;
+        match cc {
+            RPN_MSB_CC | RPN_LSB_CC => {
+                state.rpn = set_7_bit_part(state.rpn, cc == RPN_MSB_CC, value);
+                state.data = 0;
+                state.selected_parameter = if state.rpn == RPN_NULL {
+                    SelectedParameter::None
+                } else {
+                    SelectedParameter::Registered
+                };
+            }
+            NRPN_MSB_CC | NRPN_LSB_CC => {
+                state.nrpn = set_7_bit_part(state.nrpn, cc == NRPN_MSB_CC, value);
+                state.data = 0;
+                state.selected_parameter = SelectedParameter::NonRegistered;
+            }
+            DATA_ENTRY_MSB_CC | DATA_ENTRY_LSB_CC | DATA_INCREMENT_CC | DATA_DECREMENT_CC
+                if state.selected_parameter != SelectedParameter::None =>
+            {
+                state.data = match cc {
+                    // A new MSB resets the LSB
+                    DATA_ENTRY_MSB_CC => (value as u16) << 7,
+                    DATA_ENTRY_LSB_CC => set_7_bit_part(state.data, false, value),
+                    DATA_INCREMENT_CC => (state.data + 1).min(MAX_14_BIT_VALUE),
+                    DATA_DECREMENT_CC => state.data.saturating_sub(1),
+                    _ => unreachable!(),
+                };
+
+                let value = state.data as f32 / MAX_14_BIT_VALUE as f32;
+                emit(match state.selected_parameter {
+                    SelectedParameter::Registered => NoteEvent::MidiRpn {
+                        timing,
+                        channel,
+                        parameter: state.rpn,
+                        value,
+                    },
+                    SelectedParameter::NonRegistered => NoteEvent::MidiNrpn {
+                        timing,
+                        channel,
+                        parameter: state.nrpn,
+                        value,
+                    },
+                    SelectedParameter::None => unreachable!(),
+                });
+            }
+            0..=31 if cc != DATA_ENTRY_MSB_CC => {
+                state.cc_msbs[cc as usize] = Some(value);
+                emit(NoteEvent::MidiCC14Bit {
+                    timing,
+                    channel,
+                    cc,
+                    value: ((value as u16) << 7) as f32 / MAX_14_BIT_VALUE as f32,
+                });
+            }
+            32..=63 if cc != DATA_ENTRY_LSB_CC => match state.cc_msbs[cc as usize - 32] {
+                Some(msb) => emit(NoteEvent::MidiCC14Bit {
+                    timing,
+                    channel,
+                    cc: cc - 32,
+                    value: (((msb as u16) << 7) | value as u16) as f32 / MAX_14_BIT_VALUE as f32,
+                }),
+                None => emit(event),
+            },
+            _ => emit(event),
+        }
+    }
+}
+
+/// Replace either the upper or the lower seven bits of a 14-bit value.
+fn set_7_bit_part(current: u16, msb: bool, value: u8) -> u16 {
+    if msb {
+        (current & 0x7f) | ((value as u16 & 0x7f) << 7)
+    } else {
+        (current & !0x7f) | (value as u16 & 0x7f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMING: u32 = 5;
+
+    /// Send a CC through the decoder and return the resulting events.
+    fn send_cc(decoder: &mut MidiDecoder, cc: u8, value: u8) -> Vec<NoteEvent<()>> {
+        let mut events = Vec::new();
+        decoder.process(
+            NoteEvent::MidiCC {
+                timing: TIMING,
+                channel: 1,
+                cc,
+                value: value as f32 / 127.0,
+            },
+            |event| events.push(event),
+        );
+
+        events
+    }
+
+    #[test]
+    fn cc_14_bit_pairs() {
+        let mut decoder = MidiDecoder::new();
+
+        // Without an MSB, the LSB is passed through as is
+        let events = send_cc(&mut decoder, 33, 5);
+        assert_eq!(
+            events,
+            [NoteEvent::MidiCC {
+                timing: TIMING,
+                channel: 1,
+                cc: 33,
+                value: 5.0 / 127.0,
+            }]
+        );
+
+        let events = send_cc(&mut decoder, 1, 64);
+        assert_eq!(
+            events,
+            [NoteEvent::MidiCC14Bit {
+                timing: TIMING,
+                channel: 1,
+                cc: 1,
+                value: 8192.0 / 16383.0,
+            }]
+        );
+        let events = send_cc(&mut decoder, 33, 5);
+        assert_eq!(
+            events,
+            [NoteEvent::MidiCC14Bit {
+                timing: TIMING,
+                channel: 1,
+                cc: 1,
+                value: 8197.0 / 16383.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn rpn_data_entry() {
+        let mut decoder = MidiDecoder::new();
+        assert!(send_cc(&mut decoder, RPN_MSB_CC, 0).is_empty());
+        assert!(send_cc(&mut decoder, RPN_LSB_CC, 0).is_empty());
+
+        let events = send_cc(&mut decoder, DATA_ENTRY_MSB_CC, 12);
+        assert_eq!(
+            events,
+            [NoteEvent::MidiRpn {
+                timing: TIMING,
+                channel: 1,
+                parameter: 0,
+                value: (12 << 7) as f32 / 16383.0,
+            }]
+        );
+
+        let events = send_cc(&mut decoder, DATA_INCREMENT_CC, 0);
+        assert_eq!(
+            events,
+            [NoteEvent::MidiRpn {
+                timing: TIMING,
+                channel: 1,
+                parameter: 0,
+                value: ((12 << 7) + 1) as f32 / 16383.0,
+            }]
+        );
+
+        // After selecting the null RPN, data entry messages are regular CCs again
+        send_cc(&mut decoder, RPN_MSB_CC, 127);
+        send_cc(&mut decoder, RPN_LSB_CC, 127);
+        let events = send_cc(&mut decoder, DATA_ENTRY_MSB_CC, 12);
+        assert_eq!(
+            events,
+            [NoteEvent::MidiCC {
+                timing: TIMING,
+                channel: 1,
+                cc: DATA_ENTRY_MSB_CC,
+                value: 12.0 / 127.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn nrpn_data_entry() {
+        let mut decoder = MidiDecoder::new();
+        send_cc(&mut decoder, NRPN_MSB_CC, 1);
+        send_cc(&mut decoder, NRPN_LSB_CC, 2);
+        send_cc(&mut decoder, DATA_ENTRY_MSB_CC, 3);
+
+        let events = send_cc(&mut decoder, DATA_ENTRY_LSB_CC, 4);
+        assert_eq!(
+            events,
+            [NoteEvent::MidiNrpn {
+                timing: TIMING,
+                channel: 1,
+                parameter: (1 << 7) | 2,
+                value: ((3 << 7) | 4) as f32 / 16383.0,
+            }]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! MIDI learn, letting users control the plugin's parameters with hardware MIDI controllers.
+//!
+//! The editor can start MIDI learn for a parameter using
+//! [`ParamSetter::start_midi_learn()`][crate::prelude::ParamSetter::start_midi_learn()]. The next
+//! MIDI CC, 14-bit CC, or NRPN the plugin receives is then mapped to that parameter. Incoming
+//! mapped controller messages are applied to their parameters by the wrapper before the plugin's
+//! [`process()`][crate::prelude::Plugin::process()] function gets called, and if the plugin
+//! enables [`Plugin::SAMPLE_ACCURATE_AUTOMATION`][crate::prelude::Plugin::SAMPLE_ACCURATE_AUTOMATION]
+//! then this is done sample accurately just like with host automation. The mappings are stored
+//! in the plugin's state. MIDI learn requires [`Plugin::MIDI_INPUT`][crate::prelude::Plugin::MIDI_INPUT]
+//! to be set to [`MidiConfig::MidiCCs`][super::MidiConfig::MidiCCs], and the controller messages
+//! are still passed to the plugin as usual.
+
+use serde::{Deserialize, Serialize};
+
+/// The MIDI controller a [`MidiMapping`] responds to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MidiLearnSource {
+    /// A regular 7-bit MIDI CC.
+    Cc {
+        /// The MIDI channel, in `0..16`.
+        channel: u8,
+        /// The control change number, in `0..128`.
+        cc: u8,
+    },
+    /// A high resolution CC made up of an MSB message on a CC in `0..32` and an LSB message on that
+    /// CC number plus 32. Controllers that only send the MSB still work with these mappings.
+    Cc14Bit {
+        /// The MIDI channel, in `0..16`.
+        channel: u8,
+        /// The control change number of the MSB message, in `0..32`.
+        cc: u8,
+    },
+    /// A non-registered parameter number (NRPN).
+    Nrpn {
+        /// The MIDI channel, in `0..16`.
+        channel: u8,
+        /// The 14-bit non-registered parameter number, in `0..16384`.
+        parameter: u16,
+    },
+}
+
+/// A mapping from a MIDI controller to one of the plugin's parameters. These are created through
+/// MIDI learn, and they can be inspected and edited from the editor using
+/// [`GuiContext::midi_mappings()`][crate::prelude::GuiContext::midi_mappings()] and
+/// [`GuiContext::set_midi_mappings()`][crate::prelude::GuiContext::set_midi_mappings()].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MidiMapping {
+    /// The controller that changes the parameter.
+    pub source: MidiLearnSource,
+    /// The parameter's ID, as set with the `#[id = "..."]` attribute on the `Params` struct.
+    pub param_id: String,
+    /// The parameter's normalized value when the controller is at its minimum position. This may be
+    /// larger than [`max`][Self::max] to invert the controller.
+    pub min: f32,
+    /// The parameter's normalized value when the controller is at its maximum position.
+    pub max: f32,
+    /// The exponent the controller's `[0, 1]` value is raised to before it is mapped to the
+    /// `[min, max]` range. A value of 1.0 results in a linear response, values above 1.0 give the
+    /// controller more resolution near its minimum, and values below 1.0 give it more resolution
+    /// near its maximum. This needs to be positive.
+    pub curve: f32,
+}
+
+impl MidiMapping {
+    /// Create a linear mapping that covers the parameter's entire range.
+    pub fn new(source: MidiLearnSource, param_id: impl Into<String>) -> Self {
+        Self {
+            source,
+            param_id: param_id.into(),
+            min: 0.0,
+            max: 1.0,
+            curve: 1.0,
+        }
+    }
+
+    /// Compute the parameter's normalized value for a controller value in `[0, 1]`.
+    pub fn normalized_value(&self, controller_value: f32) -> f32 {
+        let shaped_value = controller_value.clamp(0.0, 1.0).powf(self.curve);
+
+        (self.min + ((self.max - self.min) * shaped_value)).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mapping_range_and_curve() {
+        let mut mapping = MidiMapping::new(MidiLearnSource::Cc { channel: 0, cc: 1 }, "test");
+        assert_eq!(mapping.normalized_value(0.5), 0.5);
+
+        mapping.min = 1.0;
+        mapping.max = 0.5;
+        mapping.curve = 2.0;
+        assert_eq!(mapping.normalized_value(0.0), 1.0);
+        assert_eq!(mapping.normalized_value(0.5), 0.875);
+        assert_eq!(mapping.normalized_value(2.0), 0.5);
+    }
+}
@@ -0,0 +1,602 @@
+//! Support for MIDI Polyphonic Expression (MPE).
+//!
+//! With MPE, every note is played on its own MIDI channel so channel-wide messages like pitch bend,
+//! channel pressure, and CC 74 only affect that single note. The [`MpeDecoder`] keeps track of the
+//! MPE zones and of the notes playing on each member channel, and it translates those channel-wide
+//! messages into per-note [`NoteEvent`]s.
+
+use super::decoder::MidiDecoder;
+use super::NoteEvent;
+
+/// The MIDI controller number used for the MPE timbre dimension. This is sent as
+/// [`NoteEvent::PolyBrightness`] events.
+const TIMBRE_CC: u8 = 74;
+
+/// The registered parameter number for the pitch bend sensitivity.
+const RPN_PITCH_BEND_SENSITIVITY: u16 = 0x0000;
+/// The registered parameter number for the MPE configuration message.
+const RPN_MPE_CONFIGURATION: u16 = 0x0006;
+
+/// The default pitch bend range for member channels, in semitones, as defined by the MPE
+/// specification.
+const DEFAULT_MEMBER_PITCH_BEND_RANGE: f32 = 48.0;
+
+/// The maximum number of notes that can be tracked on a single member channel. MPE controllers
+/// normally only play a single note per channel. When more notes than this are played on the same
+/// channel, the oldest note stops receiving per-note expressions.
+const MAX_NOTES_PER_CHANNEL: usize = 8;
+
+/// Determines whether a plugin supports MIDI Polyphonic Expression, and how MPE input should be
+/// handled. This requires [`Plugin::MIDI_INPUT`][crate::prelude::Plugin::MIDI_INPUT] to be set to
+/// [`MidiConfig::MidiCCs`][super::MidiConfig::MidiCCs] or higher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpeConfig {
+    /// The plugin does not support MPE.
+    None,
+    /// The plugin supports MPE, and it will decode the member channels' MIDI messages by itself.
+    /// For CLAP plugins this advertises support for the MPE note dialect. MIDI messages are passed
+    /// to the plugin as is.
+    Midi,
+    /// The plugin supports MPE, and the wrapper translates MPE input into per-note expressions
+    /// using an [`MpeDecoder`]. Pitch bend, channel pressure, and CC 74 messages on a member
+    /// channel are converted to [`NoteEvent::PolyTuning`], [`NoteEvent::PolyPressure`], and
+    /// [`NoteEvent::PolyBrightness`] events for every note playing on that channel. MPE
+    /// configuration messages and pitch bend sensitivity changes are handled by the wrapper.
+    /// Messages on a zone's manager channel are passed to the plugin as regular MIDI events, and
+    /// they should apply to all of the zone's notes.
+    PerNoteExpressions,
+}
+
+/// An MPE zone. The lower zone is managed from the first MIDI channel and its member channels
+/// start at the second channel. The upper zone is managed from the last MIDI channel and its member
+/// channels count down from the fifteenth channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MpeZone {
+    /// The number of member channels in this zone, in `1..16`.
+    pub member_channels: u8,
+    /// The pitch bend range for the zone's member channels, in semitones.
+    pub member_pitch_bend_range: f32,
+}
+
+/// A decoder that translates MPE input into per-note expression events. See
+/// [`MpeConfig::PerNoteExpressions`]. This is used by the wrappers, but it can also be used
+/// directly to decode MPE from other sources.
+///
+/// Until the decoder receives an MPE configuration message, it assumes a lower zone spanning all
+/// fifteen member channels, since not every host or controller sends these messages.
+#[derive(Debug, Clone)]
+pub struct MpeDecoder {
+    lower_zone: Option<MpeZone>,
+    upper_zone: Option<MpeZone>,
+    channels: [ChannelState; 16],
+    /// Used to decode the RPN messages for MPE configuration and pitch bend sensitivity changes.
+    rpn_decoder: MidiDecoder,
+}
+
+/// The decoder's state for a single MIDI channel.
+#[derive(Debug, Clone, Copy)]
+struct ChannelState {
+    /// The channel's last pitch bend, in semitones.
+    tuning: f32,
+    /// The channel's last channel pressure value, in `[0, 1]`.
+    pressure: f32,
+    /// The channel's last CC 74 value, in `[0, 1]`.
+    brightness: f32,
+
+    /// The notes currently playing on this channel, along with their voice IDs. Only the first
+    /// `num_notes` elements are used, with the oldest note being at the start.
+    notes: [(u8, Option<i32>); MAX_NOTES_PER_CHANNEL],
+    num_notes: usize,
+}
+
+impl Default for MpeDecoder {
+    fn default() -> Self {
+        Self {
+            lower_zone: Some(MpeZone {
+                member_channels: 15,
+                member_pitch_bend_range: DEFAULT_MEMBER_PITCH_BEND_RANGE,
+            }),
+            upper_zone: None,
+            channels: [ChannelState::default(); 16],
+            rpn_decoder: MidiDecoder::new(),
+        }
+    }
+}
+
+impl Default for ChannelState {
+    fn default() -> Self {
+        Self {
+            tuning: 0.0,
+            pressure: 0.0,
+            brightness: 0.5,
+
+            notes: [(0, None); MAX_NOTES_PER_CHANNEL],
+            num_notes: 0,
+        }
+    }
+}
+
+impl ChannelState {
+    fn add_note(&mut self, note: u8, voice_id: Option<i32>) {
+        self.remove_note(note);
+        if self.num_notes == MAX_NOTES_PER_CHANNEL {
+            self.notes.copy_within(1.., 0);
+            self.num_notes -= 1;
+        }
+
+        self.notes[self.num_notes] = (note, voice_id);
+        self.num_notes += 1;
+    }
+
+    fn remove_note(&mut self, note: u8) {
+        if let Some(idx) = self.notes[..self.num_notes]
+            .iter()
+            .position(|(active_note, _)| *active_note == note)
+        {
+            self.notes.copy_within(idx + 1..self.num_notes, idx);
+            self.num_notes -= 1;
+        }
+    }
+
+    fn active_notes(&self) -> &[(u8, Option<i32>)] {
+        &self.notes[..self.num_notes]
+    }
+}
+
+impl MpeDecoder {
+    /// Create a decoder with the default zone layout. See the struct's docstring.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current lower zone, if it is enabled. This zone is managed from the first MIDI channel.
+    pub fn lower_zone(&self) -> Option<MpeZone> {
+        self.lower_zone
+    }
+
+    /// The current upper zone, if it is enabled. This zone is managed from the last MIDI channel.
+    pub fn upper_zone(&self) -> Option<MpeZone> {
+        self.upper_zone
+    }
+
+    /// Whether a zero-indexed MIDI channel is a member channel in one of the enabled zones.
+    pub fn is_member_channel(&self, channel: u8) -> bool {
+        self.member_zone(channel).is_some()
+    }
+
+    /// Process an incoming event, calling `emit` for every resulting event. Note events are always
+    /// passed through, and they are followed by the channel's current expression values if those
+    /// are not at their defaults. Pitch bend, channel pressure, and CC 74 messages on member
+    /// channels are replaced by per-note expression events for the notes playing on that channel.
+    /// All other events are passed through unchanged.
+    pub fn process<S>(&mut self, event: NoteEvent<S>, mut emit: impl FnMut(NoteEvent<S>)) {
+        // Events on invalid channels cannot be part of a zone
+        if event.channel().is_some_and(|channel| channel >= 16) {
+            emit(event);
+            return;
+        }
+
+        match event {
+            NoteEvent::NoteOn {
+                timing,
+                voice_id,
+                channel,
+                note,
+                ..
+            } if self.is_member_channel(channel) => {
+                emit(event);
+
+                // MPE controllers send the initial expression values before the note on, so those
+                // need to be applied to the new note
+                let state = &mut self.channels[channel as usize];
+                state.add_note(note, voice_id);
+                if state.tuning != 0.0 {
+                    emit(NoteEvent::PolyTuning {
+                        timing,
+                        voice_id,
+                        channel,
+                        note,
+                        tuning: state.tuning,
+                    });
+                }
+                if state.pressure != 0.0 {
+                    emit(NoteEvent::PolyPressure {
+                        timing,
+                        voice_id,
+                        channel,
+                        note,
+                        pressure: state.pressure,
+                    });
+                }
+                if state.brightness != 0.5 {
+                    emit(NoteEvent::PolyBrightness {
+                        timing,
+                        voice_id,
+                        channel,
+                        note,
+                        brightness: state.brightness,
+                    });
+                }
+            }
+            NoteEvent::NoteOff { channel, note, .. } | NoteEvent::Choke { channel, note, .. } => {
+                self.channels[channel as usize].remove_note(note);
+                emit(event);
+            }
+            NoteEvent::MidiPitchBend {
+                timing,
+                channel,
+                value,
+            } => match self.member_zone(channel) {
+                Some(zone) => {
+                    // The centered value is 8192, and this maps `[0, 8192]` and `[8192, 16383]` to
+                    // the full range in both directions
+                    let raw_value = value * ((1 << 14) - 1) as f32;
+                    let tuning = ((raw_value - 8192.0) / 8192.0) * zone.member_pitch_bend_range;
+
+                    let state = &mut self.channels[channel as usize];
+                    state.tuning = tuning;
+                    for &(note, voice_id) in state.active_notes() {
+                        emit(NoteEvent::PolyTuning {
+                            timing,
+                            voice_id,
+                            channel,
+                            note,
+                            tuning,
+                        });
+                    }
+                }
+                None => emit(event),
+            },
+            NoteEvent::MidiChannelPressure {
+                timing,
+                channel,
+                pressure,
+            } if self.is_member_channel(channel) => {
+                let state = &mut self.channels[channel as usize];
+                state.pressure = pressure;
+                for &(note, voice_id) in state.active_notes() {
+                    emit(NoteEvent::PolyPressure {
+                        timing,
+                        voice_id,
+                        channel,
+                        note,
+                        pressure,
+                    });
+                }
+            }
+            NoteEvent::MidiCC {
+                timing,
+                channel,
+                cc: TIMBRE_CC,
+                value,
+            } if self.is_member_channel(channel) => {
+                let state = &mut self.channels[channel as usize];
+                state.brightness = value;
+                for &(note, voice_id) in state.active_notes() {
+                    emit(NoteEvent::PolyBrightness {
+                        timing,
+                        voice_id,
+                        channel,
+                        note,
+                        brightness: value,
+                    });
+                }
+            }
+            NoteEvent::MidiCC {
+                timing,
+                channel,
+                cc,
+                value,
+            } => {
+                let mut rpn = None;
+                self.rpn_decoder.process(
+                    NoteEvent::<()>::MidiCC {
+                        timing,
+                        channel,
+                        cc,
+                        value,
+                    },
+                    |event| {
+                        if let NoteEvent::MidiRpn {
+                            parameter, value, ..
+                        } = event
+                        {
+                            rpn = Some((parameter, value));
+                        }
+                    },
+                );
+                if let Some((parameter, value)) = rpn {
+                    self.handle_rpn(channel, parameter, value);
+                }
+
+                emit(event);
+            }
+            event => emit(event),
+        }
+    }
+
+    /// Get the zone `channel` is a member channel of, if any.
+    fn member_zone(&self, channel: u8) -> Option<MpeZone> {
+        match (self.lower_zone, self.upper_zone) {
+            (Some(zone), _) if (1..=zone.member_channels).contains(&channel) => Some(zone),
+            (_, Some(zone)) if (15 - zone.member_channels..15).contains(&channel) => Some(zone),
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to the zone `channel` is a member channel of, if any.
+    fn member_zone_mut(&mut self, channel: u8) -> Option<&mut MpeZone> {
+        match (&mut self.lower_zone, &mut self.upper_zone) {
+            (Some(zone), _) if (1..=zone.member_channels).contains(&channel) => Some(zone),
+            (_, Some(zone)) if (15 - zone.member_channels..15).contains(&channel) => Some(zone),
+            _ => None,
+        }
+    }
+
+    /// Handle MPE configuration and pitch bend sensitivity messages decoded by `rpn_decoder`.
+    /// `value` is the RPN's normalized 14-bit data value.
+    fn handle_rpn(&mut self, channel: u8, parameter: u16, value: f32) {
+        let data = (value * ((1 << 14) - 1) as f32).round() as u16;
+        let (msb, lsb) = ((data >> 7) as u8, (data & 0x7f) as u8);
+        match parameter {
+            RPN_MPE_CONFIGURATION => self.configure_zone(channel, msb),
+            // The MPE specification states that changing the pitch bend sensitivity on any member
+            // channel changes it for the entire zone. The data entry LSB contains the cents.
+            RPN_PITCH_BEND_SENSITIVITY => {
+                if let Some(zone) = self.member_zone_mut(channel) {
+                    zone.member_pitch_bend_range = msb as f32 + (lsb.min(99) as f32 / 100.0);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Handle an MPE configuration message sent to `channel`. Configuring one zone may shrink the
+    /// other zone if they would otherwise overlap, and the other zone is disabled entirely when the
+    /// new zone uses all fifteen member channels.
+    fn configure_zone(&mut self, channel: u8, member_channels: u8) {
+        let member_channels = member_channels.min(15);
+        let new_zone = if member_channels > 0 {
+            Some(MpeZone {
+                member_channels,
+                member_pitch_bend_range: DEFAULT_MEMBER_PITCH_BEND_RANGE,
+            })
+        } else {
+            None
+        };
+
+        match channel {
+            0 => {
+                self.lower_zone = new_zone;
+                if let Some(upper_zone) = &mut self.upper_zone {
+                    upper_zone.member_channels = upper_zone
+                        .member_channels
+                        .min(14u8.saturating_sub(member_channels));
+                    if upper_zone.member_channels == 0 {
+                        self.upper_zone = None;
+                    }
+                }
+            }
+            15 => {
+                self.upper_zone = new_zone;
+                if let Some(lower_zone) = &mut self.lower_zone {
+                    lower_zone.member_channels = lower_zone
+                        .member_channels
+                        .min(14u8.saturating_sub(member_channels));
+                    if lower_zone.member_channels == 0 {
+                        self.lower_zone = None;
+                    }
+                }
+            }
+            // MPE configuration messages are only valid on the manager channels
+            _ => return,
+        }
+
+        // Configuring a zone resets the expressions for all channels
+        for state in &mut self.channels {
+            state.tuning = 0.0;
+            state.pressure = 0.0;
+            state.brightness = 0.5;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMING: u32 = 5;
+
+    /// The CC used for the data entry MSB.
+    const DATA_ENTRY_MSB_CC: u8 = 6;
+    /// The CCs used to select a registered parameter number.
+    const RPN_LSB_CC: u8 = 100;
+    const RPN_MSB_CC: u8 = 101;
+
+    /// Send a single event through the decoder and return the resulting events.
+    fn process(decoder: &mut MpeDecoder, event: NoteEvent<()>) -> Vec<NoteEvent<()>> {
+        let mut events = Vec::new();
+        decoder.process(event, |event| events.push(event));
+
+        events
+    }
+
+    /// Send an RPN message to the decoder.
+    fn send_rpn(decoder: &mut MpeDecoder, channel: u8, rpn: u16, value: u8) {
+        for (cc, value) in [
+            (RPN_MSB_CC, (rpn >> 7) as u8),
+            (RPN_LSB_CC, (rpn & 0x7f) as u8),
+            (DATA_ENTRY_MSB_CC, value),
+        ] {
+            process(
+                decoder,
+                NoteEvent::MidiCC {
+                    timing: TIMING,
+                    channel,
+                    cc,
+                    value: value as f32 / 127.0,
+                },
+            );
+        }
+    }
+
+    fn note_on(channel: u8, note: u8) -> NoteEvent<()> {
+        NoteEvent::NoteOn {
+            timing: TIMING,
+            voice_id: None,
+            channel,
+            note,
+            velocity: 1.0,
+        }
+    }
+
+    #[test]
+    fn translate_member_channel_expressions() {
+        let mut decoder = MpeDecoder::new();
+        process(&mut decoder, note_on(1, 60));
+        process(&mut decoder, note_on(2, 64));
+
+        let events = process(
+            &mut decoder,
+            NoteEvent::MidiPitchBend {
+                timing: TIMING,
+                channel: 2,
+                value: 1.0,
+            },
+        );
+        assert_eq!(
+            events,
+            [NoteEvent::PolyTuning {
+                timing: TIMING,
+                voice_id: None,
+                channel: 2,
+                note: 64,
+                tuning: DEFAULT_MEMBER_PITCH_BEND_RANGE * (8191.0 / 8192.0),
+            }]
+        );
+
+        let events = process(
+            &mut decoder,
+            NoteEvent::MidiChannelPressure {
+                timing: TIMING,
+                channel: 1,
+                pressure: 0.25,
+            },
+        );
+        assert_eq!(
+            events,
+            [NoteEvent::PolyPressure {
+                timing: TIMING,
+                voice_id: None,
+                channel: 1,
+                note: 60,
+                pressure: 0.25,
+            }]
+        );
+
+        // Messages on the manager channel are passed through as is
+        let event = NoteEvent::MidiPitchBend {
+            timing: TIMING,
+            channel: 0,
+            value: 0.25,
+        };
+        assert_eq!(process(&mut decoder, event), [event]);
+    }
+
+    #[test]
+    fn initial_expressions_are_applied_to_new_notes() {
+        let mut decoder = MpeDecoder::new();
+        process(
+            &mut decoder,
+            NoteEvent::MidiCC {
+                timing: TIMING,
+                channel: 3,
+                cc: TIMBRE_CC,
+                value: 0.75,
+            },
+        );
+
+        let events = process(&mut decoder, note_on(3, 60));
+        assert_eq!(
+            events,
+            [
+                note_on(3, 60),
+                NoteEvent::PolyBrightness {
+                    timing: TIMING,
+                    voice_id: None,
+                    channel: 3,
+                    note: 60,
+                    brightness: 0.75,
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn mpe_configuration_message() {
+        let mut decoder = MpeDecoder::new();
+        send_rpn(&mut decoder, 15, RPN_MPE_CONFIGURATION, 4);
+        assert_eq!(decoder.lower_zone().unwrap().member_channels, 10);
+        assert_eq!(decoder.upper_zone().unwrap().member_channels, 4);
+        assert!(decoder.is_member_channel(11));
+        assert!(!decoder.is_member_channel(15));
+
+        send_rpn(&mut decoder, 0, RPN_MPE_CONFIGURATION, 0);
+        assert_eq!(decoder.lower_zone(), None);
+        assert!(!decoder.is_member_channel(1));
+
+        // Without any zone covering the channel, pitch bend is passed through unchanged
+        process(&mut decoder, note_on(1, 60));
+        let event = NoteEvent::MidiPitchBend {
+            timing: TIMING,
+            channel: 1,
+            value: 1.0,
+        };
+        assert_eq!(process(&mut decoder, event), [event]);
+    }
+
+    #[test]
+    fn full_zone_disables_other_zone() {
+        let mut decoder = MpeDecoder::new();
+        send_rpn(&mut decoder, 15, RPN_MPE_CONFIGURATION, 4);
+        assert!(decoder.lower_zone().is_some() && decoder.upper_zone().is_some());
+
+        send_rpn(&mut decoder, 0, RPN_MPE_CONFIGURATION, 15);
+        assert_eq!(decoder.lower_zone().unwrap().member_channels, 15);
+        assert_eq!(decoder.upper_zone(), None);
+        assert!(decoder.is_member_channel(14));
+
+        send_rpn(&mut decoder, 15, RPN_MPE_CONFIGURATION, 4);
+        send_rpn(&mut decoder, 15, RPN_MPE_CONFIGURATION, 15);
+        assert_eq!(decoder.lower_zone(), None);
+        assert_eq!(decoder.upper_zone().unwrap().member_channels, 15);
+        assert!(decoder.is_member_channel(1));
+    }
+
+    #[test]
+    fn pitch_bend_sensitivity() {
+        let mut decoder = MpeDecoder::new();
+        send_rpn(&mut decoder, 5, RPN_PITCH_BEND_SENSITIVITY, 24);
+        assert_eq!(decoder.lower_zone().unwrap().member_pitch_bend_range, 24.0);
+
+        process(&mut decoder, note_on(1, 60));
+        let events = process(
+            &mut decoder,
+            NoteEvent::MidiPitchBend {
+                timing: TIMING,
+                channel: 1,
+                value: 0.0,
+            },
+        );
+        assert_eq!(
+            events,
+            [NoteEvent::PolyTuning {
+                timing: TIMING,
+                voice_id: None,
+                channel: 1,
+                note: 60,
+                tuning: -24.0,
+            }]
+        );
+    }
+}
@@ -9,6 +9,7 @@ use std::fmt::{Debug, Display};
 use std::sync::Arc;
 
 use self::internals::ParamPtr;
+use crate::wrapper::state::StateContext;
 
 // The proc-macro for deriving `Params`
 pub use nih_plug_derive::Params;
@@ -50,6 +51,39 @@ bitflags::bitflags! {
         /// Don't show this parameter when generating a generic UI for the plugin using one of
         /// NIH-plug's generic UI widgets.
         const HIDE_IN_GENERIC_UI = 1 << 3;
+        /// Lets the host modulate the parameter for all voices playing a specific key. Requires a
+        /// polyphonic modulation ID. The modulation is sent to the plugin as
+        /// [`NoteEvent::TargetedModulation`][crate::prelude::NoteEvent::TargetedModulation]
+        /// events. This is only supported by CLAP.
+        const MODULATABLE_PER_KEY = 1 << 4;
+        /// The same as `MODULATABLE_PER_KEY`, but for all voices on a specific MIDI channel.
+        const MODULATABLE_PER_CHANNEL = 1 << 5;
+        /// The same as `MODULATABLE_PER_KEY`, but for all voices from a specific note port.
+        const MODULATABLE_PER_PORT = 1 << 6;
+        /// The parameter's value is only ever set by the plugin, for instance to expose a gain
+        /// reduction meter to the host. The host and the generic UIs display it, but they cannot
+        /// change it. This also implies `NON_AUTOMATABLE`. Use
+        /// [`ProcessContext::set_output_parameter()`][crate::prelude::ProcessContext::set_output_parameter()]
+        /// to publish a new value from the audio thread.
+        const READ_ONLY = 1 << 7;
+    }
+}
+
+bitflags::bitflags! {
+    /// Describes which parts of the plugin's parameter information have changed at runtime. Passed
+    /// to [`GuiContext::param_info_changed()`][crate::prelude::GuiContext::param_info_changed()]
+    /// and [`InitContext::param_info_changed()`][crate::prelude::InitContext::param_info_changed()]
+    /// so the host can query this information again.
+    #[repr(transparent)]
+    #[derive(Default)]
+    pub struct ParamInfoChanges: u32 {
+        /// The parameters' values have changed without the host being informed about it.
+        const VALUES = 1 << 0;
+        /// The parameters' values are now displayed differently, for instance because a delay time
+        /// parameter's `value_to_string` function shows note values when tempo sync is enabled.
+        const VALUE_STRINGS = 1 << 1;
+        /// The parameters' names or default values have changed.
+        const INFO = 1 << 2;
     }
 }
 
@@ -249,6 +283,13 @@ pub(crate) trait ParamMut: Param {
 /// with the `#[persist = "key"]` attribute containing types that can be serialized and deserialized
 /// with [Serde](https://serde.rs/).
 ///
+/// ## `#[persist("key", skip_presets)]`
+///
+/// The same as `#[persist = "key"]`, but the field is not saved when the plugin's state is saved as
+/// a preset, and it is left untouched when a preset is loaded. This is useful for things like
+/// sample paths or editor state that should be stored in the host's project but not in presets.
+/// See [`StateContext`].
+///
 /// ## `#[nested]`, `#[nested(group_name = "group name")]`
 ///
 /// Finally, the `Params` object may include parameters from other objects. Setting a group name is
@@ -311,6 +352,27 @@ pub unsafe trait Params: 'static + Send + Sync {
     /// [`persist::deserialize_field()`] under the hood.
     #[allow(unused_variables)]
     fn deserialize_fields(&self, serialized: &BTreeMap<String, String>) {}
+
+    /// The same as [`serialize_fields()`][Self::serialize_fields()], but this omits fields that
+    /// should not be stored for the [`StateContext`] the state is being saved for. The derive macro
+    /// skips fields marked with `#[persist("key", skip_presets)]` when saving a preset. The default
+    /// implementation serializes all fields.
+    #[allow(unused_variables)]
+    fn serialize_fields_with_context(&self, context: StateContext) -> BTreeMap<String, String> {
+        self.serialize_fields()
+    }
+
+    /// The same as [`deserialize_fields()`][Self::deserialize_fields()], but this ignores fields
+    /// that should not be restored for the [`StateContext`] the state is being loaded in. The
+    /// default implementation restores all fields.
+    #[allow(unused_variables)]
+    fn deserialize_fields_with_context(
+        &self,
+        serialized: &BTreeMap<String, String>,
+        context: StateContext,
+    ) {
+        self.deserialize_fields(serialized)
+    }
 }
 
 /// This may be useful when building generic UIs using nested `Params` objects.
@@ -326,4 +388,17 @@ unsafe impl<P: Params> Params for Arc<P> {
     fn deserialize_fields(&self, serialized: &BTreeMap<String, String>) {
         self.as_ref().deserialize_fields(serialized)
     }
+
+    fn serialize_fields_with_context(&self, context: StateContext) -> BTreeMap<String, String> {
+        self.as_ref().serialize_fields_with_context(context)
+    }
+
+    fn deserialize_fields_with_context(
+        &self,
+        serialized: &BTreeMap<String, String>,
+        context: StateContext,
+    ) {
+        self.as_ref()
+            .deserialize_fields_with_context(serialized, context)
+    }
 }
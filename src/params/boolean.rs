@@ -332,4 +332,28 @@ impl BoolParam {
         self.flags.insert(ParamFlags::HIDE_IN_GENERIC_UI);
         self
     }
+
+    /// Let the host modulate this parameter for all voices playing a specific key. This requires
+    /// a polyphonic modulation ID to be set using `with_poly_modulation_id()`. The modulation is
+    /// sent to the plugin as
+    /// [`NoteEvent::TargetedModulation`][crate::prelude::NoteEvent::TargetedModulation] events.
+    /// This is only supported by CLAP.
+    pub fn modulatable_per_key(mut self) -> Self {
+        self.flags.insert(ParamFlags::MODULATABLE_PER_KEY);
+        self
+    }
+
+    /// Let the host modulate this parameter for all voices on a specific MIDI channel. See
+    /// [`modulatable_per_key()`][Self::modulatable_per_key()].
+    pub fn modulatable_per_channel(mut self) -> Self {
+        self.flags.insert(ParamFlags::MODULATABLE_PER_CHANNEL);
+        self
+    }
+
+    /// Let the host modulate this parameter for all voices from a specific note port. See
+    /// [`modulatable_per_key()`][Self::modulatable_per_key()].
+    pub fn modulatable_per_port(mut self) -> Self {
+        self.flags.insert(ParamFlags::MODULATABLE_PER_PORT);
+        self
+    }
 }
@@ -413,6 +413,30 @@ impl<T: Enum + PartialEq + 'static> EnumParam<T> {
         self.inner.inner = self.inner.inner.hide_in_generic_ui();
         self
     }
+
+    /// Let the host modulate this parameter for all voices playing a specific key. This requires
+    /// a polyphonic modulation ID to be set using `with_poly_modulation_id()`. The modulation is
+    /// sent to the plugin as
+    /// [`NoteEvent::TargetedModulation`][crate::prelude::NoteEvent::TargetedModulation] events.
+    /// This is only supported by CLAP.
+    pub fn modulatable_per_key(mut self) -> Self {
+        self.inner.inner = self.inner.inner.modulatable_per_key();
+        self
+    }
+
+    /// Let the host modulate this parameter for all voices on a specific MIDI channel. See
+    /// [`modulatable_per_key()`][Self::modulatable_per_key()].
+    pub fn modulatable_per_channel(mut self) -> Self {
+        self.inner.inner = self.inner.inner.modulatable_per_channel();
+        self
+    }
+
+    /// Let the host modulate this parameter for all voices from a specific note port. See
+    /// [`modulatable_per_key()`][Self::modulatable_per_key()].
+    pub fn modulatable_per_port(mut self) -> Self {
+        self.inner.inner = self.inner.inner.modulatable_per_port();
+        self
+    }
 }
 
 impl EnumParamInner {
@@ -5,7 +5,7 @@ use std::sync::Arc;
 
 use crate::prelude::{
     AsyncExecutor, AudioIOLayout, AuxiliaryBuffers, Buffer, BufferConfig, Editor, InitContext,
-    MidiConfig, Params, PluginState, ProcessContext, SysExMessage,
+    MidiConfig, MpeConfig, NoteName, Params, PluginState, ProcessContext, SysExMessage,
 };
 
 pub mod clap;
@@ -106,6 +106,11 @@ pub trait Plugin: Default + Send + 'static {
     /// the plugin will consume all note and MIDI CC input. If you don't want that, then you will
     /// need to forward those events yourself.
     const MIDI_OUTPUT: MidiConfig = MidiConfig::None;
+    /// Whether the plugin supports MIDI Polyphonic Expression, and whether the wrapper should
+    /// translate MPE input into per-note expression events. This requires
+    /// [`MIDI_INPUT`][Self::MIDI_INPUT] to be set to [`MidiConfig::MidiCCs`]. See [`MpeConfig`] for
+    /// more information.
+    const MPE_INPUT: MpeConfig = MpeConfig::None;
     /// If enabled, the audio processing cycle may be split up into multiple smaller chunks if
     /// parameter values change occur in the middle of the buffer. Depending on the host these
     /// blocks may be as small as a single sample. Bitwig Studio sends at most one parameter change
@@ -117,6 +122,13 @@ pub trait Plugin: Default + Send + 'static {
     /// to do offline processing.
     const HARD_REALTIME_ONLY: bool = false;
 
+    /// If this is set to true, then the plugin will process audio in double precision. The wrappers
+    /// will then call [`process_f64()`][Self::process_f64()] instead of
+    /// [`process()`][Self::process()], and they will ask the host for 64-bit audio buffers. If the
+    /// host only supports 32-bit audio, then the audio is converted to and from `f64` buffers
+    /// before and after calling `process_f64()`.
+    const PROCESS_F64: bool = false;
+
     /// The plugin's SysEx message type if it supports sending or receiving MIDI SysEx messages, or
     /// `()` if it does not. This type can be a struct or enum wrapping around one or more message
     /// types, and the [`SysExMessage`] trait is then used to convert between this type and basic
@@ -171,13 +183,32 @@ pub trait Plugin: Default + Send + 'static {
     /// field. Some examples of use cases for this are renaming parameter indices, remapping
     /// parameter values, and preserving old preset compatibility when introducing new parameters
     /// with default values that would otherwise change the sound of a preset. Keep in mind that
-    /// automation may still be broken in the first two use cases.
+    /// automation may still be broken in the first two use cases. The
+    /// [`StateMigrations`][crate::prelude::StateMigrations] helper can be used to declare these
+    /// migrations instead of modifying the state by hand.
     ///
     /// # Note
     ///
     /// This is an advanced feature that the vast majority of plugins won't need to implement.
     fn filter_state(state: &mut PluginState) {}
 
+    /// The names for the plugin's notes, for instance for drum-map style instruments. Hosts may
+    /// display these in their piano rolls or drum editors. This is only used when
+    /// [`MIDI_INPUT`][Self::MIDI_INPUT] is set to [`MidiConfig::Basic`] or higher. Notes that are
+    /// not part of the returned list keep their default names. When the names change at runtime,
+    /// call `note_names_changed()` on the [`GuiContext`][crate::prelude::GuiContext] or the
+    /// [`ProcessContext`][crate::prelude::ProcessContext] to let the host know that it should query
+    /// them again.
+    ///
+    /// The wrapper calls this when the plugin instance is created and after the plugin calls
+    /// `note_names_changed()`, and it caches the result for the host. This is called from the main
+    /// thread while the plugin is locked, so this should be cheap to compute. VST3 hosts can only
+    /// query note names through a program list, so VST3 plugins that accept MIDI input always have
+    /// a program list.
+    fn note_names(&self) -> Vec<NoteName> {
+        Vec::new()
+    }
+
     //
     // The following functions follow the lifetime of the plugin.
     //
@@ -232,10 +263,9 @@ pub trait Plugin: Default + Send + 'static {
     ///
     /// The `context` object contains context information as well as callbacks for working with note
     /// events. The [`AuxiliaryBuffers`] contain the plugin's sidechain input buffers and
-    /// auxiliary output buffers if it has any.
-    ///
-    /// TODO: Provide a way to access auxiliary input channels if the IO configuration is
-    ///       asymmetric
+    /// auxiliary output buffers if it has any. They also contain a copy of all of the main input's
+    /// channels, which is needed to access the excess input channels when the main input has more
+    /// channels than the main output.
     fn process(
         &mut self,
         buffer: &mut Buffer,
@@ -243,6 +273,22 @@ pub trait Plugin: Default + Send + 'static {
         context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus;
 
+    /// The same as [`process()`][Self::process()], but for double precision audio. This is called
+    /// instead of `process()` when [`PROCESS_F64`][Self::PROCESS_F64] is set to true, and it needs
+    /// to be implemented in that case. `process()` is never called for those plugins.
+    fn process_f64(
+        &mut self,
+        buffer: &mut Buffer<f64>,
+        aux: &mut AuxiliaryBuffers<f64>,
+        context: &mut impl ProcessContext<Self>,
+    ) -> ProcessStatus {
+        nih_debug_assert_failure!(
+            "'Plugin::PROCESS_F64' is set, but 'Plugin::process_f64()' has not been implemented"
+        );
+
+        ProcessStatus::Error("'Plugin::process_f64()' has not been implemented")
+    }
+
     /// Called when the plugin is deactivated. The host will call
     /// [`initialize()`][Self::initialize()] again before the plugin resumes processing audio. These
     /// two functions will not be called when the host only temporarily stops processing audio. You
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use super::Plugin;
 use crate::prelude::{ClapFeature, RemoteControlsContext};
 
@@ -25,6 +27,43 @@ pub trait ClapPlugin: Plugin {
     /// that the host can use to provide better hardware mapping for a plugin. See the linked
     /// extension for more information.
     fn remote_controls(&self, context: &mut impl RemoteControlsContext) {}
+
+    /// Called when the host's tunings have changed, for instance because the user loaded a
+    /// different scale. This is called from the audio thread at the start of the next process
+    /// call. Note frequencies should be recomputed using
+    /// [`ProcessContext::note_frequency()`][crate::prelude::ProcessContext::note_frequency()].
+    /// Hosts only provide tunings to plugins that accept note input.
+    fn tuning_changed(&mut self) {}
+
+    /// The file extensions used for the plugin's preset files, without the leading period. Preset
+    /// files contain a [`PluginState`][crate::prelude::PluginState] serialized as JSON. If this is
+    /// not empty, then the host can index the presets in the locations returned by
+    /// [`clap_preset_locations()`][Self::clap_preset_locations()] through CLAP's preset discovery
+    /// factory, and it can then load those presets through the `preset-load` extension.
+    /// [`Plugin::filter_state()`] is called before a preset is loaded, just like when loading the
+    /// plugin's state.
+    const CLAP_PRESET_FILE_EXTENSIONS: &'static [&'static str] = &[];
+
+    /// The files or directories containing the plugin's presets. See
+    /// [`CLAP_PRESET_FILE_EXTENSIONS`][Self::CLAP_PRESET_FILE_EXTENSIONS]. This is called when the
+    /// host indexes the plugin's presets, which can happen without creating a plugin instance.
+    fn clap_preset_locations() -> Vec<ClapPresetLocation> {
+        Vec::new()
+    }
+}
+
+/// A location containing presets for the plugin. See
+/// [`ClapPlugin::clap_preset_locations()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClapPresetLocation {
+    /// A name for this location that the host may display in its preset browser.
+    pub name: String,
+    /// An absolute path to either a single preset file or to a directory containing preset files.
+    /// Directories are scanned recursively by the host.
+    pub path: PathBuf,
+    /// Whether these presets are factory content that ships with the plugin. Otherwise the presets
+    /// are treated as user content.
+    pub is_factory_content: bool,
 }
 
 /// Configuration for the plugin's polyphonic modulation options, if it supports .
@@ -1,5 +1,5 @@
 use super::Plugin;
-use crate::prelude::Vst3SubCategory;
+use crate::prelude::{PluginState, Vst3SubCategory};
 
 /// Provides auxiliary metadata needed for a VST3 plugin.
 pub trait Vst3Plugin: Plugin {
@@ -17,6 +17,27 @@ pub trait Vst3Plugin: Plugin {
     /// [`VST3_CLASS_ID`][Self::VST3_CLASS_ID`] in the correct order for the current platform so
     /// projects and presets can be shared between platforms. This should not be overridden.
     const PLATFORM_VST3_CLASS_ID: [u8; 16] = swap_vst3_uid_byte_order(Self::VST3_CLASS_ID);
+
+    /// Factory presets that are exposed to the host as a VST3 program list. Hosts without their own
+    /// preset browser use this list to let the user select presets, and the host can also switch
+    /// between presets through an automatically generated program change parameter. Selecting a
+    /// preset loads its state the same way the host would load the plugin's state, so
+    /// [`Plugin::filter_state()`] is also called for these states.
+    ///
+    /// Queried only once immediately after the plugin instance is created.
+    fn vst3_factory_presets(&self) -> Vec<Vst3FactoryPreset> {
+        Vec::new()
+    }
+}
+
+/// A factory preset that can be selected through the host's program list. See
+/// [`Vst3Plugin::vst3_factory_presets()`].
+#[derive(Debug, Clone)]
+pub struct Vst3FactoryPreset {
+    /// The preset's name as shown in the host's program list.
+    pub name: String,
+    /// The plugin state that's loaded when the preset is selected.
+    pub state: PluginState,
 }
 
 #[cfg(not(target_os = "windows"))]
@@ -14,31 +14,41 @@ pub use crate::formatters;
 pub use crate::util;
 
 pub use crate::audio_setup::{
-    new_nonzero_u32, AudioIOLayout, AuxiliaryBuffers, BufferConfig, PortNames, ProcessMode,
+    new_nonzero_u32, AudioIOLayout, AuxiliaryBuffers, BufferConfig, ChannelLayout,
+    PortChannelLayouts, PortNames, ProcessMode,
 };
 pub use crate::buffer::Buffer;
-pub use crate::context::gui::{AsyncExecutor, GuiContext, ParamSetter};
+pub use crate::context::gui::{
+    AsyncExecutor, ContextMenuItem, GuiContext, ParamBatch, ParamSetter,
+};
 pub use crate::context::init::InitContext;
 pub use crate::context::process::{ProcessContext, Transport};
 pub use crate::context::remote_controls::{
     RemoteControlsContext, RemoteControlsPage, RemoteControlsSection,
 };
-pub use crate::context::PluginApi;
+pub use crate::context::track_info::{TrackInfo, TrackType};
+pub use crate::context::{HostColor, PluginApi};
 // This also includes the derive macro
-pub use crate::editor::{Editor, ParentWindowHandle};
+pub use crate::editor::{
+    AutomationState, Editor, EditorSizeConstraints, ParamIndication, ParamMapping,
+    ParentWindowHandle,
+};
+pub use crate::midi::learn::{MidiLearnSource, MidiMapping};
+pub use crate::midi::mpe::MpeConfig;
 pub use crate::midi::sysex::SysExMessage;
-pub use crate::midi::{control_change, MidiConfig, NoteEvent, PluginNoteEvent};
+pub use crate::midi::{control_change, MidiConfig, NoteEvent, NoteName, PluginNoteEvent};
 pub use crate::params::enums::{Enum, EnumParam};
 pub use crate::params::internals::ParamPtr;
 pub use crate::params::range::{FloatRange, IntRange};
 pub use crate::params::smoothing::{AtomicF32, Smoothable, Smoother, SmoothingStyle};
 pub use crate::params::Params;
-pub use crate::params::{BoolParam, FloatParam, IntParam, Param, ParamFlags};
-pub use crate::plugin::clap::{ClapPlugin, PolyModulationConfig};
+pub use crate::params::{BoolParam, FloatParam, IntParam, Param, ParamFlags, ParamInfoChanges};
+pub use crate::plugin::clap::{ClapPlugin, ClapPresetLocation, PolyModulationConfig};
 #[cfg(feature = "vst3")]
-pub use crate::plugin::vst3::Vst3Plugin;
+pub use crate::plugin::vst3::{Vst3FactoryPreset, Vst3Plugin};
 pub use crate::plugin::{Plugin, ProcessStatus, TaskExecutor};
 pub use crate::wrapper::clap::features::ClapFeature;
-pub use crate::wrapper::state::PluginState;
+pub use crate::wrapper::state::migrations::{MigrationStep, StateMigrations};
+pub use crate::wrapper::state::{PluginState, StateContext};
 #[cfg(feature = "vst3")]
 pub use crate::wrapper::vst3::subcategories::Vst3SubCategory;
@@ -0,0 +1,908 @@
+//! A headless host for driving a [`Plugin`] from unit tests. This instantiates and initializes the
+//! plugin the same way the plugin API wrappers do, but without any CLAP or VST3 glue. Audio,
+//! scripted note events, and parameter automation are passed to [`TestHost::process()`], which
+//! returns the plugin's output audio and the events it sent.
+//!
+//! ```ignore
+//! use nih_plug::prelude::*;
+//! use nih_plug::testing::{ParamAutomation, TestHost, TestInput};
+//!
+//! let mut host = TestHost::<Gain>::new(
+//!     Gain::AUDIO_IO_LAYOUTS[0],
+//!     BufferConfig {
+//!         sample_rate: 44100.0,
+//!         min_buffer_size: None,
+//!         max_buffer_size: 512,
+//!         process_mode: ProcessMode::Offline,
+//!     },
+//! )
+//! .unwrap();
+//!
+//! let output = host.process(
+//!     1024,
+//!     TestInput {
+//!         main_input: vec![vec![1.0; 1024]; 2],
+//!         automation: vec![ParamAutomation::new(512, "gain", 0.0)],
+//!         ..TestInput::default()
+//!     },
+//! );
+//! assert_eq!(output.status, ProcessStatus::Normal);
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use crate::prelude::{
+    AudioIOLayout, AuxiliaryBuffers, BufferConfig, InitContext, ParamInfoChanges, ParamPtr, Params,
+    Plugin, PluginApi, PluginNoteEvent, ProcessContext, ProcessStatus, TaskExecutor, TrackInfo,
+    Transport,
+};
+use crate::util::{self, permit_alloc};
+use crate::wrapper::util::buffer_management::{
+    BufferManager, ChannelPointers, HostChannelPointers, PluginBufferManager, Sample,
+};
+use crate::wrapper::util::process_wrapper;
+
+/// A mock host that owns a plugin instance and calls its `process()` function over buffers
+/// supplied by the caller. The plugin is initialized when the host is created, and it gets
+/// deactivated when the host is dropped.
+pub struct TestHost<P: Plugin> {
+    plugin: P,
+    task_executor: TaskExecutor<P>,
+    /// The plugin's parameters. These `ParamPtr`s in `param_id_to_ptr` point into this object, so
+    /// it needs to be kept alive for as long as the host exists.
+    params: Arc<dyn Params>,
+    param_id_to_ptr: HashMap<String, ParamPtr>,
+
+    audio_io_layout: AudioIOLayout,
+    buffer_config: BufferConfig,
+    /// Turns the caller's audio data into `Buffer`s, exactly like in the plugin API wrappers.
+    buffer_manager: PluginBufferManager,
+
+    /// The transport information passed to the plugin. The position is advanced after every
+    /// `process()` call while the transport is playing.
+    transport: TestTransport,
+    /// The latency reported through `set_latency_samples()`.
+    current_latency: Cell<u32>,
+    /// Tasks the plugin scheduled from its process function. These are run on the calling thread
+    /// after the block they were scheduled from has been processed.
+    pending_tasks: RefCell<Vec<P::BackgroundTask>>,
+}
+
+/// The transport information [`TestHost`] passes to the plugin. This is converted to a
+/// [`Transport`] for every block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestTransport {
+    /// Whether the transport is currently running. The song position only advances while this is
+    /// set.
+    pub playing: bool,
+    /// Whether recording is enabled in the project.
+    pub recording: bool,
+    /// The project's tempo in beats per minute.
+    pub tempo: Option<f64>,
+    /// The time signature's numerator.
+    pub time_sig_numerator: Option<i32>,
+    /// The time signature's denominator.
+    pub time_sig_denominator: Option<i32>,
+    /// The position in the song in samples at the start of the next `process()` call.
+    pub pos_samples: i64,
+}
+
+/// A parameter change that should happen at a specific sample offset during a
+/// [`TestHost::process()`] call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamAutomation {
+    /// The sample offset within the processed audio.
+    pub timing: u32,
+    /// The parameter's ID, as set with the `#[id = "..."]` attribute.
+    pub param_id: String,
+    /// The new normalized value for the parameter.
+    pub normalized_value: f32,
+}
+
+/// The input for a single [`TestHost::process()`] call. Every audio channel must contain exactly
+/// as many samples as are being processed.
+pub struct TestInput<P: Plugin> {
+    /// The main input's channels. Must match the audio IO layout's main input channel count, or be
+    /// empty if the layout does not have a main input.
+    pub main_input: Vec<Vec<f32>>,
+    /// The channels for every auxiliary input port.
+    pub aux_inputs: Vec<Vec<Vec<f32>>>,
+    /// Note events sent to the plugin. The timings are relative to the start of the processed
+    /// audio, and the events don't need to be sorted.
+    pub events: Vec<PluginNoteEvent<P>>,
+    /// Parameter changes at sample offsets. If the plugin does not use
+    /// [`Plugin::SAMPLE_ACCURATE_AUTOMATION`] then these are applied at the start of the block
+    /// they fall in, like a regular host would do.
+    pub automation: Vec<ParamAutomation>,
+}
+
+/// The result of a [`TestHost::process()`] call.
+pub struct TestOutput<P: Plugin> {
+    /// The main output's channels.
+    pub main_output: Vec<Vec<f32>>,
+    /// The channels for every auxiliary output port.
+    pub aux_outputs: Vec<Vec<Vec<f32>>>,
+    /// The events the plugin sent, with timings relative to the start of the processed audio.
+    pub events: Vec<PluginNoteEvent<P>>,
+    /// The parameter changes the plugin made from its process function through
+    /// [`ProcessContext::set_parameter()`], with timings relative to the start of the processed
+    /// audio. Like in the plugin API wrappers, these changes are applied after the block they were
+    /// made in has been processed.
+    pub param_changes: Vec<ParamAutomation>,
+    /// The status returned from the last `Plugin::process()` call. Processing stops at the first
+    /// error.
+    pub status: ProcessStatus,
+}
+
+/// An error that occurred while setting up a [`TestHost`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestHostError {
+    /// The plugin returned `false` during initialization.
+    InitializationFailed,
+}
+
+/// The [`InitContext`] implementation for the test host.
+struct TestInitContext<'a, P: Plugin> {
+    task_executor: &'a TaskExecutor<P>,
+    current_latency: &'a Cell<u32>,
+    audio_io_layout: &'a AudioIOLayout,
+}
+
+/// The [`ProcessContext`] implementation for the test host.
+struct TestProcessContext<'a, P: Plugin> {
+    pending_tasks: &'a RefCell<Vec<P::BackgroundTask>>,
+    current_latency: &'a Cell<u32>,
+    input_events: &'a [PluginNoteEvent<P>],
+    // The current index in `input_events`, this works the same way as in the standalone wrapper
+    input_events_idx: usize,
+    output_events: &'a mut Vec<PluginNoteEvent<P>>,
+    param_changes: &'a mut Vec<(ParamPtr, f32)>,
+    transport: Transport,
+}
+
+impl Default for TestTransport {
+    fn default() -> Self {
+        Self {
+            playing: false,
+            recording: false,
+            tempo: Some(120.0),
+            time_sig_numerator: Some(4),
+            time_sig_denominator: Some(4),
+            pos_samples: 0,
+        }
+    }
+}
+
+impl ParamAutomation {
+    /// Set the parameter with ID `param_id` to `normalized_value` at sample `timing`.
+    pub fn new(timing: u32, param_id: impl Into<String>, normalized_value: f32) -> Self {
+        Self {
+            timing,
+            param_id: param_id.into(),
+            normalized_value,
+        }
+    }
+}
+
+impl<P: Plugin> Default for TestInput<P> {
+    fn default() -> Self {
+        Self {
+            main_input: Vec::new(),
+            aux_inputs: Vec::new(),
+            events: Vec::new(),
+            automation: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for TestHostError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TestHostError::InitializationFailed => write!(f, "The plugin failed to initialize"),
+        }
+    }
+}
+
+impl std::error::Error for TestHostError {}
+
+impl<P: Plugin> TestHost<P> {
+    /// Instantiate the plugin using its [`Default`] implementation and initialize it for the
+    /// specified audio IO layout and buffer configuration. `Plugin::reset()` is called after
+    /// initialization, just like in the plugin API wrappers.
+    pub fn new(
+        audio_io_layout: AudioIOLayout,
+        buffer_config: BufferConfig,
+    ) -> Result<Self, TestHostError> {
+        let mut plugin = P::default();
+        let task_executor = plugin.task_executor();
+        let params = plugin.params();
+        let param_id_to_ptr: HashMap<String, ParamPtr> = params
+            .param_map()
+            .into_iter()
+            .map(|(param_id, param_ptr, _)| (param_id, param_ptr))
+            .collect();
+
+        // Before initializing the plugin, make sure all smoothers are set the the default values
+        for param in param_id_to_ptr.values() {
+            unsafe { param.update_smoother(buffer_config.sample_rate, true) };
+        }
+
+        let current_latency = Cell::new(0);
+        let initialized = plugin.initialize(
+            &audio_io_layout,
+            &buffer_config,
+            &mut TestInitContext {
+                task_executor: &task_executor,
+                current_latency: &current_latency,
+                audio_io_layout: &audio_io_layout,
+            },
+        );
+        if !initialized {
+            return Err(TestHostError::InitializationFailed);
+        }
+        process_wrapper(|| plugin.reset());
+
+        Ok(Self {
+            plugin,
+            task_executor,
+            params,
+            param_id_to_ptr,
+
+            buffer_manager: PluginBufferManager::for_plugin::<P>(
+                buffer_config.max_buffer_size as usize,
+                audio_io_layout,
+            ),
+            audio_io_layout,
+            buffer_config,
+
+            transport: TestTransport::default(),
+            current_latency,
+            pending_tasks: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// The plugin instance. Useful for inspecting the plugin's internal state after processing.
+    pub fn plugin(&self) -> &P {
+        &self.plugin
+    }
+
+    /// A mutable reference to the plugin instance.
+    pub fn plugin_mut(&mut self) -> &mut P {
+        &mut self.plugin
+    }
+
+    /// The plugin's parameters object, as returned by `Plugin::params()`.
+    pub fn params(&self) -> &Arc<dyn Params> {
+        &self.params
+    }
+
+    /// The audio IO layout the plugin was initialized with.
+    pub fn audio_io_layout(&self) -> &AudioIOLayout {
+        &self.audio_io_layout
+    }
+
+    /// The buffer configuration the plugin was initialized with.
+    pub fn buffer_config(&self) -> &BufferConfig {
+        &self.buffer_config
+    }
+
+    /// The transport information passed to the plugin during the next `process()` call.
+    pub fn transport_mut(&mut self) -> &mut TestTransport {
+        &mut self.transport
+    }
+
+    /// The latency the plugin last reported, in samples.
+    pub fn latency_samples(&self) -> u32 {
+        self.current_latency.get()
+    }
+
+    /// Immediately set a parameter to a new normalized value. The parameter's smoother will
+    /// smooth towards the new value like it would with host automation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugin does not have a parameter with this ID.
+    pub fn set_parameter_normalized(&mut self, param_id: &str, normalized: f32) {
+        self.set_parameter_normalized_inner(param_id, normalized);
+    }
+
+    /// Call the plugin's `reset()` function.
+    pub fn reset(&mut self) {
+        process_wrapper(|| self.plugin.reset());
+    }
+
+    /// Process `num_samples` samples of audio. The audio is split up into blocks of at most
+    /// [`BufferConfig::max_buffer_size`] samples, and if the plugin uses
+    /// [`Plugin::SAMPLE_ACCURATE_AUTOMATION`] then the blocks are also split on parameter
+    /// changes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input's channel counts don't match the audio IO layout, if any of the input
+    /// channels does not contain `num_samples` samples, if an event or parameter change is out of
+    /// bounds, or if the automation refers to an unknown parameter.
+    pub fn process(&mut self, num_samples: usize, input: TestInput<P>) -> TestOutput<P> {
+        let TestInput {
+            mut main_input,
+            mut aux_inputs,
+            mut events,
+            mut automation,
+        } = input;
+
+        let num_main_input_channels = self
+            .audio_io_layout
+            .main_input_channels
+            .map(NonZeroU32::get)
+            .unwrap_or(0) as usize;
+        let num_main_output_channels = self
+            .audio_io_layout
+            .main_output_channels
+            .map(NonZeroU32::get)
+            .unwrap_or(0) as usize;
+        assert_eq!(
+            main_input.len(),
+            num_main_input_channels,
+            "Mismatching main input channel count"
+        );
+        assert_eq!(
+            aux_inputs.len(),
+            self.audio_io_layout.aux_input_ports.len(),
+            "Mismatching number of auxiliary inputs"
+        );
+        for (aux_input, num_channels) in aux_inputs.iter().zip(self.audio_io_layout.aux_input_ports)
+        {
+            assert_eq!(
+                aux_input.len(),
+                num_channels.get() as usize,
+                "Mismatching auxiliary input channel count"
+            );
+        }
+        for channel in main_input.iter().chain(aux_inputs.iter().flatten()) {
+            assert_eq!(
+                channel.len(),
+                num_samples,
+                "Mismatching input channel length"
+            );
+        }
+
+        // These are processed in order, so just like in the VST3 wrapper they need to be sorted
+        // first. Both sorts are stable.
+        events.sort_by_key(|event| event.timing());
+        automation.sort_by_key(|automation| automation.timing);
+        assert!(
+            events
+                .iter()
+                .all(|event| (event.timing() as usize) < num_samples),
+            "Out of bounds note event"
+        );
+        assert!(
+            automation
+                .iter()
+                .all(|param_change| (param_change.timing as usize) < num_samples),
+            "Out of bounds parameter automation"
+        );
+
+        let mut main_output = vec![vec![0.0f32; num_samples]; num_main_output_channels];
+        let mut aux_outputs: Vec<Vec<Vec<f32>>> = self
+            .audio_io_layout
+            .aux_output_ports
+            .iter()
+            .map(|num_channels| vec![vec![0.0f32; num_samples]; num_channels.get() as usize])
+            .collect();
+
+        // `BufferManager` works with raw channel pointers like the ones a plugin host would provide
+        let mut host_ptrs = HostChannelPtrs {
+            main_input: main_input.iter_mut().map(|c| c.as_mut_ptr()).collect(),
+            main_output: main_output.iter_mut().map(|c| c.as_mut_ptr()).collect(),
+            aux_inputs: aux_inputs
+                .iter_mut()
+                .map(|port| port.iter_mut().map(|c| c.as_mut_ptr()).collect())
+                .collect(),
+            aux_outputs: aux_outputs
+                .iter_mut()
+                .map(|port| port.iter_mut().map(|c| c.as_mut_ptr()).collect())
+                .collect(),
+        };
+
+        let mut output_events = Vec::new();
+        let mut block_input_events = Vec::with_capacity(events.len());
+        let mut block_output_events = Vec::new();
+        let mut param_changes = Vec::new();
+        let mut block_param_changes = Vec::new();
+        let mut status = ProcessStatus::Normal;
+
+        let max_block_size = (self.buffer_config.max_buffer_size as usize).max(1);
+        let mut event_idx = 0;
+        let mut automation_idx = 0;
+        let mut block_start = 0;
+        while block_start < num_samples {
+            // This is the block a regular host would have passed to the plugin. Any parameter
+            // changes within it are either applied at the start of the block, or the block is split
+            // up further for sample accurate automation.
+            let host_block_end =
+                ((block_start / max_block_size + 1) * max_block_size).min(num_samples);
+            let mut block_end = host_block_end;
+            while let Some(param_change) = automation.get(automation_idx) {
+                let timing = param_change.timing as usize;
+                if timing >= host_block_end {
+                    break;
+                }
+                if P::SAMPLE_ACCURATE_AUTOMATION && timing > block_start {
+                    block_end = timing;
+                    break;
+                }
+
+                self.set_parameter_normalized_inner(
+                    &param_change.param_id,
+                    param_change.normalized_value,
+                );
+                automation_idx += 1;
+            }
+
+            block_input_events.clear();
+            while let Some(event) = events.get(event_idx) {
+                if event.timing() as usize >= block_end {
+                    break;
+                }
+
+                let mut event = event.clone();
+                event.subtract_timing(block_start as u32);
+                block_input_events.push(event);
+                event_idx += 1;
+            }
+
+            let block_len = block_end - block_start;
+            let mut transport = Transport::new(self.buffer_config.sample_rate);
+            transport.playing = self.transport.playing;
+            transport.recording = self.transport.recording;
+            transport.tempo = self.transport.tempo;
+            transport.time_sig_numerator = self.transport.time_sig_numerator;
+            transport.time_sig_denominator = self.transport.time_sig_denominator;
+            transport.pos_samples = Some(if self.transport.playing {
+                self.transport.pos_samples + block_start as i64
+            } else {
+                self.transport.pos_samples
+            });
+
+            let mut context = TestProcessContext {
+                pending_tasks: &self.pending_tasks,
+                current_latency: &self.current_latency,
+                input_events: &block_input_events,
+                input_events_idx: 0,
+                output_events: &mut block_output_events,
+                param_changes: &mut block_param_changes,
+                transport,
+            };
+            let plugin = &mut self.plugin;
+            status = match &mut self.buffer_manager {
+                PluginBufferManager::F32(buffer_manager) => process_block(
+                    plugin,
+                    buffer_manager,
+                    &mut host_ptrs,
+                    block_start,
+                    block_len,
+                    &mut context,
+                ),
+                PluginBufferManager::F64(buffer_manager) => process_block(
+                    plugin,
+                    buffer_manager,
+                    &mut host_ptrs,
+                    block_start,
+                    block_len,
+                    &mut context,
+                ),
+            };
+
+            for mut event in block_output_events.drain(..) {
+                event.add_timing(block_start as u32);
+                output_events.push(event);
+            }
+
+            for (param_ptr, normalized_value) in block_param_changes.drain(..) {
+                let param_id = self
+                    .param_id_to_ptr
+                    .iter()
+                    .find(|(_, ptr)| **ptr == param_ptr)
+                    .map(|(param_id, _)| param_id.clone())
+                    .expect("The plugin changed an unknown parameter");
+
+                self.set_parameter_normalized_inner(&param_id, normalized_value);
+                param_changes.push(ParamAutomation::new(
+                    block_start as u32,
+                    param_id,
+                    normalized_value,
+                ));
+            }
+
+            // Tasks scheduled from the process function are run right after the block has been
+            // processed, there's no separate GUI or background thread here
+            let pending_tasks: Vec<_> = self.pending_tasks.borrow_mut().drain(..).collect();
+            for task in pending_tasks {
+                (self.task_executor)(task);
+            }
+
+            if let ProcessStatus::Error(_) = status {
+                break;
+            }
+
+            block_start = block_end;
+        }
+
+        if self.transport.playing {
+            self.transport.pos_samples += num_samples as i64;
+        }
+
+        TestOutput {
+            main_output,
+            aux_outputs,
+            events: output_events,
+            param_changes,
+            status,
+        }
+    }
+
+    fn set_parameter_normalized_inner(&self, param_id: &str, normalized: f32) {
+        let param_ptr = self
+            .param_id_to_ptr
+            .get(param_id)
+            .unwrap_or_else(|| panic!("Unknown parameter ID '{param_id}'"));
+
+        unsafe {
+            if param_ptr.set_normalized_value(normalized) {
+                param_ptr.update_smoother(self.buffer_config.sample_rate, false);
+            }
+        }
+    }
+}
+
+/// Pointers to the channels of the caller's audio data, laid out the same way a plugin host would
+/// pass them to the plugin.
+struct HostChannelPtrs {
+    main_input: Vec<*mut f32>,
+    main_output: Vec<*mut f32>,
+    aux_inputs: Vec<Vec<*mut f32>>,
+    aux_outputs: Vec<Vec<*mut f32>>,
+}
+
+/// Process a single block of audio using the plugin's preferred sample format. The buffer manager
+/// converts the caller's single precision audio data if the plugin processes audio in double
+/// precision.
+fn process_block<P: Plugin, T: Sample>(
+    plugin: &mut P,
+    buffer_manager: &mut BufferManager<T>,
+    host_ptrs: &mut HostChannelPtrs,
+    block_start: usize,
+    block_len: usize,
+    context: &mut TestProcessContext<P>,
+) -> ProcessStatus {
+    let buffers = unsafe {
+        buffer_manager.create_buffers(block_start, block_len, |buffer_source| {
+            *buffer_source.main_input_channel_pointers =
+                channel_pointers(&mut host_ptrs.main_input);
+            *buffer_source.main_output_channel_pointers =
+                channel_pointers(&mut host_ptrs.main_output);
+            for (port_channel_pointers, ptrs) in buffer_source
+                .aux_input_channel_pointers
+                .iter_mut()
+                .zip(host_ptrs.aux_inputs.iter_mut())
+            {
+                *port_channel_pointers = channel_pointers(ptrs);
+            }
+            for (port_channel_pointers, ptrs) in buffer_source
+                .aux_output_channel_pointers
+                .iter_mut()
+                .zip(host_ptrs.aux_outputs.iter_mut())
+            {
+                *port_channel_pointers = channel_pointers(ptrs);
+            }
+        })
+    };
+
+    let mut aux = AuxiliaryBuffers {
+        main_input: buffers.main_input,
+        inputs: buffers.aux_inputs,
+        outputs: buffers.aux_outputs,
+        inputs_active: buffers.aux_inputs_active,
+        outputs_active: buffers.aux_outputs_active,
+    };
+    let status = process_wrapper(|| T::process(plugin, buffers.main_buffer, &mut aux, context));
+    unsafe { buffer_manager.write_back_outputs() };
+
+    status
+}
+
+/// Create the `ChannelPointers` for a port backed by the caller's audio data. Returns `None` if
+/// the port has no channels.
+fn channel_pointers(ptrs: &mut [*mut f32]) -> Option<HostChannelPointers> {
+    if ptrs.is_empty() {
+        return None;
+    }
+
+    Some(
+        ChannelPointers {
+            ptrs: NonNull::new(ptrs.as_mut_ptr()).unwrap(),
+            num_channels: ptrs.len(),
+        }
+        .into(),
+    )
+}
+
+impl<P: Plugin> Drop for TestHost<P> {
+    fn drop(&mut self) {
+        self.plugin.deactivate();
+    }
+}
+
+impl<P: Plugin> InitContext<P> for TestInitContext<'_, P> {
+    fn plugin_api(&self) -> PluginApi {
+        // There's no plugin API involved here, and this behaves the closest to the standalone
+        // wrapper
+        PluginApi::Standalone
+    }
+
+    fn execute(&self, task: P::BackgroundTask) {
+        (self.task_executor)(task);
+    }
+
+    fn set_latency_samples(&self, samples: u32) {
+        self.current_latency.set(samples);
+    }
+
+    fn set_current_voice_capacity(&self, _capacity: u32) {
+        // This is only supported by CLAP
+    }
+
+    fn track_info(&self) -> Option<TrackInfo> {
+        // There's no host, so there's also no track
+        None
+    }
+
+    fn aux_input_active(&self, index: usize) -> bool {
+        // The test host never deactivates any ports
+        index < self.audio_io_layout.aux_input_ports.len()
+    }
+
+    fn aux_output_active(&self, index: usize) -> bool {
+        index < self.audio_io_layout.aux_output_ports.len()
+    }
+
+    fn param_info_changed(&self, _changes: ParamInfoChanges) {
+        // There's no host that needs to query the parameters again
+    }
+}
+
+impl<P: Plugin> ProcessContext<P> for TestProcessContext<'_, P> {
+    fn plugin_api(&self) -> PluginApi {
+        PluginApi::Standalone
+    }
+
+    fn execute_background(&self, task: P::BackgroundTask) {
+        permit_alloc(|| self.pending_tasks.borrow_mut().push(task));
+    }
+
+    fn execute_gui(&self, task: P::BackgroundTask) {
+        permit_alloc(|| self.pending_tasks.borrow_mut().push(task));
+    }
+
+    #[inline]
+    fn transport(&self) -> &Transport {
+        &self.transport
+    }
+
+    fn next_event(&mut self) -> Option<PluginNoteEvent<P>> {
+        if self.input_events_idx < self.input_events.len() {
+            let event = self.input_events[self.input_events_idx].clone();
+            self.input_events_idx += 1;
+
+            Some(event)
+        } else {
+            None
+        }
+    }
+
+    fn send_event(&mut self, event: PluginNoteEvent<P>) {
+        // The events are collected in a vector, these allocations are not the plugin's fault
+        permit_alloc(|| self.output_events.push(event));
+    }
+
+    fn set_latency_samples(&self, samples: u32) {
+        self.current_latency.set(samples);
+    }
+
+    fn set_current_voice_capacity(&self, _capacity: u32) {
+        // This is only supported by CLAP
+    }
+
+    fn note_names_changed(&self) {
+        // There's no host that needs to query the new note names
+    }
+
+    fn note_frequency(&self, _channel: u8, note: u8, _timing: u32) -> f32 {
+        // There's no host tuning, so this always uses 12-TET
+        util::midi_note_to_freq(note)
+    }
+
+    fn execute_parallel(&self, num_tasks: u32, task: &(dyn Fn(u32) + Sync)) {
+        // Running the tasks sequentially keeps the tests deterministic
+        for task_index in 0..num_tasks {
+            task(task_index);
+        }
+    }
+
+    unsafe fn raw_begin_set_parameter(&mut self, _param: ParamPtr) {
+        // There's no host that needs to know about automation gestures
+    }
+
+    unsafe fn raw_set_parameter_normalized(&mut self, param: ParamPtr, normalized: f32) {
+        permit_alloc(|| self.param_changes.push((param, normalized)));
+    }
+
+    unsafe fn raw_end_set_parameter(&mut self, _param: ParamPtr) {
+        // See `raw_begin_set_parameter()`
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{
+        Buffer, FloatParam, FloatRange, MidiConfig, NoteEvent, Param, ProcessMode,
+    };
+
+    struct GainParams {
+        gain: FloatParam,
+    }
+
+    unsafe impl Params for GainParams {
+        fn param_map(&self) -> Vec<(String, ParamPtr, String)> {
+            vec![(String::from("gain"), self.gain.as_ptr(), String::new())]
+        }
+    }
+
+    /// Applies gain and echoes all note events back to the host.
+    struct Gain {
+        params: Arc<GainParams>,
+    }
+
+    impl Default for Gain {
+        fn default() -> Self {
+            Self {
+                params: Arc::new(GainParams {
+                    gain: FloatParam::new("Gain", 1.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
+                }),
+            }
+        }
+    }
+
+    impl Plugin for Gain {
+        const NAME: &'static str = "Gain";
+        const VENDOR: &'static str = "NIH-plug";
+        const URL: &'static str = "";
+        const EMAIL: &'static str = "";
+        const VERSION: &'static str = "0.0.0";
+
+        const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
+            main_input_channels: NonZeroU32::new(2),
+            main_output_channels: NonZeroU32::new(2),
+            ..AudioIOLayout::const_default()
+        }];
+        const MIDI_INPUT: MidiConfig = MidiConfig::Basic;
+        const MIDI_OUTPUT: MidiConfig = MidiConfig::Basic;
+        const SAMPLE_ACCURATE_AUTOMATION: bool = true;
+
+        type SysExMessage = ();
+        type BackgroundTask = ();
+
+        fn params(&self) -> Arc<dyn Params> {
+            self.params.clone()
+        }
+
+        fn process(
+            &mut self,
+            buffer: &mut Buffer,
+            _aux: &mut AuxiliaryBuffers,
+            context: &mut impl ProcessContext<Self>,
+        ) -> ProcessStatus {
+            while let Some(event) = context.next_event() {
+                // Channel volume CCs change the gain from the process function
+                if let NoteEvent::MidiCC { cc: 7, value, .. } = event {
+                    context.begin_set_parameter(&self.params.gain);
+                    context.set_parameter(&self.params.gain, value);
+                    context.end_set_parameter(&self.params.gain);
+                }
+
+                context.send_event(event);
+            }
+
+            let gain = self.params.gain.value();
+            for channel_samples in buffer.iter_samples() {
+                for sample in channel_samples {
+                    *sample *= gain;
+                }
+            }
+
+            ProcessStatus::Normal
+        }
+    }
+
+    fn buffer_config(max_buffer_size: u32) -> BufferConfig {
+        BufferConfig {
+            sample_rate: 44100.0,
+            min_buffer_size: None,
+            max_buffer_size,
+            process_mode: ProcessMode::Offline,
+        }
+    }
+
+    #[test]
+    fn sample_accurate_automation() {
+        let mut host = TestHost::<Gain>::new(Gain::AUDIO_IO_LAYOUTS[0], buffer_config(4)).unwrap();
+        let output = host.process(
+            10,
+            TestInput {
+                main_input: vec![vec![1.0; 10]; 2],
+                automation: vec![ParamAutomation::new(6, "gain", 0.5)],
+                ..TestInput::default()
+            },
+        );
+
+        assert_eq!(output.status, ProcessStatus::Normal);
+        for channel in output.main_output {
+            assert_eq!(channel, [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.5, 0.5, 0.5, 0.5]);
+        }
+    }
+
+    #[test]
+    fn event_timings() {
+        let mut host = TestHost::<Gain>::new(Gain::AUDIO_IO_LAYOUTS[0], buffer_config(4)).unwrap();
+        let note_on = |timing| NoteEvent::NoteOn {
+            timing,
+            voice_id: None,
+            channel: 0,
+            note: 60,
+            velocity: 1.0,
+        };
+        let output = host.process(
+            10,
+            TestInput {
+                main_input: vec![vec![0.0; 10]; 2],
+                events: vec![note_on(9), note_on(1), note_on(5)],
+                automation: vec![ParamAutomation::new(3, "gain", 0.5)],
+                ..TestInput::default()
+            },
+        );
+
+        assert_eq!(output.events, [note_on(1), note_on(5), note_on(9)]);
+    }
+
+    #[test]
+    fn param_changes_from_process() {
+        let mut host = TestHost::<Gain>::new(Gain::AUDIO_IO_LAYOUTS[0], buffer_config(4)).unwrap();
+        let output = host.process(
+            8,
+            TestInput {
+                main_input: vec![vec![1.0; 8]; 2],
+                events: vec![NoteEvent::MidiCC {
+                    timing: 1,
+                    channel: 0,
+                    cc: 7,
+                    value: 0.25,
+                }],
+                ..TestInput::default()
+            },
+        );
+
+        // The new value is only used starting from the next block
+        assert_eq!(
+            output.param_changes,
+            [ParamAutomation::new(0, "gain", 0.25)]
+        );
+        for channel in output.main_output {
+            assert_eq!(channel, [1.0, 1.0, 1.0, 1.0, 0.25, 0.25, 0.25, 0.25]);
+        }
+    }
+}
@@ -4,13 +4,19 @@ mod util;
 mod context;
 mod descriptor;
 pub mod features;
+mod preset_discovery;
 mod wrapper;
 
 /// Re-export for the macro
 pub use self::descriptor::PluginDescriptor;
+pub use self::preset_discovery::PresetProviderDescriptor;
 pub use self::wrapper::Wrapper;
 pub use clap_sys::entry::clap_plugin_entry;
 pub use clap_sys::factory::plugin_factory::{clap_plugin_factory, CLAP_PLUGIN_FACTORY_ID};
+pub use clap_sys::factory::preset_discovery::{
+    clap_preset_discovery_factory, clap_preset_discovery_indexer, clap_preset_discovery_provider,
+    clap_preset_discovery_provider_descriptor, CLAP_PRESET_DISCOVERY_FACTORY_ID,
+};
 pub use clap_sys::host::clap_host;
 pub use clap_sys::plugin::{clap_plugin, clap_plugin_descriptor};
 pub use clap_sys::version::CLAP_VERSION;
@@ -26,8 +32,13 @@ macro_rules! nih_export_clap {
         mod clap {
             use $crate::prelude::nih_debug_assert_eq;
             use $crate::wrapper::setup_logger;
-            use $crate::wrapper::clap::{PluginDescriptor, Wrapper};
+            use $crate::wrapper::clap::{PluginDescriptor, PresetProviderDescriptor, Wrapper};
             use $crate::wrapper::clap::{CLAP_PLUGIN_FACTORY_ID, clap_host, clap_plugin, clap_plugin_descriptor, clap_plugin_factory};
+            use $crate::wrapper::clap::{
+                CLAP_PRESET_DISCOVERY_FACTORY_ID, clap_preset_discovery_factory,
+                clap_preset_discovery_indexer, clap_preset_discovery_provider,
+                clap_preset_discovery_provider_descriptor,
+            };
             use ::std::collections::HashSet;
             use ::std::ffi::{CStr, c_void};
             use ::std::os::raw::c_char;
@@ -43,6 +54,12 @@ macro_rules! nih_export_clap {
                 create_plugin: Some(create_plugin),
             };
 
+            const CLAP_PRESET_DISCOVERY_FACTORY: clap_preset_discovery_factory = clap_preset_discovery_factory {
+                count: Some(get_preset_provider_count),
+                get_descriptor: Some(get_preset_provider_descriptor),
+                create: Some(create_preset_provider),
+            };
+
             // Sneaky way to get the number of expanded elements
             const PLUGIN_COUNT: usize = [$(stringify!($plugin_ty)),+].len();
 
@@ -113,6 +130,54 @@ macro_rules! nih_export_clap {
                 ::std::ptr::null()
             }
 
+            // Only plugins that define preset file extensions get a preset provider
+            static PRESET_PROVIDER_DESCRIPTORS: OnceLock<Vec<PresetProviderDescriptor>> = OnceLock::new();
+
+            fn preset_provider_descriptors() -> &'static [PresetProviderDescriptor] {
+                PRESET_PROVIDER_DESCRIPTORS.get_or_init(|| {
+                    [$(PresetProviderDescriptor::for_plugin::<$plugin_ty>()),+]
+                        .into_iter()
+                        .flatten()
+                        .collect()
+                })
+            }
+
+            unsafe extern "C" fn get_preset_provider_count(
+                _factory: *const clap_preset_discovery_factory,
+            ) -> u32 {
+                preset_provider_descriptors().len() as u32
+            }
+
+            unsafe extern "C" fn get_preset_provider_descriptor(
+                _factory: *const clap_preset_discovery_factory,
+                index: u32,
+            ) -> *const clap_preset_discovery_provider_descriptor {
+                match preset_provider_descriptors().get(index as usize) {
+                    Some(descriptor) => descriptor.clap_provider_descriptor(),
+                    None => ::std::ptr::null()
+                }
+            }
+
+            unsafe extern "C" fn create_preset_provider(
+                _factory: *const clap_preset_discovery_factory,
+                indexer: *const clap_preset_discovery_indexer,
+                provider_id: *const c_char,
+            ) -> *const clap_preset_discovery_provider {
+                if indexer.is_null() || provider_id.is_null() {
+                    return ::std::ptr::null();
+                }
+                let provider_id_cstr = CStr::from_ptr(provider_id);
+
+                // The provider is freed again when the host destroys it
+                match preset_provider_descriptors()
+                    .iter()
+                    .find(|descriptor| descriptor.id() == provider_id_cstr)
+                {
+                    Some(descriptor) => descriptor.create_provider(indexer),
+                    None => ::std::ptr::null(),
+                }
+            }
+
             pub extern "C" fn init(_plugin_path: *const c_char) -> bool {
                 setup_logger();
                 true
@@ -121,8 +186,17 @@ macro_rules! nih_export_clap {
             pub extern "C" fn deinit() {}
 
             pub extern "C" fn get_factory(factory_id: *const c_char) -> *const c_void {
-                if !factory_id.is_null() && unsafe { CStr::from_ptr(factory_id) } == CLAP_PLUGIN_FACTORY_ID {
+                if factory_id.is_null() {
+                    return ::std::ptr::null();
+                }
+
+                let factory_id_cstr = unsafe { CStr::from_ptr(factory_id) };
+                if factory_id_cstr == CLAP_PLUGIN_FACTORY_ID {
                     &CLAP_PLUGIN_FACTORY as *const _ as *const c_void
+                } else if factory_id_cstr == CLAP_PRESET_DISCOVERY_FACTORY_ID
+                    && !preset_provider_descriptors().is_empty()
+                {
+                    &CLAP_PRESET_DISCOVERY_FACTORY as *const _ as *const c_void
                 } else {
                     ::std::ptr::null()
                 }
@@ -1,7 +1,5 @@
 use atomic_refcell::AtomicRefMut;
-use clap_sys::ext::remote_controls::{
-    clap_remote_controls_page, CLAP_REMOTE_CONTROLS_COUNT,
-};
+use clap_sys::ext::remote_controls::{clap_remote_controls_page, CLAP_REMOTE_CONTROLS_COUNT};
 use clap_sys::id::{clap_id, CLAP_INVALID_ID};
 use clap_sys::string_sizes::CLAP_NAME_SIZE;
 use std::cell::Cell;
@@ -11,10 +9,11 @@ use std::sync::Arc;
 use super::wrapper::{OutputParamEvent, Task, Wrapper};
 use crate::event_loop::EventLoop;
 use crate::prelude::{
-    ClapPlugin, GuiContext, InitContext, ParamPtr, PluginApi, PluginNoteEvent, ProcessContext,
-    RemoteControlsContext, RemoteControlsPage, RemoteControlsSection, Transport,
+    ClapPlugin, ContextMenuItem, GuiContext, InitContext, MidiMapping, ParamIndication,
+    ParamInfoChanges, ParamPtr, PluginApi, PluginNoteEvent, ProcessContext, RemoteControlsContext,
+    RemoteControlsPage, RemoteControlsSection, TrackInfo, Transport,
 };
-use crate::wrapper::util::strlcpy;
+use crate::wrapper::util::{param_batch_steps, strlcpy, ParamBatchStep};
 
 /// An [`InitContext`] implementation for the wrapper.
 ///
@@ -33,6 +32,8 @@ pub(crate) struct WrapperInitContext<'a, P: ClapPlugin> {
 pub(crate) struct PendingInitContextRequests {
     /// The value of the last `.set_latency_samples()` call.
     latency_changed: Cell<Option<u32>>,
+    /// The combined changes from all `.param_info_changed()` calls.
+    param_info_changed: Cell<ParamInfoChanges>,
 }
 
 /// A [`ProcessContext`] implementation for the wrapper. This is a separate object so it can hold on
@@ -43,6 +44,10 @@ pub(crate) struct WrapperProcessContext<'a, P: ClapPlugin> {
     pub(super) input_events_guard: AtomicRefMut<'a, VecDeque<PluginNoteEvent<P>>>,
     pub(super) output_events_guard: AtomicRefMut<'a, VecDeque<PluginNoteEvent<P>>>,
     pub(super) transport: Transport,
+    /// The offset of the current block within the host's buffer. The buffer may be split into
+    /// multiple blocks for sample accurate automation, but the host's tuning is queried using
+    /// offsets relative to the start of the entire buffer.
+    pub(super) block_start: usize,
 }
 
 /// A [`GuiContext`] implementation for the wrapper. This is passed to the plugin in
@@ -70,6 +75,10 @@ impl<P: ClapPlugin> Drop for WrapperInitContext<'_, P> {
         if let Some(samples) = self.pending_requests.latency_changed.take() {
             self.wrapper.set_latency_samples(samples)
         }
+        let param_info_changes = self.pending_requests.param_info_changed.take();
+        if !param_info_changes.is_empty() {
+            self.wrapper.param_info_changed(param_info_changes)
+        }
     }
 }
 
@@ -90,6 +99,30 @@ impl<P: ClapPlugin> InitContext<P> for WrapperInitContext<'_, P> {
     fn set_current_voice_capacity(&self, capacity: u32) {
         self.wrapper.set_current_voice_capacity(capacity)
     }
+
+    fn track_info(&self) -> Option<TrackInfo> {
+        self.wrapper.track_info()
+    }
+
+    fn aux_input_active(&self, index: usize) -> bool {
+        self.wrapper
+            .audio_ports_activation
+            .aux_input_active(&self.wrapper.current_audio_io_layout.load(), index)
+    }
+
+    fn aux_output_active(&self, index: usize) -> bool {
+        self.wrapper
+            .audio_ports_activation
+            .aux_output_active(&self.wrapper.current_audio_io_layout.load(), index)
+    }
+
+    fn param_info_changed(&self, changes: ParamInfoChanges) {
+        // See this struct's docstring
+        let pending_changes = self.pending_requests.param_info_changed.get();
+        self.pending_requests
+            .param_info_changed
+            .set(pending_changes | changes);
+    }
 }
 
 impl<P: ClapPlugin> ProcessContext<P> for WrapperProcessContext<'_, P> {
@@ -127,6 +160,54 @@ impl<P: ClapPlugin> ProcessContext<P> for WrapperProcessContext<'_, P> {
     fn set_current_voice_capacity(&self, capacity: u32) {
         self.wrapper.set_current_voice_capacity(capacity)
     }
+
+    fn note_names_changed(&self) {
+        self.wrapper.note_names_changed()
+    }
+
+    fn note_frequency(&self, channel: u8, note: u8, timing: u32) -> f32 {
+        self.wrapper
+            .note_frequency(channel, note, self.block_start as u32 + timing)
+    }
+
+    fn execute_parallel(&self, num_tasks: u32, task: &(dyn Fn(u32) + Sync)) {
+        self.wrapper.execute_parallel(num_tasks, task)
+    }
+
+    unsafe fn raw_begin_set_parameter(&mut self, param: ParamPtr) {
+        self.queue_parameter_event(param, |param_hash| OutputParamEvent::BeginGesture {
+            param_hash,
+        });
+    }
+
+    unsafe fn raw_set_parameter_normalized(&mut self, param: ParamPtr, normalized: f32) {
+        // Just like with the GUI context, the parameter's value is only changed when the event is
+        // written to the host at the end of the block
+        let clap_plain_value = normalized as f64 * param.step_count().unwrap_or(1) as f64;
+        self.queue_parameter_event(param, |param_hash| OutputParamEvent::SetValue {
+            param_hash,
+            clap_plain_value,
+        });
+    }
+
+    unsafe fn raw_end_set_parameter(&mut self, param: ParamPtr) {
+        self.queue_parameter_event(param, |param_hash| OutputParamEvent::EndGesture {
+            param_hash,
+        });
+    }
+}
+
+impl<P: ClapPlugin> WrapperProcessContext<'_, P> {
+    /// Queue an output parameter event for `param`, created using the parameter's hash.
+    fn queue_parameter_event(&self, param: ParamPtr, event: impl FnOnce(u32) -> OutputParamEvent) {
+        match self.wrapper.param_ptr_to_hash.get(&param) {
+            Some(hash) => {
+                self.wrapper
+                    .queue_parameter_event_from_process(event(*hash));
+            }
+            None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
+        }
+    }
 }
 
 impl<P: ClapPlugin> GuiContext for WrapperGuiContext<P> {
@@ -138,6 +219,44 @@ impl<P: ClapPlugin> GuiContext for WrapperGuiContext<P> {
         self.wrapper.request_resize()
     }
 
+    fn note_names_changed(&self) {
+        self.wrapper.note_names_changed()
+    }
+
+    fn param_info_changed(&self, changes: ParamInfoChanges) {
+        self.wrapper.param_info_changed(changes)
+    }
+
+    fn track_info(&self) -> Option<TrackInfo> {
+        self.wrapper.track_info()
+    }
+
+    fn param_indication(&self, param: ParamPtr) -> ParamIndication {
+        match self.wrapper.param_ptr_to_hash.get(&param) {
+            Some(hash) => self.wrapper.param_indication(*hash),
+            None => {
+                nih_debug_assert_failure!("Unknown parameter: {:?}", param);
+                ParamIndication::default()
+            }
+        }
+    }
+
+    fn show_param_context_menu(
+        &self,
+        param: ParamPtr,
+        x: i32,
+        y: i32,
+        items: Vec<ContextMenuItem>,
+    ) -> bool {
+        match self.wrapper.param_ptr_to_hash.get(&param) {
+            Some(hash) => self.wrapper.show_param_context_menu(*hash, x, y, items),
+            None => {
+                nih_debug_assert_failure!("Unknown parameter: {:?}", param);
+                false
+            }
+        }
+    }
+
     // All of these functions are supposed to be called from the main thread, so we'll put some
     // trust in the caller and assume that this is indeed the case
     unsafe fn raw_begin_set_parameter(&self, param: ParamPtr) {
@@ -233,6 +352,59 @@ impl<P: ClapPlugin> GuiContext for WrapperGuiContext<P> {
         }
     }
 
+    unsafe fn raw_set_parameters_normalized(&self, changes: &[(ParamPtr, f32)]) {
+        let changes: Vec<(ParamPtr, u32, f64)> = changes
+            .iter()
+            .filter_map(
+                |(param, normalized)| match self.wrapper.param_ptr_to_hash.get(param) {
+                    Some(hash) => Some((
+                        *param,
+                        *hash,
+                        *normalized as f64 * param.step_count().unwrap_or(1) as f64,
+                    )),
+                    None => {
+                        nih_debug_assert_failure!("Unknown parameter: {:?}", param);
+                        None
+                    }
+                },
+            )
+            .collect();
+
+        #[cfg(debug_assertions)]
+        let mut param_gesture_checker = self.param_gesture_checker.borrow_mut();
+        let events = param_batch_steps(&changes)
+            .map(|step| {
+                #[cfg(debug_assertions)]
+                if let Some(param_id) = self.wrapper.param_id_from_ptr(step.change().0) {
+                    param_gesture_checker.param_batch_step(&step, param_id);
+                }
+
+                match step {
+                    ParamBatchStep::Begin((_, param_hash, _)) => {
+                        OutputParamEvent::BeginGesture { param_hash }
+                    }
+                    ParamBatchStep::Set((_, param_hash, clap_plain_value)) => {
+                        OutputParamEvent::SetValue {
+                            param_hash,
+                            clap_plain_value,
+                        }
+                    }
+                    ParamBatchStep::End((_, param_hash, _)) => {
+                        OutputParamEvent::EndGesture { param_hash }
+                    }
+                }
+            })
+            .collect();
+
+        // Either the entire batch is sent to the host, or none of it is, so gestures are never
+        // left open
+        let success = self.wrapper.queue_parameter_events(events);
+        nih_debug_assert!(
+            success,
+            "Parameter output event queue was full, parameter changes will not be sent to the host"
+        );
+    }
+
     fn get_state(&self) -> crate::wrapper::state::PluginState {
         self.wrapper.get_state_object()
     }
@@ -240,6 +412,26 @@ impl<P: ClapPlugin> GuiContext for WrapperGuiContext<P> {
     fn set_state(&self, state: crate::wrapper::state::PluginState) {
         self.wrapper.set_state_object_from_gui(state)
     }
+
+    fn start_midi_learn(&self, param: ParamPtr) -> bool {
+        self.wrapper.start_midi_learn(param)
+    }
+
+    fn cancel_midi_learn(&self) {
+        self.wrapper.midi_learn.cancel_learning()
+    }
+
+    fn midi_learn_target(&self) -> Option<ParamPtr> {
+        self.wrapper.midi_learn.learn_target()
+    }
+
+    fn midi_mappings(&self) -> Vec<MidiMapping> {
+        self.wrapper.midi_learn.mappings()
+    }
+
+    fn set_midi_mappings(&self, mappings: Vec<MidiMapping>) {
+        self.wrapper.set_midi_mappings(&mappings)
+    }
 }
 
 /// A remote control section. The plugin can fill this with information for one or more pages.
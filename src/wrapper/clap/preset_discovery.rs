@@ -0,0 +1,313 @@
+//! An implementation of CLAP's preset discovery factory. This lets the host index the presets in
+//! the locations from [`ClapPlugin::clap_preset_locations()`] without creating a plugin instance.
+
+use clap_sys::factory::preset_discovery::{
+    clap_preset_discovery_filetype, clap_preset_discovery_indexer, clap_preset_discovery_location,
+    clap_preset_discovery_location_kind, clap_preset_discovery_metadata_receiver,
+    clap_preset_discovery_provider, clap_preset_discovery_provider_descriptor,
+    CLAP_PRESET_DISCOVERY_IS_FACTORY_CONTENT, CLAP_PRESET_DISCOVERY_IS_USER_CONTENT,
+    CLAP_PRESET_DISCOVERY_LOCATION_FILE,
+};
+use clap_sys::universal_plugin_id::clap_universal_plugin_id;
+use clap_sys::version::CLAP_VERSION;
+use std::ffi::{c_void, CStr, CString};
+use std::os::raw::c_char;
+use std::path::{Path, PathBuf};
+
+use crate::prelude::{ClapPlugin, ClapPresetLocation};
+use crate::wrapper::state::{self, PluginState};
+
+/// A static descriptor for a plugin's preset provider. The macro generated preset discovery factory
+/// contains one of these for every plugin that defines
+/// [`ClapPlugin::CLAP_PRESET_FILE_EXTENSIONS`].
+///
+/// This cannot be cloned as [`Self::provider_descriptor`] contains pointers to the other fields.
+pub struct PresetProviderDescriptor {
+    id: CString,
+    name: CString,
+    vendor: CString,
+    /// The plugin's CLAP ID. The presets are associated with the plugin using this ID.
+    plugin_id: CString,
+    /// The name for the plugin's preset file types.
+    file_type_name: CString,
+    file_extensions: Vec<CString>,
+    /// The plugin's [`ClapPlugin::clap_preset_locations()`] function. This is only called when the
+    /// host initializes a provider.
+    locations: fn() -> Vec<ClapPresetLocation>,
+
+    /// The descriptor passed to the host. This is initialized after the `CString` fields above.
+    provider_descriptor: Option<clap_preset_discovery_provider_descriptor>,
+}
+
+unsafe impl Send for PresetProviderDescriptor {}
+unsafe impl Sync for PresetProviderDescriptor {}
+
+/// A preset provider instance created by the host through the preset discovery factory.
+struct PresetProvider {
+    clap_preset_discovery_provider: clap_preset_discovery_provider,
+
+    descriptor: &'static PresetProviderDescriptor,
+    indexer: *const clap_preset_discovery_indexer,
+    /// The plugin's preset locations. These are queried when the provider is initialized.
+    locations: Vec<PresetLocation>,
+}
+
+/// A [`ClapPresetLocation`] converted to the data needed to pass it to the host.
+struct PresetLocation {
+    name: CString,
+    path: PathBuf,
+    /// The same path as `path`, but as a C-string.
+    clap_path: CString,
+    /// Either `CLAP_PRESET_DISCOVERY_IS_FACTORY_CONTENT` or `CLAP_PRESET_DISCOVERY_IS_USER_CONTENT`.
+    flags: u32,
+}
+
+impl PresetProviderDescriptor {
+    /// Construct the preset provider descriptor for a specific CLAP plugin. Returns `None` if the
+    /// plugin does not define any preset file extensions.
+    pub fn for_plugin<P: ClapPlugin>() -> Option<Self> {
+        if P::CLAP_PRESET_FILE_EXTENSIONS.is_empty() {
+            return None;
+        }
+
+        let mut descriptor = Self {
+            id: CString::new(format!("{}.presets", P::CLAP_ID))
+                .expect("`CLAP_ID` contained null bytes"),
+            name: CString::new(format!("{} Presets", P::NAME))
+                .expect("`NAME` contained null bytes"),
+            vendor: CString::new(P::VENDOR).expect("`VENDOR` contained null bytes"),
+            plugin_id: CString::new(P::CLAP_ID).expect("`CLAP_ID` contained null bytes"),
+            file_type_name: CString::new(format!("{} Preset", P::NAME))
+                .expect("`NAME` contained null bytes"),
+            file_extensions: P::CLAP_PRESET_FILE_EXTENSIONS
+                .iter()
+                .map(|extension| {
+                    CString::new(*extension)
+                        .expect("`CLAP_PRESET_FILE_EXTENSIONS` contained null bytes")
+                })
+                .collect(),
+            locations: P::clap_preset_locations,
+
+            provider_descriptor: None,
+        };
+
+        // NOTE: This is safe without pinning this struct because all of the data is already stored
+        //       on the heap
+        descriptor.provider_descriptor = Some(clap_preset_discovery_provider_descriptor {
+            clap_version: CLAP_VERSION,
+            id: descriptor.id.as_ptr(),
+            name: descriptor.name.as_ptr(),
+            vendor: descriptor.vendor.as_ptr(),
+        });
+
+        Some(descriptor)
+    }
+
+    pub fn clap_provider_descriptor(&self) -> &clap_preset_discovery_provider_descriptor {
+        self.provider_descriptor.as_ref().unwrap()
+    }
+
+    pub fn id(&self) -> &CStr {
+        self.id.as_c_str()
+    }
+
+    /// Create a new preset provider using this descriptor. The provider is freed again when the
+    /// host calls its `destroy()` function.
+    ///
+    /// # Safety
+    ///
+    /// `indexer` must be a valid pointer to the host's indexer, and it needs to outlive the
+    /// provider.
+    pub unsafe fn create_provider(
+        &'static self,
+        indexer: *const clap_preset_discovery_indexer,
+    ) -> *const clap_preset_discovery_provider {
+        let provider = Box::into_raw(Box::new(PresetProvider {
+            clap_preset_discovery_provider: clap_preset_discovery_provider {
+                desc: self.clap_provider_descriptor(),
+                // This is set below since it needs to point to the provider itself
+                provider_data: std::ptr::null_mut(),
+                init: Some(PresetProvider::init),
+                destroy: Some(PresetProvider::destroy),
+                get_metadata: Some(PresetProvider::get_metadata),
+                get_extension: Some(PresetProvider::get_extension),
+            },
+
+            descriptor: self,
+            indexer,
+            locations: Vec::new(),
+        }));
+        (*provider).clap_preset_discovery_provider.provider_data = provider as *mut c_void;
+
+        &(*provider).clap_preset_discovery_provider
+    }
+}
+
+impl PresetLocation {
+    /// Convert a location returned by the plugin. Returns `None` and logs a debug assertion failure
+    /// if the location cannot be passed to the host.
+    fn new(location: ClapPresetLocation) -> Option<Self> {
+        // CLAP uses UTF-8 paths on every platform
+        let Some(path_str) = location.path.to_str() else {
+            nih_debug_assert_failure!(
+                "Preset location '{}' is not valid UTF-8",
+                location.path.display()
+            );
+            return None;
+        };
+        let (Ok(name), Ok(clap_path)) = (CString::new(location.name), CString::new(path_str))
+        else {
+            nih_debug_assert_failure!("Preset location {:?} contains null bytes", path_str);
+            return None;
+        };
+
+        Some(Self {
+            name,
+            clap_path,
+            path: location.path,
+            flags: if location.is_factory_content {
+                CLAP_PRESET_DISCOVERY_IS_FACTORY_CONTENT
+            } else {
+                CLAP_PRESET_DISCOVERY_IS_USER_CONTENT
+            },
+        })
+    }
+}
+
+impl PresetProvider {
+    unsafe extern "C" fn init(provider: *const clap_preset_discovery_provider) -> bool {
+        check_null_ptr!(false, provider, (*provider).provider_data);
+        let this = &mut *((*provider).provider_data as *mut Self);
+        let indexer = this.indexer;
+
+        for file_extension in &this.descriptor.file_extensions {
+            let filetype = clap_preset_discovery_filetype {
+                name: this.descriptor.file_type_name.as_ptr(),
+                description: std::ptr::null(),
+                file_extension: file_extension.as_ptr(),
+            };
+
+            let success = clap_call! { indexer=>declare_filetype(indexer, &filetype) };
+            nih_debug_assert!(
+                success,
+                "The host rejected the preset file extension {:?}",
+                file_extension
+            );
+        }
+
+        this.locations = (this.descriptor.locations)()
+            .into_iter()
+            .filter_map(PresetLocation::new)
+            .collect();
+        for location in &this.locations {
+            let clap_location = clap_preset_discovery_location {
+                flags: location.flags,
+                name: location.name.as_ptr(),
+                kind: CLAP_PRESET_DISCOVERY_LOCATION_FILE,
+                location: location.clap_path.as_ptr(),
+            };
+
+            let success = clap_call! { indexer=>declare_location(indexer, &clap_location) };
+            nih_debug_assert!(
+                success,
+                "The host rejected the preset location '{}'",
+                location.path.display()
+            );
+        }
+
+        true
+    }
+
+    unsafe extern "C" fn destroy(provider: *const clap_preset_discovery_provider) {
+        assert!(!provider.is_null() && !(*provider).provider_data.is_null());
+        drop(Box::from_raw((*provider).provider_data as *mut Self));
+    }
+
+    unsafe extern "C" fn get_metadata(
+        provider: *const clap_preset_discovery_provider,
+        location_kind: clap_preset_discovery_location_kind,
+        location: *const c_char,
+        metadata_receiver: *const clap_preset_discovery_metadata_receiver,
+    ) -> bool {
+        check_null_ptr!(
+            false,
+            provider,
+            (*provider).provider_data,
+            location,
+            metadata_receiver
+        );
+        let this = &*((*provider).provider_data as *const Self);
+
+        // All of the declared locations are files or directories
+        if location_kind != CLAP_PRESET_DISCOVERY_LOCATION_FILE {
+            nih_debug_assert_failure!("Unexpected preset location kind {}", location_kind);
+            return false;
+        }
+        let Ok(path) = CStr::from_ptr(location).to_str() else {
+            nih_debug_assert_failure!("The preset location is not valid UTF-8");
+            return false;
+        };
+        let path = Path::new(path);
+
+        if let Err((os_error, message)) = read_preset_file(path) {
+            // The error message can only contain null bytes if the path does
+            let message = CString::new(message).unwrap_or_default();
+            clap_call! { metadata_receiver=>on_error(metadata_receiver, os_error, message.as_ptr()) };
+
+            return false;
+        }
+
+        // Preset files contain a single preset, so the preset's name is taken from the file name
+        // and it doesn't need a load key
+        let name = path
+            .file_stem()
+            .and_then(|name| CString::new(name.to_string_lossy().into_owned()).ok())
+            .unwrap_or_default();
+        if !clap_call! { metadata_receiver=>begin_preset(metadata_receiver, name.as_ptr(), std::ptr::null()) }
+        {
+            return true;
+        }
+
+        let plugin_id = clap_universal_plugin_id {
+            abi: c"clap".as_ptr(),
+            id: this.descriptor.plugin_id.as_ptr(),
+        };
+        clap_call! { metadata_receiver=>add_plugin_id(metadata_receiver, &plugin_id) };
+
+        // The flags are inherited from the declared location containing this preset
+        let flags = this
+            .locations
+            .iter()
+            .find(|location| path.starts_with(&location.path))
+            .map(|location| location.flags)
+            .unwrap_or(CLAP_PRESET_DISCOVERY_IS_USER_CONTENT);
+        clap_call! { metadata_receiver=>set_flags(metadata_receiver, flags) };
+
+        true
+    }
+
+    unsafe extern "C" fn get_extension(
+        _provider: *const clap_preset_discovery_provider,
+        _extension_id: *const c_char,
+    ) -> *const c_void {
+        std::ptr::null()
+    }
+}
+
+/// Read and deserialize a preset file. If this fails, then the OS error code (or 0 if there is
+/// none) and a human readable error message are returned instead.
+pub(crate) fn read_preset_file(path: &Path) -> Result<PluginState, (i32, String)> {
+    let preset = std::fs::read(path).map_err(|err| {
+        (
+            err.raw_os_error().unwrap_or(0),
+            format!("Could not read '{}': {}", path.display(), err),
+        )
+    })?;
+
+    unsafe { state::deserialize_json(&preset) }.ok_or_else(|| {
+        (
+            0,
+            format!("'{}' is not a valid preset file", path.display()),
+        )
+    })
+}
@@ -1,4 +1,10 @@
+use clap_sys::ext::log::{
+    clap_host_log, clap_log_severity, CLAP_LOG_DEBUG, CLAP_LOG_ERROR, CLAP_LOG_INFO,
+    CLAP_LOG_WARNING,
+};
+use clap_sys::host::clap_host;
 use clap_sys::stream::{clap_istream, clap_ostream};
+use std::ffi::CStr;
 use std::mem::MaybeUninit;
 use std::ops::Deref;
 use std::os::raw::c_void;
@@ -85,6 +91,38 @@ impl<T> ClapPtr<T> {
     }
 }
 
+/// Forwards the plugin's log messages to the host's `log` extension. The messages are sent using
+/// the plugin instance's own host pointer so the host can attribute them to that instance.
+pub struct ClapHostLogger {
+    pub host_callback: ClapPtr<clap_host>,
+    pub host_log: ClapPtr<clap_host_log>,
+}
+
+impl crate::wrapper::util::host_log::HostLogger for ClapHostLogger {
+    fn log(&self, level: log::Level, message: &CStr) {
+        let severity: clap_log_severity = match level {
+            log::Level::Error => CLAP_LOG_ERROR,
+            log::Level::Warn => CLAP_LOG_WARNING,
+            log::Level::Info => CLAP_LOG_INFO,
+            log::Level::Debug | log::Level::Trace => CLAP_LOG_DEBUG,
+        };
+
+        // `clap_call!()` panics on null pointers, and the panic would be logged again, so the
+        // function pointer is checked manually here instead
+        if let Some(log) = self.host_log.log {
+            unsafe { log(&*self.host_callback, severity, message.as_ptr()) };
+        }
+    }
+
+    fn request_flush(&self) {
+        // The host will call `on_main_thread()`, which sends the deferred messages. This is
+        // checked manually for the same reason as above.
+        if let Some(request_callback) = self.host_callback.request_callback {
+            unsafe { request_callback(&*self.host_callback) };
+        }
+    }
+}
+
 /// A buffer a stream can be read into. This is needed to allow reading into uninitialized vectors
 /// using slices without invoking UB.
 ///
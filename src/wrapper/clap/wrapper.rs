@@ -1,5 +1,7 @@
 use atomic_float::AtomicF32;
 use atomic_refcell::{AtomicRefCell, AtomicRefMut};
+use clap_sys::audio_buffer::clap_audio_buffer;
+use clap_sys::color::clap_color;
 use clap_sys::events::{
     clap_event_header, clap_event_midi, clap_event_midi_sysex, clap_event_note,
     clap_event_note_expression, clap_event_param_gesture, clap_event_param_mod,
@@ -15,13 +17,35 @@ use clap_sys::events::{
     CLAP_TRANSPORT_HAS_TIME_SIGNATURE, CLAP_TRANSPORT_IS_LOOP_ACTIVE, CLAP_TRANSPORT_IS_PLAYING,
     CLAP_TRANSPORT_IS_RECORDING, CLAP_TRANSPORT_IS_WITHIN_PRE_ROLL,
 };
+use clap_sys::ext::ambisonic::{
+    clap_ambisonic_config, clap_plugin_ambisonic, CLAP_AMBISONIC_NORMALIZATION_SN3D,
+    CLAP_AMBISONIC_ORDERING_ACN, CLAP_EXT_AMBISONIC, CLAP_EXT_AMBISONIC_COMPAT,
+    CLAP_PORT_AMBISONIC,
+};
 use clap_sys::ext::audio_ports::{
-    clap_audio_port_info, clap_plugin_audio_ports, CLAP_AUDIO_PORT_IS_MAIN, CLAP_EXT_AUDIO_PORTS,
+    clap_audio_port_info, clap_plugin_audio_ports, CLAP_AUDIO_PORT_IS_MAIN,
+    CLAP_AUDIO_PORT_PREFERS_64BITS, CLAP_AUDIO_PORT_SUPPORTS_64BITS, CLAP_EXT_AUDIO_PORTS,
     CLAP_PORT_MONO, CLAP_PORT_STEREO,
 };
+use clap_sys::ext::audio_ports_activation::{
+    clap_plugin_audio_ports_activation, CLAP_EXT_AUDIO_PORTS_ACTIVATION,
+    CLAP_EXT_AUDIO_PORTS_ACTIVATION_COMPAT,
+};
 use clap_sys::ext::audio_ports_config::{
     clap_audio_ports_config, clap_plugin_audio_ports_config, CLAP_EXT_AUDIO_PORTS_CONFIG,
 };
+use clap_sys::ext::context_menu::{
+    clap_context_menu_builder, clap_context_menu_check_entry, clap_context_menu_entry,
+    clap_context_menu_submenu, clap_context_menu_target, clap_host_context_menu,
+    clap_plugin_context_menu, CLAP_CONTEXT_MENU_ITEM_BEGIN_SUBMENU,
+    CLAP_CONTEXT_MENU_ITEM_CHECK_ENTRY, CLAP_CONTEXT_MENU_ITEM_END_SUBMENU,
+    CLAP_CONTEXT_MENU_ITEM_ENTRY, CLAP_CONTEXT_MENU_ITEM_SEPARATOR,
+    CLAP_CONTEXT_MENU_TARGET_KIND_PARAM, CLAP_EXT_CONTEXT_MENU,
+};
+use clap_sys::ext::draft::tuning::{
+    clap_event_tuning, clap_host_tuning, clap_plugin_tuning_t, CLAP_EXT_TUNING,
+};
+use clap_sys::ext::event_registry::{clap_host_event_registry, CLAP_EXT_EVENT_REGISTRY};
 use clap_sys::ext::remote_controls::{
     clap_plugin_remote_controls, clap_remote_controls_page, CLAP_EXT_REMOTE_CONTROLS,
 };
@@ -30,27 +54,64 @@ use clap_sys::ext::gui::{
     CLAP_WINDOW_API_COCOA, CLAP_WINDOW_API_WIN32, CLAP_WINDOW_API_X11,
 };
 use clap_sys::ext::latency::{clap_host_latency, clap_plugin_latency, CLAP_EXT_LATENCY};
+use clap_sys::ext::log::{clap_host_log, CLAP_EXT_LOG};
+use clap_sys::ext::note_name::{
+    clap_host_note_name, clap_note_name, clap_plugin_note_name, CLAP_EXT_NOTE_NAME,
+};
 use clap_sys::ext::note_ports::{
     clap_note_port_info, clap_plugin_note_ports, CLAP_EXT_NOTE_PORTS, CLAP_NOTE_DIALECT_CLAP,
-    CLAP_NOTE_DIALECT_MIDI,
+    CLAP_NOTE_DIALECT_MIDI, CLAP_NOTE_DIALECT_MIDI_MPE,
+};
+use clap_sys::ext::param_indication::{
+    clap_plugin_param_indication, CLAP_EXT_PARAM_INDICATION, CLAP_PARAM_INDICATION_AUTOMATION_NONE,
+    CLAP_PARAM_INDICATION_AUTOMATION_OVERRIDING, CLAP_PARAM_INDICATION_AUTOMATION_PLAYING,
+    CLAP_PARAM_INDICATION_AUTOMATION_PRESENT, CLAP_PARAM_INDICATION_AUTOMATION_RECORDING,
 };
 use clap_sys::ext::params::{
-    clap_host_params, clap_param_info, clap_plugin_params, CLAP_EXT_PARAMS,
-    CLAP_PARAM_IS_AUTOMATABLE, CLAP_PARAM_IS_BYPASS, CLAP_PARAM_IS_HIDDEN,
-    CLAP_PARAM_IS_MODULATABLE, CLAP_PARAM_IS_MODULATABLE_PER_NOTE_ID, CLAP_PARAM_IS_READONLY,
-    CLAP_PARAM_IS_STEPPED, CLAP_PARAM_RESCAN_VALUES,
+    clap_host_params, clap_param_info, clap_param_rescan_flags, clap_plugin_params,
+    CLAP_EXT_PARAMS, CLAP_PARAM_IS_AUTOMATABLE, CLAP_PARAM_IS_BYPASS, CLAP_PARAM_IS_HIDDEN,
+    CLAP_PARAM_IS_MODULATABLE, CLAP_PARAM_IS_MODULATABLE_PER_CHANNEL,
+    CLAP_PARAM_IS_MODULATABLE_PER_KEY, CLAP_PARAM_IS_MODULATABLE_PER_NOTE_ID,
+    CLAP_PARAM_IS_MODULATABLE_PER_PORT, CLAP_PARAM_IS_READONLY, CLAP_PARAM_IS_STEPPED,
+    CLAP_PARAM_RESCAN_INFO, CLAP_PARAM_RESCAN_TEXT, CLAP_PARAM_RESCAN_VALUES,
+};
+use clap_sys::ext::preset_load::{
+    clap_host_preset_load, clap_plugin_preset_load, CLAP_EXT_PRESET_LOAD,
 };
 use clap_sys::ext::render::{
     clap_plugin_render, clap_plugin_render_mode, CLAP_EXT_RENDER, CLAP_RENDER_OFFLINE,
     CLAP_RENDER_REALTIME,
 };
 use clap_sys::ext::state::{clap_plugin_state, CLAP_EXT_STATE};
+use clap_sys::ext::state_context::{
+    clap_plugin_state_context, clap_plugin_state_context_type, CLAP_EXT_STATE_CONTEXT,
+    CLAP_STATE_CONTEXT_FOR_DUPLICATE, CLAP_STATE_CONTEXT_FOR_PRESET,
+    CLAP_STATE_CONTEXT_FOR_PROJECT,
+};
+use clap_sys::ext::surround::{
+    clap_plugin_surround, CLAP_EXT_SURROUND, CLAP_EXT_SURROUND_COMPAT, CLAP_PORT_SURROUND,
+    CLAP_SURROUND_BL, CLAP_SURROUND_BR, CLAP_SURROUND_FC, CLAP_SURROUND_FL, CLAP_SURROUND_FR,
+    CLAP_SURROUND_LFE, CLAP_SURROUND_SL, CLAP_SURROUND_SR, CLAP_SURROUND_TBL, CLAP_SURROUND_TBR,
+    CLAP_SURROUND_TFL, CLAP_SURROUND_TFR,
+};
 use clap_sys::ext::tail::{clap_plugin_tail, CLAP_EXT_TAIL};
 use clap_sys::ext::thread_check::{clap_host_thread_check, CLAP_EXT_THREAD_CHECK};
+use clap_sys::ext::thread_pool::{
+    clap_host_thread_pool, clap_plugin_thread_pool, CLAP_EXT_THREAD_POOL,
+};
+use clap_sys::ext::track_info::{
+    clap_host_track_info, clap_plugin_track_info, clap_track_info, CLAP_EXT_TRACK_INFO,
+    CLAP_TRACK_INFO_HAS_AUDIO_CHANNEL, CLAP_TRACK_INFO_HAS_TRACK_COLOR,
+    CLAP_TRACK_INFO_HAS_TRACK_NAME, CLAP_TRACK_INFO_IS_FOR_BUS, CLAP_TRACK_INFO_IS_FOR_MASTER,
+    CLAP_TRACK_INFO_IS_FOR_RETURN_TRACK,
+};
 use clap_sys::ext::voice_info::{
     clap_host_voice_info, clap_plugin_voice_info, clap_voice_info, CLAP_EXT_VOICE_INFO,
     CLAP_VOICE_INFO_SUPPORTS_OVERLAPPING_NOTES,
 };
+use clap_sys::factory::preset_discovery::{
+    clap_preset_discovery_location_kind, CLAP_PRESET_DISCOVERY_LOCATION_FILE,
+};
 use clap_sys::fixedpoint::{CLAP_BEATTIME_FACTOR, CLAP_SECTIME_FACTOR};
 use clap_sys::host::clap_host;
 use clap_sys::id::{clap_id, CLAP_INVALID_ID};
@@ -67,10 +128,11 @@ use parking_lot::Mutex;
 use std::any::Any;
 use std::borrow::Borrow;
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::ffi::{c_void, CStr};
+use std::ffi::{c_void, CStr, CString};
 use std::mem;
 use std::num::NonZeroU32;
 use std::os::raw::c_char;
+use std::path::Path;
 use std::ptr::NonNull;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{Arc, Weak};
@@ -79,27 +141,41 @@ use std::time::Duration;
 
 use super::context::{WrapperGuiContext, WrapperInitContext, WrapperProcessContext};
 use super::descriptor::PluginDescriptor;
-use super::util::ClapPtr;
+use super::preset_discovery::read_preset_file;
+use super::util::{ClapHostLogger, ClapPtr};
 use crate::event_loop::{BackgroundThread, EventLoop, MainThreadExecutor, TASK_QUEUE_CAPACITY};
+use crate::midi::mpe::MpeDecoder;
 use crate::midi::MidiResult;
 use crate::prelude::{
-    AsyncExecutor, AudioIOLayout, AuxiliaryBuffers, BufferConfig, ClapPlugin, Editor, MidiConfig,
-    NoteEvent, ParamFlags, ParamPtr, Params, ParentWindowHandle, Plugin, PluginNoteEvent,
-    ProcessMode, ProcessStatus, SysExMessage, TaskExecutor, Transport,
+    AsyncExecutor, AudioIOLayout, AutomationState, AuxiliaryBuffers, BufferConfig, ChannelLayout,
+    ClapPlugin, ContextMenuItem, Editor, HostColor, MidiConfig, MidiMapping, MpeConfig, NoteEvent,
+    NoteName, ParamFlags, ParamIndication, ParamInfoChanges, ParamMapping, ParamPtr, Params,
+    ParentWindowHandle, Plugin, PluginNoteEvent, ProcessMode, ProcessStatus, SysExMessage,
+    TaskExecutor, TrackInfo, TrackType, Transport,
 };
-use crate::util::permit_alloc;
+use crate::util::{self, permit_alloc};
 use crate::wrapper::clap::context::RemoteControlPages;
 use crate::wrapper::clap::util::{read_stream, write_stream};
-use crate::wrapper::state::{self, PluginState};
-use crate::wrapper::util::buffer_management::{BufferManager, ChannelPointers};
+use crate::wrapper::state::{self, PluginState, StateContext};
+use crate::wrapper::util::buffer_management::{
+    AudioPortsActivation, BufferManager, ChannelPointers, HostChannelPointers, PluginBufferManager,
+    Sample,
+};
+use crate::wrapper::util::host_log::{self, HostLoggerHandle, LogScope};
+use crate::wrapper::util::midi_learn::MidiLearn;
 use crate::wrapper::util::{
-    clamp_input_event_timing, clamp_output_event_timing, hash_param_id, process_wrapper, strlcpy,
+    clamp_input_event_timing, clamp_output_event_timing, context_menu_actions, hash_param_id,
+    process_wrapper, push_all_or_nothing, strlcpy,
 };
 
 /// How many output parameter changes we can store in our output parameter change queue. Storing
 /// more than this many parameters at a time will cause changes to get lost.
 const OUTPUT_EVENT_QUEUE_CAPACITY: usize = 2048;
 
+/// The event type for tuning events within the tuning extension's event space. This is missing from
+/// clap-sys.
+const CLAP_EVENT_TUNING: u16 = 0;
+
 pub struct Wrapper<P: ClapPlugin> {
     /// A reference to this object, upgraded to an `Arc<Self>` for the GUI context.
     this: AtomicRefCell<Weak<Self>>,
@@ -143,6 +219,12 @@ pub struct Wrapper<P: ClapPlugin> {
     /// Stores any events the plugin has output during the current processing cycle, analogous to
     /// `input_events`.
     output_events: AtomicRefCell<VecDeque<PluginNoteEvent<P>>>,
+    /// Translates MPE input into per-note expression events if `P::MPE_INPUT` is set to
+    /// `MpeConfig::PerNoteExpressions`.
+    mpe_decoder: AtomicRefCell<MpeDecoder>,
+    /// The plugin's MIDI learn mappings. Incoming MIDI CCs are passed through this before they're
+    /// sent to the plugin so mapped parameters can be updated first.
+    pub midi_learn: MidiLearn,
     /// The last process status returned by the plugin. This is used for tail handling.
     last_process_status: AtomicCell<ProcessStatus>,
     /// The current latency in samples, as set by the plugin through the [`ProcessContext`]. Uses
@@ -150,7 +232,7 @@ pub struct Wrapper<P: ClapPlugin> {
     pub current_latency: AtomicU32,
     /// A data structure that helps manage and create buffers for all of the plugin's inputs and
     /// outputs based on channel pointers provided by the host.
-    buffer_manager: AtomicRefCell<BufferManager>,
+    buffer_manager: AtomicRefCell<PluginBufferManager>,
     /// The plugin is able to restore state through a method on the `GuiContext`. To avoid changing
     /// parameters mid-processing and running into garbled data if the host also tries to load state
     /// at the same time the restoring happens at the end of each processing call. If this zero
@@ -177,14 +259,49 @@ pub struct Wrapper<P: ClapPlugin> {
 
     clap_plugin_audio_ports: clap_plugin_audio_ports,
 
+    clap_plugin_audio_ports_activation: clap_plugin_audio_ports_activation,
+    /// Which of the audio ports the host has activated. This is reset whenever the host selects a
+    /// different audio ports config.
+    pub audio_ports_activation: AudioPortsActivation,
+
+    clap_plugin_ambisonic: clap_plugin_ambisonic,
+    /// Whether any of the plugin's audio IO layouts has an ambisonic port. The ambisonic extension
+    /// is only exposed if this is the case. This is computed once when the wrapper is created.
+    uses_ambisonic_ports: bool,
+
+    clap_plugin_context_menu: clap_plugin_context_menu,
+    host_context_menu: AtomicRefCell<Option<ClapPtr<clap_host_context_menu>>>,
+    /// The plugin's additional menu items for the last context menu shown through
+    /// [`show_param_context_menu()`][Self::show_param_context_menu()]. The host adds these items to
+    /// its menu through `clap_plugin_context_menu::populate()`, and it can call
+    /// `clap_plugin_context_menu::perform()` after the popup function has already returned. This is
+    /// cleared once an item has been performed or when the host populates another menu.
+    param_context_menu: AtomicRefCell<Option<ParamContextMenu>>,
+
     clap_plugin_gui: clap_plugin_gui,
     host_gui: AtomicRefCell<Option<ClapPtr<clap_host_gui>>>,
 
     clap_plugin_latency: clap_plugin_latency,
     host_latency: AtomicRefCell<Option<ClapPtr<clap_host_latency>>>,
 
+    /// Keeps the host's `log` extension registered as a destination for the `nih_log!()`,
+    /// `nih_warn!()`, and `nih_error!()` macros for as long as this instance exists.
+    host_logger_handle: AtomicRefCell<Option<HostLoggerHandle>>,
+
+    clap_plugin_note_name: clap_plugin_note_name,
+    host_note_name: AtomicRefCell<Option<ClapPtr<clap_host_note_name>>>,
+    /// The plugin's note names. These are queried from the plugin when the wrapper is created and
+    /// when the plugin calls `note_names_changed()`, so the host's queries never need to lock the
+    /// plugin.
+    note_names: AtomicRefCell<Vec<NoteName>>,
+
     clap_plugin_note_ports: clap_plugin_note_ports,
 
+    clap_plugin_param_indication: clap_plugin_param_indication,
+    /// The host's current indications for the plugin's parameters, indexed by the parameter's
+    /// hash. Parameters without an entry use the default indication.
+    param_indications: AtomicRefCell<HashMap<u32, ParamIndication>>,
+
     clap_plugin_params: clap_plugin_params,
     host_params: AtomicRefCell<Option<ClapPtr<clap_host_params>>>,
     // These fields are exactly the same as their VST3 wrapper counterparts.
@@ -222,6 +339,10 @@ pub struct Wrapper<P: ClapPlugin> {
     ///      even if it does then that should still not be a problem because the host also reads it
     ///      in the same order, right?
     output_parameter_events: ArrayQueue<OutputParamEvent>,
+    /// Held by the GUI thread while it adds a batch of parameter changes to
+    /// `output_parameter_events`. The queue is not read while this is locked, so all of the
+    /// batch's events are sent to the host and applied to the plugin during the same block.
+    output_parameter_events_batch_lock: Mutex<()>,
 
     host_thread_check: AtomicRefCell<Option<ClapPtr<clap_host_thread_check>>>,
 
@@ -229,12 +350,44 @@ pub struct Wrapper<P: ClapPlugin> {
     /// The plugin's remote control pages, if it defines any. Filled when initializing the plugin.
     remote_control_pages: Vec<clap_remote_controls_page>,
 
+    clap_plugin_preset_load: clap_plugin_preset_load,
+    host_preset_load: AtomicRefCell<Option<ClapPtr<clap_host_preset_load>>>,
+
     clap_plugin_render: clap_plugin_render,
 
     clap_plugin_state: clap_plugin_state,
 
+    clap_plugin_state_context: clap_plugin_state_context,
+
+    clap_plugin_surround: clap_plugin_surround,
+    /// Whether any of the plugin's audio IO layouts has a surround port. The surround extension is
+    /// only exposed if this is the case. This is computed once when the wrapper is created.
+    uses_surround_ports: bool,
+
     clap_plugin_tail: clap_plugin_tail,
 
+    clap_plugin_thread_pool: clap_plugin_thread_pool,
+    host_thread_pool: AtomicRefCell<Option<ClapPtr<clap_host_thread_pool>>>,
+    /// The task passed to [`execute_parallel()`][Self::execute_parallel()]. This is only set while
+    /// the host's `clap_host_thread_pool::request_exec()` is running, and the host then calls
+    /// `clap_plugin_thread_pool::exec()` to run this task from its own threads.
+    thread_pool_task: AtomicRefCell<Option<&'static (dyn Fn(u32) + Sync)>>,
+
+    clap_plugin_track_info: clap_plugin_track_info,
+    host_track_info: AtomicRefCell<Option<ClapPtr<clap_host_track_info>>>,
+
+    clap_plugin_tuning: clap_plugin_tuning_t,
+    host_tuning: AtomicRefCell<Option<ClapPtr<clap_host_tuning>>>,
+    /// The event space the host uses for tuning events. This is queried from the host's event
+    /// registry during initialization, and it is `None` if the host does not support tunings.
+    tuning_event_space_id: AtomicCell<Option<u16>>,
+    /// The tuning IDs the host assigned to each MIDI channel through tuning events. These default
+    /// to `CLAP_INVALID_ID`, which means that the host's default tuning should be used.
+    tuning_ids: [AtomicU32; 16],
+    /// Set when the host informs the plugin that its tunings have changed. The plugin's
+    /// [`ClapPlugin::tuning_changed()`] function is called at the start of the next process call.
+    tuning_changed: AtomicBool,
+
     clap_plugin_voice_info: clap_plugin_voice_info,
     host_voice_info: AtomicRefCell<Option<ClapPtr<clap_host_voice_info>>>,
     /// If `P::CLAP_POLY_MODULATION_CONFIG` is set, then the plugin can configure the current number
@@ -277,8 +430,14 @@ pub enum Task<P: Plugin> {
     LatencyChanged,
     /// Inform the host that the voice info has changed.
     VoiceInfoChanged,
-    /// Tell the host that it should rescan the current parameter values.
-    RescanParamValues,
+    /// Inform the host that the plugin's note names have changed.
+    NoteNamesChanged,
+    /// Tell the host that it should rescan the parameters using these `CLAP_PARAM_RESCAN_*`
+    /// flags.
+    RescanParams(clap_param_rescan_flags),
+    /// The audio thread received the controller for a parameter that was being MIDI learned. The
+    /// new mapping still needs to be added on the main thread.
+    MidiLearnFinished,
 }
 
 /// The types of CLAP parameter updates for events.
@@ -292,6 +451,17 @@ pub enum ClapParamUpdate {
     PlainValueMod(f64),
 }
 
+/// The plugin's own items for a single parameter context menu popup. See
+/// [`Wrapper::show_param_context_menu()`].
+struct ParamContextMenu {
+    /// The hash of the parameter the menu was shown for.
+    param_hash: u32,
+    items: Vec<ContextMenuItem>,
+    /// Whether the host has already added these items to the popup's menu. Any later menu is one
+    /// the host opened on its own, so it must not contain or perform these items.
+    is_populated: bool,
+}
+
 /// A parameter event that should be output by the plugin, stored in a queue on the wrapper and
 /// written to the host either at the end of the process function or during a flush.
 #[derive(Debug, Clone)]
@@ -360,6 +530,7 @@ impl<P: ClapPlugin> EventLoop<Task<P>, Wrapper<P>> for Wrapper<P> {
 impl<P: ClapPlugin> MainThreadExecutor<Task<P>> for Wrapper<P> {
     fn execute(&self, task: Task<P>, is_gui_thread: bool) {
         // This function is always called from the main thread, from [Self::on_main_thread].
+        let _log_scope = self.log_scope(false);
         match task {
             Task::PluginTask(task) => (self.task_executor.lock())(task),
             Task::ParameterValuesChanged => {
@@ -412,13 +583,28 @@ impl<P: ClapPlugin> MainThreadExecutor<Task<P>> for Wrapper<P> {
                 }
                 None => nih_debug_assert_failure!("Host does not support the voice-info extension"),
             },
-            Task::RescanParamValues => match &*self.host_params.borrow() {
+            Task::NoteNamesChanged => {
+                *self.note_names.borrow_mut() = self.plugin.lock().note_names();
+
+                match &*self.host_note_name.borrow() {
+                    Some(host_note_name) => {
+                        nih_debug_assert!(is_gui_thread);
+                        unsafe_clap_call! { host_note_name=>changed(&*self.host_callback) };
+                    }
+                    None => nih_trace!("Host does not support the note-name extension"),
+                }
+            }
+            Task::RescanParams(flags) => match &*self.host_params.borrow() {
                 Some(host_params) => {
                     nih_debug_assert!(is_gui_thread);
-                    unsafe_clap_call! { host_params=>rescan(&*self.host_callback, CLAP_PARAM_RESCAN_VALUES) };
+                    unsafe_clap_call! { host_params=>rescan(&*self.host_callback, flags) };
                 }
                 None => nih_debug_assert_failure!("The host does not support parameters? What?"),
             },
+            Task::MidiLearnFinished => self.midi_learn.finish_learning(|param| {
+                let param_hash = self.param_ptr_to_hash.get(&param)?;
+                Some(self.param_id_by_hash[param_hash].clone())
+            }),
         };
     }
 }
@@ -430,6 +616,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     pub unsafe fn new(host_callback: *const clap_host) -> Arc<Self> {
         let mut plugin = P::default();
         let task_executor = Mutex::new(plugin.task_executor());
+        let note_names = plugin.note_names();
 
         // This is used to allow the plugin to restore preset data from its editor, see the comment
         // on `Self::updated_state_sender`
@@ -521,6 +708,17 @@ impl<P: ClapPlugin> Wrapper<P> {
                 }
 
                 bypass_param_exists |= is_bypass;
+
+                let is_targeted_modulatable = flags.intersects(
+                    ParamFlags::MODULATABLE_PER_KEY
+                        | ParamFlags::MODULATABLE_PER_CHANNEL
+                        | ParamFlags::MODULATABLE_PER_PORT,
+                );
+                nih_debug_assert!(
+                    !is_targeted_modulatable || unsafe { ptr.poly_modulation_id() }.is_some(),
+                    "Parameters can only be modulatable per key, channel, or port if they also \
+                     have a poly modulation ID"
+                );
             }
         }
 
@@ -551,11 +749,13 @@ impl<P: ClapPlugin> Wrapper<P> {
             current_process_mode: AtomicCell::new(ProcessMode::Realtime),
             input_events: AtomicRefCell::new(VecDeque::with_capacity(512)),
             output_events: AtomicRefCell::new(VecDeque::with_capacity(512)),
+            mpe_decoder: AtomicRefCell::new(MpeDecoder::new()),
+            midi_learn: MidiLearn::default(),
             last_process_status: AtomicCell::new(ProcessStatus::Normal),
             current_latency: AtomicU32::new(0),
             // This is initialized just before calling `Plugin::initialize()` so that during the
             // process call buffers can be initialized without any allocations
-            buffer_manager: AtomicRefCell::new(BufferManager::for_audio_io_layout(
+            buffer_manager: AtomicRefCell::new(PluginBufferManager::for_plugin::<P>(
                 0,
                 AudioIOLayout::default(),
             )),
@@ -596,6 +796,29 @@ impl<P: ClapPlugin> Wrapper<P> {
                 get: Some(Self::ext_audio_ports_get),
             },
 
+            clap_plugin_audio_ports_activation: clap_plugin_audio_ports_activation {
+                can_activate_while_processing: Some(
+                    Self::ext_audio_ports_activation_can_activate_while_processing,
+                ),
+                set_active: Some(Self::ext_audio_ports_activation_set_active),
+            },
+            audio_ports_activation: AudioPortsActivation::for_plugin::<P>(),
+
+            clap_plugin_ambisonic: clap_plugin_ambisonic {
+                is_config_supported: Some(Self::ext_ambisonic_is_config_supported),
+                get_config: Some(Self::ext_ambisonic_get_config),
+            },
+            uses_ambisonic_ports: uses_channel_layout::<P>(|channel_layout| {
+                matches!(channel_layout, ChannelLayout::Ambisonic { .. })
+            }),
+
+            clap_plugin_context_menu: clap_plugin_context_menu {
+                populate: Some(Self::ext_context_menu_populate),
+                perform: Some(Self::ext_context_menu_perform),
+            },
+            host_context_menu: AtomicRefCell::new(None),
+            param_context_menu: AtomicRefCell::new(None),
+
             clap_plugin_gui: clap_plugin_gui {
                 is_api_supported: Some(Self::ext_gui_is_api_supported),
                 get_preferred_api: Some(Self::ext_gui_get_preferred_api),
@@ -620,11 +843,26 @@ impl<P: ClapPlugin> Wrapper<P> {
             },
             host_latency: AtomicRefCell::new(None),
 
+            host_logger_handle: AtomicRefCell::new(None),
+
+            clap_plugin_note_name: clap_plugin_note_name {
+                count: Some(Self::ext_note_name_count),
+                get: Some(Self::ext_note_name_get),
+            },
+            host_note_name: AtomicRefCell::new(None),
+            note_names: AtomicRefCell::new(note_names),
+
             clap_plugin_note_ports: clap_plugin_note_ports {
                 count: Some(Self::ext_note_ports_count),
                 get: Some(Self::ext_note_ports_get),
             },
 
+            clap_plugin_param_indication: clap_plugin_param_indication {
+                set_mapping: Some(Self::ext_param_indication_set_mapping),
+                set_automation: Some(Self::ext_param_indication_set_automation),
+            },
+            param_indications: AtomicRefCell::new(HashMap::new()),
+
             clap_plugin_params: clap_plugin_params {
                 count: Some(Self::ext_params_count),
                 get_info: Some(Self::ext_params_get_info),
@@ -642,6 +880,7 @@ impl<P: ClapPlugin> Wrapper<P> {
             param_ptr_to_hash,
             poly_mod_ids_by_hash,
             output_parameter_events: ArrayQueue::new(OUTPUT_EVENT_QUEUE_CAPACITY),
+            output_parameter_events_batch_lock: Mutex::new(()),
 
             host_thread_check: AtomicRefCell::new(None),
 
@@ -651,6 +890,11 @@ impl<P: ClapPlugin> Wrapper<P> {
             },
             remote_control_pages,
 
+            clap_plugin_preset_load: clap_plugin_preset_load {
+                from_location: Some(Self::ext_preset_load_from_location),
+            },
+            host_preset_load: AtomicRefCell::new(None),
+
             clap_plugin_render: clap_plugin_render {
                 has_hard_realtime_requirement: Some(Self::ext_render_has_hard_realtime_requirement),
                 set: Some(Self::ext_render_set),
@@ -661,10 +905,42 @@ impl<P: ClapPlugin> Wrapper<P> {
                 load: Some(Self::ext_state_load),
             },
 
+            clap_plugin_state_context: clap_plugin_state_context {
+                save: Some(Self::ext_state_context_save),
+                load: Some(Self::ext_state_context_load),
+            },
+
+            clap_plugin_surround: clap_plugin_surround {
+                is_channel_mask_supported: Some(Self::ext_surround_is_channel_mask_supported),
+                get_channel_map: Some(Self::ext_surround_get_channel_map),
+            },
+            uses_surround_ports: uses_channel_layout::<P>(|channel_layout| {
+                surround_channel_map(channel_layout).is_some()
+            }),
+
             clap_plugin_tail: clap_plugin_tail {
                 get: Some(Self::ext_tail_get),
             },
 
+            clap_plugin_thread_pool: clap_plugin_thread_pool {
+                exec: Some(Self::ext_thread_pool_exec),
+            },
+            host_thread_pool: AtomicRefCell::new(None),
+            thread_pool_task: AtomicRefCell::new(None),
+
+            clap_plugin_track_info: clap_plugin_track_info {
+                changed: Some(Self::ext_track_info_changed),
+            },
+            host_track_info: AtomicRefCell::new(None),
+
+            clap_plugin_tuning: clap_plugin_tuning_t {
+                changed: Some(Self::ext_tuning_changed),
+            },
+            host_tuning: AtomicRefCell::new(None),
+            tuning_event_space_id: AtomicCell::new(None),
+            tuning_ids: std::array::from_fn(|_| AtomicU32::new(CLAP_INVALID_ID)),
+            tuning_changed: AtomicBool::new(false),
+
             clap_plugin_voice_info: clap_plugin_voice_info {
                 get: Some(Self::ext_voice_info_get),
             },
@@ -746,15 +1022,29 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
-    fn make_process_context(&self, transport: Transport) -> WrapperProcessContext<'_, P> {
+    fn make_process_context(
+        &self,
+        transport: Transport,
+        block_start: usize,
+    ) -> WrapperProcessContext<'_, P> {
         WrapperProcessContext {
             wrapper: self,
             input_events_guard: self.input_events.borrow_mut(),
             output_events_guard: self.output_events.borrow_mut(),
             transport,
+            block_start,
         }
     }
 
+    /// Attribute the messages logged on the current thread to this instance in the host's log until
+    /// the returned scope is dropped. This is called at the start of every entry point.
+    fn log_scope(&self, is_audio_thread: bool) -> Option<LogScope> {
+        self.host_logger_handle
+            .borrow()
+            .as_ref()
+            .map(|host_logger_handle| host_logger_handle.enter(is_audio_thread))
+    }
+
     /// Get a parameter's ID based on a `ParamPtr`. Used in the `GuiContext` implementation for the
     /// gesture checks.
     #[allow(unused)]
@@ -786,6 +1076,45 @@ impl<P: ClapPlugin> Wrapper<P> {
         result
     }
 
+    /// Queue several parameter output events at once and request a single parameter flush from
+    /// the host. These events are either all written to the host during the same process call or
+    /// flush, or not at all. This should only be called from the main thread.
+    ///
+    /// Returns `false` if the parameter value queue did not have room for all of the events, in
+    /// which case none of them will be sent to the host.
+    pub fn queue_parameter_events(&self, events: Vec<OutputParamEvent>) -> bool {
+        let result = {
+            let _batch_guard = self.output_parameter_events_batch_lock.lock();
+            push_all_or_nothing(&self.output_parameter_events, events)
+        };
+
+        match &*self.host_params.borrow() {
+            Some(host_params) => {
+                unsafe_clap_call! { host_params=>request_flush(&*self.host_callback) }
+            }
+            None => nih_debug_assert_failure!("The host does not support parameters? What?"),
+        }
+
+        result
+    }
+
+    /// Queue a parameter output event from the audio thread while the plugin is processing audio.
+    /// These events are written to the host after the current block has been processed, so unlike
+    /// [`queue_parameter_event()`][Self::queue_parameter_event()] this does not request a parameter
+    /// flush. The host's flush request function may not be called from the audio thread.
+    ///
+    /// Returns `false` if the parameter value queue was full and the update will not be sent to the
+    /// host. This also triggers a debug assertion failure.
+    pub fn queue_parameter_event_from_process(&self, event: OutputParamEvent) -> bool {
+        let success = self.output_parameter_events.push(event).is_ok();
+        nih_debug_assert!(
+            success,
+            "Parameter output event queue was full, parameter change will not be sent to the host"
+        );
+
+        success
+    }
+
     /// Request a resize based on the editor's current reported size. As of CLAP 0.24 this can
     /// safely be called from any thread. If this returns `false`, then the plugin should reset its
     /// size back to the previous value.
@@ -810,6 +1139,43 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
+    /// Whether the parameter with this hash is marked as [`ParamFlags::READ_ONLY`]. Value changes
+    /// sent by the host for these parameters are ignored.
+    pub fn param_is_read_only(&self, hash: u32) -> bool {
+        self.param_by_hash
+            .get(&hash)
+            .is_some_and(|param_ptr| unsafe { param_ptr.flags() }.contains(ParamFlags::READ_ONLY))
+    }
+
+    /// Pass an incoming MIDI CC through the MIDI learn mappings. Mapped parameters are updated
+    /// right away, and the changes are also sent to the host as output parameter events. Must be
+    /// called from the audio thread, or during a parameter flush.
+    fn handle_midi_learn_cc(&self, channel: u8, cc: u8, value: f32) {
+        let sample_rate = self.current_buffer_config.load().map(|c| c.sample_rate);
+        let learn_finished =
+            self.midi_learn
+                .process_cc(channel, cc, value, |param_ptr, normalized_value| {
+                    let param_hash = self.param_ptr_to_hash[&param_ptr];
+                    let clap_plain_value = normalized_value as f64
+                        * unsafe { param_ptr.step_count() }.unwrap_or(1) as f64;
+
+                    self.update_plain_value_by_hash(
+                        param_hash,
+                        ClapParamUpdate::PlainValueSet(clap_plain_value),
+                        sample_rate,
+                    );
+                    self.queue_parameter_event_from_process(OutputParamEvent::SetValue {
+                        param_hash,
+                        clap_plain_value,
+                    });
+                });
+
+        if learn_finished {
+            let task_posted = self.schedule_gui(Task::MidiLearnFinished);
+            nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+        }
+    }
+
     /// Convenience function for setting a value for a parameter as triggered by a VST3 parameter
     /// update. The same rate is for updating parameter smoothing.
     ///
@@ -991,7 +1357,11 @@ impl<P: ClapPlugin> Wrapper<P> {
         // We'll always write these events to the first sample, so even when we add note output we
         // shouldn't have to think about interleaving events here
         let sample_rate = self.current_buffer_config.load().map(|c| c.sample_rate);
-        while let Some(change) = self.output_parameter_events.pop() {
+        // If the editor is currently queueing a batch of parameter changes, then the entire batch
+        // is sent during the next process call or flush instead
+        let batch_guard = self.output_parameter_events_batch_lock.try_lock();
+        let output_parameter_events = batch_guard.as_ref().map(|_| &self.output_parameter_events);
+        while let Some(change) = output_parameter_events.and_then(|events| events.pop()) {
             let push_successful = match change {
                 OutputParamEvent::BeginGesture { param_hash } => {
                     let event = clap_event_param_gesture {
@@ -1324,9 +1694,12 @@ impl<P: ClapPlugin> Wrapper<P> {
                     // the MIDI events
                     let midi_data = match midi_event.as_midi() {
                         Some(MidiResult::Basic(midi_data)) => midi_data,
-                        Some(MidiResult::SysEx(_, _)) => unreachable!(
-                            "Basic MIDI event read as SysEx, something's gone horribly wrong"
-                        ),
+                        Some(MidiResult::SysEx(_, _) | MidiResult::Multiple(_, _)) => {
+                            unreachable!(
+                                "Basic MIDI event read as SysEx or as multiple events, something's \
+                                 gone horribly wrong"
+                            )
+                        }
                         None => unreachable!("Missing MIDI conversion for MIDI event"),
                     };
 
@@ -1344,6 +1717,38 @@ impl<P: ClapPlugin> Wrapper<P> {
 
                     clap_call! { out=>try_push(out, &event.header) }
                 }
+                midi_event @ (NoteEvent::MidiCC14Bit { .. }
+                | NoteEvent::MidiRpn { .. }
+                | NoteEvent::MidiNrpn { .. })
+                    if P::MIDI_OUTPUT >= MidiConfig::MidiCCs =>
+                {
+                    // These events are sent as a sequence of regular CC messages
+                    let (messages, num_messages) = match midi_event.as_midi() {
+                        Some(MidiResult::Multiple(messages, num_messages)) => {
+                            (messages, num_messages)
+                        }
+                        _ => unreachable!("Missing MIDI conversion for multi-message MIDI event"),
+                    };
+
+                    let mut push_successful = true;
+                    for midi_data in &messages[..num_messages] {
+                        let event = clap_event_midi {
+                            header: clap_event_header {
+                                size: mem::size_of::<clap_event_midi>() as u32,
+                                time,
+                                space_id: CLAP_CORE_EVENT_SPACE_ID,
+                                type_: CLAP_EVENT_MIDI,
+                                flags: 0,
+                            },
+                            port_index: 0,
+                            data: *midi_data,
+                        };
+
+                        push_successful &= clap_call! { out=>try_push(out, &event.header) };
+                    }
+
+                    push_successful
+                }
                 NoteEvent::MidiSysEx { timing: _, message }
                     if P::MIDI_OUTPUT >= MidiConfig::Basic =>
                 {
@@ -1414,6 +1819,11 @@ impl<P: ClapPlugin> Wrapper<P> {
         match (raw_event.space_id, raw_event.type_) {
             (CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_PARAM_VALUE) => {
                 let event = &*(event as *const clap_event_param_value);
+                // Read-only parameters are only ever changed by the plugin itself
+                if self.param_is_read_only(event.param_id) {
+                    return;
+                }
+
                 self.update_plain_value_by_hash(
                     event.param_id,
                     ClapParamUpdate::PlainValueSet(event.value),
@@ -1440,6 +1850,9 @@ impl<P: ClapPlugin> Wrapper<P> {
             }
             (CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_PARAM_MOD) => {
                 let event = &*(event as *const clap_event_param_mod);
+                if self.param_is_read_only(event.param_id) {
+                    return;
+                }
 
                 if event.note_id != -1 && P::MIDI_INPUT >= MidiConfig::Basic {
                     match self.poly_mod_ids_by_hash.get(&event.param_id) {
@@ -1467,6 +1880,33 @@ impl<P: ClapPlugin> Wrapper<P> {
                              ID"
                         ),
                     }
+                } else if (event.key != -1 || event.channel != -1 || event.port_index != -1)
+                    && P::MIDI_INPUT >= MidiConfig::Basic
+                {
+                    // This is the same as above, but for parameters that are modulatable per key,
+                    // channel, or port
+                    match self.poly_mod_ids_by_hash.get(&event.param_id) {
+                        Some(poly_modulation_id) => {
+                            let param_ptr = self.param_by_hash[&event.param_id];
+                            let normalized_offset =
+                                event.amount as f32 / param_ptr.step_count().unwrap_or(1) as f32;
+
+                            input_events.push_back(NoteEvent::TargetedModulation {
+                                timing,
+                                port: (event.port_index != -1).then_some(event.port_index as u16),
+                                channel: (event.channel != -1).then_some(event.channel as u8),
+                                note: (event.key != -1).then_some(event.key as u8),
+                                poly_modulation_id: *poly_modulation_id,
+                                normalized_offset,
+                            });
+
+                            return;
+                        }
+                        None => nih_debug_assert_failure!(
+                            "Key, channel, or port modulation sent for a parameter without a poly \
+                             modulation ID"
+                        ),
+                    }
                 }
 
                 self.update_plain_value_by_hash(
@@ -1638,7 +2078,27 @@ impl<P: ClapPlugin> Wrapper<P> {
                 // messages to stay consistent with the VST3 wrapper.
                 let event = &*(event as *const clap_event_midi);
 
-                match NoteEvent::from_midi(timing, &event.data) {
+                let note_event = NoteEvent::from_midi(timing, &event.data);
+                if let Ok(NoteEvent::MidiCC {
+                    channel, cc, value, ..
+                }) = &note_event
+                {
+                    if P::MIDI_INPUT >= MidiConfig::MidiCCs {
+                        self.handle_midi_learn_cc(*channel, *cc, *value);
+                    }
+                }
+
+                match note_event {
+                    // With the MPE dialect, member channel expressions are converted to per-note
+                    // expressions
+                    Ok(note_event)
+                        if P::MPE_INPUT == MpeConfig::PerNoteExpressions
+                            && P::MIDI_INPUT >= MidiConfig::MidiCCs =>
+                    {
+                        self.mpe_decoder
+                            .borrow_mut()
+                            .process(note_event, |event| input_events.push_back(event));
+                    }
                     Ok(
                         note_event @ (NoteEvent::NoteOn { .. }
                         | NoteEvent::NoteOff { .. }
@@ -1666,6 +2126,28 @@ impl<P: ClapPlugin> Wrapper<P> {
                     input_events.push_back(note_event);
                 };
             }
+            (space_id, CLAP_EVENT_TUNING)
+                if Some(space_id) == self.tuning_event_space_id.load() =>
+            {
+                // Tuning events assign one of the host's tunings to a MIDI channel. These are
+                // only used to look up note frequencies, so they're not passed to the plugin.
+                let event = &*(event as *const clap_event_tuning);
+                match usize::try_from(event.channel) {
+                    Ok(channel) => match self.tuning_ids.get(channel) {
+                        Some(tuning_id) => tuning_id.store(event.tunning_id, Ordering::SeqCst),
+                        None => nih_debug_assert_failure!(
+                            "Tuning event for invalid channel {}",
+                            event.channel
+                        ),
+                    },
+                    // A channel of -1 means that the tuning applies to all channels
+                    Err(_) => {
+                        for tuning_id in &self.tuning_ids {
+                            tuning_id.store(event.tunning_id, Ordering::SeqCst);
+                        }
+                    }
+                }
+            }
             _ => {
                 nih_trace!(
                     "Unhandled CLAP event type {} for namespace {}",
@@ -1676,6 +2158,159 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
+    /// Create the plugin's buffers from the host's audio buffers for a block of audio and process
+    /// that block. This is generic over the sample format so it can be used for plugins that process
+    /// audio in both single and double precision. The host may provide either single or double
+    /// precision buffers, and the buffer manager will convert between the two when needed.
+    ///
+    /// # Safety
+    ///
+    /// The audio buffers in `process` must contain at least `block_start + block_len` samples.
+    unsafe fn process_block<T: Sample>(
+        &self,
+        buffer_manager: &mut BufferManager<T>,
+        process: &clap_process,
+        block_start: usize,
+        block_len: usize,
+        transport: Transport,
+    ) -> ProcessStatus {
+        let current_audio_io_layout = self.current_audio_io_layout.load();
+        let has_main_input = current_audio_io_layout.main_input_channels.is_some();
+        let has_main_output = current_audio_io_layout.main_output_channels.is_some();
+        let aux_input_start_idx = if has_main_input { 1 } else { 0 };
+        let aux_output_start_idx = if has_main_output { 1 } else { 0 };
+
+        // The buffer manager preallocated buffer slices for all the IO and storage for any
+        // axuiliary inputs.
+        // TODO: The audio buffers have a latency field, should we use those?
+        // TODO: Like with VST3, should we expose some way to access or set the silence/constant
+        //       flags?
+        buffer_manager.set_ports_activation(&self.audio_ports_activation);
+        let buffers = buffer_manager.create_buffers(block_start, block_len, |buffer_source| {
+            // Explicitly take plugins with no main output that does have auxiliary outputs into
+            // account. Shouldn't happen, but if we just start copying audio here then that would
+            // result in unsoundness.
+            if process.audio_outputs_count > 0
+                && !process.audio_outputs.is_null()
+                && has_main_output
+            {
+                *buffer_source.main_output_channel_pointers =
+                    Self::audio_buffer_channel_pointers(&*process.audio_outputs);
+            }
+
+            if process.audio_inputs_count > 0 && !process.audio_inputs.is_null() && has_main_input {
+                *buffer_source.main_input_channel_pointers =
+                    Self::audio_buffer_channel_pointers(&*process.audio_inputs);
+            }
+
+            if !process.audio_inputs.is_null() {
+                for (aux_input_no, aux_input_channel_pointers) in buffer_source
+                    .aux_input_channel_pointers
+                    .iter_mut()
+                    .enumerate()
+                {
+                    let aux_input_idx = aux_input_no + aux_input_start_idx;
+                    if aux_input_idx > process.audio_inputs_count as usize {
+                        break;
+                    }
+
+                    *aux_input_channel_pointers = Self::audio_buffer_channel_pointers(
+                        &*process.audio_inputs.add(aux_input_idx),
+                    );
+                }
+            }
+
+            if !process.audio_outputs.is_null() {
+                for (aux_output_no, aux_output_channel_pointers) in buffer_source
+                    .aux_output_channel_pointers
+                    .iter_mut()
+                    .enumerate()
+                {
+                    let aux_output_idx = aux_output_no + aux_output_start_idx;
+                    if aux_output_idx > process.audio_outputs_count as usize {
+                        break;
+                    }
+
+                    *aux_output_channel_pointers = Self::audio_buffer_channel_pointers(
+                        &*process.audio_outputs.add(aux_output_idx),
+                    );
+                }
+            }
+        });
+
+        // If the host does not provide outputs or if it does not provide the required number of
+        // channels (should not happen, but Ableton Live does this for bypassed VST3 plugins) then
+        // we'll skip audio processing. In that case `buffer_manager.create_buffers` will have set
+        // one or more of the output buffers to empty slices since there is no storage to point
+        // them to. The auxiliary input buffers always point to valid storage. Inactive auxiliary
+        // outputs may not have any buffers, and the plugin should not write to them anyways.
+        let mut buffer_is_valid = true;
+        for output_buffer_slice in buffers.main_buffer.as_slice_immutable().iter().chain(
+            buffers
+                .aux_outputs
+                .iter()
+                .zip(buffers.aux_outputs_active)
+                .filter(|(_, is_active)| **is_active)
+                .flat_map(|(buffer, _)| buffer.as_slice_immutable().iter()),
+        ) {
+            if output_buffer_slice.is_empty() {
+                buffer_is_valid = false;
+                break;
+            }
+        }
+
+        nih_debug_assert!(buffer_is_valid);
+
+        let result = if buffer_is_valid {
+            let mut plugin = self.plugin.lock();
+            // The host informs the plugin about tuning changes on the main thread, but the plugin
+            // is notified at the start of the next block to avoid having to lock it there
+            if self.tuning_changed.swap(false, Ordering::SeqCst) {
+                plugin.tuning_changed();
+            }
+
+            // SAFETY: Shortening these borrows is safe as even if the plugin overwrites the
+            //         slices (which it cannot do without using unsafe code), then they would still
+            //         be reset on the next iteration
+            let mut aux = AuxiliaryBuffers {
+                main_input: buffers.main_input,
+                inputs: buffers.aux_inputs,
+                outputs: buffers.aux_outputs,
+                inputs_active: buffers.aux_inputs_active,
+                outputs_active: buffers.aux_outputs_active,
+            };
+            let mut context = self.make_process_context(transport, block_start);
+            let result = T::process(&mut *plugin, buffers.main_buffer, &mut aux, &mut context);
+            self.last_process_status.store(result);
+            result
+        } else {
+            ProcessStatus::Normal
+        };
+
+        // If the host's buffers use a different sample format than the plugin, then the outputs
+        // still need to be copied back to the host's buffers
+        buffer_manager.write_back_outputs();
+
+        result
+    }
+
+    /// Get the channel pointers for one of the host's audio buffers. Double precision buffers are
+    /// only used when the plugin processes audio in double precision, since that's the only case
+    /// where we advertise support for them. Returns `None` if the host did not provide any
+    /// buffers.
+    unsafe fn audio_buffer_channel_pointers(
+        audio_buffer: &clap_audio_buffer,
+    ) -> Option<HostChannelPointers> {
+        let num_channels = audio_buffer.channel_count as usize;
+        if P::PROCESS_F64 {
+            if let Some(ptrs) = NonNull::new(audio_buffer.data64) {
+                return Some(ChannelPointers { ptrs, num_channels }.into());
+            }
+        }
+
+        NonNull::new(audio_buffer.data32).map(|ptrs| ChannelPointers { ptrs, num_channels }.into())
+    }
+
     /// Get the plugin's state object, may be called by the plugin's GUI as part of its own preset
     /// management. The wrapper doesn't use these functions and serializes and deserializes directly
     /// the JSON in the relevant plugin API methods instead.
@@ -1684,6 +2319,8 @@ impl<P: ClapPlugin> Wrapper<P> {
             state::serialize_object::<P>(
                 self.params.clone(),
                 state::make_params_iter(&self.param_by_hash, &self.param_id_to_hash),
+                &self.midi_learn,
+                StateContext::Preset,
             )
         }
     }
@@ -1724,13 +2361,13 @@ impl<P: ClapPlugin> Wrapper<P> {
             } else {
                 // Otherwise we'll set the state right here and now, since this function should be
                 // called from a GUI thread
-                self.set_state_inner(&mut state);
+                self.set_state_inner(&mut state, StateContext::Preset);
                 break;
             }
         }
 
         // After the state has been updated, notify the host about the new parameter values
-        let task_posted = self.schedule_gui(Task::RescanParamValues);
+        let task_posted = self.schedule_gui(Task::RescanParams(CLAP_PARAM_RESCAN_VALUES));
         nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
     }
 
@@ -1769,61 +2406,295 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
-    /// Immediately set the plugin state. Returns `false` if the deserialization failed. The plugin
-    /// state is set from a couple places, so this function aims to deduplicate that. Includes
-    /// `permit_alloc()`s around the deserialization and initialization for the use case where
-    /// `set_state_object_from_gui()` was called while the plugin is process audio.
-    ///
-    /// Implicitly emits `Task::ParameterValuesChanged`.
-    ///
-    /// # Notes
-    ///
-    /// `self.plugin` must _not_ be locked while calling this function or it will deadlock.
-    pub fn set_state_inner(&self, state: &mut PluginState) -> bool {
-        let audio_io_layout = self.current_audio_io_layout.load();
-        let buffer_config = self.current_buffer_config.load();
+    pub fn note_names_changed(&self) {
+        let task_posted = self.schedule_gui(Task::NoteNamesChanged);
+        nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+    }
 
-        // FIXME: This is obviously not realtime-safe, but loading presets without doing this could
-        //        lead to inconsistencies. It's the plugin's responsibility to not perform any
-        //        realtime-unsafe work when the initialize function is called a second time if it
-        //        supports runtime preset loading.  `state::deserialize_object()` normally never
-        //        allocates, but if the plugin has persistent non-parameter data then its
-        //        `deserialize_fields()` implementation may still allocate.
-        let mut success = permit_alloc(|| unsafe {
-            state::deserialize_object::<P>(
-                state,
-                self.params.clone(),
-                state::make_params_getter(&self.param_by_hash, &self.param_id_to_hash),
-                self.current_buffer_config.load().as_ref(),
-            )
-        });
-        if !success {
-            nih_debug_assert_failure!("Deserializing plugin state from a state object failed");
-            return false;
+    pub fn param_info_changed(&self, changes: ParamInfoChanges) {
+        let mut flags = 0;
+        if changes.contains(ParamInfoChanges::VALUES) {
+            flags |= CLAP_PARAM_RESCAN_VALUES;
+        }
+        if changes.contains(ParamInfoChanges::VALUE_STRINGS) {
+            flags |= CLAP_PARAM_RESCAN_TEXT;
+        }
+        // Changed default values also fall under the regular parameter info rescan. Only adding or
+        // removing parameters or changing their ranges would require `CLAP_PARAM_RESCAN_ALL`.
+        if changes.contains(ParamInfoChanges::INFO) {
+            flags |= CLAP_PARAM_RESCAN_INFO;
+        }
+        if flags == 0 {
+            return;
         }
 
-        // If the plugin was already initialized then it needs to be reinitialized
-        if let Some(buffer_config) = buffer_config {
-            // NOTE: This needs to be dropped after the `plugin` lock to avoid deadlocks
-            let mut init_context = self.make_init_context();
-            let mut plugin = self.plugin.lock();
+        let task_posted = self.schedule_gui(Task::RescanParams(flags));
+        nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+    }
 
-            // See above
-            success = permit_alloc(|| {
-                plugin.initialize(&audio_io_layout, &buffer_config, &mut init_context)
-            });
-            if success {
-                process_wrapper(|| plugin.reset());
-            }
+    /// Start MIDI learn for a parameter. Returns `false` if the parameter cannot be MIDI learned.
+    pub fn start_midi_learn(&self, param: ParamPtr) -> bool {
+        if !self.param_ptr_to_hash.contains_key(&param) {
+            nih_debug_assert_failure!("Unknown parameter: {:?}", param);
+            return false;
         }
 
-        nih_debug_assert!(
-            success,
-            "Plugin returned false when reinitializing after loading state"
+        self.midi_learn.start_learning::<P>(param)
+    }
+
+    /// Replace the MIDI learn mappings, resolving the mappings' parameter IDs.
+    pub fn set_midi_mappings(&self, mappings: &[MidiMapping]) {
+        self.midi_learn.set_mappings(
+            mappings,
+            state::make_params_getter(&self.param_by_hash, &self.param_id_to_hash),
         );
+    }
 
-        // Reinitialize the plugin after loading state so it can respond to the new parameter values
-        let task_posted = self.schedule_gui(Task::ParameterValuesChanged);
+    /// Ask the host to show its context menu for a parameter, with the plugin's own items added to
+    /// it. Returns `false` if the host does not support this. This must be called from the main
+    /// thread.
+    pub fn show_param_context_menu(
+        &self,
+        param_hash: u32,
+        x: i32,
+        y: i32,
+        items: Vec<ContextMenuItem>,
+    ) -> bool {
+        let host_context_menu = self.host_context_menu.borrow();
+        let Some(host_context_menu) = host_context_menu.as_ref() else {
+            return false;
+        };
+        if !unsafe_clap_call! { host_context_menu=>can_popup(&*self.host_callback) } {
+            return false;
+        }
+
+        // The host will call `ext_context_menu_populate()` to add these items to the menu
+        *self.param_context_menu.borrow_mut() = Some(ParamContextMenu {
+            param_hash,
+            items,
+            is_populated: false,
+        });
+
+        let target = clap_context_menu_target {
+            kind: CLAP_CONTEXT_MENU_TARGET_KIND_PARAM,
+            id: param_hash,
+        };
+        let success =
+            unsafe_clap_call! { host_context_menu=>popup(&*self.host_callback, &target, 0, x, y) };
+        if !success {
+            *self.param_context_menu.borrow_mut() = None;
+        }
+
+        success
+    }
+
+    /// The host's current indication for a parameter. Returns the default indication if the host
+    /// has not sent one.
+    pub fn param_indication(&self, param_hash: u32) -> ParamIndication {
+        self.param_indications
+            .borrow()
+            .get(&param_hash)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Update a parameter's indication using `update`, and notify the editor if it's open. Called
+    /// from the `param-indication` extension's functions.
+    fn update_param_indication(&self, param_hash: u32, update: impl FnOnce(&mut ParamIndication)) {
+        let Some(param_id) = self.param_id_by_hash.get(&param_hash) else {
+            nih_debug_assert_failure!("Unknown parameter hash {}", param_hash);
+            return;
+        };
+
+        // The borrow needs to end before the editor is notified since it may query the indication
+        let indication = {
+            let mut param_indications = self.param_indications.borrow_mut();
+            let indication = param_indications.entry(param_hash).or_default();
+            update(indication);
+
+            indication.clone()
+        };
+
+        if self.editor_handle.lock().is_some() {
+            if let Some(editor) = self.editor.borrow().as_ref() {
+                editor
+                    .lock()
+                    .param_indication_changed(param_id, &indication);
+            }
+        }
+    }
+
+    /// Query the host for information about the track the plugin is inserted on. Returns `None` if
+    /// the host does not support the track-info extension or if it could not provide the
+    /// information. This must be called from the main thread.
+    pub fn track_info(&self) -> Option<TrackInfo> {
+        let host_track_info = self.host_track_info.borrow();
+        let host_track_info = host_track_info.as_ref()?;
+
+        let mut info: clap_track_info = unsafe { mem::zeroed() };
+        let success = unsafe_clap_call! {
+            host_track_info=>get(&*self.host_callback, &mut info)
+        };
+        if !success {
+            return None;
+        }
+
+        let has_flag = |flag: u64| info.flags & flag != 0;
+        Some(TrackInfo {
+            name: if has_flag(CLAP_TRACK_INFO_HAS_TRACK_NAME) {
+                Some(
+                    unsafe { CStr::from_ptr(info.name.as_ptr()) }
+                        .to_string_lossy()
+                        .into_owned(),
+                )
+            } else {
+                None
+            },
+            color: if has_flag(CLAP_TRACK_INFO_HAS_TRACK_COLOR) {
+                Some(HostColor {
+                    red: info.color.red,
+                    green: info.color.green,
+                    blue: info.color.blue,
+                    alpha: info.color.alpha,
+                })
+            } else {
+                None
+            },
+            audio_channel_count: if has_flag(CLAP_TRACK_INFO_HAS_AUDIO_CHANNEL) {
+                u32::try_from(info.audio_channel_count).ok()
+            } else {
+                None
+            },
+            audio_port_type: if has_flag(CLAP_TRACK_INFO_HAS_AUDIO_CHANNEL)
+                && !info.audio_port_type.is_null()
+            {
+                Some(
+                    unsafe { CStr::from_ptr(info.audio_port_type) }
+                        .to_string_lossy()
+                        .into_owned(),
+                )
+            } else {
+                None
+            },
+            track_type: if has_flag(CLAP_TRACK_INFO_IS_FOR_MASTER) {
+                TrackType::Master
+            } else if has_flag(CLAP_TRACK_INFO_IS_FOR_BUS) {
+                TrackType::Bus
+            } else if has_flag(CLAP_TRACK_INFO_IS_FOR_RETURN_TRACK) {
+                TrackType::Return
+            } else {
+                TrackType::Regular
+            },
+        })
+    }
+
+    /// Get the frequency for a note using the tuning the host has assigned to the note's channel.
+    /// `sample_offset` is relative to the start of the current process call. Falls back to 12-TET
+    /// if the host does not support the tuning extension. This is realtime-safe.
+    pub fn note_frequency(&self, channel: u8, note: u8, sample_offset: u32) -> f32 {
+        let host_tuning = self.host_tuning.borrow();
+        let Some(host_tuning) = host_tuning.as_ref() else {
+            return util::midi_note_to_freq(note);
+        };
+
+        let tuning_id = self
+            .tuning_ids
+            .get(channel as usize)
+            .map(|tuning_id| tuning_id.load(Ordering::SeqCst))
+            .unwrap_or(CLAP_INVALID_ID);
+        // The host returns the note's offset in semitones relative to 12-TET
+        let relative = unsafe_clap_call! {
+            host_tuning=>get_relative(&*self.host_callback, tuning_id, channel as i32, note as i32, sample_offset)
+        };
+
+        util::f32_midi_note_to_freq(note as f32 + relative as f32)
+    }
+
+    /// Run `task` for every index in `0..num_tasks` using the host's thread pool, or sequentially on
+    /// the current thread if the host does not support the thread pool extension or if it could not
+    /// run the tasks. This may only be called from the audio thread during a process call.
+    pub fn execute_parallel(&self, num_tasks: u32, task: &(dyn Fn(u32) + Sync)) {
+        if num_tasks == 0 {
+            return;
+        }
+
+        if let Some(host_thread_pool) = self.host_thread_pool.borrow().as_ref() {
+            // SAFETY: The host only calls `clap_plugin_thread_pool::exec()` while `request_exec()`
+            //         is running, and the task is removed again before this function returns
+            let task: &'static (dyn Fn(u32) + Sync) = unsafe { mem::transmute(task) };
+            *self.thread_pool_task.borrow_mut() = Some(task);
+            let success = unsafe_clap_call! {
+                host_thread_pool=>request_exec(&*self.host_callback, num_tasks)
+            };
+            *self.thread_pool_task.borrow_mut() = None;
+
+            if success {
+                return;
+            }
+        }
+
+        for task_index in 0..num_tasks {
+            task(task_index);
+        }
+    }
+
+    /// Immediately set the plugin state. Returns `false` if the deserialization failed. The plugin
+    /// state is set from a couple places, so this function aims to deduplicate that. Includes
+    /// `permit_alloc()`s around the deserialization and initialization for the use case where
+    /// `set_state_object_from_gui()` was called while the plugin is process audio.
+    ///
+    /// Implicitly emits `Task::ParameterValuesChanged`.
+    ///
+    /// # Notes
+    ///
+    /// `self.plugin` must _not_ be locked while calling this function or it will deadlock.
+    pub fn set_state_inner(&self, state: &mut PluginState, context: StateContext) -> bool {
+        let audio_io_layout = self.current_audio_io_layout.load();
+        let buffer_config = self.current_buffer_config.load();
+
+        // FIXME: This is obviously not realtime-safe, but loading presets without doing this could
+        //        lead to inconsistencies. It's the plugin's responsibility to not perform any
+        //        realtime-unsafe work when the initialize function is called a second time if it
+        //        supports runtime preset loading.  `state::deserialize_object()` normally never
+        //        allocates, but if the plugin has persistent non-parameter data then its
+        //        `deserialize_fields()` implementation may still allocate.
+        let mut success = permit_alloc(|| unsafe {
+            state::deserialize_object::<P>(
+                state,
+                self.params.clone(),
+                state::make_params_getter(&self.param_by_hash, &self.param_id_to_hash),
+                &self.midi_learn,
+                self.current_buffer_config.load().as_ref(),
+                context,
+            )
+        });
+        if !success {
+            nih_debug_assert_failure!("Deserializing plugin state from a state object failed");
+            return false;
+        }
+
+        // If the plugin was already initialized then it needs to be reinitialized
+        if let Some(buffer_config) = buffer_config {
+            // NOTE: This needs to be dropped after the `plugin` lock to avoid deadlocks
+            let mut init_context = self.make_init_context();
+            let mut plugin = self.plugin.lock();
+
+            // See above
+            success = permit_alloc(|| {
+                plugin.initialize(&audio_io_layout, &buffer_config, &mut init_context)
+            });
+            if success {
+                process_wrapper(|| plugin.reset());
+            }
+        }
+
+        nih_debug_assert!(
+            success,
+            "Plugin returned false when reinitializing after loading state"
+        );
+
+        // Reinitialize the plugin after loading state so it can respond to the new parameter values
+        let task_posted = self.schedule_gui(Task::ParameterValuesChanged);
         nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
 
         // TODO: Right now there's no way to know if loading the state changed the GUI's size. We
@@ -1841,12 +2712,38 @@ impl<P: ClapPlugin> Wrapper<P> {
         let wrapper = &*((*plugin).plugin_data as *const Self);
 
         // We weren't allowed to query these in the constructor, so we need to do it now instead.
+        *wrapper.host_context_menu.borrow_mut() = query_host_extension::<clap_host_context_menu>(
+            &wrapper.host_callback,
+            CLAP_EXT_CONTEXT_MENU,
+        );
         *wrapper.host_gui.borrow_mut() =
             query_host_extension::<clap_host_gui>(&wrapper.host_callback, CLAP_EXT_GUI);
         *wrapper.host_latency.borrow_mut() =
             query_host_extension::<clap_host_latency>(&wrapper.host_callback, CLAP_EXT_LATENCY);
+        *wrapper.host_logger_handle.borrow_mut() =
+            query_host_extension::<clap_host_log>(&wrapper.host_callback, CLAP_EXT_LOG).map(
+                |host_log| {
+                    host_log::register(ClapHostLogger {
+                        host_callback: ClapPtr::new(&*wrapper.host_callback),
+                        host_log,
+                    })
+                },
+            );
+        let _log_scope = wrapper.log_scope(false);
+        *wrapper.host_note_name.borrow_mut() =
+            query_host_extension::<clap_host_note_name>(&wrapper.host_callback, CLAP_EXT_NOTE_NAME);
         *wrapper.host_params.borrow_mut() =
             query_host_extension::<clap_host_params>(&wrapper.host_callback, CLAP_EXT_PARAMS);
+        *wrapper.host_preset_load.borrow_mut() = query_host_extension::<clap_host_preset_load>(
+            &wrapper.host_callback,
+            CLAP_EXT_PRESET_LOAD,
+        );
+        *wrapper.host_track_info.borrow_mut() = query_host_extension::<clap_host_track_info>(
+            &wrapper.host_callback,
+            CLAP_EXT_TRACK_INFO,
+        );
+        *wrapper.host_tuning.borrow_mut() =
+            query_host_extension::<clap_host_tuning>(&wrapper.host_callback, CLAP_EXT_TUNING);
         *wrapper.host_voice_info.borrow_mut() = query_host_extension::<clap_host_voice_info>(
             &wrapper.host_callback,
             CLAP_EXT_VOICE_INFO,
@@ -1855,6 +2752,26 @@ impl<P: ClapPlugin> Wrapper<P> {
             &wrapper.host_callback,
             CLAP_EXT_THREAD_CHECK,
         );
+        *wrapper.host_thread_pool.borrow_mut() = query_host_extension::<clap_host_thread_pool>(
+            &wrapper.host_callback,
+            CLAP_EXT_THREAD_POOL,
+        );
+
+        // Tuning events use their own event space, which needs to be queried from the host
+        if wrapper.host_tuning.borrow().is_some() {
+            if let Some(host_event_registry) = query_host_extension::<clap_host_event_registry>(
+                &wrapper.host_callback,
+                CLAP_EXT_EVENT_REGISTRY,
+            ) {
+                let mut space_id = 0;
+                let success = clap_call! {
+                    host_event_registry=>query(&*wrapper.host_callback, CLAP_EXT_TUNING.as_ptr(), &mut space_id)
+                };
+                if success {
+                    wrapper.tuning_event_space_id.store(Some(space_id));
+                }
+            }
+        }
 
         true
     }
@@ -1875,6 +2792,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     ) -> bool {
         check_null_ptr!(false, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         let audio_io_layout = wrapper.current_audio_io_layout.load();
         let buffer_config = BufferConfig {
@@ -1899,7 +2817,7 @@ impl<P: ClapPlugin> Wrapper<P> {
             // This preallocates enough space so we can transform all of the host's raw channel
             // pointers into a set of `Buffer` objects for the plugin's main and auxiliary IO
             *wrapper.buffer_manager.borrow_mut() =
-                BufferManager::for_audio_io_layout(max_frames_count as usize, audio_io_layout);
+                PluginBufferManager::for_plugin::<P>(max_frames_count as usize, audio_io_layout);
 
             // Also store this for later, so we can reinitialize the plugin after restoring state
             wrapper.current_buffer_config.store(Some(buffer_config));
@@ -1913,6 +2831,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     unsafe extern "C" fn deactivate(plugin: *const clap_plugin) {
         check_null_ptr!((), plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         wrapper.plugin.lock().deactivate();
     }
@@ -1922,6 +2841,7 @@ impl<P: ClapPlugin> Wrapper<P> {
         // updating parameters from the GUI while the processing loop isn't running
         check_null_ptr!(false, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(true);
 
         // Always reset the processing status when the plugin gets activated or deactivated
         wrapper.last_process_status.store(ProcessStatus::Normal);
@@ -1937,6 +2857,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     unsafe extern "C" fn stop_processing(plugin: *const clap_plugin) {
         check_null_ptr!((), plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(true);
 
         wrapper.is_processing.store(false, Ordering::SeqCst);
     }
@@ -1944,6 +2865,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     unsafe extern "C" fn reset(plugin: *const clap_plugin) {
         check_null_ptr!((), plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(true);
 
         process_wrapper(|| wrapper.plugin.lock().reset());
     }
@@ -1954,6 +2876,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     ) -> clap_process_status {
         check_null_ptr!(CLAP_PROCESS_ERROR, plugin, (*plugin).plugin_data, process);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(true);
 
         // Panic on allocations if the `assert_process_allocs` feature has been enabled, and make
         // sure that FTZ is set up correctly
@@ -1964,12 +2887,6 @@ impl<P: ClapPlugin> Wrapper<P> {
             let process = &*process;
             let total_buffer_len = process.frames_count as usize;
 
-            let current_audio_io_layout = wrapper.current_audio_io_layout.load();
-            let has_main_input = current_audio_io_layout.main_input_channels.is_some();
-            let has_main_output = current_audio_io_layout.main_output_channels.is_some();
-            let aux_input_start_idx = if has_main_input { 1 } else { 0 };
-            let aux_output_start_idx = if has_main_output { 1 } else { 0 };
-
             // If `P::SAMPLE_ACCURATE_AUTOMATION` is set, then we'll split up the audio buffer into
             // chunks whenever a parameter change occurs
             let mut block_start = 0;
@@ -2008,6 +2925,13 @@ impl<P: ClapPlugin> Wrapper<P> {
                                                 .poly_mod_ids_by_hash
                                                 .contains_key(&next_event.param_id))
                                     }
+                                    // MIDI CCs mapped through MIDI learn also change parameters
+                                    (CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_MIDI)
+                                        if P::MIDI_INPUT >= MidiConfig::MidiCCs =>
+                                    {
+                                        let next_event = &*(next_event as *const clap_event_midi);
+                                        wrapper.midi_learn.may_handle_midi(&next_event.data)
+                                    }
                                     _ => false,
                                 }
                             } else {
@@ -2036,113 +2960,6 @@ impl<P: ClapPlugin> Wrapper<P> {
                 // we can start preparing audio processing
                 let block_len = block_end - block_start;
 
-                // The buffer manager preallocated buffer slices for all the IO and storage for any
-                // axuiliary inputs.
-                // TODO: The audio buffers have a latency field, should we use those?
-                // TODO: Like with VST3, should we expose some way to access or set the silence/constant
-                //       flags?
-                let mut buffer_manager = wrapper.buffer_manager.borrow_mut();
-                let buffers =
-                    buffer_manager.create_buffers(block_start, block_len, |buffer_source| {
-                        // Explicitly take plugins with no main output that does have auxiliary
-                        // outputs into account. Shouldn't happen, but if we just start copying
-                        // audio here then that would result in unsoundness.
-                        if process.audio_outputs_count > 0
-                            && !process.audio_outputs.is_null()
-                            && !(*process.audio_outputs).data32.is_null()
-                            && has_main_output
-                        {
-                            let audio_output = &*process.audio_outputs;
-                            let ptrs = NonNull::new(audio_output.data32 as *mut *mut f32).unwrap();
-                            let num_channels = audio_output.channel_count as usize;
-
-                            *buffer_source.main_output_channel_pointers =
-                                Some(ChannelPointers { ptrs, num_channels });
-                        }
-
-                        if process.audio_inputs_count > 0
-                            && !process.audio_inputs.is_null()
-                            && !(*process.audio_inputs).data32.is_null()
-                            && has_main_input
-                        {
-                            let audio_input = &*process.audio_inputs;
-                            let ptrs = NonNull::new(audio_input.data32 as *mut *mut f32).unwrap();
-                            let num_channels = audio_input.channel_count as usize;
-
-                            *buffer_source.main_input_channel_pointers =
-                                Some(ChannelPointers { ptrs, num_channels });
-                        }
-
-                        if !process.audio_inputs.is_null() {
-                            for (aux_input_no, aux_input_channel_pointers) in buffer_source
-                                .aux_input_channel_pointers
-                                .iter_mut()
-                                .enumerate()
-                            {
-                                let aux_input_idx = aux_input_no + aux_input_start_idx;
-                                if aux_input_idx > process.audio_inputs_count as usize {
-                                    break;
-                                }
-
-                                let audio_input = &*process.audio_inputs.add(aux_input_idx);
-                                match NonNull::new(audio_input.data32 as *mut *mut f32) {
-                                    Some(ptrs) => {
-                                        let num_channels = audio_input.channel_count as usize;
-
-                                        *aux_input_channel_pointers =
-                                            Some(ChannelPointers { ptrs, num_channels });
-                                    }
-                                    None => continue,
-                                }
-                            }
-                        }
-
-                        if !process.audio_outputs.is_null() {
-                            for (aux_output_no, aux_output_channel_pointers) in buffer_source
-                                .aux_output_channel_pointers
-                                .iter_mut()
-                                .enumerate()
-                            {
-                                let aux_output_idx = aux_output_no + aux_output_start_idx;
-                                if aux_output_idx > process.audio_outputs_count as usize {
-                                    break;
-                                }
-
-                                let audio_output = &*process.audio_outputs.add(aux_output_idx);
-                                match NonNull::new(audio_output.data32 as *mut *mut f32) {
-                                    Some(ptrs) => {
-                                        let num_channels = audio_output.channel_count as usize;
-
-                                        *aux_output_channel_pointers =
-                                            Some(ChannelPointers { ptrs, num_channels });
-                                    }
-                                    None => continue,
-                                }
-                            }
-                        }
-                    });
-
-                // If the host does not provide outputs or if it does not provide the required
-                // number of channels (should not happen, but Ableton Live does this for bypassed
-                // VST3 plugins) then we'll skip audio processing. In that case
-                // `buffer_manager.create_buffers` will have set one or more of the output buffers
-                // to empty slices since there is no storage to point them to. The auxiliary input
-                // buffers always point to valid storage.
-                let mut buffer_is_valid = true;
-                for output_buffer_slice in buffers.main_buffer.as_slice_immutable().iter().chain(
-                    buffers
-                        .aux_outputs
-                        .iter()
-                        .flat_map(|buffer| buffer.as_slice_immutable().iter()),
-                ) {
-                    if output_buffer_slice.is_empty() {
-                        buffer_is_valid = false;
-                        break;
-                    }
-                }
-
-                nih_debug_assert!(buffer_is_valid);
-
                 // Some of the fields are left empty because CLAP does not provide this information,
                 // but the methods on [`Transport`] can reconstruct these values from the other
                 // fields
@@ -2235,21 +3052,21 @@ impl<P: ClapPlugin> Wrapper<P> {
                     }
                 }
 
-                let result = if buffer_is_valid {
-                    let mut plugin = wrapper.plugin.lock();
-                    // SAFETY: Shortening these borrows is safe as even if the plugin overwrites the
-                    //         slices (which it cannot do without using unsafe code), then they
-                    //         would still be reset on the next iteration
-                    let mut aux = AuxiliaryBuffers {
-                        inputs: buffers.aux_inputs,
-                        outputs: buffers.aux_outputs,
-                    };
-                    let mut context = wrapper.make_process_context(transport);
-                    let result = plugin.process(buffers.main_buffer, &mut aux, &mut context);
-                    wrapper.last_process_status.store(result);
-                    result
-                } else {
-                    ProcessStatus::Normal
+                let result = match &mut *wrapper.buffer_manager.borrow_mut() {
+                    PluginBufferManager::F32(buffer_manager) => wrapper.process_block(
+                        buffer_manager,
+                        process,
+                        block_start,
+                        block_len,
+                        transport,
+                    ),
+                    PluginBufferManager::F64(buffer_manager) => wrapper.process_block(
+                        buffer_manager,
+                        process,
+                        block_start,
+                        block_len,
+                        transport,
+                    ),
                 };
 
                 let clap_result = match result {
@@ -2287,7 +3104,7 @@ impl<P: ClapPlugin> Wrapper<P> {
             //        doesn't do that
             let updated_state = permit_alloc(|| wrapper.updated_state_receiver.try_recv());
             if let Ok(mut state) = updated_state {
-                wrapper.set_state_inner(&mut state);
+                wrapper.set_state_inner(&mut state, StateContext::Preset);
 
                 // We'll pass the state object back to the GUI thread so deallocation can happen
                 // there without potentially blocking the audio thread
@@ -2309,6 +3126,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     ) -> *const c_void {
         check_null_ptr!(std::ptr::null(), plugin, (*plugin).plugin_data, id);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         let id = CStr::from_ptr(id);
 
@@ -2316,25 +3134,54 @@ impl<P: ClapPlugin> Wrapper<P> {
             &wrapper.clap_plugin_audio_ports_config as *const _ as *const c_void
         } else if id == CLAP_EXT_AUDIO_PORTS {
             &wrapper.clap_plugin_audio_ports as *const _ as *const c_void
+        } else if id == CLAP_EXT_AUDIO_PORTS_ACTIVATION
+            || id == CLAP_EXT_AUDIO_PORTS_ACTIVATION_COMPAT
+        {
+            &wrapper.clap_plugin_audio_ports_activation as *const _ as *const c_void
+        } else if (id == CLAP_EXT_AMBISONIC || id == CLAP_EXT_AMBISONIC_COMPAT)
+            && wrapper.uses_ambisonic_ports
+        {
+            // This and the surround extension are only exposed if the plugin uses them
+            &wrapper.clap_plugin_ambisonic as *const _ as *const c_void
+        } else if id == CLAP_EXT_CONTEXT_MENU {
+            &wrapper.clap_plugin_context_menu as *const _ as *const c_void
         } else if id == CLAP_EXT_GUI && wrapper.editor.borrow().is_some() {
             // Only report that we support this extension if the plugin has an editor
             &wrapper.clap_plugin_gui as *const _ as *const c_void
         } else if id == CLAP_EXT_LATENCY {
             &wrapper.clap_plugin_latency as *const _ as *const c_void
+        } else if id == CLAP_EXT_NOTE_NAME && P::MIDI_INPUT >= MidiConfig::Basic {
+            &wrapper.clap_plugin_note_name as *const _ as *const c_void
         } else if id == CLAP_EXT_NOTE_PORTS
             && (P::MIDI_INPUT >= MidiConfig::Basic || P::MIDI_OUTPUT >= MidiConfig::Basic)
         {
             &wrapper.clap_plugin_note_ports as *const _ as *const c_void
         } else if id == CLAP_EXT_PARAMS {
             &wrapper.clap_plugin_params as *const _ as *const c_void
+        } else if id == CLAP_EXT_PRESET_LOAD && !P::CLAP_PRESET_FILE_EXTENSIONS.is_empty() {
+            &wrapper.clap_plugin_preset_load as *const _ as *const c_void
         } else if id == CLAP_EXT_REMOTE_CONTROLS {
             &wrapper.clap_plugin_remote_controls as *const _ as *const c_void
         } else if id == CLAP_EXT_RENDER {
             &wrapper.clap_plugin_render as *const _ as *const c_void
         } else if id == CLAP_EXT_STATE {
             &wrapper.clap_plugin_state as *const _ as *const c_void
+        } else if id == CLAP_EXT_STATE_CONTEXT {
+            &wrapper.clap_plugin_state_context as *const _ as *const c_void
+        } else if (id == CLAP_EXT_SURROUND || id == CLAP_EXT_SURROUND_COMPAT)
+            && wrapper.uses_surround_ports
+        {
+            &wrapper.clap_plugin_surround as *const _ as *const c_void
         } else if id == CLAP_EXT_TAIL {
             &wrapper.clap_plugin_tail as *const _ as *const c_void
+        } else if id == CLAP_EXT_PARAM_INDICATION && wrapper.editor.borrow().is_some() {
+            &wrapper.clap_plugin_param_indication as *const _ as *const c_void
+        } else if id == CLAP_EXT_THREAD_POOL {
+            &wrapper.clap_plugin_thread_pool as *const _ as *const c_void
+        } else if id == CLAP_EXT_TRACK_INFO {
+            &wrapper.clap_plugin_track_info as *const _ as *const c_void
+        } else if id == CLAP_EXT_TUNING && P::MIDI_INPUT >= MidiConfig::Basic {
+            &wrapper.clap_plugin_tuning as *const _ as *const c_void
         } else if id == CLAP_EXT_VOICE_INFO && P::CLAP_POLY_MODULATION_CONFIG.is_some() {
             &wrapper.clap_plugin_voice_info as *const _ as *const c_void
         } else {
@@ -2346,12 +3193,91 @@ impl<P: ClapPlugin> Wrapper<P> {
     unsafe extern "C" fn on_main_thread(plugin: *const clap_plugin) {
         check_null_ptr!((), plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         // [Self::schedule_gui] posts a task to the queue and asks the host to call this function
         // on the main thread, so once that's done we can just handle all requests here
         while let Some(task) = wrapper.tasks.pop() {
             wrapper.execute(task, true);
         }
+
+        // Messages logged from the audio thread are also sent to the host's log from here
+        if let Some(host_logger_handle) = wrapper.host_logger_handle.borrow().as_ref() {
+            host_logger_handle.flush_deferred_messages();
+        }
+    }
+
+    unsafe extern "C" fn ext_ambisonic_is_config_supported(
+        _plugin: *const clap_plugin,
+        config: *const clap_ambisonic_config,
+    ) -> bool {
+        check_null_ptr!(false, config);
+
+        // `ChannelLayout::Ambisonic` is always in the AmbiX format
+        (*config).ordering == CLAP_AMBISONIC_ORDERING_ACN
+            && (*config).normalization == CLAP_AMBISONIC_NORMALIZATION_SN3D
+    }
+
+    unsafe extern "C" fn ext_ambisonic_get_config(
+        plugin: *const clap_plugin,
+        is_input: bool,
+        port_index: u32,
+        config: *mut clap_ambisonic_config,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, config);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        match port_channel_layout(
+            &wrapper.current_audio_io_layout.load(),
+            is_input,
+            port_index,
+        ) {
+            Some(ChannelLayout::Ambisonic { .. }) => {
+                *config = clap_ambisonic_config {
+                    ordering: CLAP_AMBISONIC_ORDERING_ACN,
+                    normalization: CLAP_AMBISONIC_NORMALIZATION_SN3D,
+                };
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    unsafe extern "C" fn ext_audio_ports_activation_can_activate_while_processing(
+        _plugin: *const clap_plugin,
+    ) -> bool {
+        // The buffer manager reads the activation state at the start of every block, but the plugin
+        // may have already done its setup based on `InitContext::aux_input_active()`
+        false
+    }
+
+    unsafe extern "C" fn ext_audio_ports_activation_set_active(
+        plugin: *const clap_plugin,
+        is_input: bool,
+        port_index: u32,
+        is_active: bool,
+        _sample_size: u32,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        let success = wrapper.audio_ports_activation.set_active(
+            &wrapper.current_audio_io_layout.load(),
+            is_input,
+            port_index as usize,
+            is_active,
+        );
+        nih_debug_assert!(
+            success,
+            "Host tried to change the activation state of out of bounds audio port {} (input: {})",
+            port_index,
+            is_input
+        );
+
+        success
     }
 
     unsafe extern "C" fn ext_audio_ports_config_count(plugin: *const clap_plugin) -> u32 {
@@ -2376,16 +3302,14 @@ impl<P: ClapPlugin> Wrapper<P> {
                 let main_input_channels = audio_io_layout.main_input_channels.map(NonZeroU32::get);
                 let main_output_channels =
                     audio_io_layout.main_output_channels.map(NonZeroU32::get);
-                let input_port_type = match main_input_channels {
-                    Some(1) => CLAP_PORT_MONO.as_ptr(),
-                    Some(2) => CLAP_PORT_STEREO.as_ptr(),
-                    _ => std::ptr::null(),
-                };
-                let output_port_type = match main_output_channels {
-                    Some(1) => CLAP_PORT_MONO.as_ptr(),
-                    Some(2) => CLAP_PORT_STEREO.as_ptr(),
-                    _ => std::ptr::null(),
-                };
+                let input_port_type = port_type(
+                    main_input_channels.unwrap_or_default(),
+                    audio_io_layout.main_input_channel_layout(),
+                );
+                let output_port_type = port_type(
+                    main_output_channels.unwrap_or_default(),
+                    audio_io_layout.main_output_channel_layout(),
+                );
 
                 *config = std::mem::zeroed();
 
@@ -2424,11 +3348,14 @@ impl<P: ClapPlugin> Wrapper<P> {
     ) -> bool {
         check_null_ptr!(false, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         // We use the vector indices for the config ID
         match P::AUDIO_IO_LAYOUTS.get(config_id as usize) {
             Some(audio_io_layout) => {
                 wrapper.current_audio_io_layout.store(*audio_io_layout);
+                // The port indices refer to the old layout's ports
+                wrapper.audio_ports_activation.reset();
 
                 true
             }
@@ -2446,6 +3373,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     unsafe extern "C" fn ext_audio_ports_count(plugin: *const clap_plugin, is_input: bool) -> u32 {
         check_null_ptr!(0, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         let audio_io_layout = wrapper.current_audio_io_layout.load();
         if is_input {
@@ -2477,6 +3405,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     ) -> bool {
         check_null_ptr!(false, plugin, (*plugin).plugin_data, info);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         let num_input_ports = Self::ext_audio_ports_count(plugin, true);
         let num_output_ports = Self::ext_audio_ports_count(plugin, false);
@@ -2531,11 +3460,10 @@ impl<P: ClapPlugin> Wrapper<P> {
             (n, false) => current_audio_io_layout.aux_output_ports[n as usize].get(),
         };
 
-        let port_type = match channel_count {
-            1 => CLAP_PORT_MONO.as_ptr(),
-            2 => CLAP_PORT_STEREO.as_ptr(),
-            _ => std::ptr::null(),
-        };
+        let port_type = port_type(
+            channel_count,
+            port_channel_layout(&current_audio_io_layout, is_input, index),
+        );
 
         *info = std::mem::zeroed();
 
@@ -2568,6 +3496,11 @@ impl<P: ClapPlugin> Wrapper<P> {
         } else {
             0
         };
+        // Plugins processing audio in double precision can still handle single precision buffers,
+        // those will be converted by the buffer manager
+        if P::PROCESS_F64 {
+            info.flags |= CLAP_AUDIO_PORT_SUPPORTS_64BITS | CLAP_AUDIO_PORT_PREFERS_64BITS;
+        }
         info.channel_count = channel_count;
         info.port_type = port_type;
         info.in_place_pair = pair_stable_id;
@@ -2575,6 +3508,73 @@ impl<P: ClapPlugin> Wrapper<P> {
         true
     }
 
+    unsafe extern "C" fn ext_context_menu_populate(
+        plugin: *const clap_plugin,
+        target: *const clap_context_menu_target,
+        builder: *const clap_context_menu_builder,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, target, builder);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        // The plugin only adds items to the parameter context menu it requested itself. That menu
+        // is only populated once, so the items are discarded when the host populates any other
+        // menu afterwards.
+        let mut param_context_menu = wrapper.param_context_menu.borrow_mut();
+        match &mut *param_context_menu {
+            Some(menu)
+                if !menu.is_populated
+                    && (*target).kind == CLAP_CONTEXT_MENU_TARGET_KIND_PARAM
+                    && (*target).id == menu.param_hash =>
+            {
+                menu.is_populated = true;
+
+                let mut next_action_id = 0;
+                populate_context_menu(builder, &menu.items, &mut next_action_id)
+            }
+            _ => {
+                *param_context_menu = None;
+                true
+            }
+        }
+    }
+
+    unsafe extern "C" fn ext_context_menu_perform(
+        plugin: *const clap_plugin,
+        target: *const clap_context_menu_target,
+        action_id: clap_id,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, target);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        // The items can only be performed once. The action may also show another context menu, so
+        // the borrow needs to end before calling it.
+        let action = match wrapper.param_context_menu.borrow_mut().take() {
+            Some(menu)
+                if menu.is_populated
+                    && (*target).kind == CLAP_CONTEXT_MENU_TARGET_KIND_PARAM
+                    && (*target).id == menu.param_hash =>
+            {
+                context_menu_actions(&menu.items)
+                    .get(action_id as usize)
+                    .cloned()
+            }
+            _ => None,
+        };
+
+        match action {
+            Some(action) => {
+                action();
+                true
+            }
+            None => {
+                nih_debug_assert_failure!("Unknown context menu action ID {}", action_id);
+                false
+            }
+        }
+    }
+
     unsafe extern "C" fn ext_gui_is_api_supported(
         _plugin: *const clap_plugin,
         api: *const c_char,
@@ -2642,6 +3642,7 @@ impl<P: ClapPlugin> Wrapper<P> {
         // something here.
         check_null_ptr!(false, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         let editor_handle = wrapper.editor_handle.lock();
         if editor_handle.is_none() {
@@ -2655,6 +3656,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     unsafe extern "C" fn ext_gui_destroy(plugin: *const clap_plugin) {
         check_null_ptr!((), plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         let mut editor_handle = wrapper.editor_handle.lock();
         if editor_handle.is_some() {
@@ -2667,6 +3669,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     unsafe extern "C" fn ext_gui_set_scale(plugin: *const clap_plugin, scale: f64) -> bool {
         check_null_ptr!(false, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         // On macOS scaling is done by the OS, and all window sizes are in logical pixels
         if cfg!(target_os = "macos") {
@@ -2698,6 +3701,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     ) -> bool {
         check_null_ptr!(false, plugin, (*plugin).plugin_data, width, height);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         // For macOS the scaling factor is always 1
         let (unscaled_width, unscaled_height) =
@@ -2711,26 +3715,90 @@ impl<P: ClapPlugin> Wrapper<P> {
         true
     }
 
-    unsafe extern "C" fn ext_gui_can_resize(_plugin: *const clap_plugin) -> bool {
-        // TODO: Implement Host->Plugin GUI resizing
-        false
+    unsafe extern "C" fn ext_gui_can_resize(plugin: *const clap_plugin) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        wrapper
+            .editor
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .lock()
+            .can_resize()
     }
 
     unsafe extern "C" fn ext_gui_get_resize_hints(
-        _plugin: *const clap_plugin,
-        _hints: *mut clap_gui_resize_hints,
+        plugin: *const clap_plugin,
+        hints: *mut clap_gui_resize_hints,
     ) -> bool {
-        // TODO: Implement Host->Plugin GUI resizing
-        false
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, hints);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        let editor = wrapper.editor.borrow();
+        let editor = editor.as_ref().unwrap().lock();
+        if !editor.can_resize() {
+            return false;
+        }
+
+        // The aspect ratio is unitless, so it doesn't need to be scaled
+        let constraints = editor.size_constraints();
+        let (min_width, min_height) = constraints.min_size;
+        let hints = &mut *hints;
+        hints.can_resize_horizontally = constraints
+            .max_size
+            .map(|(max_width, _)| max_width > min_width)
+            .unwrap_or(true);
+        hints.can_resize_vertically = constraints
+            .max_size
+            .map(|(_, max_height)| max_height > min_height)
+            .unwrap_or(true);
+        match constraints.aspect_ratio {
+            Some((aspect_ratio_width, aspect_ratio_height)) => {
+                hints.preserve_aspect_ratio = true;
+                hints.aspect_ratio_width = aspect_ratio_width;
+                hints.aspect_ratio_height = aspect_ratio_height;
+            }
+            None => {
+                hints.preserve_aspect_ratio = false;
+                hints.aspect_ratio_width = 0;
+                hints.aspect_ratio_height = 0;
+            }
+        }
+
+        true
     }
 
     unsafe extern "C" fn ext_gui_adjust_size(
-        _plugin: *const clap_plugin,
-        _width: *mut u32,
-        _height: *mut u32,
+        plugin: *const clap_plugin,
+        width: *mut u32,
+        height: *mut u32,
     ) -> bool {
-        // TODO: Implement Host->Plugin GUI resizing
-        false
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, width, height);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        let editor = wrapper.editor.borrow();
+        let editor = editor.as_ref().unwrap().lock();
+        if !editor.can_resize() {
+            return false;
+        }
+
+        // The host's sizes are in physical pixels, while the editor's constraints are in logical
+        // pixels
+        let scaling_factor = wrapper.editor_scaling_factor.load(Ordering::Relaxed);
+        let (unscaled_width, unscaled_height) = editor.size_constraints().constrain(
+            (*width as f32 / scaling_factor).round() as u32,
+            (*height as f32 / scaling_factor).round() as u32,
+        );
+        (*width, *height) = (
+            (unscaled_width as f32 * scaling_factor).round() as u32,
+            (unscaled_height as f32 * scaling_factor).round() as u32,
+        );
+
+        true
     }
 
     unsafe extern "C" fn ext_gui_set_size(
@@ -2738,20 +3806,46 @@ impl<P: ClapPlugin> Wrapper<P> {
         width: u32,
         height: u32,
     ) -> bool {
-        // TODO: Implement Host->Plugin GUI resizing
         // TODO: The host will also call this if an asynchronous (on Linux) resize request fails
         check_null_ptr!(false, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
-        let (unscaled_width, unscaled_height) =
-            wrapper.editor.borrow().as_ref().unwrap().lock().size();
+        let editor = wrapper.editor.borrow();
+        let editor = editor.as_ref().unwrap().lock();
+        let (unscaled_width, unscaled_height) = editor.size();
         let scaling_factor = wrapper.editor_scaling_factor.load(Ordering::Relaxed);
         let (editor_width, editor_height) = (
             (unscaled_width as f32 * scaling_factor).round() as u32,
             (unscaled_height as f32 * scaling_factor).round() as u32,
         );
+        if width == editor_width && height == editor_height {
+            return true;
+        }
+
+        if editor.can_resize() {
+            // The host should have called `adjust_size()` first, so sizes that don't satisfy the
+            // editor's constraints are rejected without changing the editor's size
+            let (new_width, new_height) = editor.size_constraints().constrain(
+                (width as f32 / scaling_factor).round() as u32,
+                (height as f32 / scaling_factor).round() as u32,
+            );
+            if (new_width as f32 * scaling_factor).round() as u32 != width
+                || (new_height as f32 * scaling_factor).round() as u32 != height
+            {
+                nih_debug_assert_failure!(
+                    "The host tried to set the editor's size to {}x{}, which does not satisfy the \
+                     editor's size constraints",
+                    width,
+                    height
+                );
+                return false;
+            }
 
-        width == editor_width && height == editor_height
+            editor.set_size(new_width, new_height)
+        } else {
+            false
+        }
     }
 
     unsafe extern "C" fn ext_gui_set_parent(
@@ -2761,6 +3855,7 @@ impl<P: ClapPlugin> Wrapper<P> {
         check_null_ptr!(false, plugin, (*plugin).plugin_data, window);
         // For this function we need the underlying Arc so we can pass it to the editor
         let wrapper = Arc::from_raw((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         let window = &*window;
 
@@ -2832,10 +3927,45 @@ impl<P: ClapPlugin> Wrapper<P> {
     unsafe extern "C" fn ext_latency_get(plugin: *const clap_plugin) -> u32 {
         check_null_ptr!(0, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         wrapper.current_latency.load(Ordering::SeqCst)
     }
 
+    unsafe extern "C" fn ext_note_name_count(plugin: *const clap_plugin) -> u32 {
+        check_null_ptr!(0, plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        wrapper.note_names.borrow().len() as u32
+    }
+
+    unsafe extern "C" fn ext_note_name_get(
+        plugin: *const clap_plugin,
+        index: u32,
+        note_name: *mut clap_note_name,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, note_name);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        match wrapper.note_names.borrow().get(index as usize) {
+            Some(name) => {
+                *note_name = std::mem::zeroed();
+
+                let note_name = &mut *note_name;
+                strlcpy(&mut note_name.name, &name.name);
+                // There is only a single note port
+                note_name.port = 0;
+                note_name.key = name.note as i16;
+                note_name.channel = name.channel.map(|channel| channel as i16).unwrap_or(-1);
+
+                true
+            }
+            None => false,
+        }
+    }
+
     unsafe extern "C" fn ext_note_ports_count(_plugin: *const clap_plugin, is_input: bool) -> u32 {
         match is_input {
             true if P::MIDI_INPUT >= MidiConfig::Basic => 1,
@@ -2857,8 +3987,11 @@ impl<P: ClapPlugin> Wrapper<P> {
                 let info = &mut *info;
                 info.id = 0;
                 // NOTE: REAPER won't send us SysEx if we don't support the MIDI dialect
-                // TODO: Implement MPE (would just be a toggle for the plugin to expose it) and MIDI2
+                // TODO: Implement MIDI2
                 info.supported_dialects = CLAP_NOTE_DIALECT_CLAP | CLAP_NOTE_DIALECT_MIDI;
+                if P::MPE_INPUT != MpeConfig::None && P::MIDI_INPUT >= MidiConfig::MidiCCs {
+                    info.supported_dialects |= CLAP_NOTE_DIALECT_MIDI_MPE;
+                }
                 info.preferred_dialect = CLAP_NOTE_DIALECT_CLAP;
                 strlcpy(&mut info.name, "Note Input");
 
@@ -2882,9 +4015,71 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
+    unsafe extern "C" fn ext_param_indication_set_mapping(
+        plugin: *const clap_plugin,
+        param_id: clap_id,
+        has_mapping: bool,
+        color: *const clap_color,
+        label: *const c_char,
+        description: *const c_char,
+    ) {
+        check_null_ptr!((), plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        let optional_string = |string: *const c_char| {
+            if string.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(string).to_string_lossy().into_owned())
+            }
+        };
+        let mapping = if has_mapping {
+            Some(ParamMapping {
+                color: host_color(color),
+                label: optional_string(label),
+                description: optional_string(description),
+            })
+        } else {
+            None
+        };
+
+        wrapper.update_param_indication(param_id, |indication| indication.mapping = mapping);
+    }
+
+    unsafe extern "C" fn ext_param_indication_set_automation(
+        plugin: *const clap_plugin,
+        param_id: clap_id,
+        automation_state: u32,
+        color: *const clap_color,
+    ) {
+        check_null_ptr!((), plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        let automation = match automation_state {
+            CLAP_PARAM_INDICATION_AUTOMATION_NONE => AutomationState::None,
+            CLAP_PARAM_INDICATION_AUTOMATION_PRESENT => AutomationState::Present,
+            CLAP_PARAM_INDICATION_AUTOMATION_PLAYING => AutomationState::Playing,
+            CLAP_PARAM_INDICATION_AUTOMATION_RECORDING => AutomationState::Recording,
+            CLAP_PARAM_INDICATION_AUTOMATION_OVERRIDING => AutomationState::Overriding,
+            n => {
+                nih_debug_assert_failure!("Unknown automation state {}", n);
+                return;
+            }
+        };
+        let automation_color = host_color(color);
+
+        wrapper.update_param_indication(param_id, |indication| {
+            indication.automation = automation;
+            indication.automation_color = automation_color;
+        });
+    }
+
     unsafe extern "C" fn ext_params_count(plugin: *const clap_plugin) -> u32 {
         check_null_ptr!(0, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         wrapper.param_hashes.len() as u32
     }
@@ -2896,6 +4091,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     ) -> bool {
         check_null_ptr!(false, plugin, (*plugin).plugin_data, param_info);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         if param_index > Self::ext_params_count(plugin) {
             return false;
@@ -2910,6 +4106,7 @@ impl<P: ClapPlugin> Wrapper<P> {
         let automatable = !flags.contains(ParamFlags::NON_AUTOMATABLE);
         let hidden = flags.contains(ParamFlags::HIDDEN);
         let is_bypass = flags.contains(ParamFlags::BYPASS);
+        let read_only = flags.contains(ParamFlags::READ_ONLY);
 
         *param_info = std::mem::zeroed();
 
@@ -2917,17 +4114,30 @@ impl<P: ClapPlugin> Wrapper<P> {
         //       hashmap lookup, but for now we'll stay consistent with the VST3 implementation.
         let param_info = &mut *param_info;
         param_info.id = *param_hash;
-        // TODO: Somehow expose per note/channel/port modulation
         param_info.flags = 0;
-        if automatable && !hidden {
+        if automatable && !hidden && !read_only {
             param_info.flags |= CLAP_PARAM_IS_AUTOMATABLE | CLAP_PARAM_IS_MODULATABLE;
+            // Key, channel, and port modulation are sent to the plugin using the parameter's poly
+            // modulation ID, so they can only be enabled together with polyphonic modulation
             if wrapper.poly_mod_ids_by_hash.contains_key(param_hash) {
                 param_info.flags |= CLAP_PARAM_IS_MODULATABLE_PER_NOTE_ID;
+                if flags.contains(ParamFlags::MODULATABLE_PER_KEY) {
+                    param_info.flags |= CLAP_PARAM_IS_MODULATABLE_PER_KEY;
+                }
+                if flags.contains(ParamFlags::MODULATABLE_PER_CHANNEL) {
+                    param_info.flags |= CLAP_PARAM_IS_MODULATABLE_PER_CHANNEL;
+                }
+                if flags.contains(ParamFlags::MODULATABLE_PER_PORT) {
+                    param_info.flags |= CLAP_PARAM_IS_MODULATABLE_PER_PORT;
+                }
             }
         }
         if hidden {
             param_info.flags |= CLAP_PARAM_IS_HIDDEN | CLAP_PARAM_IS_READONLY;
         }
+        if read_only {
+            param_info.flags |= CLAP_PARAM_IS_READONLY;
+        }
         if is_bypass {
             param_info.flags |= CLAP_PARAM_IS_BYPASS
         }
@@ -2957,6 +4167,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     ) -> bool {
         check_null_ptr!(false, plugin, (*plugin).plugin_data, value);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         match wrapper.param_by_hash.get(&param_id) {
             Some(param_ptr) => {
@@ -2978,6 +4189,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     ) -> bool {
         check_null_ptr!(false, plugin, (*plugin).plugin_data, display);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         let dest = std::slice::from_raw_parts_mut(display, size as usize);
 
@@ -3006,6 +4218,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     ) -> bool {
         check_null_ptr!(false, plugin, (*plugin).plugin_data, display, value);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         let display = match CStr::from_ptr(display).to_str() {
             Ok(s) => s,
@@ -3033,6 +4246,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     ) {
         check_null_ptr!((), plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(!wrapper.is_main_thread());
 
         if !in_.is_null() {
             wrapper.handle_in_events(&*in_, 0, 0);
@@ -3043,9 +4257,69 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
+    unsafe extern "C" fn ext_preset_load_from_location(
+        plugin: *const clap_plugin,
+        location_kind: clap_preset_discovery_location_kind,
+        location: *const c_char,
+        load_key: *const c_char,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, location);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        // The preset discovery factory only declares files and directories, and every preset file
+        // contains a single preset so the load key is not used
+        if location_kind != CLAP_PRESET_DISCOVERY_LOCATION_FILE {
+            nih_debug_assert_failure!("Unexpected preset location kind {}", location_kind);
+            return false;
+        }
+        let Ok(path) = CStr::from_ptr(location).to_str() else {
+            nih_debug_assert_failure!("The preset location is not valid UTF-8");
+            return false;
+        };
+
+        match read_preset_file(Path::new(path)) {
+            Ok(mut state) => {
+                // This also lets the plugin migrate old presets through `Plugin::filter_state()`
+                let success = wrapper.set_state_inner(&mut state, StateContext::Preset);
+                if success {
+                    nih_trace!("Loaded preset '{}'", path);
+
+                    if let Some(host_preset_load) = &*wrapper.host_preset_load.borrow() {
+                        unsafe_clap_call! {
+                            host_preset_load=>loaded(&*wrapper.host_callback, location_kind, location, load_key)
+                        };
+                    }
+                }
+
+                success
+            }
+            Err((os_error, message)) => {
+                nih_error!("{}", message);
+
+                if let Some(host_preset_load) = &*wrapper.host_preset_load.borrow() {
+                    let message = CString::new(message).unwrap_or_default();
+                    unsafe_clap_call! {
+                        host_preset_load=>on_error(
+                            &*wrapper.host_callback,
+                            location_kind,
+                            location,
+                            load_key,
+                            os_error,
+                            message.as_ptr(),
+                        )
+                    };
+                }
+
+                false
+            }
+        }
+    }
+
     unsafe extern "C" fn ext_remote_controls_count(plugin: *const clap_plugin) -> u32 {
         check_null_ptr!(0, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         wrapper.remote_control_pages.len() as u32
     }
@@ -3057,6 +4331,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     ) -> bool {
         check_null_ptr!(false, plugin, (*plugin).plugin_data, page);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         nih_debug_assert!(page_index as usize <= wrapper.remote_control_pages.len());
         match wrapper.remote_control_pages.get(page_index as usize) {
@@ -3080,6 +4355,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     ) -> bool {
         check_null_ptr!(false, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         let mode = match mode {
             CLAP_RENDER_REALTIME => ProcessMode::Realtime,
@@ -3101,30 +4377,80 @@ impl<P: ClapPlugin> Wrapper<P> {
     ) -> bool {
         check_null_ptr!(false, plugin, (*plugin).plugin_data, stream);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        wrapper.save_state(&*stream, StateContext::Project)
+    }
+
+    unsafe extern "C" fn ext_state_load(
+        plugin: *const clap_plugin,
+        stream: *const clap_istream,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, stream);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        wrapper.load_state(&*stream, StateContext::Project)
+    }
+
+    unsafe extern "C" fn ext_state_context_save(
+        plugin: *const clap_plugin,
+        stream: *const clap_ostream,
+        context_type: clap_plugin_state_context_type,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, stream);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        match state_context_from_clap(context_type) {
+            Some(context) => wrapper.save_state(&*stream, context),
+            None => false,
+        }
+    }
+
+    unsafe extern "C" fn ext_state_context_load(
+        plugin: *const clap_plugin,
+        stream: *const clap_istream,
+        context_type: clap_plugin_state_context_type,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, stream);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        match state_context_from_clap(context_type) {
+            Some(context) => wrapper.load_state(&*stream, context),
+            None => false,
+        }
+    }
 
+    /// Serialize the plugin's state and write it to a stream. Shared between the `state` and
+    /// `state-context` extensions.
+    unsafe fn save_state(&self, stream: &clap_ostream, context: StateContext) -> bool {
         let serialized = state::serialize_json::<P>(
-            wrapper.params.clone(),
-            state::make_params_iter(&wrapper.param_by_hash, &wrapper.param_id_to_hash),
+            self.params.clone(),
+            state::make_params_iter(&self.param_by_hash, &self.param_id_to_hash),
+            &self.midi_learn,
+            context,
         );
         match serialized {
             Ok(serialized) => {
                 // CLAP does not provide a way to tell how much data there is left in a stream, so
                 // we need to prepend it to our actual state data.
                 let length_bytes = (serialized.len() as u64).to_le_bytes();
-                if !write_stream(&*stream, &length_bytes) {
+                if !write_stream(stream, &length_bytes) {
                     nih_debug_assert_failure!(
                         "Error or end of stream while writing the state length to the stream."
                     );
                     return false;
                 }
-                if !write_stream(&*stream, &serialized) {
+                if !write_stream(stream, &serialized) {
                     nih_debug_assert_failure!(
                         "Error or end of stream while writing the state buffer to the stream."
                     );
                     return false;
                 }
 
-                nih_trace!("Saved state ({} bytes)", serialized.len());
+                nih_trace!("Saved state for {:?} ({} bytes)", context, serialized.len());
 
                 true
             }
@@ -3135,17 +4461,13 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
-    unsafe extern "C" fn ext_state_load(
-        plugin: *const clap_plugin,
-        stream: *const clap_istream,
-    ) -> bool {
-        check_null_ptr!(false, plugin, (*plugin).plugin_data, stream);
-        let wrapper = &*((*plugin).plugin_data as *const Self);
-
+    /// Read the plugin's state from a stream and restore it. Shared between the `state` and
+    /// `state-context` extensions.
+    unsafe fn load_state(&self, stream: &clap_istream, context: StateContext) -> bool {
         // CLAP does not have a way to tell how much data there is left in a stream, so we've
         // prepended the size in front of our JSON state
         let mut length_bytes = [0u8; 8];
-        if !read_stream(&*stream, length_bytes.as_mut_slice()) {
+        if !read_stream(stream, length_bytes.as_mut_slice()) {
             nih_debug_assert_failure!(
                 "Error or end of stream while reading the state length from the stream."
             );
@@ -3154,7 +4476,7 @@ impl<P: ClapPlugin> Wrapper<P> {
         let length = u64::from_le_bytes(length_bytes);
 
         let mut read_buffer: Vec<u8> = Vec::with_capacity(length as usize);
-        if !read_stream(&*stream, read_buffer.spare_capacity_mut()) {
+        if !read_stream(stream, read_buffer.spare_capacity_mut()) {
             nih_debug_assert_failure!(
                 "Error or end of stream while reading the state buffer from the stream."
             );
@@ -3164,9 +4486,13 @@ impl<P: ClapPlugin> Wrapper<P> {
 
         match state::deserialize_json(&read_buffer) {
             Some(mut state) => {
-                let success = wrapper.set_state_inner(&mut state);
+                let success = self.set_state_inner(&mut state, context);
                 if success {
-                    nih_trace!("Loaded state ({} bytes)", read_buffer.len());
+                    nih_trace!(
+                        "Loaded state for {:?} ({} bytes)",
+                        context,
+                        read_buffer.len()
+                    );
                 }
 
                 success
@@ -3175,9 +4501,58 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
+    unsafe extern "C" fn ext_surround_is_channel_mask_supported(
+        _plugin: *const clap_plugin,
+        channel_mask: u64,
+    ) -> bool {
+        // The plugin's channel layouts are fixed per audio IO layout, so this only checks whether
+        // any of the plugin's surround ports use this mask
+        uses_channel_layout::<P>(|channel_layout| {
+            surround_channel_map(channel_layout).is_some_and(|channel_map| {
+                channel_map
+                    .iter()
+                    .fold(0u64, |mask, &position| mask | (1 << position))
+                    == channel_mask
+            })
+        })
+    }
+
+    unsafe extern "C" fn ext_surround_get_channel_map(
+        plugin: *const clap_plugin,
+        is_input: bool,
+        port_index: u32,
+        channel_map: *mut u8,
+        channel_map_capacity: u32,
+    ) -> u32 {
+        check_null_ptr!(0, plugin, (*plugin).plugin_data, channel_map);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        let Some(port_channel_map) = port_channel_layout(
+            &wrapper.current_audio_io_layout.load(),
+            is_input,
+            port_index,
+        )
+        .and_then(surround_channel_map) else {
+            nih_debug_assert_failure!(
+                "Host tried to query the channel map for non-surround audio port {} (input: {})",
+                port_index,
+                is_input
+            );
+            return 0;
+        };
+
+        let num_positions = port_channel_map.len().min(channel_map_capacity as usize);
+        std::slice::from_raw_parts_mut(channel_map, num_positions)
+            .copy_from_slice(&port_channel_map[..num_positions]);
+
+        num_positions as u32
+    }
+
     unsafe extern "C" fn ext_tail_get(plugin: *const clap_plugin) -> u32 {
         check_null_ptr!(0, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(!wrapper.is_main_thread());
 
         match wrapper.last_process_status.load() {
             ProcessStatus::Tail(samples) => samples,
@@ -3186,12 +4561,54 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
+    unsafe extern "C" fn ext_thread_pool_exec(plugin: *const clap_plugin, task_index: u32) {
+        check_null_ptr!((), plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(true);
+
+        // The host's worker threads may not have the same floating point settings as the audio
+        // thread, so this is treated as part of the process call
+        process_wrapper(|| match *wrapper.thread_pool_task.borrow() {
+            Some(task) => task(task_index),
+            None => nih_debug_assert_failure!(
+                "The host called 'clap_plugin_thread_pool::exec()' outside of 'request_exec()'"
+            ),
+        });
+    }
+
+    unsafe extern "C" fn ext_track_info_changed(plugin: *const clap_plugin) {
+        check_null_ptr!((), plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        // The plugin can query the track information itself through its contexts, so this only
+        // needs to be forwarded to the editor if it's currently open
+        if wrapper.editor_handle.lock().is_some() {
+            if let Some(editor) = wrapper.editor.borrow().as_ref() {
+                if let Some(track_info) = wrapper.track_info() {
+                    editor.lock().track_info_changed(&track_info);
+                }
+            }
+        }
+    }
+
+    unsafe extern "C" fn ext_tuning_changed(plugin: *const clap_plugin) {
+        check_null_ptr!((), plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
+
+        // This is called from the main thread, so the plugin is notified from the audio thread
+        // instead to avoid contending for the plugin's lock
+        wrapper.tuning_changed.store(true, Ordering::SeqCst);
+    }
+
     unsafe extern "C" fn ext_voice_info_get(
         plugin: *const clap_plugin,
         info: *mut clap_voice_info,
     ) -> bool {
         check_null_ptr!(false, plugin, (*plugin).plugin_data, info);
         let wrapper = &*((*plugin).plugin_data as *const Self);
+        let _log_scope = wrapper.log_scope(false);
 
         match P::CLAP_POLY_MODULATION_CONFIG {
             Some(config) => {
@@ -3212,6 +4629,102 @@ impl<P: ClapPlugin> Wrapper<P> {
     }
 }
 
+/// Convert a CLAP state context type to a [`StateContext`]. Returns `None` and logs a debug
+/// assertion failure if the context type is unknown.
+fn state_context_from_clap(context_type: clap_plugin_state_context_type) -> Option<StateContext> {
+    match context_type {
+        CLAP_STATE_CONTEXT_FOR_PRESET => Some(StateContext::Preset),
+        CLAP_STATE_CONTEXT_FOR_DUPLICATE => Some(StateContext::Duplicate),
+        CLAP_STATE_CONTEXT_FOR_PROJECT => Some(StateContext::Project),
+        n => {
+            nih_debug_assert_failure!("Unknown state context type {}", n);
+            None
+        }
+    }
+}
+
+/// Convert an optional color passed by the host.
+///
+/// # Safety
+///
+/// `color` must be either a null pointer or a valid pointer to a `clap_color`.
+unsafe fn host_color(color: *const clap_color) -> Option<HostColor> {
+    color.as_ref().map(|color| HostColor {
+        red: color.red,
+        green: color.green,
+        blue: color.blue,
+        alpha: color.alpha,
+    })
+}
+
+/// Add the plugin's context menu items to the host's context menu. Action IDs are assigned in the
+/// same order as in [`context_menu_actions()`]. Returns `false` if the host rejected one of the
+/// items.
+///
+/// # Safety
+///
+/// `builder` must be a valid pointer to a context menu builder.
+unsafe fn populate_context_menu(
+    builder: *const clap_context_menu_builder,
+    items: &[ContextMenuItem],
+    next_action_id: &mut clap_id,
+) -> bool {
+    for item in items {
+        // The host copies the labels, so they only need to live until `add_item()` returns
+        let success = match item {
+            ContextMenuItem::Entry { label, enabled, .. } => {
+                let label = CString::new(label.as_str()).unwrap_or_default();
+                let entry = clap_context_menu_entry {
+                    label: label.as_ptr(),
+                    is_enabled: *enabled,
+                    action_id: *next_action_id,
+                };
+                *next_action_id += 1;
+
+                clap_call! { builder=>add_item(builder, CLAP_CONTEXT_MENU_ITEM_ENTRY, &entry as *const _ as *const c_void) }
+            }
+            ContextMenuItem::CheckEntry {
+                label,
+                enabled,
+                checked,
+                ..
+            } => {
+                let label = CString::new(label.as_str()).unwrap_or_default();
+                let entry = clap_context_menu_check_entry {
+                    label: label.as_ptr(),
+                    is_enabled: *enabled,
+                    is_checked: *checked,
+                    action_id: *next_action_id,
+                };
+                *next_action_id += 1;
+
+                clap_call! { builder=>add_item(builder, CLAP_CONTEXT_MENU_ITEM_CHECK_ENTRY, &entry as *const _ as *const c_void) }
+            }
+            ContextMenuItem::Separator => {
+                clap_call! { builder=>add_item(builder, CLAP_CONTEXT_MENU_ITEM_SEPARATOR, std::ptr::null()) }
+            }
+            ContextMenuItem::Submenu { label, items } => {
+                let label = CString::new(label.as_str()).unwrap_or_default();
+                let submenu = clap_context_menu_submenu {
+                    label: label.as_ptr(),
+                    is_enabled: true,
+                };
+
+                let success = clap_call! { builder=>add_item(builder, CLAP_CONTEXT_MENU_ITEM_BEGIN_SUBMENU, &submenu as *const _ as *const c_void) };
+                success
+                    && populate_context_menu(builder, items, next_action_id)
+                    && clap_call! { builder=>add_item(builder, CLAP_CONTEXT_MENU_ITEM_END_SUBMENU, std::ptr::null()) }
+            }
+        };
+
+        if !success {
+            return false;
+        }
+    }
+
+    true
+}
+
 /// Convenience function to query an extension from the host.
 ///
 /// # Safety
@@ -3229,3 +4742,95 @@ unsafe fn query_host_extension<T>(
         None
     }
 }
+
+/// Whether any of the plugin's audio IO layouts has a port with a channel layout matching
+/// `predicate`.
+fn uses_channel_layout<P: Plugin>(predicate: impl Fn(ChannelLayout) -> bool) -> bool {
+    P::AUDIO_IO_LAYOUTS.iter().any(|audio_io_layout| {
+        audio_io_layout
+            .main_input_channel_layout()
+            .into_iter()
+            .chain(audio_io_layout.main_output_channel_layout())
+            .chain(
+                (0..audio_io_layout.aux_input_ports.len())
+                    .filter_map(|idx| audio_io_layout.aux_input_channel_layout(idx)),
+            )
+            .chain(
+                (0..audio_io_layout.aux_output_ports.len())
+                    .filter_map(|idx| audio_io_layout.aux_output_channel_layout(idx)),
+            )
+            .any(&predicate)
+    })
+}
+
+/// Get the channel layout for one of an audio IO layout's ports. Like in the audio ports
+/// extension, `port_index` includes the main port if the layout has one.
+fn port_channel_layout(
+    audio_io_layout: &AudioIOLayout,
+    is_input: bool,
+    port_index: u32,
+) -> Option<ChannelLayout> {
+    let port_index = port_index as usize;
+    match (is_input, port_index) {
+        (true, 0) if audio_io_layout.main_input_channels.is_some() => {
+            audio_io_layout.main_input_channel_layout()
+        }
+        (false, 0) if audio_io_layout.main_output_channels.is_some() => {
+            audio_io_layout.main_output_channel_layout()
+        }
+        (true, n) if audio_io_layout.main_input_channels.is_some() => {
+            audio_io_layout.aux_input_channel_layout(n - 1)
+        }
+        (false, n) if audio_io_layout.main_output_channels.is_some() => {
+            audio_io_layout.aux_output_channel_layout(n - 1)
+        }
+        (true, n) => audio_io_layout.aux_input_channel_layout(n),
+        (false, n) => audio_io_layout.aux_output_channel_layout(n),
+    }
+}
+
+/// The CLAP port type for a port with the given channel count and channel layout.
+fn port_type(channel_count: u32, channel_layout: Option<ChannelLayout>) -> *const c_char {
+    match (channel_layout, channel_count) {
+        (Some(ChannelLayout::Ambisonic { .. }), _) => CLAP_PORT_AMBISONIC.as_ptr(),
+        (Some(_), _) => CLAP_PORT_SURROUND.as_ptr(),
+        (None, 1) => CLAP_PORT_MONO.as_ptr(),
+        (None, 2) => CLAP_PORT_STEREO.as_ptr(),
+        (None, _) => std::ptr::null(),
+    }
+}
+
+/// The CLAP speaker positions for a surround channel layout, in the channel order documented on
+/// [`ChannelLayout`]. Returns `None` for ambisonic layouts.
+fn surround_channel_map(channel_layout: ChannelLayout) -> Option<&'static [u8]> {
+    const SURROUND_5_1: [u8; 6] = [
+        CLAP_SURROUND_FL as u8,
+        CLAP_SURROUND_FR as u8,
+        CLAP_SURROUND_FC as u8,
+        CLAP_SURROUND_LFE as u8,
+        CLAP_SURROUND_BL as u8,
+        CLAP_SURROUND_BR as u8,
+    ];
+    const SURROUND_7_1_4: [u8; 12] = [
+        CLAP_SURROUND_FL as u8,
+        CLAP_SURROUND_FR as u8,
+        CLAP_SURROUND_FC as u8,
+        CLAP_SURROUND_LFE as u8,
+        CLAP_SURROUND_BL as u8,
+        CLAP_SURROUND_BR as u8,
+        CLAP_SURROUND_SL as u8,
+        CLAP_SURROUND_SR as u8,
+        CLAP_SURROUND_TFL as u8,
+        CLAP_SURROUND_TFR as u8,
+        CLAP_SURROUND_TBL as u8,
+        CLAP_SURROUND_TBR as u8,
+    ];
+
+    match channel_layout {
+        ChannelLayout::Surround5_1 => Some(&SURROUND_5_1),
+        // 7.1 is 7.1.4 without the height channels
+        ChannelLayout::Surround7_1 => Some(&SURROUND_7_1_4[..8]),
+        ChannelLayout::Surround7_1_4 => Some(&SURROUND_7_1_4),
+        ChannelLayout::Ambisonic { .. } => None,
+    }
+}
@@ -253,6 +253,13 @@ impl<P: Plugin> Backend<P> for CpalMidir {
                                                 nih_error!("Could not send MIDI event: {err}");
                                             }
                                         }
+                                        Some(MidiResult::Multiple(messages, num_messages)) => {
+                                            for midi_data in &messages[..num_messages] {
+                                                if let Err(err) = connection.send(midi_data) {
+                                                    nih_error!("Could not send MIDI event: {err}");
+                                                }
+                                            }
+                                        }
                                         None => (),
                                     },
                                     MidiOutputTask::Terminate => break,
@@ -703,8 +710,10 @@ impl CpalMidir {
             .map(NonZeroU32::get)
             .unwrap_or(0) as usize;
         // This may contain excess unused space at the end if we get fewer samples than configured
-        // from CPAL
-        let mut main_io_storage = vec![vec![0.0f32; buffer_size]; num_output_channels];
+        // from CPAL. The main input and output share this storage, so for asymmetric layouts this
+        // needs to be large enough to fit every input and output channel.
+        let num_main_io_channels = num_output_channels.max(num_input_channels);
+        let mut main_io_storage = vec![vec![0.0f32; buffer_size]; num_main_io_channels];
 
         // This backend does not support auxiliary inputs and outputs, so in order to have the same
         // behavior as the other backends we'll provide some dummy buffers that we'll zero out every
@@ -727,14 +736,10 @@ impl CpalMidir {
 
         // The actual buffer management here works the same as in the JACK backend. See that
         // implementation for more information.
-        let mut buffer_manager =
+        let mut buffer_manager: BufferManager =
             BufferManager::for_audio_io_layout(buffer_size, self.audio_io_layout);
-        let mut main_io_channel_pointers = ChannelPointerVec(Vec::with_capacity(
-            self.audio_io_layout
-                .main_output_channels
-                .map(NonZeroU32::get)
-                .unwrap_or(0) as usize,
-        ));
+        let mut main_io_channel_pointers =
+            ChannelPointerVec(Vec::with_capacity(num_main_io_channels));
         let mut aux_input_channel_pointers =
             Vec::with_capacity(self.audio_io_layout.aux_input_ports.len());
         for channel_count in self.audio_io_layout.aux_input_ports {
@@ -772,18 +777,23 @@ impl CpalMidir {
             // write-only (with `BufferManager` always zeroing them out when creating the buffers).
             match &mut input_rb_consumer {
                 Some(input_rb_consumer) => {
-                    for channel in main_io_storage.iter_mut() {
-                        for sample in channel {
+                    for sample_idx in 0..buffer_size {
+                        for channel in main_io_storage.iter_mut().take(num_input_channels) {
                             loop {
                                 // Keep spinning on this if the output callback somehow outpaces the
                                 // input callback
                                 if let Ok(input_sample) = input_rb_consumer.pop() {
-                                    *sample = input_sample;
+                                    channel[sample_idx] = input_sample;
                                     break;
                                 }
                             }
                         }
                     }
+
+                    // Any remaining output channels need to start out silent
+                    for channel in main_io_storage.iter_mut().skip(num_input_channels) {
+                        channel.fill(0.0);
+                    }
                 }
                 None => {
                     for channel in main_io_storage.iter_mut() {
@@ -836,17 +846,22 @@ impl CpalMidir {
                 );
                 let buffers = unsafe {
                     buffer_manager.create_buffers(0, actual_sample_count, |buffer_sources| {
-                        *buffer_sources.main_output_channel_pointers = Some(ChannelPointers {
-                            ptrs: NonNull::new(main_io_channel_pointers.get().as_mut_ptr())
-                                .unwrap(),
-                            num_channels: main_io_channel_pointers.get().len(),
-                        });
-                        *buffer_sources.main_input_channel_pointers = Some(ChannelPointers {
-                            ptrs: NonNull::new(main_io_channel_pointers.get().as_mut_ptr())
-                                .unwrap(),
-                            num_channels: num_input_channels
-                                .min(main_io_channel_pointers.get().len()),
-                        });
+                        *buffer_sources.main_output_channel_pointers = Some(
+                            ChannelPointers {
+                                ptrs: NonNull::new(main_io_channel_pointers.get().as_mut_ptr())
+                                    .unwrap(),
+                                num_channels: num_output_channels,
+                            }
+                            .into(),
+                        );
+                        *buffer_sources.main_input_channel_pointers = Some(
+                            ChannelPointers {
+                                ptrs: NonNull::new(main_io_channel_pointers.get().as_mut_ptr())
+                                    .unwrap(),
+                                num_channels: num_input_channels,
+                            }
+                            .into(),
+                        );
 
                         for (input_source_channel_pointers, input_channel_pointers) in
                             buffer_sources
@@ -854,11 +869,14 @@ impl CpalMidir {
                                 .iter_mut()
                                 .zip(aux_input_channel_pointers.iter_mut())
                         {
-                            *input_source_channel_pointers = Some(ChannelPointers {
-                                ptrs: NonNull::new(input_channel_pointers.get().as_mut_ptr())
-                                    .unwrap(),
-                                num_channels: input_channel_pointers.get().len(),
-                            });
+                            *input_source_channel_pointers = Some(
+                                ChannelPointers {
+                                    ptrs: NonNull::new(input_channel_pointers.get().as_mut_ptr())
+                                        .unwrap(),
+                                    num_channels: input_channel_pointers.get().len(),
+                                }
+                                .into(),
+                            );
                         }
 
                         for (output_source_channel_pointers, output_channel_pointers) in
@@ -867,11 +885,14 @@ impl CpalMidir {
                                 .iter_mut()
                                 .zip(aux_output_channel_pointers.iter_mut())
                         {
-                            *output_source_channel_pointers = Some(ChannelPointers {
-                                ptrs: NonNull::new(output_channel_pointers.get().as_mut_ptr())
-                                    .unwrap(),
-                                num_channels: output_channel_pointers.get().len(),
-                            });
+                            *output_source_channel_pointers = Some(
+                                ChannelPointers {
+                                    ptrs: NonNull::new(output_channel_pointers.get().as_mut_ptr())
+                                        .unwrap(),
+                                    num_channels: output_channel_pointers.get().len(),
+                                }
+                                .into(),
+                            );
                         }
                     })
                 };
@@ -885,8 +906,11 @@ impl CpalMidir {
 
                 midi_output_events.clear();
                 let mut aux = AuxiliaryBuffers {
+                    main_input: buffers.main_input,
                     inputs: buffers.aux_inputs,
                     outputs: buffers.aux_outputs,
+                    inputs_active: buffers.aux_inputs_active,
+                    outputs_active: buffers.aux_outputs_active,
                 };
                 if !cb(
                     buffers.main_buffer,
@@ -44,7 +44,10 @@ impl<P: Plugin> Backend<P> for Dummy {
             .main_input_channels
             .map(NonZeroU32::get)
             .unwrap_or_default() as usize;
-        let mut main_io_storage = vec![vec![0.0f32; num_samples]; num_output_channels];
+        // The main input and output share this storage, so for asymmetric layouts this needs to be
+        // large enough to fit every input and output channel
+        let mut main_io_storage =
+            vec![vec![0.0f32; num_samples]; num_output_channels.max(num_input_channels)];
 
         // We'll do the same thing for auxiliary inputs and outputs, so the plugin always gets the
         // buffers it expects
@@ -90,7 +93,7 @@ impl<P: Plugin> Backend<P> for Dummy {
 
         // The `BufferManager` can then manage buffers using this storage just like in every other
         // backend
-        let mut buffer_manager =
+        let mut buffer_manager: BufferManager =
             BufferManager::for_audio_io_layout(num_samples, self.audio_io_layout);
 
         // This queue will never actually be used
@@ -122,24 +125,33 @@ impl<P: Plugin> Backend<P> for Dummy {
 
             let buffers = unsafe {
                 buffer_manager.create_buffers(0, num_samples, |buffer_sources| {
-                    *buffer_sources.main_output_channel_pointers = Some(ChannelPointers {
-                        ptrs: NonNull::new(main_io_channel_pointers.as_mut_ptr()).unwrap(),
-                        num_channels: main_io_channel_pointers.len(),
-                    });
-                    *buffer_sources.main_input_channel_pointers = Some(ChannelPointers {
-                        ptrs: NonNull::new(main_io_channel_pointers.as_mut_ptr()).unwrap(),
-                        num_channels: num_input_channels.min(main_io_channel_pointers.len()),
-                    });
+                    *buffer_sources.main_output_channel_pointers = Some(
+                        ChannelPointers {
+                            ptrs: NonNull::new(main_io_channel_pointers.as_mut_ptr()).unwrap(),
+                            num_channels: num_output_channels,
+                        }
+                        .into(),
+                    );
+                    *buffer_sources.main_input_channel_pointers = Some(
+                        ChannelPointers {
+                            ptrs: NonNull::new(main_io_channel_pointers.as_mut_ptr()).unwrap(),
+                            num_channels: num_input_channels,
+                        }
+                        .into(),
+                    );
 
                     for (input_source_channel_pointers, input_channel_pointers) in buffer_sources
                         .aux_input_channel_pointers
                         .iter_mut()
                         .zip(aux_input_channel_pointers.iter_mut())
                     {
-                        *input_source_channel_pointers = Some(ChannelPointers {
-                            ptrs: NonNull::new(input_channel_pointers.as_mut_ptr()).unwrap(),
-                            num_channels: input_channel_pointers.len(),
-                        });
+                        *input_source_channel_pointers = Some(
+                            ChannelPointers {
+                                ptrs: NonNull::new(input_channel_pointers.as_mut_ptr()).unwrap(),
+                                num_channels: input_channel_pointers.len(),
+                            }
+                            .into(),
+                        );
                     }
 
                     for (output_source_channel_pointers, output_channel_pointers) in buffer_sources
@@ -147,18 +159,24 @@ impl<P: Plugin> Backend<P> for Dummy {
                         .iter_mut()
                         .zip(aux_output_channel_pointers.iter_mut())
                     {
-                        *output_source_channel_pointers = Some(ChannelPointers {
-                            ptrs: NonNull::new(output_channel_pointers.as_mut_ptr()).unwrap(),
-                            num_channels: output_channel_pointers.len(),
-                        });
+                        *output_source_channel_pointers = Some(
+                            ChannelPointers {
+                                ptrs: NonNull::new(output_channel_pointers.as_mut_ptr()).unwrap(),
+                                num_channels: output_channel_pointers.len(),
+                            }
+                            .into(),
+                        );
                     }
                 })
             };
 
             midi_output_events.clear();
             let mut aux = AuxiliaryBuffers {
+                main_input: buffers.main_input,
                 inputs: buffers.aux_inputs,
                 outputs: buffers.aux_outputs,
+                inputs_active: buffers.aux_inputs_active,
+                outputs_active: buffers.aux_outputs_active,
             };
             if !cb(
                 buffers.main_buffer,
@@ -72,7 +72,7 @@ impl<P: Plugin> Backend<P> for Jack {
         // JACK ports later. For consistency with the other backends we'll reuse the
         // `BufferManager`, which means we'll need to collect pointers to individual channel slices
         // into vectors so we can provide the needed `*mut *mut f32` pointers.
-        let mut buffer_manager =
+        let mut buffer_manager: BufferManager =
             BufferManager::for_audio_io_layout(buffer_size as usize, self.audio_io_layout);
         let mut main_output_channel_pointers = ChannelPointerVec(Vec::with_capacity(
             self.audio_io_layout
@@ -205,25 +205,36 @@ impl<P: Plugin> Backend<P> for Jack {
 
             let buffers = unsafe {
                 buffer_manager.create_buffers(0, num_frames as usize, |buffer_sources| {
-                    *buffer_sources.main_output_channel_pointers = Some(ChannelPointers {
-                        ptrs: NonNull::new(main_output_channel_pointers.get().as_mut_ptr())
-                            .unwrap(),
-                        num_channels: main_output_channel_pointers.get().len(),
-                    });
-                    *buffer_sources.main_input_channel_pointers = Some(ChannelPointers {
-                        ptrs: NonNull::new(main_input_channel_pointers.get().as_mut_ptr()).unwrap(),
-                        num_channels: main_input_channel_pointers.get().len(),
-                    });
+                    *buffer_sources.main_output_channel_pointers = Some(
+                        ChannelPointers {
+                            ptrs: NonNull::new(main_output_channel_pointers.get().as_mut_ptr())
+                                .unwrap(),
+                            num_channels: main_output_channel_pointers.get().len(),
+                        }
+                        .into(),
+                    );
+                    *buffer_sources.main_input_channel_pointers = Some(
+                        ChannelPointers {
+                            ptrs: NonNull::new(main_input_channel_pointers.get().as_mut_ptr())
+                                .unwrap(),
+                            num_channels: main_input_channel_pointers.get().len(),
+                        }
+                        .into(),
+                    );
 
                     for (input_source_channel_pointers, input_channel_pointers) in buffer_sources
                         .aux_input_channel_pointers
                         .iter_mut()
                         .zip(aux_input_channel_pointers.iter_mut())
                     {
-                        *input_source_channel_pointers = Some(ChannelPointers {
-                            ptrs: NonNull::new(input_channel_pointers.get().as_mut_ptr()).unwrap(),
-                            num_channels: input_channel_pointers.get().len(),
-                        });
+                        *input_source_channel_pointers = Some(
+                            ChannelPointers {
+                                ptrs: NonNull::new(input_channel_pointers.get().as_mut_ptr())
+                                    .unwrap(),
+                                num_channels: input_channel_pointers.get().len(),
+                            }
+                            .into(),
+                        );
                     }
 
                     for (output_source_channel_pointers, output_channel_pointers) in buffer_sources
@@ -231,10 +242,14 @@ impl<P: Plugin> Backend<P> for Jack {
                         .iter_mut()
                         .zip(aux_output_channel_pointers.iter_mut())
                     {
-                        *output_source_channel_pointers = Some(ChannelPointers {
-                            ptrs: NonNull::new(output_channel_pointers.get().as_mut_ptr()).unwrap(),
-                            num_channels: output_channel_pointers.get().len(),
-                        });
+                        *output_source_channel_pointers = Some(
+                            ChannelPointers {
+                                ptrs: NonNull::new(output_channel_pointers.get().as_mut_ptr())
+                                    .unwrap(),
+                                num_channels: output_channel_pointers.get().len(),
+                            }
+                            .into(),
+                        );
                     }
                 })
             };
@@ -250,8 +265,11 @@ impl<P: Plugin> Backend<P> for Jack {
 
             output_events.clear();
             let mut aux = AuxiliaryBuffers {
+                main_input: buffers.main_input,
                 inputs: buffers.aux_inputs,
                 outputs: buffers.aux_outputs,
+                inputs_active: buffers.aux_inputs_active,
+                outputs_active: buffers.aux_outputs_active,
             };
             if cb(
                 buffers.main_buffer,
@@ -287,6 +305,19 @@ impl<P: Plugin> Backend<P> for Jack {
 
                                 nih_debug_assert!(write_result.is_ok(), "The MIDI buffer is full");
                             }
+                            Some(MidiResult::Multiple(messages, num_messages)) => {
+                                for midi_data in &messages[..num_messages] {
+                                    let write_result = midi_writer.write(&jack::RawMidi {
+                                        time: timing,
+                                        bytes: midi_data,
+                                    });
+
+                                    nih_debug_assert!(
+                                        write_result.is_ok(),
+                                        "The MIDI buffer is full"
+                                    );
+                                }
+                            }
                             None => (),
                         }
                     }
@@ -3,9 +3,10 @@ use std::sync::Arc;
 use super::backend::Backend;
 use super::wrapper::{Task, Wrapper};
 use crate::prelude::{
-    GuiContext, InitContext, ParamPtr, Plugin, PluginApi, PluginNoteEvent, ProcessContext,
-    Transport,
+    ContextMenuItem, GuiContext, InitContext, MidiMapping, ParamIndication, ParamInfoChanges,
+    ParamPtr, Plugin, PluginApi, PluginNoteEvent, ProcessContext, TrackInfo, Transport,
 };
+use crate::util;
 
 /// An [`InitContext`] implementation for the standalone wrapper.
 pub(crate) struct WrapperInitContext<'a, P: Plugin, B: Backend<P>> {
@@ -52,6 +53,24 @@ impl<P: Plugin, B: Backend<P>> InitContext<P> for WrapperInitContext<'_, P, B> {
     fn set_current_voice_capacity(&self, _capacity: u32) {
         // This is only supported by CLAP
     }
+
+    fn track_info(&self) -> Option<TrackInfo> {
+        // There's no host, so there's also no track
+        None
+    }
+
+    fn aux_input_active(&self, index: usize) -> bool {
+        // The standalone wrapper never deactivates any ports
+        index < self.wrapper.audio_io_layout.aux_input_ports.len()
+    }
+
+    fn aux_output_active(&self, index: usize) -> bool {
+        index < self.wrapper.audio_io_layout.aux_output_ports.len()
+    }
+
+    fn param_info_changed(&self, _changes: ParamInfoChanges) {
+        // There's no host that needs to query the parameters again
+    }
 }
 
 impl<P: Plugin, B: Backend<P>> ProcessContext<P> for WrapperProcessContext<'_, P, B> {
@@ -97,6 +116,35 @@ impl<P: Plugin, B: Backend<P>> ProcessContext<P> for WrapperProcessContext<'_, P
     fn set_current_voice_capacity(&self, _capacity: u32) {
         // This is only supported by CLAP
     }
+
+    fn note_names_changed(&self) {
+        // Since there's no host, there's nobody to display the note names
+    }
+
+    fn note_frequency(&self, _channel: u8, note: u8, _timing: u32) -> f32 {
+        // Without a host there's no tuning, so this always uses 12-TET
+        util::midi_note_to_freq(note)
+    }
+
+    fn execute_parallel(&self, num_tasks: u32, task: &(dyn Fn(u32) + Sync)) {
+        // There's no host thread pool, so the tasks are run sequentially
+        for task_index in 0..num_tasks {
+            task(task_index);
+        }
+    }
+
+    unsafe fn raw_begin_set_parameter(&mut self, _param: ParamPtr) {
+        // Since there's no host, there's nothing to inform about automation gestures
+    }
+
+    unsafe fn raw_set_parameter_normalized(&mut self, param: ParamPtr, normalized: f32) {
+        // This is applied directly after the plugin's process function returns
+        self.wrapper.set_parameter(param, normalized);
+    }
+
+    unsafe fn raw_end_set_parameter(&mut self, _param: ParamPtr) {
+        // See `raw_begin_set_parameter()`
+    }
 }
 
 impl<P: Plugin, B: Backend<P>> GuiContext for WrapperGuiContext<P, B> {
@@ -109,6 +157,33 @@ impl<P: Plugin, B: Backend<P>> GuiContext for WrapperGuiContext<P, B> {
         true
     }
 
+    fn note_names_changed(&self) {
+        // Since there's no host, there's nobody to display the note names
+    }
+
+    fn param_info_changed(&self, _changes: ParamInfoChanges) {
+        // The same applies to the parameters
+    }
+
+    fn track_info(&self) -> Option<TrackInfo> {
+        None
+    }
+
+    fn param_indication(&self, _param: ParamPtr) -> ParamIndication {
+        ParamIndication::default()
+    }
+
+    fn show_param_context_menu(
+        &self,
+        _param: ParamPtr,
+        _x: i32,
+        _y: i32,
+        _items: Vec<ContextMenuItem>,
+    ) -> bool {
+        // There's no host to show a context menu
+        false
+    }
+
     unsafe fn raw_begin_set_parameter(&self, _param: ParamPtr) {
         // Since there's no automation being recorded here, gestures don't mean anything
 
@@ -152,6 +227,11 @@ impl<P: Plugin, B: Backend<P>> GuiContext for WrapperGuiContext<P, B> {
         }
     }
 
+    unsafe fn raw_set_parameters_normalized(&self, changes: &[(ParamPtr, f32)]) {
+        // There are no gestures or undo steps, so the changes only need to be applied together
+        self.wrapper.set_parameters(changes);
+    }
+
     fn get_state(&self) -> crate::wrapper::state::PluginState {
         self.wrapper.get_state_object()
     }
@@ -159,4 +239,24 @@ impl<P: Plugin, B: Backend<P>> GuiContext for WrapperGuiContext<P, B> {
     fn set_state(&self, state: crate::wrapper::state::PluginState) {
         self.wrapper.set_state_object_from_gui(state)
     }
+
+    fn start_midi_learn(&self, param: ParamPtr) -> bool {
+        self.wrapper.start_midi_learn(param)
+    }
+
+    fn cancel_midi_learn(&self) {
+        self.wrapper.midi_learn.cancel_learning()
+    }
+
+    fn midi_learn_target(&self) -> Option<ParamPtr> {
+        self.wrapper.midi_learn.learn_target()
+    }
+
+    fn midi_mappings(&self) -> Vec<MidiMapping> {
+        self.wrapper.midi_learn.mappings()
+    }
+
+    fn set_midi_mappings(&self, mappings: Vec<MidiMapping>) {
+        self.wrapper.set_midi_mappings(&mappings)
+    }
 }
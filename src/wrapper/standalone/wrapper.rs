@@ -6,6 +6,8 @@ use parking_lot::Mutex;
 use raw_window_handle::HasRawWindowHandle;
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
+use std::num::NonZeroU32;
+use std::ptr::NonNull;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 use std::thread;
@@ -15,13 +17,17 @@ use super::config::WrapperConfig;
 use super::context::{WrapperGuiContext, WrapperInitContext, WrapperProcessContext};
 use crate::event_loop::{EventLoop, MainThreadExecutor, OsEventLoop};
 use crate::prelude::{
-    AsyncExecutor, AudioIOLayout, BufferConfig, Editor, ParamFlags, ParamPtr, Params,
-    ParentWindowHandle, Plugin, PluginNoteEvent, ProcessMode, ProcessStatus, TaskExecutor,
-    Transport,
+    AsyncExecutor, AudioIOLayout, AuxiliaryBuffers, Buffer, BufferConfig, Editor, MidiConfig,
+    MidiMapping, NoteEvent, ParamFlags, ParamPtr, Params, ParentWindowHandle, Plugin,
+    PluginNoteEvent, ProcessContext, ProcessMode, ProcessStatus, TaskExecutor, Transport,
 };
 use crate::util::permit_alloc;
-use crate::wrapper::state::{self, PluginState};
-use crate::wrapper::util::process_wrapper;
+use crate::wrapper::state::{self, PluginState, StateContext};
+use crate::wrapper::util::buffer_management::{
+    BufferManager, ChannelPointers, HostChannelPointers,
+};
+use crate::wrapper::util::midi_learn::MidiLearn;
+use crate::wrapper::util::{process_wrapper, push_all_or_nothing};
 
 /// How many parameter changes we can store in our unprocessed parameter change queue. Storing more
 /// than this many parameters at a time will cause changes to get lost.
@@ -72,6 +78,14 @@ pub struct Wrapper<P: Plugin, B: Backend<P>> {
     /// This queue will be flushed at the end of every processing cycle, just like in the plugin
     /// versions.
     unprocessed_param_changes: ArrayQueue<(ParamPtr, f32)>,
+    /// Held by the GUI thread while it adds a batch of parameter changes to
+    /// `unprocessed_param_changes`. The queue is not flushed while this is locked, so all of the
+    /// batch's changes are applied after the same processing cycle.
+    unprocessed_param_changes_batch_lock: Mutex<()>,
+    /// The plugin's MIDI learn mappings. Mapped MIDI CCs are applied at the start of the processing
+    /// cycle, since the standalone wrapper does not split the buffer for sample accurate
+    /// automation.
+    pub midi_learn: MidiLearn,
     /// The plugin is able to restore state through a method on the `GuiContext`. To avoid changing
     /// parameters mid-processing and running into garbled data if the host also tries to load state
     /// at the same time the restoring happens at the end of each processing call. If this zero
@@ -104,6 +118,9 @@ pub enum Task<P: Plugin> {
     /// like in the plugin APIs, so we'll just use the `ParamPtr`s directly. These are used to index
     /// the hashmaps stored on `Wrapper`.
     ParameterValueChanged(ParamPtr, f32),
+    /// The audio thread received the controller for a parameter that was being MIDI learned. The
+    /// new mapping still needs to be added on the main thread.
+    MidiLearnFinished,
 }
 
 /// Errors that may arise while initializing the wrapped plugins.
@@ -168,6 +185,9 @@ impl<P: Plugin, B: Backend<P>> MainThreadExecutor<Task<P>> for Wrapper<P, B> {
                         .param_value_changed(param_id, normalized_value);
                 }
             }
+            Task::MidiLearnFinished => self
+                .midi_learn
+                .finish_learning(|param| self.param_ptr_to_id.get(&param).cloned()),
         }
     }
 }
@@ -250,6 +270,8 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
             config,
 
             unprocessed_param_changes: ArrayQueue::new(EVENT_QUEUE_CAPACITY),
+            unprocessed_param_changes_batch_lock: Mutex::new(()),
+            midi_learn: MidiLearn::default(),
             updated_state_sender,
             updated_state_receiver,
             current_latency: AtomicU32::new(0),
@@ -419,6 +441,63 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
         push_successful
     }
 
+    /// Set several parameters at once. These changes are all applied at the end of the same
+    /// processing cycle. Unknown parameters are skipped.
+    ///
+    /// This returns false if the queue does not have room for all of the changes, in which case
+    /// none of them are set.
+    pub fn set_parameters(&self, changes: &[(ParamPtr, f32)]) -> bool {
+        let changes: Vec<(ParamPtr, f32)> = changes
+            .iter()
+            .filter(|(param, _)| self.param_ptr_to_id.contains_key(param))
+            .copied()
+            .collect();
+
+        let _batch_guard = self.unprocessed_param_changes_batch_lock.lock();
+        let push_successful = push_all_or_nothing(&self.unprocessed_param_changes, changes);
+        nih_debug_assert!(push_successful, "The parameter change queue was full");
+
+        push_successful
+    }
+
+    /// Start MIDI learn for a parameter. Returns `false` if the parameter cannot be MIDI learned.
+    pub fn start_midi_learn(&self, param: ParamPtr) -> bool {
+        if !self.param_ptr_to_id.contains_key(&param) {
+            nih_debug_assert_failure!("Unknown parameter: {:?}", param);
+            return false;
+        }
+
+        self.midi_learn.start_learning::<P>(param)
+    }
+
+    /// Replace the MIDI learn mappings, resolving the mappings' parameter IDs.
+    pub fn set_midi_mappings(&self, mappings: &[MidiMapping]) {
+        self.midi_learn.set_mappings(mappings, |param_id| {
+            self.param_id_to_ptr.get(param_id).copied()
+        });
+    }
+
+    /// Pass an incoming MIDI CC through the MIDI learn mappings, updating the mapped parameters.
+    /// Must be called from the audio thread.
+    fn handle_midi_learn_cc(&self, channel: u8, cc: u8, value: f32) {
+        let sample_rate = self.buffer_config.sample_rate;
+        let learn_finished =
+            self.midi_learn
+                .process_cc(channel, cc, value, |param_ptr, normalized_value| {
+                    if unsafe { param_ptr.set_normalized_value(normalized_value) } {
+                        unsafe { param_ptr.update_smoother(sample_rate, false) };
+                        let task_posted = self
+                            .schedule_gui(Task::ParameterValueChanged(param_ptr, normalized_value));
+                        nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+                    }
+                });
+
+        if learn_finished {
+            let task_posted = self.schedule_gui(Task::MidiLearnFinished);
+            nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+        }
+    }
+
     /// Get the plugin's state object, may be called by the plugin's GUI as part of its own preset
     /// management. The wrapper doesn't use these functions and serializes and deserializes directly
     /// the JSON in the relevant plugin API methods instead.
@@ -429,6 +508,8 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
                 self.param_id_to_ptr
                     .iter()
                     .map(|(param_id, param_ptr)| (param_id, *param_ptr)),
+                &self.midi_learn,
+                StateContext::Preset,
             )
         }
     }
@@ -505,6 +586,17 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
         should_terminate: Arc<AtomicBool>,
         gui_task_sender: channel::Sender<GuiTask>,
     ) {
+        // The backends only deal in single precision audio, so plugins that process audio in double
+        // precision need their buffers to be converted first
+        let mut f64_converter = if P::PROCESS_F64 {
+            Some(F64Converter::new(
+                self.buffer_config.max_buffer_size as usize,
+                self.audio_io_layout,
+            ))
+        } else {
+            None
+        };
+
         self.clone().backend.borrow_mut().run(
             move |buffer, aux, transport, input_events, output_events| {
                 // TODO: This process wrapper should actually be in the backends (since the backends
@@ -515,14 +607,32 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
                         return false;
                     }
 
+                    // Parameters mapped through MIDI learn are updated before the plugin gets to
+                    // see the block's events
+                    if P::MIDI_INPUT >= MidiConfig::MidiCCs {
+                        for event in input_events {
+                            if let NoteEvent::MidiCC {
+                                channel, cc, value, ..
+                            } = event
+                            {
+                                self.handle_midi_learn_cc(*channel, *cc, *value);
+                            }
+                        }
+                    }
+
                     let sample_rate = self.buffer_config.sample_rate;
                     {
                         let mut plugin = self.plugin.lock();
-                        if let ProcessStatus::Error(err) = plugin.process(
-                            buffer,
-                            aux,
-                            &mut self.make_process_context(transport, input_events, output_events),
-                        ) {
+                        let mut context =
+                            self.make_process_context(transport, input_events, output_events);
+                        let result = match &mut f64_converter {
+                            Some(f64_converter) => {
+                                f64_converter.process(&mut *plugin, buffer, aux, &mut context)
+                            }
+                            None => plugin.process(buffer, aux, &mut context),
+                        };
+
+                        if let ProcessStatus::Error(err) = result {
                             nih_error!("The plugin returned an error while processing:");
                             nih_error!("{}", err);
 
@@ -540,9 +650,15 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
                     // audio/MIDI backend
 
                     // We'll always write these events to the first sample, so even when we add note
-                    // output we shouldn't have to think about interleaving events here
+                    // output we shouldn't have to think about interleaving events here. If the
+                    // editor is currently adding a batch of changes, then the entire batch is
+                    // applied after the next processing cycle instead.
+                    let batch_guard = self.unprocessed_param_changes_batch_lock.try_lock();
+                    let unprocessed_param_changes = batch_guard
+                        .as_ref()
+                        .map(|_| &self.unprocessed_param_changes);
                     while let Some((param_ptr, normalized_value)) =
-                        self.unprocessed_param_changes.pop()
+                        unprocessed_param_changes.and_then(|changes| changes.pop())
                     {
                         if unsafe { param_ptr.set_normalized_value(normalized_value) } {
                             unsafe { param_ptr.update_smoother(sample_rate, false) };
@@ -633,7 +749,10 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
                 state,
                 self.params.clone(),
                 |param_id| self.param_id_to_ptr.get(param_id).copied(),
+                &self.midi_learn,
                 Some(&self.buffer_config),
+                // The state is only ever set from the plugin's own GUI
+                StateContext::Preset,
             )
         });
         if !success {
@@ -677,3 +796,134 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
         success
     }
 }
+
+/// Converts the single precision buffers provided by the audio backends to double precision
+/// buffers for plugins that set [`Plugin::PROCESS_F64`], using the same buffer manager as the plugin
+/// API wrappers.
+struct F64Converter {
+    buffer_manager: BufferManager<f64>,
+
+    // These contain pointers to the backend's channels, and they are filled in on every process
+    // call. The capacity for these vectors is preallocated so this doesn't allocate.
+    main_input_ptrs: Vec<*mut f32>,
+    main_output_ptrs: Vec<*mut f32>,
+    aux_input_ptrs: Vec<Vec<*mut f32>>,
+    aux_output_ptrs: Vec<Vec<*mut f32>>,
+}
+
+/// The channel pointers are only ever dereferenced on the audio thread while the backend's buffers
+/// are borrowed.
+unsafe impl Send for F64Converter {}
+
+impl F64Converter {
+    fn new(max_buffer_size: usize, audio_io_layout: AudioIOLayout) -> Self {
+        let num_input_channels = audio_io_layout
+            .main_input_channels
+            .map(NonZeroU32::get)
+            .unwrap_or_default() as usize;
+        let num_output_channels = audio_io_layout
+            .main_output_channels
+            .map(NonZeroU32::get)
+            .unwrap_or_default() as usize;
+
+        Self {
+            buffer_manager: BufferManager::for_audio_io_layout(max_buffer_size, audio_io_layout),
+
+            main_input_ptrs: Vec::with_capacity(num_input_channels),
+            main_output_ptrs: Vec::with_capacity(num_output_channels),
+            aux_input_ptrs: audio_io_layout
+                .aux_input_ports
+                .iter()
+                .map(|num_channels| Vec::with_capacity(num_channels.get() as usize))
+                .collect(),
+            aux_output_ptrs: audio_io_layout
+                .aux_output_ports
+                .iter()
+                .map(|num_channels| Vec::with_capacity(num_channels.get() as usize))
+                .collect(),
+        }
+    }
+
+    /// Convert the backend's buffers to double precision, call the plugin's
+    /// [`Plugin::process_f64()`] function, and then convert the outputs back to single precision.
+    fn process<P: Plugin>(
+        &mut self,
+        plugin: &mut P,
+        buffer: &mut Buffer,
+        aux: &mut AuxiliaryBuffers,
+        context: &mut impl ProcessContext<P>,
+    ) -> ProcessStatus {
+        fill_channel_ptrs(&mut self.main_input_ptrs, aux.main_input);
+        fill_channel_ptrs(&mut self.main_output_ptrs, buffer);
+        for (ptrs, buffer) in self.aux_input_ptrs.iter_mut().zip(aux.inputs.iter_mut()) {
+            fill_channel_ptrs(ptrs, buffer);
+        }
+        for (ptrs, buffer) in self.aux_output_ptrs.iter_mut().zip(aux.outputs.iter_mut()) {
+            fill_channel_ptrs(ptrs, buffer);
+        }
+
+        let buffers = unsafe {
+            self.buffer_manager
+                .create_buffers(0, buffer.samples(), |buffer_source| {
+                    *buffer_source.main_input_channel_pointers =
+                        channel_pointers(&mut self.main_input_ptrs);
+                    *buffer_source.main_output_channel_pointers =
+                        channel_pointers(&mut self.main_output_ptrs);
+                    for (channel_pointers_ref, ptrs) in buffer_source
+                        .aux_input_channel_pointers
+                        .iter_mut()
+                        .zip(self.aux_input_ptrs.iter_mut())
+                    {
+                        *channel_pointers_ref = channel_pointers(ptrs);
+                    }
+                    for (channel_pointers_ref, ptrs) in buffer_source
+                        .aux_output_channel_pointers
+                        .iter_mut()
+                        .zip(self.aux_output_ptrs.iter_mut())
+                    {
+                        *channel_pointers_ref = channel_pointers(ptrs);
+                    }
+                })
+        };
+
+        let mut aux = AuxiliaryBuffers {
+            main_input: buffers.main_input,
+            inputs: buffers.aux_inputs,
+            outputs: buffers.aux_outputs,
+            inputs_active: buffers.aux_inputs_active,
+            outputs_active: buffers.aux_outputs_active,
+        };
+        let result = plugin.process_f64(buffers.main_buffer, &mut aux, context);
+        unsafe { self.buffer_manager.write_back_outputs() };
+
+        result
+    }
+}
+
+/// Store pointers to `buffer`'s channels in `ptrs`. This does not allocate as long as `ptrs` has
+/// enough capacity for all of the buffer's channels.
+fn fill_channel_ptrs(ptrs: &mut Vec<*mut f32>, buffer: &mut Buffer) {
+    ptrs.clear();
+    ptrs.extend(
+        buffer
+            .as_slice()
+            .iter_mut()
+            .map(|channel| channel.as_mut_ptr()),
+    );
+}
+
+/// Create the `ChannelPointers` for a port from the pointers stored by [`fill_channel_ptrs()`].
+/// Returns `None` if the port has no channels.
+fn channel_pointers(ptrs: &mut [*mut f32]) -> Option<HostChannelPointers> {
+    if ptrs.is_empty() {
+        return None;
+    }
+
+    Some(
+        ChannelPointers {
+            ptrs: NonNull::new(ptrs.as_mut_ptr()).unwrap(),
+            num_channels: ptrs.len(),
+        }
+        .into(),
+    )
+}
@@ -6,14 +6,18 @@ use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
+use crate::midi::learn::MidiMapping;
 use crate::params::ParamMut;
 use crate::prelude::{BufferConfig, Param, ParamPtr, Params, Plugin};
+use crate::wrapper::util::midi_learn::MidiLearn;
+
+pub mod migrations;
 
 // These state objects are also exposed directly to the plugin so it can do its own internal preset
 // management
 
 /// A plain, unnormalized value for a parameter.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ParamValue {
     F32(f32),
@@ -29,9 +33,9 @@ pub enum ParamValue {
 /// The fields are stored as `BTreeMap`s so the order in the serialized file is consistent.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginState {
-    /// The plugin version this state was saved with. Right now this is not used, but later versions
-    /// of NIH-plug may allow you to modify the plugin state object directly before it is loaded to
-    /// allow migrating plugin states between breaking parameter changes.
+    /// The plugin version this state was saved with. This can be used in
+    /// [`Plugin::filter_state()`] to migrate old states after breaking parameter changes, see
+    /// [`migrations::StateMigrations`].
     ///
     /// # Notes
     ///
@@ -50,6 +54,29 @@ pub struct PluginState {
     /// The individual fields are also serialized as JSON so they can safely be restored
     /// independently of the other fields.
     pub fields: BTreeMap<String, String>,
+    /// The plugin's MIDI learn mappings. These are not stored in or loaded from presets, see
+    /// [`StateContext::Preset`].
+    #[serde(default)]
+    pub midi_mappings: Vec<MidiMapping>,
+}
+
+/// Why the plugin's state is being saved or loaded. Persistent fields marked with
+/// `#[persist("key", skip_presets)]` are not stored in or loaded from presets. See
+/// [`Params::serialize_fields_with_context()`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StateContext {
+    /// The state is saved as part of the host's project, or loaded from one. The host uses this
+    /// when it does not specify a context.
+    #[default]
+    Project,
+    /// The state is saved as a preset, or a preset is being loaded. This is also used for the state
+    /// objects from [`GuiContext::get_state()`][crate::prelude::GuiContext::get_state()] and
+    /// [`GuiContext::set_state()`][crate::prelude::GuiContext::set_state()], as these are meant to
+    /// be used for preset management.
+    Preset,
+    /// The state is copied to another instance of the plugin, for instance when the user duplicates
+    /// the plugin or the track it's on.
+    Duplicate,
 }
 
 /// Create a parameters iterator from the hashtables stored in the plugin wrappers. This avoids
@@ -85,6 +112,8 @@ pub(crate) fn make_params_getter<'a>(
 pub(crate) unsafe fn serialize_object<'a, P: Plugin>(
     plugin_params: Arc<dyn Params>,
     params_iter: impl IntoIterator<Item = (&'a String, ParamPtr)>,
+    midi_learn: &MidiLearn,
+    context: StateContext,
 ) -> PluginState {
     // We'll serialize parameter values as a simple `string_param_id: display_value` map.
     // NOTE: If the plugin is being modulated (and the plugin is a CLAP plugin in Bitwig Studio),
@@ -119,12 +148,20 @@ pub(crate) unsafe fn serialize_object<'a, P: Plugin>(
 
     // The plugin can also persist arbitrary fields alongside its parameters. This is useful for
     // storing things like sample data.
-    let fields = plugin_params.serialize_fields();
+    let fields = plugin_params.serialize_fields_with_context(context);
+
+    // MIDI mappings belong to the user's setup and not to the sound, so presets don't include them
+    let midi_mappings = if context == StateContext::Preset {
+        Vec::new()
+    } else {
+        midi_learn.mappings()
+    };
 
     PluginState {
         version: String::from(P::VERSION),
         params,
         fields,
+        midi_mappings,
     }
 }
 
@@ -134,8 +171,10 @@ pub(crate) unsafe fn serialize_object<'a, P: Plugin>(
 pub(crate) unsafe fn serialize_json<'a, P: Plugin>(
     plugin_params: Arc<dyn Params>,
     params_iter: impl IntoIterator<Item = (&'a String, ParamPtr)>,
+    midi_learn: &MidiLearn,
+    context: StateContext,
 ) -> Result<Vec<u8>> {
-    let plugin_state = serialize_object::<P>(plugin_params, params_iter);
+    let plugin_state = serialize_object::<P>(plugin_params, params_iter, midi_learn, context);
     let json = serde_json::to_vec(&plugin_state).context("Could not format as JSON")?;
 
     #[cfg(feature = "zstd")]
@@ -175,7 +214,9 @@ pub(crate) unsafe fn deserialize_object<P: Plugin>(
     state: &mut PluginState,
     plugin_params: Arc<dyn Params>,
     params_getter: impl Fn(&str) -> Option<ParamPtr>,
+    midi_learn: &MidiLearn,
     current_buffer_config: Option<&BufferConfig>,
+    context: StateContext,
 ) -> bool {
     // This lets the plugin perform migrations on old state if needed
     P::filter_state(state);
@@ -233,7 +274,12 @@ pub(crate) unsafe fn deserialize_object<P: Plugin>(
 
     // The plugin can also persist arbitrary fields alongside its parameters. This is useful for
     // storing things like sample data.
-    plugin_params.deserialize_fields(&state.fields);
+    plugin_params.deserialize_fields_with_context(&state.fields, context);
+
+    // Loading a preset keeps the current MIDI mappings, see `serialize_object()`
+    if context != StateContext::Preset {
+        midi_learn.set_mappings(&state.midi_mappings, &params_getter);
+    }
 
     true
 }
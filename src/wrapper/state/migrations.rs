@@ -0,0 +1,398 @@
+//! Declarative migrations for old plugin state. These are meant to be applied from
+//! [`Plugin::filter_state()`][crate::prelude::Plugin::filter_state()]:
+//!
+//! ```ignore
+//! fn filter_state(state: &mut PluginState) {
+//!     StateMigrations::new()
+//!         .add("0.2.0", [MigrationStep::rename_param("gain", "input_gain")])
+//!         .add(
+//!             "0.4.0",
+//!             [
+//!                 MigrationStep::rescale_param("cutoff", (0.0, 1.0), (20.0, 20_000.0)),
+//!                 MigrationStep::set_field("safe-mode", "false"),
+//!             ],
+//!         )
+//!         .apply(state);
+//! }
+//! ```
+//!
+//! Every step is applied to states that were saved with a plugin version older than the step's
+//! version, in the order the steps were added. The state's [`PluginState::version`] field is parsed
+//! as a semver version. States with a missing or invalid version are treated as being older than
+//! every migration, since very old NIH-plug states did not store a version.
+
+use semver::Version;
+
+use super::{ParamValue, PluginState};
+
+/// An ordered list of migrations for a plugin's [`PluginState`]. See the [module
+/// docs][self] for an example.
+#[derive(Debug, Clone, Default)]
+pub struct StateMigrations {
+    /// The migration steps, along with the version that introduced them. These are sorted by
+    /// version.
+    migrations: Vec<(Version, Vec<MigrationStep>)>,
+}
+
+/// A single step in a state migration.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MigrationStep {
+    /// Change a parameter's ID. This also updates the MIDI learn mappings for the parameter.
+    RenameParam { from: String, to: String },
+    /// Linearly map a float or integer parameter's plain value from the `from` range to the `to`
+    /// range. Integer values are rounded to the nearest integer. The step is skipped if the `from`
+    /// range is empty.
+    RescaleParam {
+        id: String,
+        from: (f32, f32),
+        to: (f32, f32),
+    },
+    /// Change an enum parameter's stored variant index from the first value to the second value.
+    /// This is needed when variants without a stable `#[id = "..."]` are reordered.
+    MapEnumIndices { id: String, map: Vec<(i32, i32)> },
+    /// Change an enum parameter's stored variant ID from the first value to the second value.
+    MapEnumIds {
+        id: String,
+        map: Vec<(String, String)>,
+    },
+    /// Change the key of a persisted field.
+    RenameField { from: String, to: String },
+    /// Remove a persisted field.
+    RemoveField { key: String },
+    /// Set a persisted field to a JSON-serialized value, for instance to preserve the old behavior
+    /// for a newly added field whose default would otherwise change the sound of old presets.
+    SetField { key: String, value: String },
+}
+
+impl StateMigrations {
+    /// Create an empty list of migrations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add migration steps that are applied to states saved with a version before `version`.
+    /// `version` needs to be a valid semver version that's not lower than any of the versions added
+    /// before it. Otherwise these steps are ignored, and a debug assertion failure is logged.
+    pub fn add(mut self, version: &str, steps: impl IntoIterator<Item = MigrationStep>) -> Self {
+        let version = match Version::parse(version) {
+            Ok(version) => version,
+            Err(err) => {
+                nih_debug_assert_failure!(
+                    "Invalid migration version '{}', these migrations will be skipped: {}",
+                    version,
+                    err
+                );
+                return self;
+            }
+        };
+        if let Some((last_version, _)) = self.migrations.last() {
+            if version < *last_version {
+                nih_debug_assert_failure!(
+                    "Migrations need to be added in order, but {} was added after {}. These \
+                     migrations will be skipped.",
+                    version,
+                    last_version
+                );
+                return self;
+            }
+        }
+
+        self.migrations.push((version, steps.into_iter().collect()));
+        self
+    }
+
+    /// Apply all migrations that are newer than the state's version, logging every applied step.
+    /// Returns the number of steps that were applied.
+    pub fn apply(&self, state: &mut PluginState) -> usize {
+        let state_version = Version::parse(&state.version).ok();
+
+        let mut num_applied_steps = 0;
+        for (version, steps) in &self.migrations {
+            if matches!(&state_version, Some(state_version) if state_version >= version) {
+                continue;
+            }
+
+            for step in steps {
+                nih_log!(
+                    "Migrating state from version '{}' to {}: {:?}",
+                    state.version,
+                    version,
+                    step
+                );
+                step.apply(state);
+                num_applied_steps += 1;
+            }
+        }
+
+        num_applied_steps
+    }
+}
+
+impl MigrationStep {
+    /// Create a [`MigrationStep::RenameParam`] step.
+    pub fn rename_param(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self::RenameParam {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    /// Create a [`MigrationStep::RescaleParam`] step. The `from` range's start and end must be
+    /// different finite values.
+    pub fn rescale_param(id: impl Into<String>, from: (f32, f32), to: (f32, f32)) -> Self {
+        nih_debug_assert!(
+            !is_degenerate_range(from),
+            "Cannot rescale from the range {:?}, this migration step will be skipped",
+            from
+        );
+
+        Self::RescaleParam {
+            id: id.into(),
+            from,
+            to,
+        }
+    }
+
+    /// Create a [`MigrationStep::MapEnumIndices`] step.
+    pub fn map_enum_indices(
+        id: impl Into<String>,
+        map: impl IntoIterator<Item = (i32, i32)>,
+    ) -> Self {
+        Self::MapEnumIndices {
+            id: id.into(),
+            map: map.into_iter().collect(),
+        }
+    }
+
+    /// Create a [`MigrationStep::MapEnumIds`] step.
+    pub fn map_enum_ids<S: Into<String>>(
+        id: impl Into<String>,
+        map: impl IntoIterator<Item = (S, S)>,
+    ) -> Self {
+        Self::MapEnumIds {
+            id: id.into(),
+            map: map
+                .into_iter()
+                .map(|(from, to)| (from.into(), to.into()))
+                .collect(),
+        }
+    }
+
+    /// Create a [`MigrationStep::RenameField`] step.
+    pub fn rename_field(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self::RenameField {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    /// Create a [`MigrationStep::RemoveField`] step.
+    pub fn remove_field(key: impl Into<String>) -> Self {
+        Self::RemoveField { key: key.into() }
+    }
+
+    /// Create a [`MigrationStep::SetField`] step. `value` needs to be serialized as JSON.
+    pub fn set_field(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::SetField {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Apply this step to a state object. Steps for parameters or fields that are not stored in
+    /// the state don't do anything.
+    pub fn apply(&self, state: &mut PluginState) {
+        match self {
+            MigrationStep::RenameParam { from, to } => {
+                if let Some(value) = state.params.remove(from) {
+                    state.params.insert(to.clone(), value);
+                }
+                for mapping in &mut state.midi_mappings {
+                    if mapping.param_id == *from {
+                        mapping.param_id.clone_from(to);
+                    }
+                }
+            }
+            MigrationStep::RescaleParam { id, from, to } => {
+                // This would write NaN or infinite values to the state
+                if is_degenerate_range(*from) {
+                    nih_warn!(
+                        "Skipping the migration step for parameter \"{}\" since it rescales \
+                         from the range {:?}",
+                        id,
+                        from
+                    );
+                    return;
+                }
+
+                let rescale = |value: f32| {
+                    let normalized = (value - from.0) / (from.1 - from.0);
+                    to.0 + (normalized * (to.1 - to.0))
+                };
+
+                match state.params.get_mut(id) {
+                    Some(ParamValue::F32(value)) => *value = rescale(*value),
+                    Some(ParamValue::I32(value)) => *value = rescale(*value as f32).round() as i32,
+                    Some(value) => nih_debug_assert_failure!(
+                        "Cannot rescale parameter \"{}\" with value {:?}",
+                        id,
+                        value
+                    ),
+                    None => (),
+                }
+            }
+            MigrationStep::MapEnumIndices { id, map } => {
+                if let Some(ParamValue::I32(index)) = state.params.get_mut(id) {
+                    if let Some((_, new_index)) = map.iter().find(|(old, _)| old == index) {
+                        *index = *new_index;
+                    }
+                }
+            }
+            MigrationStep::MapEnumIds { id, map } => {
+                if let Some(ParamValue::String(variant_id)) = state.params.get_mut(id) {
+                    if let Some((_, new_id)) = map.iter().find(|(old, _)| old == variant_id) {
+                        variant_id.clone_from(new_id);
+                    }
+                }
+            }
+            MigrationStep::RenameField { from, to } => {
+                if let Some(value) = state.fields.remove(from) {
+                    state.fields.insert(to.clone(), value);
+                }
+            }
+            MigrationStep::RemoveField { key } => {
+                state.fields.remove(key);
+            }
+            MigrationStep::SetField { key, value } => {
+                state.fields.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Whether values cannot be rescaled from this range because it's empty or not finite.
+fn is_degenerate_range(range: (f32, f32)) -> bool {
+    let size = range.1 - range.0;
+    size == 0.0 || !size.is_finite()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::midi::learn::{MidiLearnSource, MidiMapping};
+
+    fn make_state(version: &str) -> PluginState {
+        PluginState {
+            version: String::from(version),
+            params: BTreeMap::from([
+                (String::from("gain"), ParamValue::F32(0.5)),
+                (String::from("steps"), ParamValue::I32(2)),
+                (String::from("mode"), ParamValue::I32(1)),
+                (
+                    String::from("shape"),
+                    ParamValue::String(String::from("sine")),
+                ),
+            ]),
+            fields: BTreeMap::from([(String::from("editor-state"), String::from("{}"))]),
+            midi_mappings: vec![MidiMapping::new(
+                MidiLearnSource::Cc { channel: 0, cc: 7 },
+                "gain",
+            )],
+        }
+    }
+
+    fn make_migrations() -> StateMigrations {
+        StateMigrations::new()
+            .add(
+                "0.2.0",
+                [
+                    MigrationStep::rename_param("gain", "input_gain"),
+                    MigrationStep::rename_field("editor-state", "editor"),
+                ],
+            )
+            .add(
+                "0.3.0-beta.1",
+                [
+                    MigrationStep::rescale_param("steps", (0.0, 4.0), (0.0, 8.0)),
+                    MigrationStep::map_enum_indices("mode", [(0, 1), (1, 0)]),
+                    MigrationStep::map_enum_ids("shape", [("sine", "sin")]),
+                ],
+            )
+            .add("0.3.0", [MigrationStep::set_field("safe-mode", "false")])
+    }
+
+    #[test]
+    fn migrate_old_state() {
+        let mut state = make_state("0.1.5");
+        assert_eq!(make_migrations().apply(&mut state), 6);
+
+        assert_eq!(state.params.get("gain"), None);
+        assert_eq!(state.params["input_gain"], ParamValue::F32(0.5));
+        assert_eq!(state.params["steps"], ParamValue::I32(4));
+        assert_eq!(state.params["mode"], ParamValue::I32(0));
+        assert_eq!(
+            state.params["shape"],
+            ParamValue::String(String::from("sin"))
+        );
+        assert_eq!(state.midi_mappings[0].param_id, "input_gain");
+        assert_eq!(
+            state.fields,
+            BTreeMap::from([
+                (String::from("editor"), String::from("{}")),
+                (String::from("safe-mode"), String::from("false")),
+            ])
+        );
+    }
+
+    #[test]
+    fn skip_applied_migrations() {
+        // Pre-release versions come before the release
+        let mut state = make_state("0.3.0-alpha");
+        assert_eq!(make_migrations().apply(&mut state), 4);
+        assert_eq!(state.params["gain"], ParamValue::F32(0.5));
+
+        let mut state = make_state("0.3.0");
+        assert_eq!(make_migrations().apply(&mut state), 0);
+    }
+
+    #[test]
+    fn missing_version() {
+        let mut state = make_state("");
+        assert_eq!(make_migrations().apply(&mut state), 6);
+    }
+
+    #[test]
+    fn skip_degenerate_rescale() {
+        let mut state = make_state("0.1.0");
+        MigrationStep::RescaleParam {
+            id: String::from("gain"),
+            from: (1.0, 1.0),
+            to: (0.0, 2.0),
+        }
+        .apply(&mut state);
+
+        assert_eq!(state.params["gain"], ParamValue::F32(0.5));
+    }
+
+    #[test]
+    #[should_panic]
+    fn degenerate_rescale_range() {
+        MigrationStep::rescale_param("gain", (1.0, 1.0), (0.0, 2.0));
+    }
+
+    // Outside of tests these migrations would be skipped and a debug assertion failure would be
+    // logged instead
+    #[test]
+    #[should_panic(expected = "Migrations need to be added in order")]
+    fn unordered_migrations() {
+        StateMigrations::new().add("0.2.0", []).add("0.1.0", []);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid migration version")]
+    fn invalid_migration_version() {
+        StateMigrations::new().add("1.0", []);
+    }
+}
@@ -1,13 +1,18 @@
 use backtrace::Backtrace;
+use crossbeam::queue::ArrayQueue;
 use std::cmp;
 use std::marker::PhantomData;
 use std::os::raw::c_char;
+use std::sync::Arc;
 
+use crate::prelude::ContextMenuItem;
 use crate::util::permit_alloc;
 
 pub(crate) mod buffer_management;
 #[cfg(debug_assertions)]
 pub(crate) mod context_checks;
+pub(crate) mod host_log;
+pub(crate) mod midi_learn;
 
 /// The bit that controls flush-to-zero behavior for denormals in 32 and 64-bit floating point
 /// numbers on x86 family architectures. Rust 1.75 deprecated the built in functions for controlling
@@ -50,6 +55,80 @@ pub fn hash_param_id(id: &str) -> u32 {
     hash
 }
 
+/// Collect the actions of a plugin's context menu items in depth-first order. The wrappers assign
+/// action IDs or tags to the menu entries in this same order, so an action's index in this vector
+/// is its ID.
+pub fn context_menu_actions(items: &[ContextMenuItem]) -> Vec<Arc<dyn Fn() + Send + Sync>> {
+    let mut actions = Vec::new();
+    for item in items {
+        match item {
+            ContextMenuItem::Entry { action, .. } | ContextMenuItem::CheckEntry { action, .. } => {
+                actions.push(action.clone())
+            }
+            ContextMenuItem::Separator => (),
+            ContextMenuItem::Submenu { items, .. } => actions.extend(context_menu_actions(items)),
+        }
+    }
+
+    actions
+}
+
+/// A single step in a grouped edit of several parameters. See [`param_batch_steps()`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamBatchStep<T> {
+    /// Begin the automation gesture for this change.
+    Begin(T),
+    /// Set the parameter to the change's new value.
+    Set(T),
+    /// End the automation gesture for this change.
+    End(T),
+}
+
+impl<T: Copy> ParamBatchStep<T> {
+    /// The parameter change this step belongs to. Used for the gesture checks in debug builds.
+    #[cfg(debug_assertions)]
+    pub fn change(&self) -> T {
+        match self {
+            ParamBatchStep::Begin(change)
+            | ParamBatchStep::Set(change)
+            | ParamBatchStep::End(change) => *change,
+        }
+    }
+}
+
+/// The steps needed to apply a [`ParamBatch`][crate::prelude::ParamBatch] in the order they
+/// should be sent to the host. All gestures are started before any of the values are changed, and
+/// they are only ended after all values have been set, so the host can treat this as a single
+/// edit.
+pub fn param_batch_steps<T: Copy>(changes: &[T]) -> impl Iterator<Item = ParamBatchStep<T>> + '_ {
+    let begin_steps = changes.iter().copied().map(ParamBatchStep::Begin);
+    let set_steps = changes.iter().copied().map(ParamBatchStep::Set);
+    let end_steps = changes.iter().copied().map(ParamBatchStep::End);
+
+    begin_steps.chain(set_steps).chain(end_steps)
+}
+
+/// Push all items to a queue if it has enough free space for all of them, or push none of them if
+/// it does not. Returns whether the items were added. If another thread adds items to the queue at
+/// the same time, then some of the items may still be dropped.
+pub fn push_all_or_nothing<T, I>(queue: &ArrayQueue<T>, items: I) -> bool
+where
+    I: IntoIterator<Item = T>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let items = items.into_iter();
+    if queue.capacity() - queue.len() < items.len() {
+        return false;
+    }
+
+    for item in items {
+        let push_successful = queue.push(item).is_ok();
+        nih_debug_assert!(push_successful, "The queue was filled by another thread");
+    }
+
+    true
+}
+
 /// The equivalent of the `strlcpy()` C function. Copy `src` to `dest` as a null-terminated
 /// C-string. If `dest` does not have enough capacity, add a null terminator at the end to prevent
 /// buffer overflows.
@@ -310,4 +389,64 @@ mod miri {
             Ok("Hello")
         );
     }
+
+    #[test]
+    fn param_batch_gesture_order() {
+        let steps: Vec<_> = param_batch_steps(&[(1, 0.25), (2, 0.5)]).collect();
+        assert_eq!(
+            steps,
+            [
+                ParamBatchStep::Begin((1, 0.25)),
+                ParamBatchStep::Begin((2, 0.5)),
+                ParamBatchStep::Set((1, 0.25)),
+                ParamBatchStep::Set((2, 0.5)),
+                ParamBatchStep::End((1, 0.25)),
+                ParamBatchStep::End((2, 0.5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn push_all_or_nothing_full_queue() {
+        let queue = ArrayQueue::new(4);
+        assert!(push_all_or_nothing(&queue, vec![1, 2]));
+
+        // Only two of these three items would fit, so none of them should be added
+        assert!(!push_all_or_nothing(&queue, vec![3, 4, 5]));
+        assert_eq!(queue.len(), 2);
+
+        assert!(push_all_or_nothing(&queue, vec![3, 4]));
+        assert_eq!(
+            std::iter::from_fn(|| queue.pop()).collect::<Vec<_>>(),
+            [1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn context_menu_actions_order() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let last_action = Arc::new(AtomicUsize::new(0));
+        let entry = |label: &str, id: usize| {
+            let last_action = last_action.clone();
+            ContextMenuItem::entry(label, move || last_action.store(id, Ordering::Relaxed))
+        };
+
+        let items = vec![
+            entry("First", 1),
+            ContextMenuItem::Separator,
+            ContextMenuItem::Submenu {
+                label: String::from("Submenu"),
+                items: vec![entry("Second", 2), entry("Third", 3)],
+            },
+            entry("Fourth", 4),
+        ];
+
+        let actions = context_menu_actions(&items);
+        assert_eq!(actions.len(), 4);
+        for (action_id, action) in actions.iter().enumerate() {
+            action();
+            assert_eq!(last_action.load(Ordering::Relaxed), action_id + 1);
+        }
+    }
 }
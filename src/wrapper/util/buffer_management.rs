@@ -1,106 +1,428 @@
 //! Helpers for safely constructing [`Buffer`]s from a plugin host's audio buffers.
 
+use std::any::TypeId;
 use std::num::NonZeroU32;
 use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use crate::prelude::{AudioIOLayout, Buffer};
+use crate::prelude::{
+    AudioIOLayout, AuxiliaryBuffers, Buffer, Plugin, ProcessContext, ProcessStatus,
+};
 
-/// Buffers created using [`create_buffers`]. At some point the main `Plugin::process()` should
-/// probably also take an argument like this instead of main+aux buffers if we also want to provide
-/// access to overflowing input channels for e.g. stereo to mono plugins.
-pub struct Buffers<'a, 'buffer: 'a> {
-    pub main_buffer: &'a mut Buffer<'buffer>,
+/// Buffers created using [`create_buffers`].
+pub struct Buffers<'a, 'buffer: 'a, T = f32> {
+    pub main_buffer: &'a mut Buffer<'buffer, T>,
+    /// A copy of all of the main input's channels. This is needed for asymmetric main IO layouts
+    /// where `main_buffer` does not contain (all of) the main input channels. This only has
+    /// channels if the main input has more channels than the main output.
+    pub main_input: &'a mut Buffer<'buffer, T>,
 
     // We can't use `AuxiliaryBuffers` here directly because we need different lifetimes for `'a`
     // and `'buffer` while `AuxiliaryBuffers` uses the same lifetime for both.
-    pub aux_inputs: &'a mut [Buffer<'buffer>],
-    pub aux_outputs: &'a mut [Buffer<'buffer>],
+    pub aux_inputs: &'a mut [Buffer<'buffer, T>],
+    pub aux_outputs: &'a mut [Buffer<'buffer, T>],
+    /// Whether the ports in `aux_inputs` and `aux_outputs` are active. See
+    /// [`AudioPortsActivation`].
+    pub aux_inputs_active: &'a [bool],
+    pub aux_outputs_active: &'a [bool],
+}
+
+/// Which of the plugin's audio ports the host has activated. Hosts can deactivate ports that are
+/// not connected to anything, like an unused sidechain input. All ports are active by default. The
+/// wrappers store this separately from the [`BufferManager`] since the buffer manager is recreated
+/// when the plugin is activated, and the host can change these values before that happens. Atomics
+/// are used so the state can be read from the audio thread without locking.
+pub struct AudioPortsActivation {
+    main_input: AtomicBool,
+    /// These are sized for the plugin's audio IO layout with the most auxiliary ports, so they
+    /// never need to be resized when the host picks a different layout.
+    aux_inputs: Vec<AtomicBool>,
+    aux_outputs: Vec<AtomicBool>,
+}
+
+/// A sample format a plugin can process audio in. This is implemented for `f32` and `f64`, and it
+/// is used to convert between the host's and the plugin's sample formats when they don't match.
+pub trait Sample: Copy + Default + Send + Sync + 'static {
+    fn from_f64(sample: f64) -> Self;
+    fn to_f64(self) -> f64;
+
+    /// Call either [`Plugin::process()`] or [`Plugin::process_f64()`], depending on the sample
+    /// format.
+    fn process<P: Plugin>(
+        plugin: &mut P,
+        buffer: &mut Buffer<Self>,
+        aux: &mut AuxiliaryBuffers<Self>,
+        context: &mut impl ProcessContext<P>,
+    ) -> ProcessStatus;
+}
+
+impl Sample for f32 {
+    #[inline]
+    fn from_f64(sample: f64) -> Self {
+        sample as f32
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    #[inline]
+    fn process<P: Plugin>(
+        plugin: &mut P,
+        buffer: &mut Buffer<Self>,
+        aux: &mut AuxiliaryBuffers<Self>,
+        context: &mut impl ProcessContext<P>,
+    ) -> ProcessStatus {
+        plugin.process(buffer, aux, context)
+    }
+}
+
+impl Sample for f64 {
+    #[inline]
+    fn from_f64(sample: f64) -> Self {
+        sample
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    #[inline]
+    fn process<P: Plugin>(
+        plugin: &mut P,
+        buffer: &mut Buffer<Self>,
+        aux: &mut AuxiliaryBuffers<Self>,
+        context: &mut impl ProcessContext<P>,
+    ) -> ProcessStatus {
+        plugin.process_f64(buffer, aux, context)
+    }
+}
+
+/// A [`BufferManager`] for the sample format the plugin processes audio in. This is decided by
+/// [`Plugin::PROCESS_F64`].
+pub enum PluginBufferManager {
+    F32(BufferManager<f32>),
+    F64(BufferManager<f64>),
 }
 
 /// A helper for safely creating and initializing [`Buffer`]s based on the host's input and output
-/// buffers.
-pub struct BufferManager {
+/// buffers. `T` is the sample format used by the plugin. The host's buffers may use a different
+/// sample format, in which case the audio is converted to and from `T`.
+pub struct BufferManager<T = f32> {
     // These are the storage backing the fields in `BufferSource`. The wrapper needs to set these
     // values to match the channel pointers provided by the host. If audio buffers are not provided
     // for a bus, then they should be set to `None`. This helper will then copy data to the buffers
     // or fill them with zeroes if there is no data, while also accounting for in-place main IO
     // buffers.
-    main_input_channel_pointers: Option<ChannelPointers>,
-    main_output_channel_pointers: Option<ChannelPointers>,
-    aux_input_channel_pointers: Vec<Option<ChannelPointers>>,
-    aux_output_channel_pointers: Vec<Option<ChannelPointers>>,
+    main_input_channel_pointers: Option<HostChannelPointers>,
+    main_output_channel_pointers: Option<HostChannelPointers>,
+    aux_input_channel_pointers: Vec<Option<HostChannelPointers>>,
+    aux_output_channel_pointers: Vec<Option<HostChannelPointers>>,
+    /// The sample offset passed to the last `create_buffers()` call. Needed to write converted
+    /// outputs back to the host's buffers.
+    sample_offset: usize,
 
     /// The backing buffers that will be filled during `create_buffers`. This `'static` lifetime
     /// will be shortened when returning a reference to these buffers in `create_buffers` to match
     /// the function's lifetime.
-    main_buffer: Buffer<'static>,
+    main_buffer: Buffer<'static, T>,
+    /// Stores the data to back `main_buffer` if the host's main output buffers use a different
+    /// sample format than the plugin. The outputs are then converted and copied back to the host's
+    /// buffers in `write_back_outputs()`.
+    main_output_storage: Vec<Vec<T>>,
 
-    aux_input_buffers: Vec<Buffer<'static>>,
+    main_input_buffer: Buffer<'static, T>,
+    /// Stores the data to back `main_input_buffer`. The main input's data is also copied to
+    /// `main_buffer`, but that buffer may have fewer channels than the main input. This is empty
+    /// unless the main input has more channels than the main output.
+    main_input_storage: Vec<Vec<T>>,
+
+    aux_input_buffers: Vec<Buffer<'static, T>>,
     /// Stores the data to back `aux_input_buffers`. We need to copy the host's auxiliary input
     /// buffers to our own first because the `Buffer` API is designed around mutable buffers, and
     /// the host may reuse its input buffers between plugins.
-    aux_input_storage: Vec<Vec<Vec<f32>>>,
+    aux_input_storage: Vec<Vec<Vec<T>>>,
+
+    aux_output_buffers: Vec<Buffer<'static, T>>,
+    /// The same as `main_output_storage`, but for the auxiliary outputs.
+    aux_output_storage: Vec<Vec<Vec<T>>>,
 
-    aux_output_buffers: Vec<Buffer<'static>>,
+    // These are copied from an `AudioPortsActivation` using `set_ports_activation()`. Inactive
+    // inputs are filled with zeroes instead of being copied from the host's buffers, and inactive
+    // outputs are not cleared.
+    main_input_active: bool,
+    aux_inputs_active: Vec<bool>,
+    aux_outputs_active: Vec<bool>,
 }
 
 // SAFETY: The raw pointers in the `ChannelPointers` fields/vectors are only used as scratch storage
-//         inside of the `create_buffers()` function.
-unsafe impl Send for BufferManager {}
-unsafe impl Sync for BufferManager {}
+//         inside of the `create_buffers()` and `write_back_outputs()` functions.
+unsafe impl<T: Sample> Send for BufferManager<T> {}
+unsafe impl<T: Sample> Sync for BufferManager<T> {}
 
 /// Host data that the plugin's [`Buffer`]s should be created from. Leave these fields as `None`
 /// values
 #[derive(Debug)]
 pub struct BufferSource<'a> {
-    pub main_input_channel_pointers: &'a mut Option<ChannelPointers>,
-    pub main_output_channel_pointers: &'a mut Option<ChannelPointers>,
-    pub aux_input_channel_pointers: &'a mut [Option<ChannelPointers>],
-    pub aux_output_channel_pointers: &'a mut [Option<ChannelPointers>],
+    pub main_input_channel_pointers: &'a mut Option<HostChannelPointers>,
+    pub main_output_channel_pointers: &'a mut Option<HostChannelPointers>,
+    pub aux_input_channel_pointers: &'a mut [Option<HostChannelPointers>],
+    pub aux_output_channel_pointers: &'a mut [Option<HostChannelPointers>],
 }
 
 /// Pointers to raw multichannel audio data for this port.
 #[derive(Debug, Clone, Copy)]
-pub struct ChannelPointers {
-    /// A raw pointer to an array of f32 arrays, containing one array for each channel. `ptrs` must
-    /// contain (at least) `num_channel` `*const f32`s, and each of those inner arrays must contain
-    /// (at least) `num_samples` `f32` values.
-    pub ptrs: NonNull<*mut f32>,
+pub struct ChannelPointers<S = f32> {
+    /// A raw pointer to an array of sample arrays, containing one array for each channel. `ptrs`
+    /// must contain (at least) `num_channel` `*const S`s, and each of those inner arrays must
+    /// contain (at least) `num_samples` `S` values.
+    pub ptrs: NonNull<*mut S>,
     /// The number of audio channels used for this port.
     pub num_channels: usize,
 }
 
-impl BufferManager {
+/// A port's [`ChannelPointers`] in the sample format provided by the host. Can be created from
+/// `ChannelPointers<f32>` and `ChannelPointers<f64>` using [`Into::into()`].
+#[derive(Debug, Clone, Copy)]
+pub enum HostChannelPointers {
+    F32(ChannelPointers<f32>),
+    F64(ChannelPointers<f64>),
+}
+
+impl From<ChannelPointers<f32>> for HostChannelPointers {
+    fn from(channel_pointers: ChannelPointers<f32>) -> Self {
+        HostChannelPointers::F32(channel_pointers)
+    }
+}
+
+impl From<ChannelPointers<f64>> for HostChannelPointers {
+    fn from(channel_pointers: ChannelPointers<f64>) -> Self {
+        HostChannelPointers::F64(channel_pointers)
+    }
+}
+
+impl<S> ChannelPointers<S> {
+    /// Get a slice of `num_samples` samples for a channel, starting at `sample_offset`.
+    ///
+    /// # Safety
+    ///
+    /// `channel_idx` must be in the range `0..num_channels`, the channel must contain at least
+    /// `sample_offset + num_samples` samples, and the slice may not outlive the host's buffers.
+    unsafe fn channel<'a>(
+        &self,
+        channel_idx: usize,
+        sample_offset: usize,
+        num_samples: usize,
+    ) -> &'a mut [S] {
+        let channel_pointer = *self.ptrs.as_ptr().add(channel_idx);
+
+        std::slice::from_raw_parts_mut(channel_pointer.add(sample_offset), num_samples)
+    }
+}
+
+impl HostChannelPointers {
+    /// The number of audio channels used for this port.
+    pub fn num_channels(&self) -> usize {
+        match self {
+            HostChannelPointers::F32(channel_pointers) => channel_pointers.num_channels,
+            HostChannelPointers::F64(channel_pointers) => channel_pointers.num_channels,
+        }
+    }
+
+    /// Get the channel pointers as `T` pointers if the host's buffers contain `T` samples.
+    fn cast<T: Sample>(self) -> Option<ChannelPointers<T>> {
+        let (ptrs, num_channels, sample_type) = match self {
+            HostChannelPointers::F32(channel_pointers) => (
+                channel_pointers.ptrs.cast::<*mut T>(),
+                channel_pointers.num_channels,
+                TypeId::of::<f32>(),
+            ),
+            HostChannelPointers::F64(channel_pointers) => (
+                channel_pointers.ptrs.cast::<*mut T>(),
+                channel_pointers.num_channels,
+                TypeId::of::<f64>(),
+            ),
+        };
+
+        if sample_type == TypeId::of::<T>() {
+            Some(ChannelPointers { ptrs, num_channels })
+        } else {
+            None
+        }
+    }
+
+    /// The address of a channel's first sample starting from `sample_offset`. Used to check whether
+    /// the host processes audio in place.
+    unsafe fn channel_address(&self, channel_idx: usize, sample_offset: usize) -> *const () {
+        match self {
+            HostChannelPointers::F32(channel_pointers) => {
+                (*channel_pointers.ptrs.as_ptr().add(channel_idx)).add(sample_offset) as *const ()
+            }
+            HostChannelPointers::F64(channel_pointers) => {
+                (*channel_pointers.ptrs.as_ptr().add(channel_idx)).add(sample_offset) as *const ()
+            }
+        }
+    }
+}
+
+impl AudioPortsActivation {
+    /// Create the activation state for all of `P`'s audio IO layouts, with all ports marked as
+    /// active.
+    pub fn for_plugin<P: Plugin>() -> Self {
+        let max_aux_inputs = P::AUDIO_IO_LAYOUTS
+            .iter()
+            .map(|layout| layout.aux_input_ports.len())
+            .max()
+            .unwrap_or(0);
+        let max_aux_outputs = P::AUDIO_IO_LAYOUTS
+            .iter()
+            .map(|layout| layout.aux_output_ports.len())
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            main_input: AtomicBool::new(true),
+            aux_inputs: (0..max_aux_inputs).map(|_| AtomicBool::new(true)).collect(),
+            aux_outputs: (0..max_aux_outputs)
+                .map(|_| AtomicBool::new(true))
+                .collect(),
+        }
+    }
+
+    /// Mark all ports as active again. This should be called when the host changes the audio IO
+    /// layout.
+    pub fn reset(&self) {
+        self.main_input.store(true, Ordering::SeqCst);
+        for is_active in self.aux_inputs.iter().chain(&self.aux_outputs) {
+            is_active.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Activate or deactivate a port. `port_index` includes the main port if the layout has one,
+    /// just like the port indices used by CLAP and VST3. Returns `false` if the port does not
+    /// exist. Deactivating the main output has no effect since the plugin always processes audio
+    /// in place.
+    pub fn set_active(
+        &self,
+        audio_io_layout: &AudioIOLayout,
+        is_input: bool,
+        port_index: usize,
+        is_active: bool,
+    ) -> bool {
+        let (has_main_port, aux_ports, num_aux_ports) = if is_input {
+            (
+                audio_io_layout.main_input_channels.is_some(),
+                &self.aux_inputs,
+                audio_io_layout.aux_input_ports.len(),
+            )
+        } else {
+            (
+                audio_io_layout.main_output_channels.is_some(),
+                &self.aux_outputs,
+                audio_io_layout.aux_output_ports.len(),
+            )
+        };
+
+        let aux_port_index = match (has_main_port, port_index) {
+            (true, 0) => {
+                if is_input {
+                    self.main_input.store(is_active, Ordering::SeqCst);
+                }
+
+                return true;
+            }
+            (true, port_index) => port_index - 1,
+            (false, port_index) => port_index,
+        };
+        if aux_port_index >= num_aux_ports {
+            return false;
+        }
+
+        match aux_ports.get(aux_port_index) {
+            Some(aux_port) => {
+                aux_port.store(is_active, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether an auxiliary input port is active. Returns `false` for ports that don't exist.
+    pub fn aux_input_active(&self, audio_io_layout: &AudioIOLayout, index: usize) -> bool {
+        index < audio_io_layout.aux_input_ports.len()
+            && self
+                .aux_inputs
+                .get(index)
+                .is_some_and(|is_active| is_active.load(Ordering::SeqCst))
+    }
+
+    /// Whether an auxiliary output port is active. Returns `false` for ports that don't exist.
+    pub fn aux_output_active(&self, audio_io_layout: &AudioIOLayout, index: usize) -> bool {
+        index < audio_io_layout.aux_output_ports.len()
+            && self
+                .aux_outputs
+                .get(index)
+                .is_some_and(|is_active| is_active.load(Ordering::SeqCst))
+    }
+}
+
+impl PluginBufferManager {
+    /// Initialize a [`BufferManager`] for `P`'s sample format. See
+    /// [`BufferManager::for_audio_io_layout()`].
+    pub fn for_plugin<P: Plugin>(max_buffer_size: usize, audio_io_layout: AudioIOLayout) -> Self {
+        if P::PROCESS_F64 {
+            PluginBufferManager::F64(BufferManager::for_audio_io_layout(
+                max_buffer_size,
+                audio_io_layout,
+            ))
+        } else {
+            PluginBufferManager::F32(BufferManager::for_audio_io_layout(
+                max_buffer_size,
+                audio_io_layout,
+            ))
+        }
+    }
+}
+
+impl<T: Sample> BufferManager<T> {
     /// Initialize managed buffers for a specific audio IO layout. The actual buffers can be set up
     /// using channel pointer data using [`create_buffers()`][Self::create_buffers()].
     pub fn for_audio_io_layout(max_buffer_size: usize, audio_io_layout: AudioIOLayout) -> Self {
-        nih_debug_assert!(
-            audio_io_layout
-                .main_input_channels
-                .map(NonZeroU32::get)
-                .unwrap_or(0)
-                <= audio_io_layout
-                    .main_output_channels
-                    .map(NonZeroU32::get)
-                    .unwrap_or(0),
-            "Stereo-to-mono and other many-to-few audio channel configurations are currently not \
-             supported"
-        );
-
         // The buffers are preallocated so that `create_buffers()` can be called without having to
         // allocate
+        let num_main_output_channels = audio_io_layout
+            .main_output_channels
+            .map(NonZeroU32::get)
+            .unwrap_or(0) as usize;
         let mut main_buffer = Buffer::default();
         unsafe {
             main_buffer.set_slices(0, |output_slices| {
-                output_slices.resize_with(
-                    audio_io_layout
-                        .main_output_channels
-                        .map(NonZeroU32::get)
-                        .unwrap_or(0) as usize,
-                    || &mut [],
-                );
+                output_slices.resize_with(num_main_output_channels, || &mut []);
             })
         };
+        let main_output_storage =
+            vec![vec![T::default(); max_buffer_size]; num_main_output_channels];
+
+        // The main input is only copied when it has more channels than the main output. Otherwise
+        // `main_buffer` already contains all of the main input's channels.
+        let num_main_input_channels = audio_io_layout
+            .main_input_channels
+            .map(NonZeroU32::get)
+            .unwrap_or(0) as usize;
+        let num_main_input_channels = if num_main_input_channels > num_main_output_channels {
+            num_main_input_channels
+        } else {
+            0
+        };
+        let mut main_input_buffer = Buffer::default();
+        unsafe {
+            main_input_buffer.set_slices(0, |slices| {
+                slices.resize_with(num_main_input_channels, || &mut []);
+            })
+        };
+        let main_input_storage = vec![vec![T::default(); max_buffer_size]; num_main_input_channels];
 
         let mut aux_input_buffers = Vec::with_capacity(audio_io_layout.aux_input_ports.len());
         let mut aux_input_storage = Vec::with_capacity(audio_io_layout.aux_input_ports.len());
@@ -114,12 +436,13 @@ impl BufferManager {
 
             aux_input_buffers.push(buffer);
             aux_input_storage.push(vec![
-                vec![0.0; max_buffer_size];
+                vec![T::default(); max_buffer_size];
                 num_channels.get() as usize
             ]);
         }
 
         let mut aux_output_buffers = Vec::with_capacity(audio_io_layout.aux_output_ports.len());
+        let mut aux_output_storage = Vec::with_capacity(audio_io_layout.aux_output_ports.len());
         for num_channels in audio_io_layout.aux_output_ports {
             let mut buffer = Buffer::default();
             unsafe {
@@ -129,6 +452,10 @@ impl BufferManager {
             };
 
             aux_output_buffers.push(buffer);
+            aux_output_storage.push(vec![
+                vec![T::default(); max_buffer_size];
+                num_channels.get() as usize
+            ]);
         }
 
         Self {
@@ -136,13 +463,44 @@ impl BufferManager {
             main_output_channel_pointers: None,
             aux_input_channel_pointers: vec![None; audio_io_layout.aux_input_ports.len()],
             aux_output_channel_pointers: vec![None; audio_io_layout.aux_output_ports.len()],
+            sample_offset: 0,
 
             main_buffer,
+            main_output_storage,
+
+            main_input_buffer,
+            main_input_storage,
 
             aux_input_buffers,
             aux_input_storage,
 
             aux_output_buffers,
+            aux_output_storage,
+
+            main_input_active: true,
+            aux_inputs_active: vec![true; audio_io_layout.aux_input_ports.len()],
+            aux_outputs_active: vec![true; audio_io_layout.aux_output_ports.len()],
+        }
+    }
+
+    /// Copy the ports' activation states from `activation`. Inactive inputs are not copied from
+    /// the host's buffers in [`create_buffers()`][Self::create_buffers()] and they are filled with
+    /// zeroes instead, and inactive auxiliary outputs are not cleared. This is realtime-safe.
+    pub fn set_ports_activation(&mut self, activation: &AudioPortsActivation) {
+        self.main_input_active = activation.main_input.load(Ordering::SeqCst);
+        for (is_active, new_is_active) in self
+            .aux_inputs_active
+            .iter_mut()
+            .zip(&activation.aux_inputs)
+        {
+            *is_active = new_is_active.load(Ordering::SeqCst);
+        }
+        for (is_active, new_is_active) in self
+            .aux_outputs_active
+            .iter_mut()
+            .zip(&activation.aux_outputs)
+        {
+            *is_active = new_is_active.load(Ordering::SeqCst);
         }
     }
 
@@ -150,7 +508,13 @@ impl BufferManager {
     /// created buffers that can be passed to `Plugin::process()`. This accounts for in-place main
     /// IO, missing channel pointers, null pointers, and mismatching channel counts. All
     /// uninitialized buffer data (aux outputs, and main output channels with no matching input
-    /// channel) are filled with zeroes.
+    /// channel) are filled with zeroes. If the main input has more channels than the main output,
+    /// then the excess channels are only available through [`Buffers::main_input`].
+    ///
+    /// If the host's buffers use a different sample format than `T`, then the inputs are converted
+    /// to `T` and the plugin's outputs are written to internal storage instead. Those outputs are
+    /// copied back to the host's buffers by [`write_back_outputs()`][Self::write_back_outputs()],
+    /// which needs to be called after processing the returned buffers.
     ///
     /// `sample_offset` and `num_samples` can be used to slice a set of host channel pointers for
     /// sample accurate automation. If any of the outputs are missing because the host hasn't
@@ -169,7 +533,7 @@ impl BufferManager {
         sample_offset: usize,
         num_samples: usize,
         set_buffer_sources: impl FnOnce(&mut BufferSource),
-    ) -> Buffers<'a, 'buffer> {
+    ) -> Buffers<'a, 'buffer, T> {
         // Make sure the caller can't forget to unset previously set values
         self.main_input_channel_pointers = None;
         self.main_output_channel_pointers = None;
@@ -181,202 +545,389 @@ impl BufferManager {
             aux_input_channel_pointers: &mut self.aux_input_channel_pointers,
             aux_output_channel_pointers: &mut self.aux_output_channel_pointers,
         });
+        self.sample_offset = sample_offset;
 
-        // The main buffer points directly to the main output pointers
-        self.main_buffer.set_slices(num_samples, |output_slices| {
-            match self.main_output_channel_pointers {
-                Some(output_channel_pointers) => {
-                    nih_debug_assert_eq!(output_slices.len(), output_channel_pointers.num_channels);
-                    for (channel_idx, output_slice) in output_slices
-                        .iter_mut()
-                        .enumerate()
-                        .take(output_channel_pointers.num_channels)
-                    {
-                        let output_channel_pointer =
-                            output_channel_pointers.ptrs.as_ptr().add(channel_idx);
-
-                        *output_slice = std::slice::from_raw_parts_mut(
-                            (*output_channel_pointer).add(sample_offset),
-                            num_samples,
-                        );
-                    }
+        // The host's buffers for inactive inputs are never read from
+        let main_input_channel_pointers = if self.main_input_active {
+            self.main_input_channel_pointers
+        } else {
+            None
+        };
 
-                    // If the caller/host should have provided buffer pointers but didn't then we
-                    // must get rid of any dangling slices
-                    output_slices[output_channel_pointers.num_channels..].fill_with(|| &mut [])
-                }
-                None => {
-                    nih_debug_assert_eq!(output_slices.len(), 0);
+        // If the main output has fewer channels than the main input, then the main input is also
+        // copied to our own storage so the plugin can access all of the main input's channels. This
+        // needs to happen before touching the main output as the host may process the main IO in
+        // place. The storage is empty for all other layouts.
+        if !self.main_input_storage.is_empty() {
+            copy_input_channels(
+                main_input_channel_pointers,
+                &mut self.main_input_storage,
+                &mut self.main_input_buffer,
+                sample_offset,
+                num_samples,
+            );
+        }
 
-                    // Same as above
-                    output_slices.fill_with(|| &mut [])
-                }
-            }
-        });
+        // The main buffer points directly to the main output pointers, unless the host uses a
+        // different sample format
+        set_output_slices(
+            self.main_output_channel_pointers,
+            &mut self.main_output_storage,
+            &mut self.main_buffer,
+            sample_offset,
+            num_samples,
+        );
 
         // Since NIH-plug processes audio in-place, main input data needs to be copied to the main
         // output buffers
         if let (Some(input_channel_pointers), Some(output_channel_pointers)) = (
-            self.main_input_channel_pointers,
+            main_input_channel_pointers,
             self.main_output_channel_pointers,
         ) {
+            let num_input_channels = input_channel_pointers.num_channels();
+            let num_output_channels = output_channel_pointers.num_channels();
             self.main_buffer.set_slices(num_samples, |output_slices| {
                 for (channel_idx, output_slice) in output_slices
                     .iter_mut()
                     .enumerate()
-                    .take(input_channel_pointers.num_channels)
+                    .take(num_input_channels)
                 {
-                    let input_channel_pointer =
-                        *input_channel_pointers.ptrs.as_ptr().add(channel_idx);
-                    debug_assert!(channel_idx < output_channel_pointers.num_channels);
-                    let output_channel_pointer =
-                        *output_channel_pointers.ptrs.as_ptr().add(channel_idx);
+                    debug_assert!(channel_idx < num_output_channels);
 
                     // If the host processes the main IO out of place then the inputs need to be
                     // copied to the output buffers. Otherwise the input should already be there.
-                    if input_channel_pointer != output_channel_pointer {
-                        output_slice.copy_from_slice(std::slice::from_raw_parts_mut(
-                            input_channel_pointer.add(sample_offset),
-                            num_samples,
-                        ))
+                    // Converted outputs never alias the host's input buffers.
+                    if input_channel_pointers.channel_address(channel_idx, sample_offset)
+                        != output_slice.as_ptr() as *const ()
+                    {
+                        copy_from_host(
+                            output_slice,
+                            input_channel_pointers,
+                            channel_idx,
+                            sample_offset,
+                        );
                     }
                 }
             });
 
             // Any excess channels will need to be filled with zeroes since they'd otherwise point
             // to whatever was left in the buffer
-            if input_channel_pointers.num_channels < output_channel_pointers.num_channels {
+            if num_input_channels < num_output_channels {
                 self.main_buffer.set_slices(num_samples, |output_slices| {
-                    for slice in &mut output_slices[input_channel_pointers.num_channels..] {
-                        slice.fill(0.0);
+                    for slice in &mut output_slices[num_input_channels..] {
+                        slice.fill(T::default());
                     }
                 });
             }
+        } else if !self.main_input_active {
+            // When the main input has been deactivated the output buffers would otherwise contain
+            // whatever the host left there
+            self.main_buffer.set_slices(num_samples, |output_slices| {
+                for slice in output_slices.iter_mut() {
+                    slice.fill(T::default());
+                }
+            });
         }
 
         // Because NIH-plug's `Buffer` type is geared around in-place processing, auxiliary inputs
         // need to be copied to our own buffers first (backed by the 'storage' vectors on this
         // object). That way the plugin can modify those buffers like any other buffers.
-        for (input_channel_pointers, (input_storage, input_buffer)) in
-            self.aux_input_channel_pointers.iter().zip(
+        for ((input_channel_pointers, is_active), (input_storage, input_buffer)) in self
+            .aux_input_channel_pointers
+            .iter()
+            .zip(self.aux_inputs_active.iter())
+            .zip(
                 self.aux_input_storage
                     .iter_mut()
                     .zip(self.aux_input_buffers.iter_mut()),
             )
         {
             // Since these buffers are backed by our own storage, we can fill them with zeroes if
-            // the pointers are missing for whatever reason that might be
-            nih_debug_assert!(input_channel_pointers.is_some());
-            match input_channel_pointers {
-                Some(input_channel_pointers) => {
-                    nih_debug_assert_eq!(input_channel_pointers.num_channels, input_storage.len());
-                    for (channel_idx, channel) in input_storage
-                        .iter_mut()
-                        .enumerate()
-                        .take(input_channel_pointers.num_channels)
-                    {
-                        let input_channel_pointer =
-                            input_channel_pointers.ptrs.as_ptr().add(channel_idx);
-
-                        nih_debug_assert!(num_samples <= channel.capacity());
-                        channel.resize(num_samples, 0.0);
-                        channel.copy_from_slice(std::slice::from_raw_parts_mut(
-                            (*input_channel_pointer).add(sample_offset),
-                            num_samples,
-                        ))
-                    }
+            // the pointers are missing for whatever reason that might be. The host may not provide
+            // any buffers for inactive ports.
+            nih_debug_assert!(!is_active || input_channel_pointers.is_some());
+            copy_input_channels(
+                if *is_active {
+                    *input_channel_pointers
+                } else {
+                    None
+                },
+                input_storage,
+                input_buffer,
+                sample_offset,
+                num_samples,
+            );
+        }
 
-                    // In case we were provided too few channels we'll fill the rest with zeroes to
-                    // avoid unexpected situations
-                    for channel in input_storage
-                        .iter_mut()
-                        .skip(input_channel_pointers.num_channels)
-                    {
-                        channel.fill(0.0);
-                    }
-                }
-                None => {
-                    for channel in input_storage.iter_mut() {
-                        channel.fill(0.0);
-                    }
-                }
+        // The auxiliary output buffers can point directly to the host's buffers. This logic is the
+        // same as the main outputs, minus the copying of input cdata
+        for ((output_channel_pointers, is_active), (output_storage, output_buffer)) in self
+            .aux_output_channel_pointers
+            .iter()
+            .zip(self.aux_outputs_active.iter())
+            .zip(
+                self.aux_output_storage
+                    .iter_mut()
+                    .zip(self.aux_output_buffers.iter_mut()),
+            )
+        {
+            // The host may not provide any buffers for inactive ports, and nothing will read from
+            // them anyways
+            if !is_active && output_channel_pointers.is_none() {
+                output_buffer.set_slices(num_samples, |output_slices| {
+                    output_slices.fill_with(|| &mut [])
+                });
+                continue;
             }
 
-            input_buffer.set_slices(num_samples, |input_slices| {
-                // Since we initialized both `input_buffer` and `input_storage` this invariant
-                // should never fail unless we made an error ourselves
-                debug_assert_eq!(input_slices.len(), input_storage.len());
+            set_output_slices(
+                *output_channel_pointers,
+                output_storage,
+                output_buffer,
+                sample_offset,
+                num_samples,
+            );
 
-                for (channel_slice, channel_storage) in
-                    input_slices.iter_mut().zip(input_storage.iter_mut())
-                {
-                    // SAFETY: `channel_storage` is no longer used accessed directly after this
-                    *channel_slice = &mut *(channel_storage.as_mut_slice() as *mut [f32]);
+            // The host may not zero out the buffers, and assume the plugin always write something
+            // there
+            if *is_active {
+                for output_slice in output_buffer.as_slice() {
+                    output_slice.fill(T::default());
                 }
-            });
+            }
         }
 
-        // The auxiliary output buffers can point directly to the host's buffers. This logic is the
-        // same as the main outputs, minus the copying of input cdata
+        // SAFETY: The 'static lifetimes on the objects are needed so we can store the buffers.
+        //         Their actual lifetimes are `'a`, so we need to shrink them here. The contents are
+        //         valid for as long as the returned object is borrowed.
+        Buffers {
+            main_buffer: &mut *(&mut self.main_buffer as *mut Buffer<'static, T>
+                as *mut Buffer<'buffer, T>),
+            main_input: &mut *(&mut self.main_input_buffer as *mut Buffer<'static, T>
+                as *mut Buffer<'buffer, T>),
+            aux_inputs: &mut *(self.aux_input_buffers.as_mut_slice() as *mut [Buffer<'static, T>]
+                as *mut [Buffer<'buffer, T>]),
+            aux_outputs: &mut *(self.aux_output_buffers.as_mut_slice()
+                as *mut [Buffer<'static, T>]
+                as *mut [Buffer<'buffer, T>]),
+            aux_inputs_active: &self.aux_inputs_active,
+            aux_outputs_active: &self.aux_outputs_active,
+        }
+    }
+
+    /// Copy the plugin's outputs back to the host's output buffers for any outputs where the host
+    /// uses a different sample format than the plugin. This needs to be called after the plugin has
+    /// processed the buffers returned by [`create_buffers()`][Self::create_buffers()]. This does
+    /// not do anything if the host and the plugin use the same sample format.
+    ///
+    /// # Safety
+    ///
+    /// The `ChannelPointers` provided during the last `create_buffers()` call must still be valid
+    /// to write to, and the buffers returned by that call may no longer be used.
+    pub unsafe fn write_back_outputs(&mut self) {
+        write_back_output_channels(
+            self.main_output_channel_pointers,
+            &self.main_buffer,
+            self.sample_offset,
+        );
+
         for (output_channel_pointers, output_buffer) in self
             .aux_output_channel_pointers
             .iter()
-            .zip(self.aux_output_buffers.iter_mut())
+            .zip(self.aux_output_buffers.iter())
         {
-            output_buffer.set_slices(num_samples, |output_slices| {
-                match output_channel_pointers {
-                    Some(output_channel_pointers) => {
-                        nih_debug_assert_eq!(
-                            output_slices.len(),
-                            output_channel_pointers.num_channels
-                        );
-                        for (channel_idx, output_slice) in output_slices
-                            .iter_mut()
-                            .enumerate()
-                            .take(output_channel_pointers.num_channels)
-                        {
-                            let output_channel_pointer =
-                                output_channel_pointers.ptrs.as_ptr().add(channel_idx);
-
-                            *output_slice = std::slice::from_raw_parts_mut(
-                                (*output_channel_pointer).add(sample_offset),
-                                num_samples,
-                            );
-
-                            // The host may not zero out the buffers, and assume the plugin always
-                            // write something there
-                            output_slice.fill(0.0);
-                        }
+            write_back_output_channels(*output_channel_pointers, output_buffer, self.sample_offset);
+        }
+    }
+}
 
-                        // If the caller/host should have provided buffer pointers but didn't then
-                        // we must get rid of any dangling slices
-                        output_slices[output_channel_pointers.num_channels..].fill_with(|| &mut [])
+/// Point `buffer` to the host's output channels for a port. If the host's buffers use a different
+/// sample format than the plugin, then `buffer` will point to `storage` instead, and the outputs
+/// need to be written back to the host's buffers using [`write_back_output_channels()`] after
+/// processing. Missing channels are replaced by empty slices.
+///
+/// # Safety
+///
+/// Every channel in `output_channel_pointers` must contain at least `sample_offset + num_samples`
+/// samples. `storage` may not be accessed directly while `buffer` is being used.
+unsafe fn set_output_slices<T: Sample>(
+    output_channel_pointers: Option<HostChannelPointers>,
+    storage: &mut [Vec<T>],
+    buffer: &mut Buffer<'static, T>,
+    sample_offset: usize,
+    num_samples: usize,
+) {
+    buffer.set_slices(num_samples, |output_slices| match output_channel_pointers {
+        Some(output_channel_pointers) => {
+            let num_channels = output_channel_pointers.num_channels();
+            nih_debug_assert_eq!(output_slices.len(), num_channels);
+            match output_channel_pointers.cast::<T>() {
+                Some(output_channel_pointers) => {
+                    for (channel_idx, output_slice) in
+                        output_slices.iter_mut().enumerate().take(num_channels)
+                    {
+                        *output_slice = output_channel_pointers.channel(
+                            channel_idx,
+                            sample_offset,
+                            num_samples,
+                        );
                     }
-                    None => {
-                        nih_debug_assert_eq!(output_slices.len(), 0);
+                }
+                None => {
+                    for (output_slice, channel_storage) in output_slices
+                        .iter_mut()
+                        .zip(storage.iter_mut())
+                        .take(num_channels)
+                    {
+                        nih_debug_assert!(num_samples <= channel_storage.capacity());
+                        channel_storage.resize(num_samples, T::default());
 
-                        // Same as above
-                        output_slices.fill_with(|| &mut [])
+                        // SAFETY: `channel_storage` is no longer used accessed directly after this
+                        *output_slice = &mut *(channel_storage.as_mut_slice() as *mut [T]);
                     }
                 }
-            });
+            }
+
+            // If the caller/host should have provided buffer pointers but didn't then we must get
+            // rid of any dangling slices
+            output_slices[num_channels..].fill_with(|| &mut [])
         }
+        None => {
+            nih_debug_assert_eq!(output_slices.len(), 0);
 
-        // SAFETY: The 'static lifetimes on the objects are needed so we can store the buffers.
-        //         Their actual lifetimes are `'a`, so we need to shrink them here. The contents are
-        //         valid for as long as the returned object is borrowed.
-        std::mem::transmute::<Buffers<'a, 'static>, Buffers<'a, 'buffer>>(Buffers {
-            main_buffer: &mut self.main_buffer,
-            aux_inputs: &mut self.aux_input_buffers,
-            aux_outputs: &mut self.aux_output_buffers,
-        })
+            // Same as above
+            output_slices.fill_with(|| &mut [])
+        }
+    });
+}
+
+/// Convert and copy the outputs stored in `buffer` to the host's output channels for a port if
+/// `buffer` does not already point to the host's buffers. See [`set_output_slices()`].
+///
+/// # Safety
+///
+/// Every channel in `output_channel_pointers` must contain at least `sample_offset +
+/// buffer.samples()` samples.
+unsafe fn write_back_output_channels<T: Sample>(
+    output_channel_pointers: Option<HostChannelPointers>,
+    buffer: &Buffer<T>,
+    sample_offset: usize,
+) {
+    let output_channel_pointers = match output_channel_pointers {
+        Some(output_channel_pointers) if output_channel_pointers.cast::<T>().is_none() => {
+            output_channel_pointers
+        }
+        _ => return,
+    };
+
+    for (channel_idx, output_slice) in buffer
+        .as_slice_immutable()
+        .iter()
+        .enumerate()
+        .take(output_channel_pointers.num_channels())
+    {
+        match output_channel_pointers {
+            HostChannelPointers::F32(channel_pointers) => convert_samples(
+                output_slice,
+                channel_pointers.channel(channel_idx, sample_offset, output_slice.len()),
+            ),
+            HostChannelPointers::F64(channel_pointers) => convert_samples(
+                output_slice,
+                channel_pointers.channel(channel_idx, sample_offset, output_slice.len()),
+            ),
+        }
+    }
+}
+
+/// Copy the host's input channels for a port to `storage`, and point `buffer` to that storage. Any
+/// channels missing from `input_channel_pointers` are filled with zeroes.
+///
+/// # Safety
+///
+/// Every channel in `input_channel_pointers` must contain at least `sample_offset + num_samples`
+/// samples. `storage` may not be accessed directly while `buffer` is being used.
+unsafe fn copy_input_channels<T: Sample>(
+    input_channel_pointers: Option<HostChannelPointers>,
+    storage: &mut [Vec<T>],
+    buffer: &mut Buffer<'static, T>,
+    sample_offset: usize,
+    num_samples: usize,
+) {
+    for channel in storage.iter_mut() {
+        nih_debug_assert!(num_samples <= channel.capacity());
+        channel.resize(num_samples, T::default());
+    }
+
+    match input_channel_pointers {
+        Some(input_channel_pointers) => {
+            let num_channels = input_channel_pointers.num_channels();
+            nih_debug_assert_eq!(num_channels, storage.len());
+            for (channel_idx, channel) in storage.iter_mut().enumerate().take(num_channels) {
+                copy_from_host(channel, input_channel_pointers, channel_idx, sample_offset);
+            }
+
+            // In case we were provided too few channels we'll fill the rest with zeroes to avoid
+            // unexpected situations
+            for channel in storage.iter_mut().skip(num_channels) {
+                channel.fill(T::default());
+            }
+        }
+        None => {
+            for channel in storage.iter_mut() {
+                channel.fill(T::default());
+            }
+        }
+    }
+
+    buffer.set_slices(num_samples, |input_slices| {
+        // Since we initialized both `buffer` and `storage` this invariant should never fail unless
+        // we made an error ourselves
+        debug_assert_eq!(input_slices.len(), storage.len());
+
+        for (channel_slice, channel_storage) in input_slices.iter_mut().zip(storage.iter_mut()) {
+            // SAFETY: `channel_storage` is no longer used accessed directly after this
+            *channel_slice = &mut *(channel_storage.as_mut_slice() as *mut [T]);
+        }
+    });
+}
+
+/// Fill `dest` with one of the host's channels, starting at `sample_offset`. The samples are
+/// converted to the plugin's sample format if needed.
+///
+/// # Safety
+///
+/// The channel must contain at least `sample_offset + dest.len()` samples.
+unsafe fn copy_from_host<T: Sample>(
+    dest: &mut [T],
+    channel_pointers: HostChannelPointers,
+    channel_idx: usize,
+    sample_offset: usize,
+) {
+    match channel_pointers {
+        HostChannelPointers::F32(channel_pointers) => convert_samples(
+            channel_pointers.channel(channel_idx, sample_offset, dest.len()),
+            dest,
+        ),
+        HostChannelPointers::F64(channel_pointers) => convert_samples(
+            channel_pointers.channel(channel_idx, sample_offset, dest.len()),
+            dest,
+        ),
+    }
+}
+
+/// Copy `src` to `dest` while converting between sample formats. This compiles down to a regular
+/// copy when both sample formats are the same.
+fn convert_samples<S: Sample, T: Sample>(src: &[S], dest: &mut [T]) {
+    nih_debug_assert_eq!(src.len(), dest.len());
+    for (dest_sample, src_sample) in dest.iter_mut().zip(src) {
+        *dest_sample = T::from_f64(src_sample.to_f64());
     }
 }
 
 #[cfg(any(miri, test))]
 mod miri {
     use super::*;
-    use crate::prelude::{new_nonzero_u32, PortNames};
+    use crate::prelude::{new_nonzero_u32, PortChannelLayouts, PortNames};
 
     const BUFFER_SIZE: usize = 512;
     const NUM_MAIN_INPUT_CHANNELS: usize = 1;
@@ -391,6 +942,7 @@ mod miri {
         aux_input_ports: &[new_nonzero_u32(NUM_AUX_CHANNELS as u32); NUM_AUX_PORTS],
         aux_output_ports: &[new_nonzero_u32(NUM_AUX_CHANNELS as u32); NUM_AUX_PORTS],
         names: PortNames::const_default(),
+        channel_layouts: PortChannelLayouts::const_default(),
     };
 
     #[test]
@@ -427,27 +979,37 @@ mod miri {
 
         // The actual buffer management here works the same as in the JACK backend. See that
         // implementation for more information.
-        let mut buffer_manager = BufferManager::for_audio_io_layout(BUFFER_SIZE, AUDIO_IO_LAYOUT);
+        let mut buffer_manager: BufferManager =
+            BufferManager::for_audio_io_layout(BUFFER_SIZE, AUDIO_IO_LAYOUT);
         let buffers = unsafe {
             buffer_manager.create_buffers(0, BUFFER_SIZE, |buffer_sources| {
-                *buffer_sources.main_output_channel_pointers = Some(ChannelPointers {
-                    ptrs: NonNull::new(main_io_channel_pointers.as_mut_ptr()).unwrap(),
-                    num_channels: main_io_channel_pointers.len(),
-                });
-                *buffer_sources.main_input_channel_pointers = Some(ChannelPointers {
-                    ptrs: NonNull::new(main_io_channel_pointers.as_mut_ptr()).unwrap(),
-                    num_channels: NUM_MAIN_INPUT_CHANNELS.min(main_io_channel_pointers.len()),
-                });
+                *buffer_sources.main_output_channel_pointers = Some(
+                    ChannelPointers {
+                        ptrs: NonNull::new(main_io_channel_pointers.as_mut_ptr()).unwrap(),
+                        num_channels: main_io_channel_pointers.len(),
+                    }
+                    .into(),
+                );
+                *buffer_sources.main_input_channel_pointers = Some(
+                    ChannelPointers {
+                        ptrs: NonNull::new(main_io_channel_pointers.as_mut_ptr()).unwrap(),
+                        num_channels: NUM_MAIN_INPUT_CHANNELS.min(main_io_channel_pointers.len()),
+                    }
+                    .into(),
+                );
 
                 for (input_source_channel_pointers, input_channel_pointers) in buffer_sources
                     .aux_input_channel_pointers
                     .iter_mut()
                     .zip(aux_input_channel_pointers.iter_mut())
                 {
-                    *input_source_channel_pointers = Some(ChannelPointers {
-                        ptrs: NonNull::new(input_channel_pointers.as_mut_ptr()).unwrap(),
-                        num_channels: input_channel_pointers.len(),
-                    });
+                    *input_source_channel_pointers = Some(
+                        ChannelPointers {
+                            ptrs: NonNull::new(input_channel_pointers.as_mut_ptr()).unwrap(),
+                            num_channels: input_channel_pointers.len(),
+                        }
+                        .into(),
+                    );
                 }
 
                 for (output_source_channel_pointers, output_channel_pointers) in buffer_sources
@@ -455,10 +1017,13 @@ mod miri {
                     .iter_mut()
                     .zip(aux_output_channel_pointers.iter_mut())
                 {
-                    *output_source_channel_pointers = Some(ChannelPointers {
-                        ptrs: NonNull::new(output_channel_pointers.as_mut_ptr()).unwrap(),
-                        num_channels: output_channel_pointers.len(),
-                    });
+                    *output_source_channel_pointers = Some(
+                        ChannelPointers {
+                            ptrs: NonNull::new(output_channel_pointers.as_mut_ptr()).unwrap(),
+                            num_channels: output_channel_pointers.len(),
+                        }
+                        .into(),
+                    );
                 }
             })
         };
@@ -501,4 +1066,317 @@ mod miri {
             }
         }
     }
+
+    #[test]
+    fn inactive_aux_input() {
+        let mut aux_input_storage =
+            vec![vec![vec![1.0f32; BUFFER_SIZE]; NUM_AUX_CHANNELS]; NUM_AUX_PORTS];
+        let mut aux_input_channel_pointers: Vec<Vec<*mut f32>> = aux_input_storage
+            .iter_mut()
+            .map(|aux_input_storage| {
+                aux_input_storage
+                    .iter_mut()
+                    .map(|channel_slice| channel_slice.as_mut_ptr())
+                    .collect()
+            })
+            .collect();
+
+        let activation = AudioPortsActivation {
+            main_input: AtomicBool::new(true),
+            aux_inputs: (0..NUM_AUX_PORTS).map(|_| AtomicBool::new(true)).collect(),
+            aux_outputs: (0..NUM_AUX_PORTS).map(|_| AtomicBool::new(true)).collect(),
+        };
+        // The port indices include the main input
+        assert!(activation.set_active(&AUDIO_IO_LAYOUT, true, 1, false));
+        assert!(!activation.set_active(&AUDIO_IO_LAYOUT, true, NUM_AUX_PORTS + 1, false));
+        assert!(!activation.aux_input_active(&AUDIO_IO_LAYOUT, 0));
+        assert!(activation.aux_input_active(&AUDIO_IO_LAYOUT, 1));
+
+        let mut buffer_manager: BufferManager =
+            BufferManager::for_audio_io_layout(BUFFER_SIZE, AUDIO_IO_LAYOUT);
+        buffer_manager.set_ports_activation(&activation);
+        let buffers = unsafe {
+            buffer_manager.create_buffers(0, BUFFER_SIZE, |buffer_sources| {
+                for (input_source_channel_pointers, input_channel_pointers) in buffer_sources
+                    .aux_input_channel_pointers
+                    .iter_mut()
+                    .zip(aux_input_channel_pointers.iter_mut())
+                {
+                    *input_source_channel_pointers = Some(
+                        ChannelPointers {
+                            ptrs: NonNull::new(input_channel_pointers.as_mut_ptr()).unwrap(),
+                            num_channels: input_channel_pointers.len(),
+                        }
+                        .into(),
+                    );
+                }
+            })
+        };
+
+        assert_eq!(buffers.aux_inputs_active, [false, true]);
+        assert_eq!(buffers.aux_outputs_active, [true, true]);
+        for (buffer, expected) in buffers.aux_inputs.iter_mut().zip([0.0, 1.0]) {
+            for channel_samples in buffer.iter_samples() {
+                for sample in channel_samples {
+                    assert!(*sample == expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn asymmetric_main_io() {
+        const NUM_INPUT_CHANNELS: usize = 2;
+        const NUM_OUTPUT_CHANNELS: usize = 1;
+        const STEREO_TO_MONO_LAYOUT: AudioIOLayout = AudioIOLayout {
+            main_input_channels: Some(new_nonzero_u32(NUM_INPUT_CHANNELS as u32)),
+            main_output_channels: Some(new_nonzero_u32(NUM_OUTPUT_CHANNELS as u32)),
+            ..AudioIOLayout::const_default()
+        };
+
+        // The main input channels contain 1.0 and 2.0, respectively
+        let mut main_input_storage: Vec<Vec<f32>> = (0..NUM_INPUT_CHANNELS)
+            .map(|channel_idx| vec![(channel_idx + 1) as f32; BUFFER_SIZE])
+            .collect();
+        let mut main_output_storage = vec![vec![0.0f32; BUFFER_SIZE]; NUM_OUTPUT_CHANNELS];
+
+        let mut main_input_channel_pointers: Vec<*mut f32> = main_input_storage
+            .iter_mut()
+            .map(|channel_slice| channel_slice.as_mut_ptr())
+            .collect();
+        let mut main_output_channel_pointers: Vec<*mut f32> = main_output_storage
+            .iter_mut()
+            .map(|channel_slice| channel_slice.as_mut_ptr())
+            .collect();
+
+        let mut buffer_manager: BufferManager =
+            BufferManager::for_audio_io_layout(BUFFER_SIZE, STEREO_TO_MONO_LAYOUT);
+        let buffers = unsafe {
+            buffer_manager.create_buffers(0, BUFFER_SIZE, |buffer_sources| {
+                *buffer_sources.main_input_channel_pointers = Some(
+                    ChannelPointers {
+                        ptrs: NonNull::new(main_input_channel_pointers.as_mut_ptr()).unwrap(),
+                        num_channels: main_input_channel_pointers.len(),
+                    }
+                    .into(),
+                );
+                *buffer_sources.main_output_channel_pointers = Some(
+                    ChannelPointers {
+                        ptrs: NonNull::new(main_output_channel_pointers.as_mut_ptr()).unwrap(),
+                        num_channels: main_output_channel_pointers.len(),
+                    }
+                    .into(),
+                );
+            })
+        };
+
+        // The main buffer only contains the first input channel, but all of the input channels
+        // should be available through the main input buffer
+        assert_eq!(buffers.main_buffer.channels(), NUM_OUTPUT_CHANNELS);
+        assert_eq!(buffers.main_input.channels(), NUM_INPUT_CHANNELS);
+        for (channel_idx, channel) in buffers.main_input.as_slice().iter().enumerate() {
+            for sample in channel.iter() {
+                assert!(*sample == (channel_idx + 1) as f32);
+            }
+        }
+
+        // Writing to the main input buffer should not affect the host's buffers
+        for channel_samples in buffers.main_input.iter_samples() {
+            for sample in channel_samples {
+                *sample = 0.0;
+            }
+        }
+
+        for channel in main_output_storage.iter() {
+            for sample in channel {
+                assert!(*sample == 1.0);
+            }
+        }
+        for (channel_idx, channel) in main_input_storage.iter().enumerate() {
+            for sample in channel {
+                assert!(*sample == (channel_idx + 1) as f32);
+            }
+        }
+    }
+
+    #[test]
+    fn asymmetric_main_io_in_place() {
+        const NUM_INPUT_CHANNELS: usize = 2;
+        const NUM_OUTPUT_CHANNELS: usize = 1;
+        const STEREO_TO_MONO_LAYOUT: AudioIOLayout = AudioIOLayout {
+            main_input_channels: Some(new_nonzero_u32(NUM_INPUT_CHANNELS as u32)),
+            main_output_channels: Some(new_nonzero_u32(NUM_OUTPUT_CHANNELS as u32)),
+            ..AudioIOLayout::const_default()
+        };
+
+        // The host processes the first channel in place
+        let mut main_io_storage: Vec<Vec<f32>> = (0..NUM_INPUT_CHANNELS)
+            .map(|channel_idx| vec![(channel_idx + 1) as f32; BUFFER_SIZE])
+            .collect();
+        let mut main_io_channel_pointers: Vec<*mut f32> = main_io_storage
+            .iter_mut()
+            .map(|channel_slice| channel_slice.as_mut_ptr())
+            .collect();
+
+        let mut buffer_manager: BufferManager =
+            BufferManager::for_audio_io_layout(BUFFER_SIZE, STEREO_TO_MONO_LAYOUT);
+        let buffers = unsafe {
+            buffer_manager.create_buffers(0, BUFFER_SIZE, |buffer_sources| {
+                let ptrs = NonNull::new(main_io_channel_pointers.as_mut_ptr()).unwrap();
+                *buffer_sources.main_input_channel_pointers = Some(
+                    ChannelPointers {
+                        ptrs,
+                        num_channels: NUM_INPUT_CHANNELS,
+                    }
+                    .into(),
+                );
+                *buffer_sources.main_output_channel_pointers = Some(
+                    ChannelPointers {
+                        ptrs,
+                        num_channels: NUM_OUTPUT_CHANNELS,
+                    }
+                    .into(),
+                );
+            })
+        };
+
+        // The main input is copied the same way as when the host processes audio out of place
+        assert_eq!(buffers.main_buffer.channels(), NUM_OUTPUT_CHANNELS);
+        assert_eq!(buffers.main_input.channels(), NUM_INPUT_CHANNELS);
+        for (channel_idx, channel) in buffers.main_input.as_slice().iter().enumerate() {
+            for sample in channel.iter() {
+                assert!(*sample == (channel_idx + 1) as f32);
+            }
+        }
+    }
+
+    #[test]
+    fn symmetric_main_io() {
+        const NUM_CHANNELS: usize = 2;
+        const STEREO_LAYOUT: AudioIOLayout = AudioIOLayout {
+            main_input_channels: Some(new_nonzero_u32(NUM_CHANNELS as u32)),
+            main_output_channels: Some(new_nonzero_u32(NUM_CHANNELS as u32)),
+            ..AudioIOLayout::const_default()
+        };
+
+        // The main input should never be copied for symmetric layouts, regardless of whether the
+        // host processes audio in place or not
+        for in_place in [true, false] {
+            let mut main_input_storage = vec![vec![0.25f32; BUFFER_SIZE]; NUM_CHANNELS];
+            let mut main_output_storage = vec![vec![0.0f32; BUFFER_SIZE]; NUM_CHANNELS];
+
+            let mut main_input_channel_pointers: Vec<*mut f32> = main_input_storage
+                .iter_mut()
+                .map(|channel_slice| channel_slice.as_mut_ptr())
+                .collect();
+            let mut main_output_channel_pointers: Vec<*mut f32> = if in_place {
+                main_input_channel_pointers.clone()
+            } else {
+                main_output_storage
+                    .iter_mut()
+                    .map(|channel_slice| channel_slice.as_mut_ptr())
+                    .collect()
+            };
+
+            let mut buffer_manager: BufferManager =
+                BufferManager::for_audio_io_layout(BUFFER_SIZE, STEREO_LAYOUT);
+            let buffers = unsafe {
+                buffer_manager.create_buffers(0, BUFFER_SIZE, |buffer_sources| {
+                    *buffer_sources.main_input_channel_pointers = Some(
+                        ChannelPointers {
+                            ptrs: NonNull::new(main_input_channel_pointers.as_mut_ptr()).unwrap(),
+                            num_channels: main_input_channel_pointers.len(),
+                        }
+                        .into(),
+                    );
+                    *buffer_sources.main_output_channel_pointers = Some(
+                        ChannelPointers {
+                            ptrs: NonNull::new(main_output_channel_pointers.as_mut_ptr()).unwrap(),
+                            num_channels: main_output_channel_pointers.len(),
+                        }
+                        .into(),
+                    );
+                })
+            };
+
+            // The main buffer already contains the entire input
+            assert_eq!(buffers.main_input.channels(), 0);
+            assert_eq!(buffers.main_buffer.channels(), NUM_CHANNELS);
+            for channel_samples in buffers.main_buffer.iter_samples() {
+                for sample in channel_samples {
+                    assert!(*sample == 0.25);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn f64_conversion() {
+        const NUM_CHANNELS: usize = 2;
+        const STEREO_LAYOUT: AudioIOLayout = AudioIOLayout {
+            main_input_channels: Some(new_nonzero_u32(NUM_CHANNELS as u32)),
+            main_output_channels: Some(new_nonzero_u32(NUM_CHANNELS as u32)),
+            ..AudioIOLayout::const_default()
+        };
+
+        // The host only provides single precision buffers, processed out of place
+        let mut main_input_storage = vec![vec![0.25f32; BUFFER_SIZE]; NUM_CHANNELS];
+        let mut main_output_storage = vec![vec![0.0f32; BUFFER_SIZE]; NUM_CHANNELS];
+
+        let mut main_input_channel_pointers: Vec<*mut f32> = main_input_storage
+            .iter_mut()
+            .map(|channel_slice| channel_slice.as_mut_ptr())
+            .collect();
+        let mut main_output_channel_pointers: Vec<*mut f32> = main_output_storage
+            .iter_mut()
+            .map(|channel_slice| channel_slice.as_mut_ptr())
+            .collect();
+
+        let mut buffer_manager: BufferManager<f64> =
+            BufferManager::for_audio_io_layout(BUFFER_SIZE, STEREO_LAYOUT);
+        let buffers = unsafe {
+            buffer_manager.create_buffers(0, BUFFER_SIZE, |buffer_sources| {
+                *buffer_sources.main_input_channel_pointers = Some(
+                    ChannelPointers {
+                        ptrs: NonNull::new(main_input_channel_pointers.as_mut_ptr()).unwrap(),
+                        num_channels: main_input_channel_pointers.len(),
+                    }
+                    .into(),
+                );
+                *buffer_sources.main_output_channel_pointers = Some(
+                    ChannelPointers {
+                        ptrs: NonNull::new(main_output_channel_pointers.as_mut_ptr()).unwrap(),
+                        num_channels: main_output_channel_pointers.len(),
+                    }
+                    .into(),
+                );
+            })
+        };
+
+        for channel_samples in buffers.main_buffer.iter_samples() {
+            for sample in channel_samples {
+                assert!(*sample == 0.25);
+                *sample *= 2.0;
+            }
+        }
+
+        // The outputs are only written to the host's buffers after processing
+        for channel in main_output_storage.iter() {
+            for sample in channel {
+                assert!(*sample == 0.0);
+            }
+        }
+
+        unsafe { buffer_manager.write_back_outputs() };
+        for channel in main_output_storage.iter() {
+            for sample in channel {
+                assert!(*sample == 0.5);
+            }
+        }
+        for channel in main_input_storage.iter() {
+            for sample in channel {
+                assert!(*sample == 0.25);
+            }
+        }
+    }
 }
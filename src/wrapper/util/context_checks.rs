@@ -2,6 +2,8 @@
 
 use std::collections::HashSet;
 
+use super::ParamBatchStep;
+
 /// Ensures that parameter changes send from the GUI are wrapped in parameter gestures, and that the
 /// gestures are handled consistently (no duplicate starts and ends, no end before start, etc.).
 ///
@@ -64,4 +66,15 @@ impl ParamGestureChecker {
         );
         self.active_params.remove(param_id);
     }
+
+    /// Called for every step of
+    /// [`GuiContext::raw_set_parameters_normalized()`][crate::prelude::GuiContext::raw_set_parameters_normalized()],
+    /// so batched changes go through the same checks as individual parameter changes.
+    pub fn param_batch_step<T>(&mut self, step: &ParamBatchStep<T>, param_id: &str) {
+        match step {
+            ParamBatchStep::Begin(_) => self.begin_set_parameter(param_id),
+            ParamBatchStep::Set(_) => self.set_parameter(param_id),
+            ParamBatchStep::End(_) => self.end_set_parameter(param_id),
+        }
+    }
 }
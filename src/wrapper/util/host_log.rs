@@ -0,0 +1,306 @@
+//! Forwards messages from the [`nih_log!()`][crate::nih_log!()], [`nih_warn!()`][crate::nih_warn!()],
+//! and [`nih_error!()`][crate::nih_error!()] macros to the host's own log, for hosts that have one.
+//! This is used in addition to NIH-log's regular output.
+//!
+//! The logging macros are process-wide, so the wrappers mark the plugin instance that's currently
+//! running on a thread using [`HostLoggerHandle::enter()`] at the start of every entry point. That
+//! way messages are sent to the log of the instance that emitted them. Messages emitted from the
+//! audio thread are not sent to the host right away. Instead, they're stored in a preallocated
+//! queue and sent from the main thread later.
+
+use crossbeam::queue::ArrayQueue;
+use parking_lot::RwLock;
+use std::cell::Cell;
+use std::ffi::CStr;
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// The maximum length of a forwarded message, including the trailing null byte. Longer messages are
+/// truncated. Messages are formatted into a buffer on the stack so logging to the host does not
+/// allocate.
+const MAX_MESSAGE_LEN: usize = 1024;
+/// The number of messages from the audio thread that can be queued before they are sent to the
+/// host. Any additional messages are dropped.
+const DEFERRED_MESSAGES_CAPACITY: usize = 32;
+
+/// The host loggers registered by the plugin instances in this process.
+static HOST_LOGGERS: RwLock<Vec<Arc<InstanceLogger>>> = parking_lot::const_rwlock(Vec::new());
+/// Used to quickly skip formatting the message when no host loggers have been registered.
+static HAS_HOST_LOGGERS: AtomicBool = AtomicBool::new(false);
+static NEXT_HOST_LOGGER_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// The ID of the plugin instance that's currently running on this thread, and whether that
+    /// instance was called from the audio thread. Set using [`HostLoggerHandle::enter()`].
+    static CURRENT_INSTANCE: Cell<Option<(u64, bool)>> = const { Cell::new(None) };
+}
+
+/// A host-provided log that messages can be sent to. Messages logged from the audio thread are
+/// deferred, so [`log()`][Self::log()] is never called from there.
+pub trait HostLogger: Send + Sync {
+    /// Send a message to the host.
+    fn log(&self, level: log::Level, message: &CStr);
+
+    /// Ask the host to call the plugin back on the main thread, where the plugin should call
+    /// [`HostLoggerHandle::flush_deferred_messages()`]. This is called from the audio thread, so it
+    /// must be realtime-safe.
+    fn request_flush(&self);
+}
+
+/// A registered [`HostLogger`] along with the messages from the audio thread that still need to be
+/// sent to it.
+struct InstanceLogger {
+    id: u64,
+    host_logger: Box<dyn HostLogger>,
+    deferred_messages: ArrayQueue<(log::Level, MessageBuffer)>,
+}
+
+/// Unregisters a [`HostLogger`] when dropped. See [`register()`].
+pub struct HostLoggerHandle {
+    logger: Arc<InstanceLogger>,
+}
+
+/// Restores the previous plugin instance for the current thread when dropped. See
+/// [`HostLoggerHandle::enter()`].
+#[must_use]
+pub struct LogScope {
+    previous_instance: Option<(u64, bool)>,
+}
+
+/// A [`fmt::Write`] implementation that writes to a fixed size null-terminated buffer. Output that
+/// does not fit in the buffer is discarded.
+struct MessageBuffer {
+    buffer: [u8; MAX_MESSAGE_LEN],
+    /// The number of bytes written to `buffer`, excluding the trailing null byte.
+    len: usize,
+}
+
+impl Drop for HostLoggerHandle {
+    fn drop(&mut self) {
+        let mut host_loggers = HOST_LOGGERS.write();
+        host_loggers.retain(|logger| logger.id != self.logger.id);
+        HAS_HOST_LOGGERS.store(!host_loggers.is_empty(), Ordering::SeqCst);
+    }
+}
+
+impl Drop for LogScope {
+    fn drop(&mut self) {
+        CURRENT_INSTANCE.with(|current_instance| current_instance.set(self.previous_instance));
+    }
+}
+
+impl Write for MessageBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // There always needs to be room for the trailing null byte
+        let capacity = MAX_MESSAGE_LEN - 1 - self.len;
+        let mut num_bytes = s.len().min(capacity);
+        while !s.is_char_boundary(num_bytes) {
+            num_bytes -= 1;
+        }
+
+        // Null bytes would end the message early, so they're skipped
+        for &byte in s.as_bytes()[..num_bytes].iter().filter(|&&byte| byte != 0) {
+            self.buffer[self.len] = byte;
+            self.len += 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl MessageBuffer {
+    fn new() -> Self {
+        Self {
+            buffer: [0; MAX_MESSAGE_LEN],
+            len: 0,
+        }
+    }
+
+    fn as_c_str(&self) -> &CStr {
+        CStr::from_bytes_until_nul(&self.buffer[..self.len + 1])
+            .expect("The message buffer is always null-terminated")
+    }
+}
+
+impl HostLoggerHandle {
+    /// Attribute messages logged on the current thread to this plugin instance until the returned
+    /// scope is dropped. This should be called at the start of every entry point. If
+    /// `is_audio_thread` is set, then the messages are queued and the host is asked to call the
+    /// plugin back on the main thread instead of sending them right away.
+    pub fn enter(&self, is_audio_thread: bool) -> LogScope {
+        let previous_instance = CURRENT_INSTANCE.with(|current_instance| {
+            current_instance.replace(Some((self.logger.id, is_audio_thread)))
+        });
+
+        LogScope { previous_instance }
+    }
+
+    /// Send the messages logged from the audio thread to the host. This should be called from the
+    /// main thread after [`HostLogger::request_flush()`] has been called.
+    pub fn flush_deferred_messages(&self) {
+        while let Some((level, message)) = self.logger.deferred_messages.pop() {
+            self.logger.host_logger.log(level, message.as_c_str());
+        }
+    }
+}
+
+/// Register a host logger. Messages are forwarded to it until the returned handle is dropped. Only
+/// messages logged while the plugin instance is running on a thread, as set by
+/// [`HostLoggerHandle::enter()`], are sent to its log. Messages from other threads, like the
+/// editor's or the plugin's own threads, can only be attributed when there is a single plugin
+/// instance with a host logger. Otherwise they are only written to NIH-log's regular output.
+pub fn register(host_logger: impl HostLogger + 'static) -> HostLoggerHandle {
+    let logger = Arc::new(InstanceLogger {
+        id: NEXT_HOST_LOGGER_ID.fetch_add(1, Ordering::SeqCst),
+        host_logger: Box::new(host_logger),
+        deferred_messages: ArrayQueue::new(DEFERRED_MESSAGES_CAPACITY),
+    });
+    HOST_LOGGERS.write().push(logger.clone());
+    HAS_HOST_LOGGERS.store(true, Ordering::SeqCst);
+
+    HostLoggerHandle { logger }
+}
+
+/// Forward a message to the host's log if a host logger has been registered for the plugin instance
+/// that emitted it. This is used by the logging macros and should not be called directly. This
+/// neither allocates nor blocks, and messages from the audio thread are only queued, so it does not
+/// add any realtime-safety concerns on top of the logging macros themselves. If a plugin instance
+/// is registering or unregistering its logger at the same time, or if the audio thread's queue is
+/// full, then the message is not forwarded.
+#[doc(hidden)]
+pub fn log_to_host(level: log::Level, args: fmt::Arguments) {
+    if !HAS_HOST_LOGGERS.load(Ordering::Relaxed) || level > log::max_level() {
+        return;
+    }
+
+    let Some(host_loggers) = HOST_LOGGERS.try_read() else {
+        return;
+    };
+    let (logger, is_audio_thread) = match CURRENT_INSTANCE.with(Cell::get) {
+        Some((id, is_audio_thread)) => match host_loggers.iter().find(|logger| logger.id == id) {
+            Some(logger) => (logger, is_audio_thread),
+            None => return,
+        },
+        None if host_loggers.len() == 1 => (&host_loggers[0], false),
+        None => return,
+    };
+
+    let mut message = MessageBuffer::new();
+    // Truncation is handled by the buffer, so this cannot fail
+    let _ = message.write_fmt(args);
+
+    if is_audio_thread {
+        if logger.deferred_messages.push((level, message)).is_ok() {
+            logger.host_logger.request_flush();
+        }
+    } else {
+        logger.host_logger.log(level, message.as_c_str());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::sync::atomic::AtomicUsize;
+
+    #[derive(Default)]
+    struct TestLog {
+        messages: Mutex<Vec<String>>,
+        flush_requests: AtomicUsize,
+    }
+
+    struct TestLogger(Arc<TestLog>);
+
+    impl HostLogger for TestLogger {
+        fn log(&self, _level: log::Level, message: &CStr) {
+            self.0
+                .messages
+                .lock()
+                .push(message.to_str().unwrap().to_owned());
+        }
+
+        fn request_flush(&self) {
+            self.0.flush_requests.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    impl TestLog {
+        fn contains(&self, message: &str) -> bool {
+            self.messages.lock().iter().any(|m| m == message)
+        }
+    }
+
+    /// These are all in one test since the registered loggers are global. Other tests may log at
+    /// the same time, so this only checks for this test's own messages.
+    #[test]
+    fn instance_routing() {
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let (log_1, log_2) = (Arc::new(TestLog::default()), Arc::new(TestLog::default()));
+        let handle_1 = register(TestLogger(log_1.clone()));
+        let handle_2 = register(TestLogger(log_2.clone()));
+
+        // With multiple instances, messages outside of a scope cannot be attributed
+        log_to_host(log::Level::Info, format_args!("unattributed"));
+        {
+            let _scope = handle_2.enter(false);
+            log_to_host(log::Level::Info, format_args!("main thread"));
+
+            // Nested scopes restore the previous instance when dropped
+            {
+                let _scope = handle_1.enter(true);
+                log_to_host(log::Level::Info, format_args!("audio thread"));
+            }
+            log_to_host(log::Level::Info, format_args!("main thread again"));
+        }
+
+        assert!(!log_1.contains("unattributed") && !log_2.contains("unattributed"));
+        assert!(log_2.contains("main thread") && log_2.contains("main thread again"));
+        assert!(!log_2.contains("audio thread"));
+        assert_eq!(log_2.flush_requests.load(Ordering::SeqCst), 0);
+
+        // Messages from the audio thread are only sent when they're flushed
+        assert!(!log_1.contains("audio thread"));
+        assert_eq!(log_1.flush_requests.load(Ordering::SeqCst), 1);
+        handle_1.flush_deferred_messages();
+        assert!(log_1.contains("audio thread"));
+
+        // With a single instance, messages outside of a scope are sent to that instance
+        drop(handle_1);
+        log_to_host(log::Level::Info, format_args!("single instance"));
+        assert!(log_2.contains("single instance"));
+
+        drop(handle_2);
+        assert!(!HAS_HOST_LOGGERS.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn message_buffer_truncates() {
+        let mut message = MessageBuffer::new();
+        write!(message, "{}", "a".repeat(MAX_MESSAGE_LEN * 2)).unwrap();
+
+        assert_eq!(message.as_c_str().to_bytes().len(), MAX_MESSAGE_LEN - 1);
+    }
+
+    #[test]
+    fn message_buffer_char_boundaries() {
+        let mut message = MessageBuffer::new();
+        write!(message, "{}é", "a".repeat(MAX_MESSAGE_LEN - 2)).unwrap();
+
+        // The two byte character no longer fits, so it's dropped entirely
+        assert_eq!(
+            message.as_c_str().to_str().unwrap(),
+            "a".repeat(MAX_MESSAGE_LEN - 2)
+        );
+    }
+
+    #[test]
+    fn message_buffer_null_bytes() {
+        let mut message = MessageBuffer::new();
+        write!(message, "Hello,\0 world!").unwrap();
+
+        assert_eq!(message.as_c_str().to_str(), Ok("Hello, world!"));
+    }
+}
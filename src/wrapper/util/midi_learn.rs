@@ -0,0 +1,375 @@
+//! The wrappers' shared MIDI learn implementation. See [`crate::midi::learn`].
+
+use atomic_refcell::AtomicRefCell;
+use crossbeam::atomic::AtomicCell;
+use midi_consts::channel_event as midi;
+use parking_lot::RwLock;
+
+use crate::midi::decoder::MidiDecoder;
+use crate::midi::learn::{MidiLearnSource, MidiMapping};
+use crate::prelude::{MidiConfig, NoteEvent, ParamFlags, ParamPtr, Plugin};
+
+/// The LSB messages for 14-bit CCs are sent on CCs `32..64`.
+const CC_LSB_RANGE: std::ops::Range<u8> = 32..64;
+/// The data entry, data increment, and data decrement CCs that change the selected NRPN's value.
+const NRPN_DATA_CCS: [u8; 4] = [6, 38, 96, 97];
+
+/// The wrapper's MIDI learn state. Mappings are edited from the main thread, and incoming MIDI CCs
+/// are handled on the audio thread using [`process_cc()`][Self::process_cc()]. Finishing MIDI learn
+/// requires allocating, so when the audio thread receives the controller message for a learning
+/// parameter it stores the result and the wrapper then needs to call
+/// [`finish_learning()`][Self::finish_learning()] from the main thread.
+#[derive(Default)]
+pub struct MidiLearn {
+    /// The parameter the next incoming controller message should be mapped to, if MIDI learn is
+    /// active.
+    learn_target: AtomicCell<Option<ParamPtr>>,
+    /// A controller the audio thread learned for a parameter, waiting to be added to `mappings`
+    /// from the main thread.
+    learned: AtomicCell<Option<(ParamPtr, MidiLearnSource)>>,
+    /// The active mappings with their resolved parameters. The audio thread only tries to read
+    /// these, so incoming controller messages are not applied while the mappings are being edited.
+    mappings: RwLock<Vec<(MidiMapping, ParamPtr)>>,
+    /// State that's only used on the audio thread.
+    audio_state: AtomicRefCell<AudioState>,
+}
+
+/// The part of [`MidiLearn`] that's only used from the audio thread.
+#[derive(Default)]
+struct AudioState {
+    /// Used to detect 14-bit CCs and NRPNs in the incoming CC messages.
+    decoder: MidiDecoder,
+    /// The controller learned in response to the previous CC message, if any. If the next message
+    /// turns out to be the LSB for that CC, then the controller is learned as a 14-bit CC instead.
+    last_learned: Option<(ParamPtr, MidiLearnSource)>,
+}
+
+impl MidiLearn {
+    /// Start MIDI learn for `param`. Returns `false` if the parameter cannot be mapped to a MIDI
+    /// controller.
+    pub fn start_learning<P: Plugin>(&self, param: ParamPtr) -> bool {
+        if P::MIDI_INPUT < MidiConfig::MidiCCs {
+            nih_debug_assert_failure!("MIDI learn requires MidiConfig::MidiCCs");
+            return false;
+        }
+        if !Self::can_map(param) {
+            nih_debug_assert_failure!(
+                "Non-automatable and read-only parameters cannot be MIDI learned"
+            );
+            return false;
+        }
+
+        self.learn_target.store(Some(param));
+        true
+    }
+
+    /// Cancel MIDI learn if it is active.
+    pub fn cancel_learning(&self) {
+        self.learn_target.store(None);
+    }
+
+    /// The parameter MIDI learn is currently active for, if any.
+    pub fn learn_target(&self) -> Option<ParamPtr> {
+        self.learn_target.load()
+    }
+
+    /// Add the controller learned on the audio thread to the mappings. Replaces the existing
+    /// mappings for the parameter, keeping the range and curve of the first one. `param_id` is used
+    /// to look up the parameter's string ID. Should be called from the main thread after
+    /// [`process_cc()`][Self::process_cc()] returned `true`.
+    pub fn finish_learning(&self, param_id: impl Fn(ParamPtr) -> Option<String>) {
+        let Some((param, source)) = self.learned.take() else {
+            return;
+        };
+        let Some(param_id) = param_id(param) else {
+            nih_debug_assert_failure!("Unknown parameter: {:?}", param);
+            return;
+        };
+
+        let mut mappings = self.mappings.write();
+        match mappings.iter().position(|(_, p)| *p == param) {
+            Some(idx) => {
+                mappings[idx].0.source = source;
+
+                let mut current_idx = 0;
+                mappings.retain(|(_, p)| {
+                    let keep = *p != param || current_idx == idx;
+                    current_idx += 1;
+                    keep
+                });
+            }
+            None => mappings.push((MidiMapping::new(source, param_id), param)),
+        }
+    }
+
+    /// Get a copy of the current mappings.
+    pub fn mappings(&self) -> Vec<MidiMapping> {
+        self.mappings
+            .read()
+            .iter()
+            .map(|(mapping, _)| mapping.clone())
+            .collect()
+    }
+
+    /// Replace the current mappings. `params_getter` is used to resolve the parameter IDs, and
+    /// mappings for unknown, non-automatable, and read-only parameters are dropped.
+    pub fn set_mappings(
+        &self,
+        mappings: &[MidiMapping],
+        params_getter: impl Fn(&str) -> Option<ParamPtr>,
+    ) {
+        let resolved_mappings = mappings
+            .iter()
+            .filter_map(|mapping| match params_getter(&mapping.param_id) {
+                Some(param) if Self::can_map(param) => Some((mapping.clone(), param)),
+                Some(_) => {
+                    nih_debug_assert_failure!(
+                        "Parameter \"{}\" cannot be mapped to a MIDI controller",
+                        mapping.param_id
+                    );
+                    None
+                }
+                None => {
+                    nih_debug_assert_failure!("Unknown parameter: {}", mapping.param_id);
+                    None
+                }
+            })
+            .collect();
+
+        *self.mappings.write() = resolved_mappings;
+    }
+
+    /// Whether a MIDI CC may change a mapped parameter. If this is the case, then the wrappers also
+    /// split the buffer on the CC when sample accurate automation is enabled. Data entry messages
+    /// are shared between all NRPNs, so those are always considered for NRPN mappings on the same
+    /// channel.
+    pub fn may_handle_cc(&self, channel: u8, cc: u8) -> bool {
+        let Some(mappings) = self.mappings.try_read() else {
+            return false;
+        };
+
+        mappings.iter().any(|(mapping, _)| match mapping.source {
+            MidiLearnSource::Cc {
+                channel: mapped_channel,
+                cc: mapped_cc,
+            } => mapped_channel == channel && mapped_cc == cc,
+            MidiLearnSource::Cc14Bit {
+                channel: mapped_channel,
+                cc: mapped_cc,
+            } => {
+                mapped_channel == channel
+                    && (mapped_cc == cc || mapped_cc + CC_LSB_RANGE.start == cc)
+            }
+            MidiLearnSource::Nrpn {
+                channel: mapped_channel,
+                ..
+            } => mapped_channel == channel && NRPN_DATA_CCS.contains(&cc),
+        })
+    }
+
+    /// Whether a raw MIDI message may change a mapped parameter. See
+    /// [`may_handle_cc()`][Self::may_handle_cc()].
+    pub fn may_handle_midi(&self, midi_data: &[u8]) -> bool {
+        match midi_data {
+            [status, cc, ..] if status & midi::EVENT_TYPE_MASK == midi::CONTROL_CHANGE => {
+                self.may_handle_cc(status & midi::MIDI_CHANNEL_MASK, *cc)
+            }
+            _ => false,
+        }
+    }
+
+    /// Handle an incoming MIDI CC message on the audio thread. `set_param` is called with the new
+    /// normalized value for every parameter mapped to the controller. If MIDI learn is active,
+    /// then the controller is learned for the target parameter. Returns `true` if the wrapper
+    /// needs to call [`finish_learning()`][Self::finish_learning()] from the main thread.
+    pub fn process_cc(
+        &self,
+        channel: u8,
+        cc: u8,
+        value: f32,
+        mut set_param: impl FnMut(ParamPtr, f32),
+    ) -> bool {
+        let mut audio_state = self.audio_state.borrow_mut();
+
+        // The decoder consumes (N)RPN parameter selection messages, and turns data entry messages
+        // into RPN and NRPN events. Everything else is still a regular CC.
+        let mut is_regular_cc = false;
+        let mut cc_14_bit = None;
+        let mut nrpn = None;
+        audio_state.decoder.process(
+            NoteEvent::<()>::MidiCC {
+                timing: 0,
+                channel,
+                cc,
+                value,
+            },
+            |event| match event {
+                NoteEvent::MidiCC { .. } => is_regular_cc = true,
+                NoteEvent::MidiCC14Bit {
+                    channel, cc, value, ..
+                } => {
+                    is_regular_cc = true;
+                    cc_14_bit = Some((MidiLearnSource::Cc14Bit { channel, cc }, value));
+                }
+                NoteEvent::MidiNrpn {
+                    channel,
+                    parameter,
+                    value,
+                    ..
+                } => nrpn = Some((MidiLearnSource::Nrpn { channel, parameter }, value)),
+                _ => (),
+            },
+        );
+        let cc_source = MidiLearnSource::Cc { channel, cc };
+
+        // An LSB message that completes a 14-bit CC is learned as that 14-bit CC
+        let learnable_source = match (nrpn, cc_14_bit) {
+            (Some((source, _)), _) => Some(source),
+            (None, Some((source, _))) if CC_LSB_RANGE.contains(&cc) => Some(source),
+            (None, _) if is_regular_cc => Some(cc_source),
+            (None, _) => None,
+        };
+        let last_learned = audio_state.last_learned.take();
+        let mut learn_finished = false;
+        if let Some(source) = learnable_source {
+            if let Some(param) = self.learn_target.take() {
+                audio_state.last_learned = Some((param, source));
+                self.learned.store(Some((param, source)));
+                learn_finished = true;
+            } else if let Some((param, MidiLearnSource::Cc { channel, cc })) = last_learned {
+                // 14-bit controllers send the MSB first, so that's what gets learned initially
+                if source == (MidiLearnSource::Cc14Bit { channel, cc }) {
+                    self.learned.store(Some((param, source)));
+                    learn_finished = true;
+                }
+            }
+        }
+
+        if let Some(mappings) = self.mappings.try_read() {
+            for (mapping, param) in mappings.iter() {
+                let controller_value = match mapping.source {
+                    source if source == cc_source && is_regular_cc => value,
+                    source => match (cc_14_bit, nrpn) {
+                        (Some((cc_14_bit_source, value)), _) if source == cc_14_bit_source => value,
+                        (_, Some((nrpn_source, value))) if source == nrpn_source => value,
+                        _ => continue,
+                    },
+                };
+
+                set_param(*param, mapping.normalized_value(controller_value));
+            }
+        }
+
+        learn_finished
+    }
+
+    /// Whether the parameter can be mapped to a MIDI controller.
+    fn can_map(param: ParamPtr) -> bool {
+        !unsafe { param.flags() }.intersects(ParamFlags::NON_AUTOMATABLE | ParamFlags::READ_ONLY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{FloatParam, FloatRange, Param};
+
+    fn make_param() -> FloatParam {
+        FloatParam::new("Test", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+    }
+
+    /// Send a CC and return the values `set_param` was called with.
+    fn send_cc(midi_learn: &MidiLearn, cc: u8, value: u8) -> (bool, Vec<f32>) {
+        let mut values = Vec::new();
+        let learn_finished =
+            midi_learn.process_cc(0, cc, value as f32 / 127.0, |_, value| values.push(value));
+
+        (learn_finished, values)
+    }
+
+    #[test]
+    fn learn_14_bit_cc() {
+        let param = make_param();
+        let midi_learn = MidiLearn::default();
+        midi_learn.learn_target.store(Some(param.as_ptr()));
+
+        // The MSB is learned first, and the LSB right after it upgrades the mapping
+        assert_eq!(send_cc(&midi_learn, 1, 64), (true, vec![]));
+        midi_learn.finish_learning(|_| Some(String::from("test")));
+        assert_eq!(
+            midi_learn.mappings()[0].source,
+            MidiLearnSource::Cc { channel: 0, cc: 1 }
+        );
+
+        assert_eq!(send_cc(&midi_learn, 33, 0), (true, vec![]));
+        midi_learn.finish_learning(|_| Some(String::from("test")));
+        assert_eq!(
+            midi_learn.mappings(),
+            [MidiMapping::new(
+                MidiLearnSource::Cc14Bit { channel: 0, cc: 1 },
+                "test"
+            )]
+        );
+
+        assert_eq!(
+            send_cc(&midi_learn, 33, 127),
+            (false, vec![8319.0 / 16383.0])
+        );
+    }
+
+    #[test]
+    fn may_handle_mapped_ccs() {
+        let param = make_param();
+        let midi_learn = MidiLearn::default();
+        midi_learn.set_mappings(
+            &[
+                MidiMapping::new(MidiLearnSource::Cc { channel: 0, cc: 7 }, "cc"),
+                MidiMapping::new(MidiLearnSource::Cc14Bit { channel: 1, cc: 2 }, "cc14"),
+                MidiMapping::new(
+                    MidiLearnSource::Nrpn {
+                        channel: 2,
+                        parameter: 3,
+                    },
+                    "nrpn",
+                ),
+            ],
+            |_| Some(param.as_ptr()),
+        );
+
+        assert!(midi_learn.may_handle_cc(0, 7));
+        assert!(midi_learn.may_handle_cc(1, 2));
+        assert!(midi_learn.may_handle_cc(1, 34));
+        assert!(midi_learn.may_handle_cc(2, 6));
+        assert!(midi_learn.may_handle_midi(&[midi::CONTROL_CHANGE, 7, 64]));
+
+        // Unmapped controllers, like the mod wheel, should not cause the buffer to be split
+        assert!(!midi_learn.may_handle_cc(0, 1));
+        assert!(!midi_learn.may_handle_cc(1, 7));
+        assert!(!midi_learn.may_handle_cc(2, 99));
+        assert!(!midi_learn.may_handle_midi(&[midi::CONTROL_CHANGE | 3, 7, 64]));
+        assert!(!midi_learn.may_handle_midi(&[midi::CHANNEL_KEY_PRESSURE, 64]));
+    }
+
+    #[test]
+    fn learn_nrpn() {
+        let param = make_param();
+        let midi_learn = MidiLearn::default();
+        midi_learn.learn_target.store(Some(param.as_ptr()));
+
+        // The parameter selection messages themselves are not learned
+        assert_eq!(send_cc(&midi_learn, 99, 1), (false, vec![]));
+        assert_eq!(send_cc(&midi_learn, 98, 2), (false, vec![]));
+        assert_eq!(send_cc(&midi_learn, 6, 0), (true, vec![]));
+        midi_learn.finish_learning(|_| Some(String::from("test")));
+        assert_eq!(
+            midi_learn.mappings()[0].source,
+            MidiLearnSource::Nrpn {
+                channel: 0,
+                parameter: (1 << 7) | 2
+            }
+        );
+
+        let (_, values) = send_cc(&midi_learn, 6, 127);
+        assert_eq!(values, [(127 << 7) as f32 / 16383.0]);
+    }
+}
@@ -2,7 +2,9 @@
 mod util;
 
 mod context;
+mod context_menu;
 mod factory;
+mod factory_presets;
 mod inner;
 mod note_expressions;
 mod param_units;
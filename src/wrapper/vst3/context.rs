@@ -3,12 +3,15 @@ use std::cell::Cell;
 use std::collections::VecDeque;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use vst3_sys::vst::IComponentHandler;
+use vst3_sys::vst::{IComponentHandler, IComponentHandler2};
 
 use crate::prelude::{
-    GuiContext, InitContext, ParamPtr, PluginApi, PluginNoteEvent, PluginState, ProcessContext,
-    Transport, Vst3Plugin,
+    ContextMenuItem, GuiContext, InitContext, MidiMapping, ParamIndication, ParamInfoChanges,
+    ParamPtr, PluginApi, PluginNoteEvent, PluginState, ProcessContext, TrackInfo, Transport,
+    Vst3Plugin,
 };
+use crate::util;
+use crate::wrapper::util::{param_batch_steps, ParamBatchStep};
 
 use super::inner::{Task, WrapperInner};
 
@@ -32,6 +35,8 @@ pub(crate) struct WrapperInitContext<'a, P: Vst3Plugin> {
 pub(crate) struct PendingInitContextRequests {
     /// The value of the last `.set_latency_samples()` call.
     latency_changed: Cell<Option<u32>>,
+    /// The combined changes from all `.param_info_changed()` calls.
+    param_info_changed: Cell<ParamInfoChanges>,
 }
 
 /// A [`ProcessContext`] implementation for the wrapper. This is a separate object so it can hold on
@@ -59,6 +64,10 @@ impl<P: Vst3Plugin> Drop for WrapperInitContext<'_, P> {
         if let Some(samples) = self.pending_requests.latency_changed.take() {
             self.inner.set_latency_samples(samples)
         }
+        let param_info_changes = self.pending_requests.param_info_changed.take();
+        if !param_info_changes.is_empty() {
+            self.inner.param_info_changed(param_info_changes)
+        }
     }
 }
 
@@ -79,6 +88,31 @@ impl<P: Vst3Plugin> InitContext<P> for WrapperInitContext<'_, P> {
     fn set_current_voice_capacity(&self, _capacity: u32) {
         // This is only supported by CLAP
     }
+
+    fn track_info(&self) -> Option<TrackInfo> {
+        // This is only supported by CLAP
+        None
+    }
+
+    fn aux_input_active(&self, index: usize) -> bool {
+        self.inner
+            .audio_ports_activation
+            .aux_input_active(&self.inner.current_audio_io_layout.load(), index)
+    }
+
+    fn aux_output_active(&self, index: usize) -> bool {
+        self.inner
+            .audio_ports_activation
+            .aux_output_active(&self.inner.current_audio_io_layout.load(), index)
+    }
+
+    fn param_info_changed(&self, changes: ParamInfoChanges) {
+        // See this struct's docstring
+        let pending_changes = self.pending_requests.param_info_changed.get();
+        self.pending_requests
+            .param_info_changed
+            .set(pending_changes | changes);
+    }
 }
 
 impl<P: Vst3Plugin> ProcessContext<P> for WrapperProcessContext<'_, P> {
@@ -116,6 +150,43 @@ impl<P: Vst3Plugin> ProcessContext<P> for WrapperProcessContext<'_, P> {
     fn set_current_voice_capacity(&self, _capacity: u32) {
         // This is only supported by CLAP
     }
+
+    fn note_names_changed(&self) {
+        let task_posted = self.inner.schedule_gui(Task::NoteNamesChanged);
+        nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+    }
+
+    fn note_frequency(&self, _channel: u8, note: u8, _timing: u32) -> f32 {
+        // Host tunings are only supported by CLAP
+        util::midi_note_to_freq(note)
+    }
+
+    fn execute_parallel(&self, num_tasks: u32, task: &(dyn Fn(u32) + Sync)) {
+        // VST3 does not have a thread pool extension, so the tasks are run sequentially
+        for task_index in 0..num_tasks {
+            task(task_index);
+        }
+    }
+
+    unsafe fn raw_begin_set_parameter(&mut self, _param: ParamPtr) {
+        // VST3 only has automation gestures for the main thread's `IComponentHandler`, parameter
+        // changes from the audio thread are written directly to the output parameter changes
+    }
+
+    unsafe fn raw_set_parameter_normalized(&mut self, param: ParamPtr, normalized: f32) {
+        match self.inner.param_ptr_to_hash.get(&param) {
+            Some(hash) => {
+                // The parameter's value is changed and the change is sent to the host at the end of
+                // the current block
+                self.inner.queue_output_param_change(*hash, normalized);
+            }
+            None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
+        }
+    }
+
+    unsafe fn raw_end_set_parameter(&mut self, _param: ParamPtr) {
+        // See `raw_begin_set_parameter()`
+    }
 }
 
 impl<P: Vst3Plugin> GuiContext for WrapperGuiContext<P> {
@@ -132,6 +203,43 @@ impl<P: Vst3Plugin> GuiContext for WrapperGuiContext<P> {
         true
     }
 
+    fn note_names_changed(&self) {
+        let task_posted = self.inner.schedule_gui(Task::NoteNamesChanged);
+        nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+    }
+
+    fn param_info_changed(&self, changes: ParamInfoChanges) {
+        self.inner.param_info_changed(changes)
+    }
+
+    fn track_info(&self) -> Option<TrackInfo> {
+        // This is only supported by CLAP
+        None
+    }
+
+    fn param_indication(&self, _param: ParamPtr) -> ParamIndication {
+        // This is only supported by CLAP
+        ParamIndication::default()
+    }
+
+    fn show_param_context_menu(
+        &self,
+        param: ParamPtr,
+        x: i32,
+        y: i32,
+        items: Vec<ContextMenuItem>,
+    ) -> bool {
+        let Some(hash) = self.inner.param_ptr_to_hash.get(&param) else {
+            nih_debug_assert_failure!("Unknown parameter: {:?}", param);
+            return false;
+        };
+
+        match &*self.inner.plug_view.read() {
+            Some(plug_view) => unsafe { plug_view.show_param_context_menu(*hash, x, y, &items) },
+            None => false,
+        }
+    }
+
     // All of these functions are supposed to be called from the main thread, so we'll put some
     // trust in the caller and assume that this is indeed the case
     unsafe fn raw_begin_set_parameter(&self, param: ParamPtr) {
@@ -221,6 +329,83 @@ impl<P: Vst3Plugin> GuiContext for WrapperGuiContext<P> {
         }
     }
 
+    unsafe fn raw_set_parameters_normalized(&self, changes: &[(ParamPtr, f32)]) {
+        let component_handler = self.inner.component_handler.borrow();
+        let Some(handler) = &*component_handler else {
+            nih_debug_assert_failure!("Component handler not yet set");
+            return;
+        };
+
+        let changes: Vec<(u32, f32)> = changes
+            .iter()
+            .filter_map(
+                |(param, normalized)| match self.inner.param_ptr_to_hash.get(param) {
+                    Some(hash) => Some((*hash, *normalized)),
+                    None => {
+                        nih_debug_assert_failure!("Unknown parameter: {:?}", param);
+                        None
+                    }
+                },
+            )
+            .collect();
+
+        // Hosts that support `IComponentHandler2` record the whole batch as a single edit
+        let group_edit_handler = handler.cast::<dyn IComponentHandler2>();
+        if let Some(group_edit_handler) = &group_edit_handler {
+            group_edit_handler.start_group_edit();
+        }
+
+        // See `raw_set_parameter_normalized()`. While processing audio, the changes are applied at
+        // the start of the next block so they all take effect at the same time. They're added to
+        // the pending batch after the host has been notified so the lock is never held during those
+        // calls, since the audio thread skips the batch for that block if the lock is held.
+        let is_processing = self.inner.is_processing.load(Ordering::SeqCst);
+        let sample_rate = self
+            .inner
+            .current_buffer_config
+            .load()
+            .map(|c| c.sample_rate);
+
+        #[cfg(debug_assertions)]
+        let mut param_gesture_checker = self.param_gesture_checker.borrow_mut();
+        for step in param_batch_steps(&changes) {
+            match step {
+                ParamBatchStep::Begin((param_hash, _)) => {
+                    handler.begin_edit(param_hash);
+                }
+                ParamBatchStep::Set((param_hash, normalized)) => {
+                    if !is_processing {
+                        self.inner.set_normalized_value_by_hash(
+                            param_hash,
+                            normalized,
+                            sample_rate,
+                        );
+                    }
+
+                    handler.perform_edit(param_hash, normalized as f64);
+                }
+                ParamBatchStep::End((param_hash, _)) => {
+                    handler.end_edit(param_hash);
+                }
+            }
+
+            #[cfg(debug_assertions)]
+            param_gesture_checker
+                .param_batch_step(&step, &self.inner.param_id_by_hash[&step.change().0]);
+        }
+
+        if is_processing {
+            self.inner
+                .pending_param_batch
+                .lock()
+                .extend(changes.iter().copied());
+        }
+
+        if let Some(group_edit_handler) = &group_edit_handler {
+            group_edit_handler.finish_group_edit();
+        }
+    }
+
     fn get_state(&self) -> PluginState {
         self.inner.get_state_object()
     }
@@ -228,4 +413,24 @@ impl<P: Vst3Plugin> GuiContext for WrapperGuiContext<P> {
     fn set_state(&self, state: PluginState) {
         self.inner.set_state_object_from_gui(state)
     }
+
+    fn start_midi_learn(&self, param: ParamPtr) -> bool {
+        self.inner.start_midi_learn(param)
+    }
+
+    fn cancel_midi_learn(&self) {
+        self.inner.midi_learn.cancel_learning()
+    }
+
+    fn midi_learn_target(&self) -> Option<ParamPtr> {
+        self.inner.midi_learn.learn_target()
+    }
+
+    fn midi_mappings(&self) -> Vec<MidiMapping> {
+        self.inner.midi_learn.mappings()
+    }
+
+    fn set_midi_mappings(&self, mappings: Vec<MidiMapping>) {
+        self.inner.set_midi_mappings(&mappings)
+    }
 }
@@ -0,0 +1,133 @@
+//! Support for adding the plugin's own items to the host's parameter context menus through
+//! `IComponentHandler3`.
+
+use std::mem;
+use std::sync::Arc;
+use vst3_sys::base::{kInvalidArgument, kResultOk, tresult};
+use vst3_sys::utils::SharedVstPtr;
+use vst3_sys::vst::{IContextMenu, IContextMenuItem, IContextMenuTarget};
+use vst3_sys::VST3;
+
+use super::util::u16strlcpy;
+use crate::prelude::ContextMenuItem;
+use crate::wrapper::util::context_menu_actions;
+
+// Alias needed for the VST3 attribute macro
+use vst3_sys as vst3_com;
+
+// Context menu item flags missing from vst3-sys
+const VST3_CONTEXT_MENU_ITEM_IS_SEPARATOR: i32 = 1 << 0;
+const VST3_CONTEXT_MENU_ITEM_IS_DISABLED: i32 = 1 << 1;
+const VST3_CONTEXT_MENU_ITEM_IS_CHECKED: i32 = 1 << 2;
+const VST3_CONTEXT_MENU_ITEM_IS_GROUP_START: i32 = (1 << 3) | VST3_CONTEXT_MENU_ITEM_IS_DISABLED;
+const VST3_CONTEXT_MENU_ITEM_IS_GROUP_END: i32 = (1 << 4) | VST3_CONTEXT_MENU_ITEM_IS_SEPARATOR;
+
+/// The target for all of the plugin's items in a context menu. The items' tags are indices into
+/// `actions`. The host's context menu holds a reference to this object for as long as the menu
+/// exists.
+#[VST3(implements(IContextMenuTarget))]
+pub(crate) struct ContextMenuTarget {
+    actions: Vec<Arc<dyn Fn() + Send + Sync>>,
+}
+
+impl ContextMenuTarget {
+    pub fn new(items: &[ContextMenuItem]) -> Box<Self> {
+        Self::allocate(context_menu_actions(items))
+    }
+
+    /// Add `items` to the host's context menu. The items' tags are assigned in the same order as
+    /// the actions stored on this object.
+    ///
+    /// # Safety
+    ///
+    /// `menu` must be a valid context menu created by the host.
+    pub unsafe fn add_items(
+        &self,
+        menu: &vst3_sys::VstPtr<dyn IContextMenu>,
+        items: &[ContextMenuItem],
+    ) {
+        let mut next_tag = 0;
+        self.add_items_inner(menu, items, &mut next_tag);
+    }
+
+    /// vst3-sys provides no way to convert an object to a `SharedVstPtr`, so this returns a pointer
+    /// to this object's vtable pointer instead.
+    unsafe fn as_target(&self) -> SharedVstPtr<dyn IContextMenuTarget> {
+        mem::transmute(&self.__icontextmenutargetvptr as *const *const _)
+    }
+
+    unsafe fn add_items_inner(
+        &self,
+        menu: &vst3_sys::VstPtr<dyn IContextMenu>,
+        items: &[ContextMenuItem],
+        next_tag: &mut i32,
+    ) {
+        for item in items {
+            let mut menu_item: IContextMenuItem = mem::zeroed();
+            match item {
+                ContextMenuItem::Entry { label, enabled, .. } => {
+                    u16strlcpy(&mut menu_item.name, label);
+                    menu_item.tag = *next_tag;
+                    if !enabled {
+                        menu_item.flags |= VST3_CONTEXT_MENU_ITEM_IS_DISABLED;
+                    }
+                    *next_tag += 1;
+                }
+                ContextMenuItem::CheckEntry {
+                    label,
+                    enabled,
+                    checked,
+                    ..
+                } => {
+                    u16strlcpy(&mut menu_item.name, label);
+                    menu_item.tag = *next_tag;
+                    if !enabled {
+                        menu_item.flags |= VST3_CONTEXT_MENU_ITEM_IS_DISABLED;
+                    }
+                    if *checked {
+                        menu_item.flags |= VST3_CONTEXT_MENU_ITEM_IS_CHECKED;
+                    }
+                    *next_tag += 1;
+                }
+                ContextMenuItem::Separator => {
+                    menu_item.tag = -1;
+                    menu_item.flags = VST3_CONTEXT_MENU_ITEM_IS_SEPARATOR;
+                }
+                ContextMenuItem::Submenu { label, items } => {
+                    // VST3 represents submenus as groups that start with an item containing the
+                    // submenu's label and that end with a special separator
+                    u16strlcpy(&mut menu_item.name, label);
+                    menu_item.tag = -1;
+                    menu_item.flags = VST3_CONTEXT_MENU_ITEM_IS_GROUP_START;
+                    menu.add_item(&menu_item, self.as_target());
+
+                    self.add_items_inner(menu, items, next_tag);
+
+                    menu_item = mem::zeroed();
+                    menu_item.tag = -1;
+                    menu_item.flags = VST3_CONTEXT_MENU_ITEM_IS_GROUP_END;
+                }
+            }
+
+            menu.add_item(&menu_item, self.as_target());
+        }
+    }
+}
+
+impl IContextMenuTarget for ContextMenuTarget {
+    unsafe fn execute_menu_item(&self, tag: i32) -> tresult {
+        match usize::try_from(tag)
+            .ok()
+            .and_then(|tag| self.actions.get(tag))
+        {
+            Some(action) => {
+                action();
+                kResultOk
+            }
+            None => {
+                nih_debug_assert_failure!("Unknown context menu item tag {}", tag);
+                kInvalidArgument
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Keeps track of which factory preset is selected through the program change parameter. VST3
+//! hosts send program changes to both the edit controller and the audio processor, and they may
+//! write the parameter's reported value back at any time, so not every program change should load
+//! a preset.
+
+use crossbeam::atomic::AtomicCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Where a program change came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramChangeSource {
+    /// `IEditController::setParamNormalized()`.
+    EditController,
+    /// The program change parameter's queue in `IAudioProcessor::process()`.
+    AudioProcessor,
+}
+
+/// Decides which program changes load a factory preset. See the module's docstring.
+#[derive(Debug, Default)]
+pub struct FactoryPresetSelection {
+    /// The index of the last selected preset, if any. This is reported as the program change
+    /// parameter's value, and it's kept when another state is loaded so the host never sees a
+    /// different preset being selected.
+    selected: AtomicCell<Option<usize>>,
+    /// Whether the plugin's state still comes from the selected preset. This is cleared when any
+    /// other state is loaded.
+    is_loaded: AtomicBool,
+    /// The last program change and where it came from. When the same selection then arrives
+    /// through the other source, it is skipped.
+    last_program_change: AtomicCell<Option<(usize, ProgramChangeSource)>>,
+}
+
+impl FactoryPresetSelection {
+    /// The preset index the program change parameter should report.
+    pub fn selected(&self) -> usize {
+        self.selected.load().unwrap_or(0)
+    }
+
+    /// Called after the preset with this index has been loaded.
+    pub fn preset_loaded(&self, index: usize) {
+        self.selected.store(Some(index));
+        self.is_loaded.store(true, Ordering::SeqCst);
+    }
+
+    /// Called whenever any state is loaded, including presets.
+    pub fn state_loaded(&self) {
+        self.is_loaded.store(false, Ordering::SeqCst);
+    }
+
+    /// Whether a program change from the host should load the preset with this index. This should
+    /// only be called from the main thread.
+    ///
+    /// - The same selection arriving through the other source right after is skipped.
+    /// - Selecting the preset that's currently loaded again reloads it, reverting any changes made
+    ///   since.
+    /// - After another state has been loaded, writing the currently reported index is treated as
+    ///   the host sending the parameter's value back, and it does not overwrite that state.
+    pub fn program_change(&self, index: usize, source: ProgramChangeSource) -> bool {
+        if let Some((last_index, last_source)) = self.last_program_change.take() {
+            if last_index == index && last_source != source {
+                return false;
+            }
+        }
+        self.last_program_change.store(Some((index, source)));
+
+        index != self.selected() || self.is_loaded.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_other_source() {
+        let selection = FactoryPresetSelection::default();
+        assert!(selection.program_change(1, ProgramChangeSource::EditController));
+        selection.preset_loaded(1);
+        assert!(!selection.program_change(1, ProgramChangeSource::AudioProcessor));
+
+        // Explicitly selecting the loaded preset again reloads it
+        assert!(selection.program_change(1, ProgramChangeSource::EditController));
+        selection.preset_loaded(1);
+        assert!(!selection.program_change(1, ProgramChangeSource::AudioProcessor));
+
+        // A selection is only skipped once
+        assert!(selection.program_change(1, ProgramChangeSource::AudioProcessor));
+    }
+
+    #[test]
+    fn load_state_then_writeback() {
+        let selection = FactoryPresetSelection::default();
+        assert!(selection.program_change(2, ProgramChangeSource::EditController));
+        selection.state_loaded();
+        selection.preset_loaded(2);
+
+        // Restoring a project keeps the reported preset, and writing it back doesn't load it
+        selection.state_loaded();
+        assert_eq!(selection.selected(), 2);
+        assert!(!selection.program_change(2, ProgramChangeSource::EditController));
+        assert!(!selection.program_change(2, ProgramChangeSource::AudioProcessor));
+
+        // Selecting a different preset still works
+        assert!(selection.program_change(0, ProgramChangeSource::EditController));
+    }
+
+    #[test]
+    fn writeback_without_selection() {
+        let selection = FactoryPresetSelection::default();
+        selection.state_loaded();
+        assert!(!selection.program_change(0, ProgramChangeSource::AudioProcessor));
+    }
+}
@@ -1,29 +1,41 @@
 use atomic_refcell::AtomicRefCell;
 use crossbeam::atomic::AtomicCell;
 use crossbeam::channel::{self, SendTimeoutError};
+use crossbeam::queue::ArrayQueue;
 use parking_lot::{Mutex, RwLock};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use vst3_sys::base::{kInvalidArgument, kResultOk, tresult};
-use vst3_sys::vst::{IComponentHandler, RestartFlags};
+use vst3_sys::vst::{IComponentHandler, IUnitHandler, RestartFlags};
 
 use super::context::{WrapperGuiContext, WrapperInitContext, WrapperProcessContext};
+use super::factory_presets::{FactoryPresetSelection, ProgramChangeSource};
 use super::note_expressions::NoteExpressionController;
 use super::param_units::ParamUnits;
-use super::util::{ObjectPtr, VstPtr, VST3_MIDI_PARAMS_END, VST3_MIDI_PARAMS_START};
+use super::util::{
+    ObjectPtr, VstPtr, VST3_MIDI_PARAMS_END, VST3_MIDI_PARAMS_START, VST3_PROGRAM_CHANGE_PARAM_ID,
+    VST3_PROGRAM_LIST_ID,
+};
 use super::view::WrapperView;
 use crate::event_loop::{EventLoop, MainThreadExecutor, OsEventLoop};
+use crate::midi::mpe::MpeDecoder;
 use crate::prelude::{
-    AsyncExecutor, AudioIOLayout, BufferConfig, Editor, MidiConfig, ParamFlags, ParamPtr, Params,
-    Plugin, PluginNoteEvent, ProcessMode, ProcessStatus, TaskExecutor, Transport, Vst3Plugin,
+    AsyncExecutor, AudioIOLayout, BufferConfig, Editor, MidiConfig, MidiMapping, NoteName,
+    ParamFlags, ParamInfoChanges, ParamPtr, Params, Plugin, PluginNoteEvent, ProcessMode,
+    ProcessStatus, TaskExecutor, Transport, Vst3FactoryPreset, Vst3Plugin,
 };
 use crate::util::permit_alloc;
-use crate::wrapper::state::{self, PluginState};
-use crate::wrapper::util::buffer_management::BufferManager;
+use crate::wrapper::state::{self, PluginState, StateContext};
+use crate::wrapper::util::buffer_management::{AudioPortsActivation, PluginBufferManager};
+use crate::wrapper::util::midi_learn::MidiLearn;
 use crate::wrapper::util::{hash_param_id, process_wrapper};
 
+/// The number of parameter changes the plugin can make from its process function during a single
+/// block. Any changes beyond this will be dropped.
+const OUTPUT_PARAM_CHANGES_CAPACITY: usize = 2048;
+
 /// The actual wrapper bits. We need this as an `Arc<T>` so we can safely use our event loop API.
 /// Since we can't combine that with VST3's interior reference counting this just has to be moved to
 /// its own struct.
@@ -68,6 +80,9 @@ pub(crate) struct WrapperInner<P: Vst3Plugin> {
     /// IO layout is chosen as the default. Because of the way VST3 works it's not possible to
     /// change the number of busses from that default, only the channel counts can change.
     pub current_audio_io_layout: AtomicCell<AudioIOLayout>,
+    /// Which of the audio busses the host has activated through `IComponent::activateBus()`. This
+    /// is reset when the bus arrangement changes.
+    pub audio_ports_activation: AudioPortsActivation,
     /// The current buffer configuration, containing the sample rate and the maximum block size.
     /// Will be set in `IAudioProcessor::setupProcessing()`.
     pub current_buffer_config: AtomicCell<Option<BufferConfig>>,
@@ -80,7 +95,7 @@ pub(crate) struct WrapperInner<P: Vst3Plugin> {
     pub current_latency: AtomicU32,
     /// A data structure that helps manage and create buffers for all of the plugin's inputs and
     /// outputs based on channel pointers provided by the host.
-    pub buffer_manager: AtomicRefCell<BufferManager>,
+    pub buffer_manager: AtomicRefCell<PluginBufferManager>,
     /// The incoming events for the plugin, if `P::ACCEPTS_MIDI` is set. If
     /// `P::SAMPLE_ACCURATE_AUTOMATION`, this is also read in lockstep with the parameter change
     /// block splitting.
@@ -92,11 +107,41 @@ pub(crate) struct WrapperInner<P: Vst3Plugin> {
     /// Stores any events the plugin has output during the current processing cycle, analogous to
     /// `input_events`.
     pub output_events: AtomicRefCell<VecDeque<PluginNoteEvent<P>>>,
+    /// Parameter changes made by the plugin from its process function through the
+    /// [`ProcessContext`], stored as `(param_hash, normalized_value)` pairs. These are applied and
+    /// written to the host's output parameter changes after the current block has been processed.
+    pub output_param_changes: ArrayQueue<(u32, f32)>,
+    /// Parameter changes made by the editor as a single batch while the plugin is processing
+    /// audio, stored as `(param_hash, normalized_value)` pairs. The host also sends these changes
+    /// back to the plugin, but it may spread them out over multiple blocks. These are applied
+    /// together at the start of the next block instead. The audio thread only tries to lock this.
+    pub pending_param_batch: Mutex<Vec<(u32, f32)>>,
     /// VST3 has several useful predefined note expressions, but for some reason they are the only
     /// note event type that don't have MIDI note ID and channel fields. So we need to keep track of
     /// the most recent VST3 note IDs we've seen, and then map those back to MIDI note IDs and
     /// channels as needed.
     pub note_expression_controller: AtomicRefCell<NoteExpressionController>,
+    /// Translates MPE input into per-note expression events if `P::MPE_INPUT` is set to
+    /// `MpeConfig::PerNoteExpressions`.
+    pub mpe_decoder: AtomicRefCell<MpeDecoder>,
+    /// The plugin's MIDI learn mappings. Incoming MIDI CCs are passed through this before they're
+    /// sent to the plugin so mapped parameters can be updated first.
+    pub midi_learn: MidiLearn,
+    /// The plugin's note names. These are queried from the plugin when the wrapper is created and
+    /// when the plugin calls `note_names_changed()`, so the host's queries never need to lock the
+    /// plugin. VST3 hosts query these through the program list.
+    pub note_names: AtomicRefCell<Vec<NoteName>>,
+    /// The plugin's factory presets, exposed to the host as a program list with a corresponding
+    /// program change parameter. These are fetched once during initialization.
+    pub factory_presets: Vec<Vst3FactoryPreset>,
+    /// Whether the root unit has a program list. This is the case when the plugin has factory
+    /// presets or when it accepts MIDI input, so the host can query the plugin's note names at
+    /// any time. Without factory presets, the list contains a single program that only serves to
+    /// expose the note names.
+    pub has_program_list: bool,
+    /// Keeps track of the factory preset selected through the program change parameter, and
+    /// decides which program changes from the host should load a preset.
+    pub factory_preset_selection: FactoryPresetSelection,
     /// Unprocessed parameter changes and note events sent by the host during a process call.
     /// Parameter changes are sent as separate queues for each parameter, and note events are in
     /// another queue on top of that. And if `P::MIDI_INPUT >= MidiConfig::MidiCCs`, then we can
@@ -157,6 +202,14 @@ pub enum Task<P: Plugin> {
     /// Request the editor to be resized according to its current size. Right now there is no way to
     /// handle "denied resize" requests yet.
     RequestResize,
+    /// Inform the host that the plugin's note names have changed.
+    NoteNamesChanged,
+    /// Load the factory preset with this index. Sent from the audio thread when the host changes
+    /// the program change parameter during processing.
+    LoadFactoryPreset(usize),
+    /// The audio thread received the controller for a parameter that was being MIDI learned. The
+    /// new mapping still needs to be added on the main thread.
+    MidiLearnFinished,
 }
 
 /// VST3 makes audio processing pretty complicated. In order to support both block splitting for
@@ -201,6 +254,11 @@ impl<P: Vst3Plugin> WrapperInner<P> {
         // we'll calculate from the string ID specified by the plugin. These parameters should also
         // remain in the same order as the one returned by the plugin.
         let params = plugin.params();
+        let factory_presets = plugin.vst3_factory_presets();
+        let note_names = plugin.note_names();
+        // VST3 hosts can only query pitch names through a program list. This list always exists
+        // for plugins with MIDI input, since their note names may only be added later.
+        let has_program_list = !factory_presets.is_empty() || P::MIDI_INPUT >= MidiConfig::Basic;
         let param_id_hashes_ptrs_groups: Vec<_> = params
             .param_map()
             .into_iter()
@@ -244,6 +302,14 @@ impl<P: Vst3Plugin> WrapperInner<P> {
                         id
                     );
                 }
+
+                if !factory_presets.is_empty() && *hash == VST3_PROGRAM_CHANGE_PARAM_ID {
+                    nih_debug_assert_failure!(
+                        "Parameter '{}' collides with the automatically generated program change \
+                         parameter, consider giving it a different ID",
+                        id
+                    );
+                }
             }
         }
 
@@ -295,19 +361,28 @@ impl<P: Vst3Plugin> WrapperInner<P> {
             current_audio_io_layout: AtomicCell::new(
                 P::AUDIO_IO_LAYOUTS.first().copied().unwrap_or_default(),
             ),
+            audio_ports_activation: AudioPortsActivation::for_plugin::<P>(),
             current_buffer_config: AtomicCell::new(None),
             current_process_mode: AtomicCell::new(ProcessMode::Realtime),
             last_process_status: AtomicCell::new(ProcessStatus::Normal),
             current_latency: AtomicU32::new(0),
             // This is initialized just before calling `Plugin::initialize()` so that during the
             // process call buffers can be initialized without any allocations
-            buffer_manager: AtomicRefCell::new(BufferManager::for_audio_io_layout(
+            buffer_manager: AtomicRefCell::new(PluginBufferManager::for_plugin::<P>(
                 0,
                 AudioIOLayout::default(),
             )),
             input_events: AtomicRefCell::new(VecDeque::with_capacity(1024)),
             output_events: AtomicRefCell::new(VecDeque::with_capacity(1024)),
+            output_param_changes: ArrayQueue::new(OUTPUT_PARAM_CHANGES_CAPACITY),
+            pending_param_batch: Mutex::new(Vec::new()),
             note_expression_controller: AtomicRefCell::new(NoteExpressionController::default()),
+            mpe_decoder: AtomicRefCell::new(MpeDecoder::new()),
+            midi_learn: MidiLearn::default(),
+            note_names: AtomicRefCell::new(note_names),
+            factory_presets,
+            has_program_list,
+            factory_preset_selection: FactoryPresetSelection::default(),
             process_events: AtomicRefCell::new(Vec::with_capacity(4096)),
             updated_state_sender,
             updated_state_receiver,
@@ -430,6 +505,14 @@ impl<P: Vst3Plugin> WrapperInner<P> {
             .map(|s| s.as_str())
     }
 
+    /// Whether the parameter with this hash is marked as [`ParamFlags::READ_ONLY`]. Value changes
+    /// sent by the host for these parameters are ignored.
+    pub fn param_is_read_only(&self, hash: u32) -> bool {
+        self.param_by_hash
+            .get(&hash)
+            .is_some_and(|param_ptr| unsafe { param_ptr.flags() }.contains(ParamFlags::READ_ONLY))
+    }
+
     /// Convenience function for setting a value for a parameter as triggered by a VST3 parameter
     /// update. The same rate is for updating parameter smoothing.
     ///
@@ -469,6 +552,8 @@ impl<P: Vst3Plugin> WrapperInner<P> {
             state::serialize_object::<P>(
                 self.params.clone(),
                 state::make_params_iter(&self.param_by_hash, &self.param_id_to_hash),
+                &self.midi_learn,
+                StateContext::Preset,
             )
         }
     }
@@ -509,7 +594,7 @@ impl<P: Vst3Plugin> WrapperInner<P> {
             } else {
                 // Otherwise we'll set the state right here and now, since this function should be
                 // called from a GUI thread
-                self.set_state_inner(&mut state);
+                self.set_state_inner(&mut state, StateContext::Preset);
                 break;
             }
         }
@@ -536,6 +621,112 @@ impl<P: Vst3Plugin> WrapperInner<P> {
         }
     }
 
+    pub fn param_info_changed(&self, changes: ParamInfoChanges) {
+        // VST3 hosts query the value strings again together with the values
+        let mut flags = 0;
+        if changes.intersects(ParamInfoChanges::VALUES | ParamInfoChanges::VALUE_STRINGS) {
+            flags |= RestartFlags::kParamValuesChanged as i32;
+        }
+        if changes.contains(ParamInfoChanges::INFO) {
+            flags |= RestartFlags::kParamTitlesChanged as i32;
+        }
+        if flags == 0 {
+            return;
+        }
+
+        let task_posted = self.schedule_gui(Task::TriggerRestart(flags));
+        nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+    }
+
+    /// Start MIDI learn for a parameter. Returns `false` if the parameter cannot be MIDI learned.
+    pub fn start_midi_learn(&self, param: ParamPtr) -> bool {
+        if !self.param_ptr_to_hash.contains_key(&param) {
+            nih_debug_assert_failure!("Unknown parameter: {:?}", param);
+            return false;
+        }
+
+        self.midi_learn.start_learning::<P>(param)
+    }
+
+    /// Replace the MIDI learn mappings, resolving the mappings' parameter IDs.
+    pub fn set_midi_mappings(&self, mappings: &[MidiMapping]) {
+        self.midi_learn.set_mappings(
+            mappings,
+            state::make_params_getter(&self.param_by_hash, &self.param_id_to_hash),
+        );
+    }
+
+    /// Queue a parameter change from the audio thread to be written to the host's output parameter
+    /// changes at the end of the current block. Returns `false` and triggers a debug assertion
+    /// failure if the queue was full.
+    pub fn queue_output_param_change(&self, param_hash: u32, normalized_value: f32) -> bool {
+        let success = self
+            .output_param_changes
+            .push((param_hash, normalized_value))
+            .is_ok();
+        nih_debug_assert!(
+            success,
+            "Parameter output queue was full, parameter change will not be sent to the host"
+        );
+
+        success
+    }
+
+    /// Pass an incoming MIDI CC through the MIDI learn mappings. Mapped parameters are updated
+    /// right away, and the changes are also written to the host's output parameter changes at the
+    /// end of the block. Must be called from the audio thread.
+    pub fn handle_midi_learn_cc(&self, channel: u8, cc: u8, value: f32, sample_rate: f32) {
+        let learn_finished =
+            self.midi_learn
+                .process_cc(channel, cc, value, |param_ptr, normalized_value| {
+                    let param_hash = self.param_ptr_to_hash[&param_ptr];
+                    self.set_normalized_value_by_hash(
+                        param_hash,
+                        normalized_value,
+                        Some(sample_rate),
+                    );
+                    self.queue_output_param_change(param_hash, normalized_value);
+                });
+
+        if learn_finished {
+            let task_posted = self.schedule_gui(Task::MidiLearnFinished);
+            nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+        }
+    }
+
+    /// Convert a normalized value for the program change parameter to a factory preset index.
+    pub fn factory_preset_index(&self, normalized_value: f32) -> usize {
+        let max_index = self.factory_presets.len().saturating_sub(1);
+        ((normalized_value.clamp(0.0, 1.0) * max_index as f32).round() as usize).min(max_index)
+    }
+
+    /// Convert a factory preset index to a normalized value for the program change parameter.
+    pub fn factory_preset_normalized_value(&self, index: usize) -> f32 {
+        let max_index = self.factory_presets.len().saturating_sub(1);
+        if max_index == 0 {
+            0.0
+        } else {
+            index as f32 / max_index as f32
+        }
+    }
+
+    /// Load one of the plugin's factory presets. This should be called from the main thread. See
+    /// [`set_state_object_from_gui()`][Self::set_state_object_from_gui()].
+    pub fn load_factory_preset(&self, index: usize) {
+        let Some(preset) = self.factory_presets.get(index) else {
+            nih_debug_assert_failure!("Unknown factory preset index {}", index);
+            return;
+        };
+
+        nih_trace!("Loading factory preset '{}'", preset.name);
+        self.set_state_object_from_gui(preset.state.clone());
+
+        // Loading the state marks the preset as no longer being loaded, so this needs to be done
+        // afterwards. At this point the state has already been applied, even if the plugin was
+        // processing audio.
+        self.factory_preset_selection.preset_loaded(index);
+    }
+
     /// Immediately set the plugin state. Returns `false` if the deserialization failed. The plugin
     /// state is set from a couple places, so this function aims to deduplicate that. Includes
     /// `permit_alloc()`s around the deserialization and initialization for the use case where
@@ -546,7 +737,10 @@ impl<P: Vst3Plugin> WrapperInner<P> {
     /// # Notes
     ///
     /// `self.plugin` must _not_ be locked while calling this function or it will deadlock.
-    pub fn set_state_inner(&self, state: &mut PluginState) -> bool {
+    pub fn set_state_inner(&self, state: &mut PluginState, context: StateContext) -> bool {
+        // The state no longer matches the last loaded factory preset, if any
+        self.factory_preset_selection.state_loaded();
+
         let audio_io_layout = self.current_audio_io_layout.load();
         let buffer_config = self.current_buffer_config.load();
 
@@ -561,7 +755,9 @@ impl<P: Vst3Plugin> WrapperInner<P> {
                 state,
                 self.params.clone(),
                 state::make_params_getter(&self.param_by_hash, &self.param_id_to_hash),
+                &self.midi_learn,
                 buffer_config.as_ref(),
+                context,
             )
         });
         if !success {
@@ -648,6 +844,47 @@ impl<P: Vst3Plugin> MainThreadExecutor<Task<P>> for WrapperInner<P> {
                 },
                 None => nih_debug_assert_failure!("Can't resize a closed editor"),
             },
+            Task::NoteNamesChanged => {
+                *self.note_names.borrow_mut() = self.plugin.lock().note_names();
+                if !self.has_program_list {
+                    return;
+                }
+
+                match &*self.component_handler.borrow() {
+                    Some(handler) => unsafe {
+                        nih_debug_assert!(is_gui_thread);
+
+                        // Pitch name changes can only be signaled through the program list change
+                        // notification on `IUnitHandler`
+                        match handler.cast::<dyn IUnitHandler>() {
+                            Some(unit_handler) => {
+                                // -1 is `kAllProgramInvalid`
+                                let result = unit_handler
+                                    .notify_program_list_change(VST3_PROGRAM_LIST_ID, -1);
+                                nih_debug_assert_eq!(
+                                    result,
+                                    kResultOk,
+                                    "Failed to notify the host about changed note names"
+                                );
+                            }
+                            None => nih_trace!("The host does not support IUnitHandler"),
+                        }
+                    },
+                    None => nih_debug_assert_failure!("Component handler not yet set"),
+                }
+            }
+            Task::LoadFactoryPreset(index) => {
+                if self
+                    .factory_preset_selection
+                    .program_change(index, ProgramChangeSource::AudioProcessor)
+                {
+                    self.load_factory_preset(index);
+                }
+            }
+            Task::MidiLearnFinished => self.midi_learn.finish_learning(|param| {
+                let param_hash = self.param_ptr_to_hash.get(&param)?;
+                Some(self.param_id_by_hash[param_hash].clone())
+            }),
         }
     }
 }
@@ -1,10 +1,12 @@
 use std::cmp;
 use std::ops::Deref;
 use vst3_sys::interfaces::IUnknown;
-use vst3_sys::vst::TChar;
+use vst3_sys::vst::{SpeakerArrangement, TChar};
 use vst3_sys::ComInterface;
 use widestring::U16CString;
 
+use crate::prelude::ChannelLayout;
+
 /// When `Plugin::MIDI_INPUT` is set to `MidiConfig::MidiCCs` or higher then we'll register 130*16
 /// additional parameters to handle MIDI CCs, channel pressure, and pitch bend, in that order.
 /// vst3-sys doesn't expose these constants.
@@ -18,6 +20,42 @@ pub const VST3_MIDI_NUM_PARAMS: u32 = VST3_MIDI_CCS * VST3_MIDI_CHANNELS;
 pub const VST3_MIDI_PARAMS_START: u32 = VST3_MIDI_PARAMS_END - VST3_MIDI_NUM_PARAMS;
 /// The (exclusive) end of the MIDI CC parameter range. Anything above this is reserved by the host.
 pub const VST3_MIDI_PARAMS_END: u32 = 1 << 31;
+/// The ID of the generated program change parameter used to select one of the plugin's factory
+/// presets. This is only registered if the plugin has factory presets, and it sits right below the
+/// MIDI CC parameter range.
+pub const VST3_PROGRAM_CHANGE_PARAM_ID: u32 = VST3_MIDI_PARAMS_START - 1;
+/// The ID of the program list containing the plugin's factory presets. This program list belongs to
+/// the root unit.
+pub const VST3_PROGRAM_LIST_ID: i32 = 1;
+
+// The speaker bits used in VST3 speaker arrangements. vst3-sys only exposes a couple of predefined
+// arrangements, and none of the newer immersive or ambisonic ones.
+const SPEAKER_L: SpeakerArrangement = 1 << 0;
+const SPEAKER_R: SpeakerArrangement = 1 << 1;
+const SPEAKER_C: SpeakerArrangement = 1 << 2;
+const SPEAKER_LFE: SpeakerArrangement = 1 << 3;
+const SPEAKER_LS: SpeakerArrangement = 1 << 4;
+const SPEAKER_RS: SpeakerArrangement = 1 << 5;
+const SPEAKER_SL: SpeakerArrangement = 1 << 9;
+const SPEAKER_SR: SpeakerArrangement = 1 << 10;
+const SPEAKER_TFL: SpeakerArrangement = 1 << 12;
+const SPEAKER_TFR: SpeakerArrangement = 1 << 14;
+const SPEAKER_TRL: SpeakerArrangement = 1 << 15;
+const SPEAKER_TRR: SpeakerArrangement = 1 << 17;
+/// The bits for ambisonic channels (ACN) 0 through 3. The bits for ACN 4 through 24 start at
+/// [`SPEAKER_ACN4`].
+const SPEAKER_ACN0_TO_ACN3: SpeakerArrangement = 0b1111 << 20;
+const SPEAKER_ACN4: u32 = 38;
+/// The highest ambisonic order that can be expressed using VST3's speaker bits. This is lower than
+/// [`ChannelLayout::MAX_AMBISONIC_ORDER`] because VST3's speaker bits only cover orders 1 to 4.
+const VST3_MAX_AMBISONIC_ORDER: u32 = 4;
+
+const SPEAKER_ARRANGEMENT_5_1: SpeakerArrangement =
+    SPEAKER_L | SPEAKER_R | SPEAKER_C | SPEAKER_LFE | SPEAKER_LS | SPEAKER_RS;
+const SPEAKER_ARRANGEMENT_7_1: SpeakerArrangement =
+    SPEAKER_ARRANGEMENT_5_1 | SPEAKER_SL | SPEAKER_SR;
+const SPEAKER_ARRANGEMENT_7_1_4: SpeakerArrangement =
+    SPEAKER_ARRANGEMENT_7_1 | SPEAKER_TFL | SPEAKER_TFR | SPEAKER_TRL | SPEAKER_TRR;
 
 /// Early exit out of a VST3 function when one of the passed pointers is null
 macro_rules! check_null_ptr {
@@ -59,6 +97,24 @@ pub fn u16strlcpy(dest: &mut [TChar], src: &str) {
     dest[copy_len] = 0;
 }
 
+/// Get the VST3 speaker arrangement for a channel layout. Channels in VST3 buffers are ordered by
+/// their speaker bits, which matches the orders documented on [`ChannelLayout`]. Returns `None`
+/// for ambisonic layouts above fourth order since VST3 does not define speakers for those.
+pub fn speaker_arrangement(channel_layout: ChannelLayout) -> Option<SpeakerArrangement> {
+    match channel_layout {
+        ChannelLayout::Surround5_1 => Some(SPEAKER_ARRANGEMENT_5_1),
+        ChannelLayout::Surround7_1 => Some(SPEAKER_ARRANGEMENT_7_1),
+        ChannelLayout::Surround7_1_4 => Some(SPEAKER_ARRANGEMENT_7_1_4),
+        ChannelLayout::Ambisonic { order } if (1..=VST3_MAX_AMBISONIC_ORDER).contains(&order) => {
+            // First order ambisonics uses the four ACN0-3 bits, and the remaining channels use a
+            // contiguous range of bits
+            let num_higher_order_channels = channel_layout.num_channels()? - 4;
+            Some(SPEAKER_ACN0_TO_ACN3 | (((1 << num_higher_order_channels) - 1) << SPEAKER_ACN4))
+        }
+        ChannelLayout::Ambisonic { .. } => None,
+    }
+}
+
 /// Send+Sync wrapper for these interface pointers.
 #[repr(transparent)]
 pub struct VstPtr<T: vst3_sys::ComInterface + ?Sized> {
@@ -147,4 +203,32 @@ mod miri {
             "Hello"
         );
     }
+
+    #[test]
+    fn speaker_arrangements() {
+        for channel_layout in [
+            ChannelLayout::Surround5_1,
+            ChannelLayout::Surround7_1,
+            ChannelLayout::Surround7_1_4,
+            ChannelLayout::Ambisonic { order: 1 },
+            ChannelLayout::Ambisonic { order: 4 },
+        ] {
+            assert_eq!(
+                speaker_arrangement(channel_layout).map(|arrangement| arrangement.count_ones()),
+                channel_layout.num_channels()
+            );
+        }
+
+        // The last channel of fourth order ambisonics is ACN24
+        assert_eq!(
+            speaker_arrangement(ChannelLayout::Ambisonic { order: 4 })
+                .unwrap()
+                .leading_zeros(),
+            63 - 58
+        );
+        assert_eq!(
+            speaker_arrangement(ChannelLayout::Ambisonic { order: 5 }),
+            None
+        );
+    }
 }
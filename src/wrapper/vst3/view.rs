@@ -3,17 +3,23 @@ use parking_lot::{Mutex, RwLock};
 use std::any::Any;
 use std::ffi::{c_void, CStr};
 use std::mem;
+use std::ptr::NonNull;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use vst3_sys::base::{kInvalidArgument, kNotImplemented, kResultFalse, kResultOk, tresult, TBool};
+use vst3_sys::base::{
+    kInvalidArgument, kNotImplemented, kResultFalse, kResultOk, kResultTrue, tresult, TBool,
+};
 use vst3_sys::gui::{IPlugFrame, IPlugView, IPlugViewContentScaleSupport, ViewRect};
+use vst3_sys::interfaces::IUnknown;
 use vst3_sys::utils::SharedVstPtr;
+use vst3_sys::vst::{IComponentHandler3, IContextMenu};
 use vst3_sys::VST3;
 
+use super::context_menu::ContextMenuTarget;
 use super::inner::{Task, WrapperInner};
 use super::util::{ObjectPtr, VstPtr};
 use crate::plugin::vst3::Vst3Plugin;
-use crate::prelude::{Editor, ParentWindowHandle};
+use crate::prelude::{ContextMenuItem, Editor, ParentWindowHandle};
 
 // Alias needed for the VST3 attribute macro
 use vst3_sys as vst3_com;
@@ -158,6 +164,43 @@ impl<P: Vst3Plugin> WrapperView<P> {
         }
     }
 
+    /// Ask the host to show its context menu for a parameter at a position relative to the view,
+    /// with the plugin's own items added to it. Returns `false` if the host does not support
+    /// `IComponentHandler3`. This **needs** to be run from the GUI thread.
+    pub unsafe fn show_param_context_menu(
+        &self,
+        param_id: u32,
+        x: i32,
+        y: i32,
+        items: &[ContextMenuItem],
+    ) -> bool {
+        let component_handler = self.inner.component_handler.borrow();
+        let Some(component_handler) = component_handler
+            .as_ref()
+            .and_then(|handler| handler.cast::<dyn IComponentHandler3>())
+        else {
+            return false;
+        };
+
+        let plug_view: SharedVstPtr<dyn IPlugView> =
+            mem::transmute(&self.__iplugviewvptr as *const *const _);
+        let menu = component_handler.create_context_menu(plug_view, &param_id);
+        // The menu already has a reference count of one, which is released again when this
+        // `VstPtr` is dropped
+        let Some(menu) =
+            NonNull::new(menu as *mut _).map(vst3_sys::VstPtr::<dyn IContextMenu>::new)
+        else {
+            return false;
+        };
+
+        // The menu holds its own references to the target, so ours can be released right away
+        let target = Box::into_raw(ContextMenuTarget::new(items));
+        (*target).add_items(&menu, items);
+        (*target).release();
+
+        menu.popup(x, y) == kResultOk
+    }
+
     /// If the host supports `IRunLoop`, then this will post the task to a task queue that will be
     /// run on the host's UI thread. If not, then this will return an `Err` value containing the
     /// task so it can be run elsewhere.
@@ -376,8 +419,8 @@ impl<P: Vst3Plugin> IPlugView for WrapperView<P> {
     unsafe fn on_size(&self, new_size: *mut ViewRect) -> tresult {
         check_null_ptr!(new_size);
 
-        // TODO: Implement Host->Plugin resizing
-        let (unscaled_width, unscaled_height) = self.editor.lock().size();
+        let editor = self.editor.lock();
+        let (unscaled_width, unscaled_height) = editor.size();
         let scaling_factor = self.scaling_factor.load(Ordering::Relaxed);
         let (editor_width, editor_height) = (
             (unscaled_width as f32 * scaling_factor).round() as i32,
@@ -387,6 +430,28 @@ impl<P: Vst3Plugin> IPlugView for WrapperView<P> {
         let width = (*new_size).right - (*new_size).left;
         let height = (*new_size).bottom - (*new_size).top;
         if width == editor_width && height == editor_height {
+            return kResultOk;
+        }
+
+        if editor.can_resize() && width > 0 && height > 0 {
+            // The host should have called `checkSizeConstraint()` first, but not all hosts do that
+            let (new_width, new_height) = editor.size_constraints().constrain(
+                (width as f32 / scaling_factor).round() as u32,
+                (height as f32 / scaling_factor).round() as u32,
+            );
+            if !editor.set_size(new_width, new_height) {
+                return kResultFalse;
+            }
+            drop(editor);
+
+            // If the constraints changed the size, then the host's window needs to be resized to
+            // the size that was actually applied
+            if (new_width as f32 * scaling_factor).round() as i32 != width
+                || (new_height as f32 * scaling_factor).round() as i32 != height
+            {
+                let _ = self.request_resize();
+            }
+
             kResultOk
         } else {
             kResultFalse
@@ -425,19 +490,37 @@ impl<P: Vst3Plugin> IPlugView for WrapperView<P> {
     }
 
     unsafe fn can_resize(&self) -> tresult {
-        // TODO: Implement Host->Plugin resizing
-        kResultFalse
+        if self.editor.lock().can_resize() {
+            kResultTrue
+        } else {
+            kResultFalse
+        }
     }
 
     unsafe fn check_size_constraint(&self, rect: *mut ViewRect) -> tresult {
         check_null_ptr!(rect);
 
-        // TODO: Implement Host->Plugin resizing
-        if (*rect).right - (*rect).left > 0 && (*rect).bottom - (*rect).top > 0 {
-            kResultOk
-        } else {
-            kResultFalse
+        let rect = &mut *rect;
+        let width = rect.right - rect.left;
+        let height = rect.bottom - rect.top;
+        if width <= 0 || height <= 0 {
+            return kResultFalse;
+        }
+
+        // The rectangle is adjusted in place to the closest size the editor supports. The host's
+        // sizes are in physical pixels, while the editor's constraints are in logical pixels.
+        let editor = self.editor.lock();
+        if editor.can_resize() {
+            let scaling_factor = self.scaling_factor.load(Ordering::Relaxed);
+            let (unscaled_width, unscaled_height) = editor.size_constraints().constrain(
+                (width as f32 / scaling_factor).round() as u32,
+                (height as f32 / scaling_factor).round() as u32,
+            );
+            rect.right = rect.left + (unscaled_width as f32 * scaling_factor).round() as i32;
+            rect.bottom = rect.top + (unscaled_height as f32 * scaling_factor).round() as i32;
         }
+
+        kResultOk
     }
 }
 
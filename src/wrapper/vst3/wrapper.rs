@@ -6,7 +6,9 @@ use std::ptr::NonNull;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use vst3_com::vst::{DataEvent, IProcessContextRequirementsFlags, ProcessModes};
-use vst3_sys::base::{kInvalidArgument, kNoInterface, kResultFalse, kResultOk, tresult, TBool};
+use vst3_sys::base::{
+    kInvalidArgument, kNoInterface, kResultFalse, kResultOk, kResultTrue, tresult, TBool,
+};
 use vst3_sys::base::{IBStream, IPluginBase};
 use vst3_sys::utils::SharedVstPtr;
 use vst3_sys::vst::{
@@ -19,20 +21,27 @@ use vst3_sys::vst::{
 use vst3_sys::VST3;
 use widestring::U16CStr;
 
-use super::inner::{ProcessEvent, WrapperInner};
+use super::factory_presets::ProgramChangeSource;
+use super::inner::{ProcessEvent, Task, WrapperInner};
 use super::note_expressions::{self, NoteExpressionController};
 use super::util::{
-    u16strlcpy, VstPtr, VST3_MIDI_CCS, VST3_MIDI_NUM_PARAMS, VST3_MIDI_PARAMS_START,
+    speaker_arrangement, u16strlcpy, VstPtr, VST3_MIDI_CCS, VST3_MIDI_NUM_PARAMS,
+    VST3_MIDI_PARAMS_START,
+};
+use super::util::{
+    VST3_MIDI_CHANNELS, VST3_MIDI_PARAMS_END, VST3_PROGRAM_CHANGE_PARAM_ID, VST3_PROGRAM_LIST_ID,
 };
-use super::util::{VST3_MIDI_CHANNELS, VST3_MIDI_PARAMS_END};
 use super::view::WrapperView;
+use crate::midi::MidiResult;
 use crate::prelude::{
-    AuxiliaryBuffers, BufferConfig, MidiConfig, NoteEvent, ParamFlags, ProcessMode, ProcessStatus,
-    SysExMessage, Transport, Vst3Plugin,
+    AuxiliaryBuffers, BufferConfig, ChannelLayout, MidiConfig, MpeConfig, NoteEvent, ParamFlags,
+    ProcessMode, ProcessStatus, SysExMessage, Transport, Vst3Plugin,
 };
 use crate::util::permit_alloc;
-use crate::wrapper::state;
-use crate::wrapper::util::buffer_management::{BufferManager, ChannelPointers};
+use crate::wrapper::state::{self, StateContext};
+use crate::wrapper::util::buffer_management::{
+    BufferManager, ChannelPointers, HostChannelPointers, PluginBufferManager, Sample,
+};
 use crate::wrapper::util::{clamp_input_event_timing, clamp_output_event_timing, process_wrapper};
 
 // Alias needed for the VST3 attribute macro
@@ -55,6 +64,138 @@ impl<P: Vst3Plugin> Wrapper<P> {
     pub fn new() -> Box<Self> {
         Self::allocate(WrapperInner::new())
     }
+
+    /// The number of programs in the root unit's program list. If the plugin doesn't have any
+    /// factory presets, then the list contains a single program that's only used to expose the
+    /// plugin's note names.
+    fn num_programs(&self) -> usize {
+        self.inner.factory_presets.len().max(1)
+    }
+
+    /// Whether a list ID and program index passed by the host refers to a program in the program
+    /// list.
+    fn is_valid_program(&self, list_id: i32, program_index: i32) -> bool {
+        self.inner.has_program_list
+            && list_id == VST3_PROGRAM_LIST_ID
+            && program_index >= 0
+            && (program_index as usize) < self.num_programs()
+    }
+
+    /// Create the plugin's buffers from the host's audio buffers for a block of audio and process
+    /// that block. This is generic over the sample format so it can be used for plugins that process
+    /// audio in both single and double precision. The buffer manager converts the host's buffers to
+    /// the plugin's sample format when the two don't match.
+    ///
+    /// # Safety
+    ///
+    /// The audio buffers in `data` must contain at least `block_start + block_len` samples.
+    unsafe fn process_block<T: Sample>(
+        &self,
+        buffer_manager: &mut BufferManager<T>,
+        data: &vst3_sys::vst::ProcessData,
+        block_start: usize,
+        block_len: usize,
+        transport: Transport,
+    ) -> ProcessStatus {
+        let current_audio_io_layout = self.inner.current_audio_io_layout.load();
+        let has_main_input = current_audio_io_layout.main_input_channels.is_some();
+        let has_main_output = current_audio_io_layout.main_output_channels.is_some();
+        let aux_input_start_idx = if has_main_input { 1 } else { 0 };
+        let aux_output_start_idx = if has_main_output { 1 } else { 0 };
+        let sample_size = data.symbolic_sample_size;
+
+        // The buffer manager preallocated buffer slices for all the IO and storage for any
+        // axuiliary inputs.
+        buffer_manager.set_ports_activation(&self.inner.audio_ports_activation);
+        let buffers = buffer_manager.create_buffers(block_start, block_len, |buffer_source| {
+            if data.num_outputs > 0 && !data.outputs.is_null() && has_main_output {
+                *buffer_source.main_output_channel_pointers =
+                    bus_channel_pointers(&*data.outputs, sample_size);
+            }
+
+            if data.num_inputs > 0 && !data.inputs.is_null() && has_main_input {
+                *buffer_source.main_input_channel_pointers =
+                    bus_channel_pointers(&*data.inputs, sample_size);
+            }
+
+            if !data.inputs.is_null() {
+                for (aux_input_no, aux_input_channel_pointers) in buffer_source
+                    .aux_input_channel_pointers
+                    .iter_mut()
+                    .enumerate()
+                {
+                    let aux_input_idx = aux_input_no + aux_input_start_idx;
+                    if aux_input_idx > data.num_outputs as usize {
+                        break;
+                    }
+
+                    *aux_input_channel_pointers =
+                        bus_channel_pointers(&*data.inputs.add(aux_input_idx), sample_size);
+                }
+            }
+
+            if !data.outputs.is_null() {
+                for (aux_output_no, aux_output_channel_pointers) in buffer_source
+                    .aux_output_channel_pointers
+                    .iter_mut()
+                    .enumerate()
+                {
+                    let aux_output_idx = aux_output_no + aux_output_start_idx;
+                    if aux_output_idx > data.num_outputs as usize {
+                        break;
+                    }
+
+                    *aux_output_channel_pointers =
+                        bus_channel_pointers(&*data.outputs.add(aux_output_idx), sample_size);
+                }
+            }
+        });
+
+        // We already checked whether the host has initiated a parameter flush, but in case it still
+        // did something unexpected that we did not catch we'll still try to prevent processing
+        // audio when the slices don't contain the values we expect. Deactivated auxiliary outputs
+        // may not have any buffers.
+        let mut buffer_is_valid = true;
+        for output_buffer_slice in buffers.main_buffer.as_slice_immutable().iter().chain(
+            buffers
+                .aux_outputs
+                .iter()
+                .zip(buffers.aux_outputs_active)
+                .filter(|(_, is_active)| **is_active)
+                .flat_map(|(buffer, _)| buffer.as_slice_immutable().iter()),
+        ) {
+            if output_buffer_slice.is_empty() {
+                buffer_is_valid = false;
+                break;
+            }
+        }
+        nih_debug_assert!(buffer_is_valid);
+
+        let result = if buffer_is_valid {
+            // NOTE: `parking_lot`'s mutexes sometimes allocate because of their use of thread
+            //       locals
+            let mut plugin = permit_alloc(|| self.inner.plugin.lock());
+            let mut aux = AuxiliaryBuffers {
+                main_input: buffers.main_input,
+                inputs: buffers.aux_inputs,
+                outputs: buffers.aux_outputs,
+                inputs_active: buffers.aux_inputs_active,
+                outputs_active: buffers.aux_outputs_active,
+            };
+            let mut context = self.inner.make_process_context(transport);
+            let result = T::process(&mut *plugin, buffers.main_buffer, &mut aux, &mut context);
+            self.inner.last_process_status.store(result);
+            result
+        } else {
+            ProcessStatus::Normal
+        };
+
+        // If the host's buffers use a different sample format than the plugin, then the outputs
+        // still need to be copied back to the host's buffers
+        buffer_manager.write_back_outputs();
+
+        result
+    }
 }
 
 impl<P: Vst3Plugin> Drop for Wrapper<P> {
@@ -308,42 +449,21 @@ impl<P: Vst3Plugin> IComponent for Wrapper<P> {
         type_: vst3_sys::vst::MediaType,
         dir: vst3_sys::vst::BusDirection,
         index: i32,
-        _state: vst3_sys::base::TBool,
+        state: vst3_sys::base::TBool,
     ) -> tresult {
-        let current_audio_io_layout = self.inner.current_audio_io_layout.load();
-
-        // We don't support this, but the validator will get very angry with us if we let it know
-        // that
+        // Deactivated audio busses are not copied from or written to while processing, and the
+        // plugin can query their state through `InitContext` and `AuxiliaryBuffers`. Event busses
+        // are always active.
         match (type_, dir, index) {
-            (t, d, _)
-                if t == vst3_sys::vst::MediaTypes::kAudio as i32
-                    && d == vst3_sys::vst::BusDirections::kInput as i32 =>
-            {
-                let main_busses = if current_audio_io_layout.main_input_channels.is_some() {
-                    1
-                } else {
-                    0
-                };
-                let aux_busses = current_audio_io_layout.aux_input_ports.len() as i32;
-
-                if (0..main_busses + aux_busses).contains(&index) {
-                    kResultOk
-                } else {
-                    kInvalidArgument
-                }
-            }
-            (t, d, _)
-                if t == vst3_sys::vst::MediaTypes::kAudio as i32
-                    && d == vst3_sys::vst::BusDirections::kOutput as i32 =>
-            {
-                let main_busses = if current_audio_io_layout.main_output_channels.is_some() {
-                    1
-                } else {
-                    0
-                };
-                let aux_busses = current_audio_io_layout.aux_output_ports.len() as i32;
+            (t, d, index) if t == vst3_sys::vst::MediaTypes::kAudio as i32 && index >= 0 => {
+                let success = self.inner.audio_ports_activation.set_active(
+                    &self.inner.current_audio_io_layout.load(),
+                    d == vst3_sys::vst::BusDirections::kInput as i32,
+                    index as usize,
+                    state != 0,
+                );
 
-                if (0..main_busses + aux_busses).contains(&index) {
+                if success {
                     kResultOk
                 } else {
                     kInvalidArgument
@@ -390,7 +510,7 @@ impl<P: Vst3Plugin> IComponent for Wrapper<P> {
                     // This preallocates enough space so we can transform all of the host's raw
                     // channel pointers into a set of `Buffer` objects for the plugin's main and
                     // auxiliary IO
-                    *self.inner.buffer_manager.borrow_mut() = BufferManager::for_audio_io_layout(
+                    *self.inner.buffer_manager.borrow_mut() = PluginBufferManager::for_plugin::<P>(
                         buffer_config.max_buffer_size as usize,
                         audio_io_layout,
                     );
@@ -451,7 +571,11 @@ impl<P: Vst3Plugin> IComponent for Wrapper<P> {
 
         match state::deserialize_json(&read_buffer) {
             Some(mut state) => {
-                if self.inner.set_state_inner(&mut state) {
+                // VST3 does not tell the plugin why its state is being loaded
+                if self
+                    .inner
+                    .set_state_inner(&mut state, StateContext::Project)
+                {
                     nih_trace!("Loaded state ({} bytes)", read_buffer.len());
                     kResultOk
                 } else {
@@ -470,6 +594,8 @@ impl<P: Vst3Plugin> IComponent for Wrapper<P> {
         let serialized = state::serialize_json::<P>(
             self.inner.params.clone(),
             state::make_params_iter(&self.inner.param_by_hash, &self.inner.param_id_to_hash),
+            &self.inner.midi_learn,
+            StateContext::Project,
         );
         match serialized {
             Ok(serialized) => {
@@ -515,11 +641,19 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
 
     unsafe fn get_parameter_count(&self) -> i32 {
         // We need to add a whole bunch of parameters if the plugin accepts MIDI CCs
-        if P::MIDI_INPUT >= MidiConfig::MidiCCs {
-            self.inner.param_hashes.len() as i32 + VST3_MIDI_NUM_PARAMS as i32
+        let num_midi_params = if P::MIDI_INPUT >= MidiConfig::MidiCCs {
+            VST3_MIDI_NUM_PARAMS as i32
         } else {
-            self.inner.param_hashes.len() as i32
-        }
+            0
+        };
+        // And a program change parameter comes after that if the plugin has factory presets
+        let num_program_change_params = if self.inner.factory_presets.is_empty() {
+            0
+        } else {
+            1
+        };
+
+        self.inner.param_hashes.len() as i32 + num_midi_params + num_program_change_params
     }
 
     unsafe fn get_parameter_info(
@@ -536,10 +670,19 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
         *info = std::mem::zeroed();
         let info = &mut *info;
 
-        // If the parameter is a generated MIDI CC/channel pressure/pitch bend then it needs to be
-        // handled separately
+        // If the parameter is a generated MIDI CC/channel pressure/pitch bend or program change
+        // parameter then it needs to be handled separately
         let num_actual_params = self.inner.param_hashes.len() as i32;
-        if P::MIDI_INPUT >= MidiConfig::MidiCCs && param_index >= num_actual_params {
+        if !self.inner.factory_presets.is_empty() && param_index == self.get_parameter_count() - 1 {
+            info.id = VST3_PROGRAM_CHANGE_PARAM_ID;
+            u16strlcpy(&mut info.title, "Program");
+            u16strlcpy(&mut info.short_title, "Program");
+            info.step_count = self.inner.factory_presets.len() as i32 - 1;
+            info.default_normalized_value = 0.0;
+            // The root unit contains the factory preset program list
+            info.unit_id = kRootUnitId;
+            info.flags = ParameterFlags::kIsProgramChange as i32 | ParameterFlags::kIsList as i32;
+        } else if P::MIDI_INPUT >= MidiConfig::MidiCCs && param_index >= num_actual_params {
             let midi_param_relative_idx = (param_index - num_actual_params) as u32;
             // This goes up to 130 for the 128 CCs followed by channel pressure and pitch bend
             let midi_cc = midi_param_relative_idx % VST3_MIDI_CCS;
@@ -569,6 +712,7 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
             let automatable = !flags.contains(ParamFlags::NON_AUTOMATABLE);
             let hidden = flags.contains(ParamFlags::HIDDEN);
             let is_bypass = flags.contains(ParamFlags::BYPASS);
+            let read_only = flags.contains(ParamFlags::READ_ONLY);
 
             info.id = *param_hash;
             u16strlcpy(&mut info.title, param_ptr.name());
@@ -578,12 +722,15 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
             info.default_normalized_value = default_value as f64;
             info.unit_id = *param_unit;
             info.flags = 0;
-            if automatable && !hidden {
+            if automatable && !hidden && !read_only {
                 info.flags |= ParameterFlags::kCanAutomate as i32;
             }
             if hidden {
                 info.flags |= ParameterFlags::kIsReadOnly as i32 | (1 << 4); // kIsHidden
             }
+            if read_only {
+                info.flags |= ParameterFlags::kIsReadOnly as i32;
+            }
             if is_bypass {
                 info.flags |= ParameterFlags::kIsBypass as i32;
             }
@@ -602,6 +749,17 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
 
         let dest = &mut *(string as *mut [TChar; 128]);
 
+        if id == VST3_PROGRAM_CHANGE_PARAM_ID {
+            let preset_idx = self.inner.factory_preset_index(value_normalized as f32);
+            return match self.inner.factory_presets.get(preset_idx) {
+                Some(preset) => {
+                    u16strlcpy(dest, &preset.name);
+                    kResultOk
+                }
+                None => kInvalidArgument,
+            };
+        }
+
         // TODO: We don't implement these methods at all for our generated MIDI CC parameters,
         //       should be fine right? They should be hidden anyways.
         match self.inner.param_by_hash.get(&id) {
@@ -630,6 +788,22 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
             Err(_) => return kInvalidArgument,
         };
 
+        if id == VST3_PROGRAM_CHANGE_PARAM_ID {
+            return match self
+                .inner
+                .factory_presets
+                .iter()
+                .position(|preset| preset.name == string)
+            {
+                Some(preset_idx) => {
+                    *value_normalized =
+                        self.inner.factory_preset_normalized_value(preset_idx) as f64;
+                    kResultOk
+                }
+                None => kResultFalse,
+            };
+        }
+
         match self.inner.param_by_hash.get(&id) {
             Some(param_ptr) => {
                 let value = match param_ptr.string_to_normalized_value(&string) {
@@ -645,6 +819,10 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
     }
 
     unsafe fn normalized_param_to_plain(&self, id: u32, value_normalized: f64) -> f64 {
+        if id == VST3_PROGRAM_CHANGE_PARAM_ID {
+            return self.inner.factory_preset_index(value_normalized as f32) as f64;
+        }
+
         match self.inner.param_by_hash.get(&id) {
             Some(param_ptr) => param_ptr.preview_plain(value_normalized as f32) as f64,
             _ => value_normalized,
@@ -652,6 +830,11 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
     }
 
     unsafe fn plain_param_to_normalized(&self, id: u32, plain_value: f64) -> f64 {
+        if id == VST3_PROGRAM_CHANGE_PARAM_ID {
+            let preset_idx = plain_value.round().max(0.0) as usize;
+            return self.inner.factory_preset_normalized_value(preset_idx) as f64;
+        }
+
         match self.inner.param_by_hash.get(&id) {
             Some(param_ptr) => param_ptr.preview_normalized(plain_value as f32) as f64,
             _ => plain_value,
@@ -659,6 +842,11 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
     }
 
     unsafe fn get_param_normalized(&self, id: u32) -> f64 {
+        if id == VST3_PROGRAM_CHANGE_PARAM_ID {
+            let preset_idx = self.inner.factory_preset_selection.selected();
+            return self.inner.factory_preset_normalized_value(preset_idx) as f64;
+        }
+
         match self.inner.param_by_hash.get(&id) {
             Some(param_ptr) => param_ptr.modulated_normalized_value() as f64,
             _ => 0.5,
@@ -666,9 +854,25 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
     }
 
     unsafe fn set_param_normalized(&self, id: u32, value: f64) -> tresult {
+        // Selecting a program loads the corresponding factory preset. This is safe to do while the
+        // plugin is processing audio. See `FactoryPresetSelection::program_change()` for the
+        // program changes that are skipped.
+        if id == VST3_PROGRAM_CHANGE_PARAM_ID && !self.inner.factory_presets.is_empty() {
+            let preset_idx = self.inner.factory_preset_index(value as f32);
+            if self
+                .inner
+                .factory_preset_selection
+                .program_change(preset_idx, ProgramChangeSource::EditController)
+            {
+                self.inner.load_factory_preset(preset_idx);
+            }
+
+            return kResultOk;
+        }
+
         // If the plugin is currently processing audio, then this parameter change will also be sent
-        // to the process function
-        if self.inner.is_processing.load(Ordering::SeqCst) {
+        // to the process function. Read-only parameters are only ever changed by the plugin.
+        if self.inner.is_processing.load(Ordering::SeqCst) || self.inner.param_is_read_only(id) {
             return kResultOk;
         }
 
@@ -738,37 +942,56 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                     return false;
                 }
 
-                // NOTE: We completely ignore the speaker arrangements and only look at the channel
-                //       counts here. This may cause issues at some point, but it works for now.
+                // Ports with a channel layout need to have that exact speaker arrangement. For all
+                // other ports only the channel counts are compared.
+                let arrangement_matches =
+                    |arrangement: vst3_sys::vst::SpeakerArrangement,
+                     channel_count: NonZeroU32,
+                     channel_layout: Option<ChannelLayout>| {
+                        match channel_layout.and_then(speaker_arrangement) {
+                            Some(expected_arrangement) => arrangement == expected_arrangement,
+                            None => arrangement.count_ones() == channel_count.get(),
+                        }
+                    };
+
                 let has_main_input = layout.main_input_channels.is_some();
-                let aux_input_start_idx = if has_main_input { 0 } else { 1 };
+                let aux_input_start_idx = if has_main_input { 1 } else { 0 };
                 if has_main_input
-                    && (*inputs).count_ones() != layout.main_input_channels.unwrap().get()
+                    && !arrangement_matches(
+                        *inputs,
+                        layout.main_input_channels.unwrap(),
+                        layout.main_input_channel_layout(),
+                    )
                 {
                     return false;
                 }
                 for (aux_input_idx, channel_count) in layout.aux_input_ports.iter().enumerate() {
-                    if (*inputs.add(aux_input_idx + aux_input_start_idx)).count_ones()
-                        != channel_count.get()
-                    {
+                    if !arrangement_matches(
+                        *inputs.add(aux_input_idx + aux_input_start_idx),
+                        *channel_count,
+                        layout.aux_input_channel_layout(aux_input_idx),
+                    ) {
                         return false;
                     }
                 }
 
                 let has_main_output = layout.main_output_channels.is_some();
-                let aux_output_start_idx = if has_main_output { 0 } else { 1 };
-                if (*outputs).count_ones()
-                    != layout
-                        .main_output_channels
-                        .map(NonZeroU32::get)
-                        .unwrap_or_default()
+                let aux_output_start_idx = if has_main_output { 1 } else { 0 };
+                if has_main_output
+                    && !arrangement_matches(
+                        *outputs,
+                        layout.main_output_channels.unwrap(),
+                        layout.main_output_channel_layout(),
+                    )
                 {
                     return false;
                 }
                 for (aux_output_idx, channel_count) in layout.aux_output_ports.iter().enumerate() {
-                    if (*outputs.add(aux_output_idx + aux_output_start_idx)).count_ones()
-                        != channel_count.get()
-                    {
+                    if !arrangement_matches(
+                        *outputs.add(aux_output_idx + aux_output_start_idx),
+                        *channel_count,
+                        layout.aux_output_channel_layout(aux_output_idx),
+                    ) {
                         return false;
                     }
                 }
@@ -782,6 +1005,7 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                 // This layout is used from hereon onwards, at least until this function is called
                 // again
                 self.inner.current_audio_io_layout.store(layout);
+                self.inner.audio_ports_activation.reset();
 
                 kResultOk
             }
@@ -815,14 +1039,20 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
         };
 
         let current_audio_io_layout = self.inner.current_audio_io_layout.load();
-        let num_channels = if dir == vst3_sys::vst::BusDirections::kInput as i32 {
+        let (num_channels, channel_layout) = if dir == vst3_sys::vst::BusDirections::kInput as i32 {
             let has_main_input = current_audio_io_layout.main_input_channels.is_some();
             let aux_input_start_idx = if has_main_input { 1 } else { 0 };
             let aux_input_idx = (index - aux_input_start_idx).max(0) as usize;
             if index == 0 && has_main_input {
-                current_audio_io_layout.main_input_channels.unwrap().get()
+                (
+                    current_audio_io_layout.main_input_channels.unwrap().get(),
+                    current_audio_io_layout.main_input_channel_layout(),
+                )
             } else if aux_input_idx < current_audio_io_layout.aux_input_ports.len() {
-                current_audio_io_layout.aux_input_ports[aux_input_idx].get()
+                (
+                    current_audio_io_layout.aux_input_ports[aux_input_idx].get(),
+                    current_audio_io_layout.aux_input_channel_layout(aux_input_idx),
+                )
             } else {
                 return kInvalidArgument;
             }
@@ -831,16 +1061,27 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
             let aux_output_start_idx = if has_main_output { 1 } else { 0 };
             let aux_output_idx = (index - aux_output_start_idx).max(0) as usize;
             if index == 0 && has_main_output {
-                current_audio_io_layout.main_output_channels.unwrap().get()
+                (
+                    current_audio_io_layout.main_output_channels.unwrap().get(),
+                    current_audio_io_layout.main_output_channel_layout(),
+                )
             } else if aux_output_idx < current_audio_io_layout.aux_output_ports.len() {
-                current_audio_io_layout.aux_output_ports[aux_output_idx].get()
+                (
+                    current_audio_io_layout.aux_output_ports[aux_output_idx].get(),
+                    current_audio_io_layout.aux_output_channel_layout(aux_output_idx),
+                )
             } else {
                 return kInvalidArgument;
             }
         } else {
             return kInvalidArgument;
         };
-        let channel_map = channel_count_to_map(num_channels);
+        // Ports without an explicit channel layout get a default arrangement based on the number of
+        // channels
+        let channel_map = match channel_layout.and_then(speaker_arrangement) {
+            Some(channel_map) => channel_map,
+            None => channel_count_to_map(num_channels),
+        };
 
         nih_debug_assert_eq!(num_channels, channel_map.count_ones());
         *arr = channel_map;
@@ -849,7 +1090,12 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
     }
 
     unsafe fn can_process_sample_size(&self, symbolic_sample_size: i32) -> tresult {
-        if symbolic_sample_size == vst3_sys::vst::SymbolicSampleSizes::kSample32 as i32 {
+        // Plugins that process audio in double precision can also handle single precision audio,
+        // the buffer manager will convert between the two
+        if symbolic_sample_size == vst3_sys::vst::SymbolicSampleSizes::kSample32 as i32
+            || (P::PROCESS_F64
+                && symbolic_sample_size == vst3_sys::vst::SymbolicSampleSizes::kSample64 as i32)
+        {
             kResultOk
         } else {
             kResultFalse
@@ -866,8 +1112,8 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
         // There's no special handling for offline processing at the moment
         let setup = &*setup;
         nih_debug_assert_eq!(
-            setup.symbolic_sample_size,
-            vst3_sys::vst::SymbolicSampleSizes::kSample32 as i32
+            self.can_process_sample_size(setup.symbolic_sample_size),
+            kResultOk
         );
 
         // This is needed when activating the plugin and when restoring state
@@ -947,18 +1193,15 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
 
             nih_debug_assert!(data.num_inputs >= 0 && data.num_outputs >= 0);
             nih_debug_assert_eq!(
-                data.symbolic_sample_size,
-                vst3_sys::vst::SymbolicSampleSizes::kSample32 as i32
+                self.can_process_sample_size(data.symbolic_sample_size),
+                kResultOk
             );
             nih_debug_assert!(data.num_samples >= 0);
 
             let total_buffer_len = data.num_samples as usize;
 
             let current_audio_io_layout = self.inner.current_audio_io_layout.load();
-            let has_main_input = current_audio_io_layout.main_input_channels.is_some();
             let has_main_output = current_audio_io_layout.main_output_channels.is_some();
-            let aux_input_start_idx = if has_main_input { 1 } else { 0 };
-            let aux_output_start_idx = if has_main_output { 1 } else { 0 };
 
             // NOTE: VST3 hosts may trigger a 'parameter flush' by calling the process function for
             //       0 input samples. If this is the case then we'll only handle events and skip all
@@ -981,6 +1224,19 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
             let mut process_events = self.inner.process_events.borrow_mut();
             process_events.clear();
 
+            // Batched parameter changes from the editor are applied all at once, see
+            // `WrapperInner::pending_param_batch`. If the editor is still adding to the batch, then
+            // this is done during the next block instead.
+            if let Some(mut pending_param_batch) = self.inner.pending_param_batch.try_lock() {
+                for (param_hash, normalized_value) in pending_param_batch.drain(..) {
+                    self.inner.set_normalized_value_by_hash(
+                        param_hash,
+                        normalized_value,
+                        Some(sample_rate),
+                    );
+                }
+            }
+
             // First we'll go through the parameter changes. This may also include MIDI CC messages
             // if the plugin supports those
             if let Some(param_changes) = data.input_param_changes.upgrade() {
@@ -997,6 +1253,9 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
 
                         let mut sample_offset = 0i32;
                         let mut value = 0.0f64;
+                        // Hosts may write the same program change value several times in a block,
+                        // but that should only load the preset once
+                        let mut last_factory_preset_idx = None;
                         for change_idx in 0..num_changes {
                             if param_change_queue.get_point(
                                 change_idx,
@@ -1013,12 +1272,31 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                                 );
                                 let value = value as f32;
 
-                                // MIDI CC messages, channel pressure, and pitch bend are also sent
-                                // as parameter changes
-                                if P::MIDI_INPUT >= MidiConfig::MidiCCs
+                                // Program changes load one of the plugin's factory presets. That
+                                // can't be done from the audio thread, so it's deferred to the main
+                                // thread instead. There the same selection sent to the edit
+                                // controller is skipped, see `FactoryPresetSelection`.
+                                if param_hash == VST3_PROGRAM_CHANGE_PARAM_ID
+                                    && !self.inner.factory_presets.is_empty()
+                                {
+                                    let preset_idx = self.inner.factory_preset_index(value);
+                                    if last_factory_preset_idx.replace(preset_idx)
+                                        != Some(preset_idx)
+                                    {
+                                        let task_posted = self
+                                            .inner
+                                            .schedule_gui(Task::LoadFactoryPreset(preset_idx));
+                                        nih_debug_assert!(
+                                            task_posted,
+                                            "The task queue is full, dropping task..."
+                                        );
+                                    }
+                                } else if P::MIDI_INPUT >= MidiConfig::MidiCCs
                                     && (VST3_MIDI_PARAMS_START..VST3_MIDI_PARAMS_END)
                                         .contains(&param_hash)
                                 {
+                                    // MIDI CC messages, channel pressure, and pitch bend are also
+                                    // sent as parameter changes
                                     let midi_param_relative_idx =
                                         param_hash - VST3_MIDI_PARAMS_START;
                                     // This goes up to 130 for the 128 CCs followed by channel pressure and pitch bend
@@ -1045,6 +1323,9 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                                             value,
                                         },
                                     }));
+                                } else if self.inner.param_is_read_only(param_hash) {
+                                    // The host should not send changes for read-only parameters,
+                                    // these are only ever changed by the plugin itself
                                 } else if P::SAMPLE_ACCURATE_AUTOMATION {
                                     process_events.push(ProcessEvent::ParameterChange {
                                         timing,
@@ -1183,6 +1464,7 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                 // The extra scope is here to make sure we release the borrow on input_events
                 {
                     let mut input_events = self.inner.input_events.borrow_mut();
+                    let mut mpe_decoder = self.inner.mpe_decoder.borrow_mut();
                     input_events.clear();
 
                     block_end = total_buffer_len;
@@ -1209,11 +1491,47 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                                 );
                             }
                             ProcessEvent::NoteEvent(event) => {
+                                // MIDI CCs mapped through MIDI learn change parameters, so just like
+                                // with parameter changes the block is split on them
+                                if let NoteEvent::MidiCC {
+                                    timing,
+                                    channel,
+                                    cc,
+                                    value,
+                                } = event
+                                {
+                                    if P::SAMPLE_ACCURATE_AUTOMATION
+                                        && *timing != block_start as u32
+                                        && self.inner.midi_learn.may_handle_cc(*channel, *cc)
+                                    {
+                                        event_start_idx = event_idx;
+                                        block_end = *timing as usize;
+                                        break;
+                                    }
+
+                                    self.inner.handle_midi_learn_cc(
+                                        *channel,
+                                        *cc,
+                                        *value,
+                                        sample_rate,
+                                    );
+                                }
+
                                 // We need to make sure to compensate the event for any block splitting,
                                 // since we had to create the event object beforehand
                                 let mut event = event.clone();
                                 event.subtract_timing(block_start as u32);
-                                input_events.push_back(event);
+
+                                // MPE member channel expressions are converted to per-note
+                                // expressions here, since the events are now in the right order
+                                if P::MPE_INPUT == MpeConfig::PerNoteExpressions
+                                    && P::MIDI_INPUT >= MidiConfig::MidiCCs
+                                {
+                                    mpe_decoder
+                                        .process(event, |event| input_events.push_back(event));
+                                } else {
+                                    input_events.push_back(event);
+                                }
                             }
                         }
                     }
@@ -1226,108 +1544,6 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                     // and we can start preparing audio processing
                     let block_len = block_end - block_start;
 
-                    // The buffer manager preallocated buffer slices for all the IO and storage for
-                    // any axuiliary inputs.
-                    let mut buffer_manager = self.inner.buffer_manager.borrow_mut();
-                    let buffers =
-                        buffer_manager.create_buffers(block_start, block_len, |buffer_source| {
-                            if data.num_outputs > 0
-                                && !data.outputs.is_null()
-                                && !(*data.outputs).buffers.is_null()
-                                && has_main_output
-                            {
-                                let audio_output = &*data.outputs;
-                                let ptrs =
-                                    NonNull::new(audio_output.buffers as *mut *mut f32).unwrap();
-                                let num_channels = audio_output.num_channels as usize;
-
-                                *buffer_source.main_output_channel_pointers =
-                                    Some(ChannelPointers { ptrs, num_channels });
-                            }
-
-                            if data.num_inputs > 0
-                                && !data.inputs.is_null()
-                                && !(*data.inputs).buffers.is_null()
-                                && has_main_input
-                            {
-                                let audio_input = &*data.inputs;
-                                let ptrs =
-                                    NonNull::new(audio_input.buffers as *mut *mut f32).unwrap();
-                                let num_channels = audio_input.num_channels as usize;
-
-                                *buffer_source.main_input_channel_pointers =
-                                    Some(ChannelPointers { ptrs, num_channels });
-                            }
-
-                            if !data.inputs.is_null() {
-                                for (aux_input_no, aux_input_channel_pointers) in buffer_source
-                                    .aux_input_channel_pointers
-                                    .iter_mut()
-                                    .enumerate()
-                                {
-                                    let aux_input_idx = aux_input_no + aux_input_start_idx;
-                                    if aux_input_idx > data.num_outputs as usize {
-                                        break;
-                                    }
-
-                                    let audio_input = &*data.inputs.add(aux_input_idx);
-                                    match NonNull::new(audio_input.buffers as *mut *mut f32) {
-                                        Some(ptrs) => {
-                                            let num_channels = audio_input.num_channels as usize;
-
-                                            *aux_input_channel_pointers =
-                                                Some(ChannelPointers { ptrs, num_channels });
-                                        }
-                                        None => continue,
-                                    }
-                                }
-                            }
-
-                            if !data.outputs.is_null() {
-                                for (aux_output_no, aux_output_channel_pointers) in buffer_source
-                                    .aux_output_channel_pointers
-                                    .iter_mut()
-                                    .enumerate()
-                                {
-                                    let aux_output_idx = aux_output_no + aux_output_start_idx;
-                                    if aux_output_idx > data.num_outputs as usize {
-                                        break;
-                                    }
-
-                                    let audio_output = &*data.outputs.add(aux_output_idx);
-                                    match NonNull::new(audio_output.buffers as *mut *mut f32) {
-                                        Some(ptrs) => {
-                                            let num_channels = audio_output.num_channels as usize;
-
-                                            *aux_output_channel_pointers =
-                                                Some(ChannelPointers { ptrs, num_channels });
-                                        }
-                                        None => continue,
-                                    }
-                                }
-                            }
-                        });
-
-                    // We already checked whether the host has initiated a parameter flush, but in
-                    // case it still did something unexpected that we did not catch we'll still try
-                    // to prevent processing audio when the slices don't contain the values we
-                    // expect.
-                    let mut buffer_is_valid = true;
-                    for output_buffer_slice in
-                        buffers.main_buffer.as_slice_immutable().iter().chain(
-                            buffers
-                                .aux_outputs
-                                .iter()
-                                .flat_map(|buffer| buffer.as_slice_immutable().iter()),
-                        )
-                    {
-                        if output_buffer_slice.is_empty() {
-                            buffer_is_valid = false;
-                            break;
-                        }
-                    }
-                    nih_debug_assert!(buffer_is_valid);
-
                     // Some of the fields are left empty because VST3 does not provide this
                     // information, but the methods on [`Transport`] can reconstruct these values
                     // from the other fields
@@ -1389,20 +1605,21 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                         }
                     }
 
-                    let result = if buffer_is_valid {
-                        // NOTE: `parking_lot`'s mutexes sometimes allocate because of their use of
-                        //       thread locals
-                        let mut plugin = permit_alloc(|| self.inner.plugin.lock());
-                        let mut aux = AuxiliaryBuffers {
-                            inputs: buffers.aux_inputs,
-                            outputs: buffers.aux_outputs,
-                        };
-                        let mut context = self.inner.make_process_context(transport);
-                        let result = plugin.process(buffers.main_buffer, &mut aux, &mut context);
-                        self.inner.last_process_status.store(result);
-                        result
-                    } else {
-                        ProcessStatus::Normal
+                    let result = match &mut *self.inner.buffer_manager.borrow_mut() {
+                        PluginBufferManager::F32(buffer_manager) => self.process_block(
+                            buffer_manager,
+                            data,
+                            block_start,
+                            block_len,
+                            transport,
+                        ),
+                        PluginBufferManager::F64(buffer_manager) => self.process_block(
+                            buffer_manager,
+                            data,
+                            block_start,
+                            block_len,
+                            transport,
+                        ),
                     };
 
                     match result {
@@ -1415,6 +1632,41 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                     }
                 };
 
+                // Parameter changes made by the plugin from its process function are applied here
+                // so they don't change in the middle of the block. They're also written to the
+                // host's output parameter changes so the host can record them.
+                let output_param_changes = data.output_param_changes.upgrade();
+                while let Some((param_hash, normalized_value)) =
+                    self.inner.output_param_changes.pop()
+                {
+                    self.inner.set_normalized_value_by_hash(
+                        param_hash,
+                        normalized_value,
+                        Some(sample_rate),
+                    );
+
+                    if let Some(output_param_changes) = &output_param_changes {
+                        let mut queue_idx = 0;
+                        let param_queue = output_param_changes
+                            .add_parameter_data(&param_hash, &mut queue_idx)
+                            .upgrade();
+                        match param_queue {
+                            Some(param_queue) => {
+                                let mut point_idx = 0;
+                                param_queue.add_point(
+                                    block_start as i32,
+                                    normalized_value as f64,
+                                    &mut point_idx,
+                                );
+                            }
+                            None => nih_debug_assert_failure!(
+                                "The host did not provide a parameter value queue for an output \
+                                 parameter change"
+                            ),
+                        }
+                    }
+                }
+
                 // Send any events output by the plugin during the process cycle
                 if let Some(events) = data.output_events.upgrade() {
                     let mut output_events = self.inner.output_events.borrow_mut();
@@ -1602,6 +1854,32 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                                     value2: 0,
                                 };
                             }
+                            midi_event @ (NoteEvent::MidiCC14Bit { .. }
+                            | NoteEvent::MidiRpn { .. }
+                            | NoteEvent::MidiNrpn { .. })
+                                if P::MIDI_OUTPUT >= MidiConfig::MidiCCs =>
+                            {
+                                // These events are sent as a sequence of regular CC messages
+                                if let Some(MidiResult::Multiple(messages, num_messages)) =
+                                    midi_event.as_midi()
+                                {
+                                    for [status, cc, value] in &messages[..num_messages] {
+                                        vst3_event.type_ = EventTypes::kLegacyMIDICCOutEvent as u16;
+                                        vst3_event.event.legacy_midi_cc_out =
+                                            LegacyMidiCCOutEvent {
+                                                control_number: *cc,
+                                                channel: (status & 0x0f) as i8,
+                                                value: *value as i8,
+                                                value2: 0,
+                                            };
+
+                                        let result = events.add_event(&mut vst3_event);
+                                        nih_debug_assert_eq!(result, kResultOk);
+                                    }
+                                }
+
+                                continue;
+                            }
                             NoteEvent::MidiSysEx { timing: _, message }
                                 if P::MIDI_OUTPUT >= MidiConfig::Basic =>
                             {
@@ -1654,7 +1932,7 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
             //        doesn't do that
             let updated_state = permit_alloc(|| self.inner.updated_state_receiver.try_recv());
             if let Ok(mut state) = updated_state {
-                self.inner.set_state_inner(&mut state);
+                self.inner.set_state_inner(&mut state, StateContext::Preset);
 
                 // We'll pass the state object back to the GUI thread so deallocation can happen
                 // there without potentially blocking the audio thread
@@ -1794,12 +2072,34 @@ impl<P: Vst3Plugin> IProcessContextRequirements for Wrapper<P> {
 
 impl<P: Vst3Plugin> IUnitInfo for Wrapper<P> {
     unsafe fn get_unit_count(&self) -> i32 {
-        self.inner.param_units.len() as i32
+        // The root unit only needs to be listed when it contains the program list
+        if self.inner.has_program_list {
+            self.inner.param_units.len() as i32 + 1
+        } else {
+            self.inner.param_units.len() as i32
+        }
     }
 
     unsafe fn get_unit_info(&self, unit_index: i32, info: *mut UnitInfo) -> tresult {
         check_null_ptr!(info);
 
+        let mut unit_index = unit_index;
+        if self.inner.has_program_list {
+            if unit_index == 0 {
+                *info = mem::zeroed();
+
+                let info = &mut *info;
+                info.id = kRootUnitId;
+                info.parent_unit_id = kNoParentUnitId;
+                u16strlcpy(&mut info.name, "Root");
+                info.program_list_id = VST3_PROGRAM_LIST_ID;
+
+                return kResultOk;
+            }
+
+            unit_index -= 1;
+        }
+
         match self.inner.param_units.info(unit_index as usize) {
             Some((unit_id, unit_info)) => {
                 *info = mem::zeroed();
@@ -1817,25 +2117,50 @@ impl<P: Vst3Plugin> IUnitInfo for Wrapper<P> {
     }
 
     unsafe fn get_program_list_count(&self) -> i32 {
-        // TODO: Do we want program lists? Probably not, CLAP doesn't even support them.
-        0
+        // The plugin's factory presets and note names are exposed through a single program list
+        if self.inner.has_program_list {
+            1
+        } else {
+            0
+        }
     }
 
-    unsafe fn get_program_list_info(
-        &self,
-        _list_index: i32,
-        _info: *mut ProgramListInfo,
-    ) -> tresult {
-        kInvalidArgument
+    unsafe fn get_program_list_info(&self, list_index: i32, info: *mut ProgramListInfo) -> tresult {
+        check_null_ptr!(info);
+
+        if list_index != 0 || !self.inner.has_program_list {
+            return kInvalidArgument;
+        }
+
+        *info = mem::zeroed();
+
+        let info = &mut *info;
+        info.id = VST3_PROGRAM_LIST_ID;
+        if self.inner.factory_presets.is_empty() {
+            u16strlcpy(&mut info.name, "Programs");
+        } else {
+            u16strlcpy(&mut info.name, "Factory Presets");
+        }
+        info.program_count = self.num_programs() as i32;
+
+        kResultOk
     }
 
-    unsafe fn get_program_name(
-        &self,
-        _list_id: i32,
-        _program_index: i32,
-        _name: *mut u16,
-    ) -> tresult {
-        kInvalidArgument
+    unsafe fn get_program_name(&self, list_id: i32, program_index: i32, name: *mut u16) -> tresult {
+        check_null_ptr!(name);
+
+        if !self.is_valid_program(list_id, program_index) {
+            return kInvalidArgument;
+        }
+
+        // Without factory presets, the list only contains a single program for the note names
+        let dest = &mut *(name as *mut [TChar; 128]);
+        match self.inner.factory_presets.get(program_index as usize) {
+            Some(preset) => u16strlcpy(dest, &preset.name),
+            None => u16strlcpy(dest, "Default"),
+        }
+
+        kResultOk
     }
 
     unsafe fn get_program_info(
@@ -1848,19 +2173,53 @@ impl<P: Vst3Plugin> IUnitInfo for Wrapper<P> {
         kInvalidArgument
     }
 
-    unsafe fn has_program_pitch_names(&self, _id: i32, _index: i32) -> tresult {
-        // TODO: Support note names once someone requests it
-        kInvalidArgument
+    unsafe fn has_program_pitch_names(&self, list_id: i32, program_index: i32) -> tresult {
+        if !self.is_valid_program(list_id, program_index) {
+            return kInvalidArgument;
+        }
+
+        // The note names apply to every program
+        if P::MIDI_INPUT >= MidiConfig::Basic && !self.inner.note_names.borrow().is_empty() {
+            kResultTrue
+        } else {
+            kResultFalse
+        }
     }
 
     unsafe fn get_program_pitch_name(
         &self,
-        _id: i32,
-        _index: i32,
-        _pitch: i16,
-        _name: *mut u16,
+        list_id: i32,
+        program_index: i32,
+        pitch: i16,
+        name: *mut u16,
     ) -> tresult {
-        kInvalidArgument
+        check_null_ptr!(name);
+
+        if !self.is_valid_program(list_id, program_index) {
+            return kInvalidArgument;
+        }
+
+        // VST3 doesn't have per-channel pitch names, so names for the first channel are used if
+        // there is no name that applies to all channels
+        let note_names = self.inner.note_names.borrow();
+        let note_name = note_names
+            .iter()
+            .filter(|note_name| note_name.note as i16 == pitch)
+            .find(|note_name| note_name.channel.is_none())
+            .or_else(|| {
+                note_names.iter().find(|note_name| {
+                    note_name.note as i16 == pitch && note_name.channel == Some(0)
+                })
+            });
+        match note_name {
+            Some(note_name) => {
+                let dest = &mut *(name as *mut [TChar; 128]);
+                u16strlcpy(dest, &note_name.name);
+
+                kResultOk
+            }
+            None => kResultFalse,
+        }
     }
 
     unsafe fn get_selected_unit(&self) -> i32 {
@@ -1893,3 +2252,20 @@ impl<P: Vst3Plugin> IUnitInfo for Wrapper<P> {
         kInvalidArgument
     }
 }
+
+/// Get the channel pointers for one of the host's audio buses. `symbolic_sample_size` determines
+/// whether the bus contains single or double precision samples. Returns `None` if the host did not
+/// provide any buffers for the bus.
+unsafe fn bus_channel_pointers(
+    bus: &vst3_sys::vst::AudioBusBuffers,
+    symbolic_sample_size: i32,
+) -> Option<HostChannelPointers> {
+    let num_channels = bus.num_channels as usize;
+    if symbolic_sample_size == vst3_sys::vst::SymbolicSampleSizes::kSample64 as i32 {
+        NonNull::new(bus.buffers as *mut *mut f64)
+            .map(|ptrs| ChannelPointers { ptrs, num_channels }.into())
+    } else {
+        NonNull::new(bus.buffers as *mut *mut f32)
+            .map(|ptrs| ChannelPointers { ptrs, num_channels }.into())
+    }
+}